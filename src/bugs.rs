@@ -1,9 +1,14 @@
+use std::collections::HashSet;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Duration;
 
 use lru::LruCache;
+use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
+use crate::disk_cache::DiskCache;
+
 mod debbugs;
 mod launchpad;
 
@@ -27,14 +32,22 @@ const BUG_IDS_CACHE_CAPACITY: usize = 1024;
 /// a few hundred packages without evictions.
 const BUG_DETAILS_CACHE_CAPACITY: usize = 32_768;
 
+/// How long bug lists and details are kept on disk before being re-queried.
+/// Bugs get filed and closed all the time, so this is kept short.
+const BUG_DISK_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
 /// Cached bug data used by changelog completions.
 pub struct BugCache {
     pub pool: crate::udd::SharedPool,
     bug_ids_by_package: LruCache<String, Vec<u32>>,
     bug_details_by_id: LruCache<u32, CachedDebbugsBugDetails>,
-    /// Last UDD connection error, set by fetch methods and drained by callers
-    /// that want to surface it (e.g. via `window/showMessage`).
-    pub last_udd_error: Option<String>,
+    /// Bug lists per package on disk, keyed like `bug_ids_by_package`.
+    package_disk_cache: DiskCache,
+    /// Details of bugs looked up by ID on disk.
+    bug_disk_cache: DiskCache,
+    /// Bugs whose details are an expired on-disk copy, used because UDD
+    /// could not be reached.
+    stale_bug_ids: HashSet<u32>,
     #[cfg(feature = "launchpad")]
     launchpad_client: launchpadlib::r#async::Client,
     #[cfg(feature = "launchpad")]
@@ -44,7 +57,7 @@ pub struct BugCache {
 }
 
 /// Cached details for a single Debian bug report.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct CachedDebbugsBugDetails {
     title: Option<String>,
     severity: Option<String>,
@@ -52,6 +65,12 @@ struct CachedDebbugsBugDetails {
     tags: Option<String>,
     forwarded: Option<String>,
     originator: Option<String>,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    merged_with: Vec<u32>,
 }
 
 /// Cached details for a Launchpad bug relevant to completion.
@@ -72,6 +91,30 @@ pub struct BugRow {
     tags: Option<String>,
     forwarded: Option<String>,
     submitter: Option<String>,
+    source: Option<String>,
+    archived: bool,
+    merged_with: Option<String>,
+}
+
+impl From<BugRow> for CachedDebbugsBugDetails {
+    fn from(row: BugRow) -> Self {
+        Self {
+            title: row.title,
+            severity: row.severity,
+            done: row.done.as_ref().is_some_and(|d| !d.is_empty()),
+            tags: row.tags,
+            forwarded: row.forwarded,
+            originator: row.submitter,
+            source: row.source,
+            archived: row.archived,
+            merged_with: row
+                .merged_with
+                .iter()
+                .flat_map(|ids| ids.split(','))
+                .filter_map(|id| id.trim().parse().ok())
+                .collect(),
+        }
+    }
 }
 
 impl BugCache {
@@ -85,7 +128,9 @@ impl BugCache {
             bug_details_by_id: LruCache::new(
                 NonZeroUsize::new(BUG_DETAILS_CACHE_CAPACITY).expect("non-zero capacity"),
             ),
-            last_udd_error: None,
+            package_disk_cache: DiskCache::open("debbugs-packages", BUG_DISK_TTL),
+            bug_disk_cache: DiskCache::open("debbugs", BUG_DISK_TTL),
+            stale_bug_ids: HashSet::new(),
             #[cfg(feature = "launchpad")]
             launchpad_client: launchpadlib::r#async::Client::anonymous(LAUNCHPAD_CONSUMER_KEY),
             #[cfg(feature = "launchpad")]
@@ -107,20 +152,30 @@ pub fn new_shared_bug_cache(pool: crate::udd::SharedPool) -> SharedBugCache {
 
 /// Look up a Debian bug summary, fetching from UDD on a cache miss.
 ///
-/// Avoids holding the cache lock across the network call: it checks the
-/// in-memory cache first; if absent, clones the pool, drops the lock, queries
-/// UDD, then re-acquires to insert.
+/// Avoids holding the cache lock across disk and network access: it checks
+/// the in-memory cache first; if absent, clones the pool and the on-disk
+/// cache, drops the lock, reads the on-disk cache or queries UDD, then
+/// re-acquires to insert. When the query fails an expired on-disk entry is
+/// used instead, marked stale.
 pub async fn debian_bug_summary(cache: &SharedBugCache, id: u32) -> Option<DebbugsBugSummary> {
     // Fast path: already cached.
-    if let Some(s) = cache.write().await.get_cached_debian_bug_summary(id) {
-        return Some(s);
-    }
-    // Slow path: fetch from UDD. The write guard is dropped here before the
-    // network await so other callers aren't blocked.
-    let pool = cache.read().await.pool.clone();
-    let row = BugCache::query_bug_by_id(&pool, id).await?;
+    let (pool, disk) = {
+        let mut guard = cache.write().await;
+        if let Some(s) = guard.get_cached_debian_bug_summary(id) {
+            return Some(s);
+        }
+        (guard.pool.clone(), guard.bug_disk_cache.clone())
+    };
+    // Slow path: the write guard is dropped here so other callers aren't
+    // blocked.
+    let query = async {
+        BugCache::query_bug_by_id(&pool, id)
+            .await
+            .map(CachedDebbugsBugDetails::from)
+    };
+    let (details, stale) = disk.fetch(&pool.url(), &id.to_string(), query).await?;
     let mut guard = cache.write().await;
-    guard.insert_bug_row(id, row);
+    guard.insert_bug_details(id, details, stale);
     guard.get_cached_debian_bug_summary(id)
 }
 
@@ -1,4 +1,4 @@
-use super::{BugCache, BugRow, CachedDebbugsBugDetails};
+use super::{BugCache, BugRow, CachedDebbugsBugDetails, SharedBugCache};
 
 /// Debian bug data returned to completion providers.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -17,68 +17,121 @@ pub struct DebbugsBugSummary {
     pub forwarded: Option<String>,
     /// Email address of the person who reported the bug.
     pub originator: Option<String>,
+    /// Source package the bug is filed against, when known.
+    pub source: Option<String>,
+    /// Whether the bug has been archived.
+    pub archived: bool,
+    /// IDs of the bugs this bug is merged with.
+    pub merged_with: Vec<u32>,
+    /// Whether the details are an expired on-disk copy, used because UDD
+    /// could not be reached.
+    pub stale: bool,
 }
 
 impl BugCache {
     /// Fetch bug IDs and details for a source package from UDD in a single query.
-    async fn fetch_bugs_for_source_package(&mut self, source_package: &str) {
+    async fn fetch_bugs_for_source_package(cache: &SharedBugCache, source_package: &str) {
         let key = format!("src:{}", source_package);
-        if self.bug_ids_by_package.contains(&key) {
-            return;
-        }
-
-        let rows: Vec<BugRow> = match sqlx::query_as(
+        Self::fetch_package_bugs(
+            cache,
+            key,
+            source_package,
             "SELECT b.id, b.title, b.severity::text, b.done, b.forwarded, b.submitter, \
-                    (SELECT string_agg(t.tag, ', ') FROM bugs_tags t WHERE t.id = b.id) AS tags \
+                    (SELECT string_agg(t.tag, ', ') FROM bugs_tags t WHERE t.id = b.id) AS tags, \
+                    b.source, false AS archived, \
+                    (SELECT string_agg(m.merged_with::text, ', ') FROM bugs_merged_with m WHERE m.id = b.id) AS merged_with \
              FROM bugs b WHERE b.source = $1 \
              UNION ALL \
              SELECT b.id, b.title, b.severity::text, b.done, b.forwarded, b.submitter, \
-                    (SELECT string_agg(t.tag, ', ') FROM archived_bugs_tags t WHERE t.id = b.id) AS tags \
+                    (SELECT string_agg(t.tag, ', ') FROM archived_bugs_tags t WHERE t.id = b.id) AS tags, \
+                    b.source, true AS archived, \
+                    (SELECT string_agg(m.merged_with::text, ', ') FROM archived_bugs_merged_with m WHERE m.id = b.id) AS merged_with \
              FROM archived_bugs b WHERE b.source = $1 \
              ORDER BY id",
+            true,
         )
-        .bind(source_package)
-        .fetch_all(&*self.pool)
-        .await
-        {
-            Ok(rows) => rows,
-            Err(e) => {
-                tracing::warn!(source_package, error = %e, "UDD bug query failed");
-                self.last_udd_error = Some(e.to_string());
+        .await;
+    }
+
+    /// Cache the bugs that `query` returns for `package` under `key`.
+    ///
+    /// A fresh on-disk entry is used without querying UDD; an expired one
+    /// only when UDD can't be reached, in which case its bugs are marked
+    /// stale. With `overwrite` unset, details of bugs that are already
+    /// cached are kept. The cache lock is not held while the on-disk cache
+    /// or UDD are read.
+    async fn fetch_package_bugs(
+        cache: &SharedBugCache,
+        key: String,
+        package: &str,
+        query: &'static str,
+        overwrite: bool,
+    ) {
+        let (pool, disk) = {
+            let guard = cache.read().await;
+            if guard.bug_ids_by_package.contains(&key) {
                 return;
             }
+            (guard.pool.clone(), guard.package_disk_cache.clone())
         };
+        let bugs = async {
+            let udd = pool.get()?;
+            match sqlx::query_as::<_, BugRow>(query)
+                .bind(package)
+                .fetch_all(&udd)
+                .await
+            {
+                Ok(rows) => Some(
+                    rows.into_iter()
+                        .filter_map(|row| Some((u32::try_from(row.id).ok()?, row.into())))
+                        .collect::<Vec<(u32, CachedDebbugsBugDetails)>>(),
+                ),
+                Err(e) => {
+                    tracing::warn!(package, error = %e, "UDD bug query failed");
+                    pool.report_error(&e);
+                    None
+                }
+            }
+        };
+        let Some((bugs, stale)) = disk.fetch(&pool.url(), &key, bugs).await else {
+            return;
+        };
+        cache
+            .write()
+            .await
+            .insert_package_bugs(key, bugs, overwrite, stale);
+    }
 
-        let mut ids = Vec::new();
-        for row in rows {
-            let Some(id) = u32::try_from(row.id).ok() else {
-                continue;
-            };
+    fn insert_package_bugs(
+        &mut self,
+        key: String,
+        bugs: Vec<(u32, CachedDebbugsBugDetails)>,
+        overwrite: bool,
+        stale: bool,
+    ) {
+        let mut ids = Vec::with_capacity(bugs.len());
+        for (id, details) in bugs {
             ids.push(id);
-            self.bug_details_by_id.put(
-                id,
-                CachedDebbugsBugDetails {
-                    title: row.title,
-                    severity: row.severity,
-                    done: row.done.as_ref().is_some_and(|d| !d.is_empty()),
-                    tags: row.tags,
-                    forwarded: row.forwarded,
-                    originator: row.submitter,
-                },
-            );
+            if !overwrite && self.bug_details_by_id.contains(&id) {
+                continue;
+            }
+            self.bug_details_by_id.put(id, details);
+            if stale {
+                self.stale_bug_ids.insert(id);
+            } else {
+                self.stale_bug_ids.remove(&id);
+            }
         }
-
         self.bug_ids_by_package.put(key, ids);
     }
 
-    /// Return Debian bug summaries for a source `package` that match a decimal prefix.
-    pub async fn get_bug_summaries_with_prefix(
+    /// Return Debian bug summaries for a source `package` that match a
+    /// decimal prefix, from cache only: prefetch the package's bugs first.
+    pub fn get_bug_summaries_with_prefix(
         &mut self,
         package: &str,
         prefix: &str,
     ) -> Vec<DebbugsBugSummary> {
-        self.fetch_bugs_for_source_package(package).await;
-
         let normalized_prefix = prefix.trim();
         let key = format!("src:{}", package);
         // Snapshot the matching IDs to release the borrow on
@@ -110,6 +163,10 @@ impl BugCache {
                 tags: details.tags.clone(),
                 forwarded: details.forwarded.clone(),
                 originator: details.originator.clone(),
+                source: details.source.clone(),
+                archived: details.archived,
+                merged_with: details.merged_with.clone(),
+                stale: self.stale_bug_ids.contains(&id),
             },
             None => DebbugsBugSummary {
                 id,
@@ -119,6 +176,10 @@ impl BugCache {
                 tags: None,
                 forwarded: None,
                 originator: None,
+                source: None,
+                archived: false,
+                merged_with: Vec::new(),
+                stale: false,
             },
         }
     }
@@ -137,41 +198,48 @@ impl BugCache {
     /// Query UDD for a single bug without touching the cache. Returns the raw
     /// row so the caller can insert it after re-acquiring the lock.
     pub async fn query_bug_by_id(pool: &crate::udd::SharedPool, id: u32) -> Option<BugRow> {
+        let udd = pool.get()?;
         match sqlx::query_as(
             "SELECT b.id, b.title, b.severity::text, b.done, b.forwarded, b.submitter, \
-                    (SELECT string_agg(t.tag, ', ') FROM bugs_tags t WHERE t.id = b.id) AS tags \
+                    (SELECT string_agg(t.tag, ', ') FROM bugs_tags t WHERE t.id = b.id) AS tags, \
+                    b.source, false AS archived, \
+                    (SELECT string_agg(m.merged_with::text, ', ') FROM bugs_merged_with m WHERE m.id = b.id) AS merged_with \
              FROM bugs b WHERE b.id = $1 \
              UNION ALL \
              SELECT b.id, b.title, b.severity::text, b.done, b.forwarded, b.submitter, \
-                    (SELECT string_agg(t.tag, ', ') FROM archived_bugs_tags t WHERE t.id = b.id) AS tags \
+                    (SELECT string_agg(t.tag, ', ') FROM archived_bugs_tags t WHERE t.id = b.id) AS tags, \
+                    b.source, true AS archived, \
+                    (SELECT string_agg(m.merged_with::text, ', ') FROM archived_bugs_merged_with m WHERE m.id = b.id) AS merged_with \
              FROM archived_bugs b WHERE b.id = $1 \
              LIMIT 1",
         )
         .bind(id as i32)
-        .fetch_optional(&**pool)
+        .fetch_optional(&udd)
         .await
         {
             Ok(row) => row,
             Err(e) => {
                 tracing::warn!(id, error = %e, "UDD single bug query failed");
+                pool.report_error(&e);
                 None
             }
         }
     }
 
-    /// Insert a raw `BugRow` into the cache.
-    pub fn insert_bug_row(&mut self, id: u32, row: BugRow) {
-        self.bug_details_by_id.put(
-            id,
-            CachedDebbugsBugDetails {
-                title: row.title,
-                severity: row.severity,
-                done: row.done.as_ref().is_some_and(|d| !d.is_empty()),
-                tags: row.tags,
-                forwarded: row.forwarded,
-                originator: row.submitter,
-            },
-        );
+    /// Cache the details of bug `id`; `stale` if they are an expired on-disk
+    /// copy.
+    pub(super) fn insert_bug_details(
+        &mut self,
+        id: u32,
+        details: CachedDebbugsBugDetails,
+        stale: bool,
+    ) {
+        if stale {
+            self.stale_bug_ids.insert(id);
+        } else {
+            self.stale_bug_ids.remove(&id);
+        }
+        self.bug_details_by_id.put(id, details);
     }
 
     /// Count open bugs for a source package from cache only, without fetching.
@@ -201,78 +269,76 @@ impl BugCache {
         )
     }
 
+    /// Whether the cached bugs of a source package are an expired on-disk
+    /// copy, used because UDD could not be reached.
+    pub fn is_source_package_stale(&self, source_package: &str) -> bool {
+        self.is_package_stale(&format!("src:{}", source_package))
+    }
+
+    /// Whether the cached bugs of a binary package are an expired on-disk
+    /// copy, used because UDD could not be reached.
+    pub fn is_binary_package_stale(&self, binary_package: &str) -> bool {
+        self.is_package_stale(binary_package)
+    }
+
+    fn is_package_stale(&self, key: &str) -> bool {
+        self.bug_ids_by_package
+            .peek(key)
+            .is_some_and(|ids| ids.iter().any(|id| self.stale_bug_ids.contains(id)))
+    }
+
     /// Return whether bug data for a source package is already in the cache.
     pub fn is_source_package_cached(&self, source_package: &str) -> bool {
         let key = format!("src:{}", source_package);
         self.bug_ids_by_package.contains(&key)
     }
 
+    /// IDs of all the bugs of a source package, open or closed, from cache
+    /// only. Read-only: uses `peek` so the cache LRU order is unchanged.
+    ///
+    /// Returns `None` if the source package has not been fetched yet.
+    pub fn get_cached_source_package_bug_ids(&self, source_package: &str) -> Option<Vec<u32>> {
+        let key = format!("src:{}", source_package);
+        self.bug_ids_by_package.peek(&key).cloned()
+    }
+
     /// Pre-fetch open bug IDs and their details for a source package.
     ///
     /// Call this in the background so the data is cached before the user
     /// triggers completion.
-    pub async fn prefetch_bugs_for_package(&mut self, package: &str) {
-        self.fetch_bugs_for_source_package(package).await;
+    pub async fn prefetch_bugs_for_package(cache: &SharedBugCache, package: &str) {
+        Self::fetch_bugs_for_source_package(cache, package).await;
     }
 
     /// Fetch bugs filed against a binary package name from UDD and cache them.
-    async fn fetch_bugs_for_binary_package(&mut self, binary_package: &str) {
-        if self.bug_ids_by_package.contains(binary_package) {
-            return;
-        }
-
-        let rows: Vec<BugRow> = match sqlx::query_as(
+    async fn fetch_bugs_for_binary_package(cache: &SharedBugCache, binary_package: &str) {
+        // Don't overwrite previously-cached entries — bugs filed against a
+        // binary may already have been seen via the source package and
+        // shouldn't lose their (richer) record.
+        Self::fetch_package_bugs(
+            cache,
+            binary_package.to_string(),
+            binary_package,
             "SELECT b.id, b.title, b.severity::text, b.done, b.forwarded, b.submitter, \
-                    (SELECT string_agg(t.tag, ', ') FROM bugs_tags t WHERE t.id = b.id) AS tags \
+                    (SELECT string_agg(t.tag, ', ') FROM bugs_tags t WHERE t.id = b.id) AS tags, \
+                    b.source, false AS archived, \
+                    (SELECT string_agg(m.merged_with::text, ', ') FROM bugs_merged_with m WHERE m.id = b.id) AS merged_with \
              FROM bugs b WHERE b.package = $1 \
              UNION ALL \
              SELECT b.id, b.title, b.severity::text, b.done, b.forwarded, b.submitter, \
-                    (SELECT string_agg(t.tag, ', ') FROM archived_bugs_tags t WHERE t.id = b.id) AS tags \
+                    (SELECT string_agg(t.tag, ', ') FROM archived_bugs_tags t WHERE t.id = b.id) AS tags, \
+                    b.source, true AS archived, \
+                    (SELECT string_agg(m.merged_with::text, ', ') FROM archived_bugs_merged_with m WHERE m.id = b.id) AS merged_with \
              FROM archived_bugs b WHERE b.package = $1 \
              ORDER BY id",
+            false,
         )
-        .bind(binary_package)
-        .fetch_all(&*self.pool)
-        .await
-        {
-            Ok(rows) => rows,
-            Err(e) => {
-                tracing::warn!(binary_package, error = %e, "UDD binary bug query failed");
-                self.last_udd_error = Some(e.to_string());
-                return;
-            }
-        };
-
-        let mut ids = Vec::new();
-        for row in rows {
-            let Some(id) = u32::try_from(row.id).ok() else {
-                continue;
-            };
-            ids.push(id);
-            // Don't overwrite a previously-cached entry — bugs filed
-            // against a binary may already have been seen via the
-            // source package and shouldn't lose their (richer) record.
-            if !self.bug_details_by_id.contains(&id) {
-                self.bug_details_by_id.put(
-                    id,
-                    CachedDebbugsBugDetails {
-                        title: row.title,
-                        severity: row.severity,
-                        done: row.done.as_ref().is_some_and(|d| !d.is_empty()),
-                        tags: row.tags,
-                        forwarded: row.forwarded,
-                        originator: row.submitter,
-                    },
-                );
-            }
-        }
-
-        self.bug_ids_by_package.put(binary_package.to_string(), ids);
+        .await;
     }
 
     /// Pre-fetch bugs filed against a binary package name.
-    pub async fn prefetch_bugs_for_binary_package(&mut self, binary_package: &str) {
-        self.fetch_bugs_for_binary_package(binary_package).await;
+    pub async fn prefetch_bugs_for_binary_package(cache: &SharedBugCache, binary_package: &str) {
+        Self::fetch_bugs_for_binary_package(cache, binary_package).await;
     }
 
     #[cfg(test)]
@@ -294,6 +360,9 @@ impl BugCache {
                     tags: None,
                     forwarded: None,
                     originator: None,
+                    source: None,
+                    archived: false,
+                    merged_with: Vec::new(),
                 },
             );
         }
@@ -304,6 +373,36 @@ impl BugCache {
         );
     }
 
+    /// Keep bugs on disk in `dir`, and store bug `id` of `source_package`
+    /// there without caching it in memory.
+    #[cfg(test)]
+    pub(crate) async fn insert_disk_cached_bug(
+        &mut self,
+        dir: &std::path::Path,
+        id: u32,
+        source_package: &str,
+    ) {
+        self.bug_disk_cache =
+            crate::disk_cache::DiskCache::in_dir(dir.to_path_buf(), super::BUG_DISK_TTL);
+        self.bug_disk_cache
+            .store(
+                &self.pool.url(),
+                &id.to_string(),
+                &CachedDebbugsBugDetails {
+                    title: None,
+                    severity: None,
+                    done: false,
+                    tags: None,
+                    forwarded: None,
+                    originator: None,
+                    source: Some(source_package.to_string()),
+                    archived: false,
+                    merged_with: Vec::new(),
+                },
+            )
+            .await;
+    }
+
     #[cfg(test)]
     pub(crate) fn insert_cached_open_bugs_for_binary_package(
         &mut self,
@@ -323,6 +422,9 @@ impl BugCache {
                     tags: None,
                     forwarded: None,
                     originator: None,
+                    source: None,
+                    archived: false,
+                    merged_with: Vec::new(),
                 },
             );
         }
@@ -337,6 +439,7 @@ impl BugCache {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
 
     #[tokio::test]
     async fn test_get_bug_summaries_with_prefix_from_cache() {
@@ -350,7 +453,7 @@ mod tests {
             ],
         );
 
-        let summaries = cache.get_bug_summaries_with_prefix("foo", "1234").await;
+        let summaries = cache.get_bug_summaries_with_prefix("foo", "1234");
         assert_eq!(summaries.len(), 2);
         assert_eq!(summaries[0].id, 123456);
         assert_eq!(summaries[0].title.as_deref(), Some("Fix crash on startup"));
@@ -358,11 +461,54 @@ mod tests {
         assert_eq!(summaries[1].title, None);
     }
 
+    #[tokio::test]
+    async fn test_expired_disk_entry_used_offline() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = crate::udd::shared_pool();
+        pool.configure(crate::udd::DEFAULT_UDD_URL, true);
+        let mut cache = BugCache::new(pool);
+        cache.package_disk_cache =
+            crate::disk_cache::DiskCache::in_dir(dir.path().to_path_buf(), Duration::ZERO);
+        let details = CachedDebbugsBugDetails {
+            title: Some("Fix crash on startup".to_string()),
+            severity: None,
+            done: false,
+            tags: None,
+            forwarded: None,
+            originator: None,
+            source: None,
+            archived: false,
+            merged_with: Vec::new(),
+        };
+        cache
+            .package_disk_cache
+            .store(
+                crate::udd::DEFAULT_UDD_URL,
+                "src:foo",
+                &vec![(123456u32, details)],
+            )
+            .await;
+        let cache: SharedBugCache = std::sync::Arc::new(tokio::sync::RwLock::new(cache));
+
+        BugCache::prefetch_bugs_for_package(&cache, "foo").await;
+        let mut cache = cache.write().await;
+        let summaries = cache.get_bug_summaries_with_prefix("foo", "");
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].title.as_deref(), Some("Fix crash on startup"));
+        assert!(summaries[0].stale);
+        assert!(cache.is_source_package_stale("foo"));
+        assert_eq!(cache.get_cached_open_bug_count("foo"), Some(1));
+    }
+
     #[tokio::test]
     #[ignore] // requires network access to UDD
     async fn test_fetch_bugs_from_udd() {
-        let mut cache = BugCache::new(crate::udd::shared_pool());
-        let summaries = cache.get_bug_summaries_with_prefix("lintian", "").await;
+        let cache = crate::bugs::new_shared_bug_cache(crate::udd::shared_pool());
+        BugCache::prefetch_bugs_for_package(&cache, "lintian").await;
+        let summaries = cache
+            .write()
+            .await
+            .get_bug_summaries_with_prefix("lintian", "");
         assert!(!summaries.is_empty(), "lintian should have bugs in UDD");
         // Every summary should have a title
         assert!(
@@ -377,8 +523,13 @@ mod tests {
         let pool = crate::udd::shared_pool();
         // Pick a known open lintian bug; if it gets closed pick a different one.
         let summaries = {
-            let mut cache = BugCache::new(pool.clone());
-            cache.get_bug_summaries_with_prefix("lintian", "").await
+            let cache = crate::bugs::new_shared_bug_cache(pool.clone());
+            BugCache::prefetch_bugs_for_package(&cache, "lintian").await;
+            let summaries = cache
+                .write()
+                .await
+                .get_bug_summaries_with_prefix("lintian", "");
+            summaries
         };
         let open = summaries
             .iter()
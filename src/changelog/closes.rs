@@ -0,0 +1,424 @@
+//! Checks of the Debian bugs closed by `Closes: #NNN` in debian/changelog.
+//!
+//! The entries being prepared — the leading UNRELEASED ones, or else the
+//! latest entry — are checked against the bug data from UDD: a closed bug
+//! that belongs to a different package, is already closed or archived, or was
+//! already closed by an earlier entry is reported, and open merged and
+//! release-critical bugs get an information diagnostic so the uploader knows
+//! what the upload closes.
+
+use std::collections::{HashMap, HashSet};
+
+use debian_changelog::bugs::{iter_bug_refs, Bug};
+use debian_changelog::{ChangeLog, Entry, SyntaxKind};
+use rowan::ast::AstNode;
+use text_size::{TextRange, TextSize};
+use tower_lsp_server::ls_types::{Diagnostic, DiagnosticSeverity};
+
+use super::diagnostics::finding;
+use crate::bugs::{DebbugsBugSummary, SharedBugCache};
+use crate::position::Source;
+
+const OTHER_PACKAGE_CODE: &str = "bug-for-other-package";
+const ALREADY_CLOSED_CODE: &str = "bug-already-closed";
+const ARCHIVED_CODE: &str = "bug-archived";
+const CLOSED_TWICE_CODE: &str = "bug-closed-twice";
+const MERGED_CODE: &str = "bug-merged";
+const RELEASE_CRITICAL_CODE: &str = "release-critical-bug";
+
+/// Bug severities that make a bug release-critical.
+const RC_SEVERITIES: &[&str] = &["serious", "grave", "critical"];
+
+/// What is known about the Debian bugs a changelog refers to.
+#[derive(Debug, Default)]
+pub struct KnownBugs {
+    /// IDs of the bugs of the source package, if they have been fetched.
+    pub package_bugs: Option<HashSet<u32>>,
+    /// Details of the bugs closed by the entries being prepared.
+    pub summaries: HashMap<u32, DebbugsBugSummary>,
+}
+
+impl KnownBugs {
+    /// Gather the bug data for the Debian bugs `ids` closed in the changelog
+    /// of the source package `package`.
+    ///
+    /// Without `fetch` only the cache is consulted; with it the bug list of
+    /// the package and any bug not on it are fetched from UDD.
+    pub async fn load(cache: &SharedBugCache, package: &str, ids: &[u32], fetch: bool) -> Self {
+        if ids.is_empty() {
+            return Self::default();
+        }
+
+        if fetch {
+            // A no-op when the package's bugs are cached already.
+            crate::bugs::BugCache::prefetch_bugs_for_package(cache, package).await;
+        }
+        let package_bugs = cache
+            .read()
+            .await
+            .get_cached_source_package_bug_ids(package)
+            .map(|ids| ids.into_iter().collect::<HashSet<_>>());
+
+        let mut summaries = HashMap::new();
+        for &id in ids {
+            let summary = if fetch {
+                crate::bugs::debian_bug_summary(cache, id).await
+            } else {
+                cache.write().await.get_cached_debian_bug_summary(id)
+            };
+            if let Some(summary) = summary {
+                summaries.insert(id, summary);
+            }
+        }
+        Self {
+            package_bugs,
+            summaries,
+        }
+    }
+}
+
+/// The source package of `changelog` and the Debian bugs closed by the
+/// entries being prepared, which [`KnownBugs::load`] gathers the data for.
+pub fn pending_bugs(changelog: &ChangeLog) -> Option<(String, Vec<u32>)> {
+    let package = changelog.iter().next()?.package()?;
+    let ids = pending_entries(changelog)
+        .iter()
+        .flat_map(closed_bugs)
+        .map(|(id, _)| id)
+        .collect();
+    Some((package, ids))
+}
+
+/// The entries whose closed bugs are checked: the leading UNRELEASED ones,
+/// or the latest entry when it has been released.
+fn pending_entries(changelog: &ChangeLog) -> Vec<Entry> {
+    let unreleased: Vec<Entry> = changelog
+        .iter()
+        .take_while(|entry| entry.is_unreleased() == Some(true))
+        .collect();
+    if unreleased.is_empty() {
+        changelog.iter().take(1).collect()
+    } else {
+        unreleased
+    }
+}
+
+/// The Debian bugs closed by `entry`, with the range of each `#NNN`.
+fn closed_bugs(entry: &Entry) -> Vec<(u32, TextRange)> {
+    let mut bugs = Vec::new();
+    for token in entry
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|e| e.into_token())
+        .filter(|t| t.kind() == SyntaxKind::DETAIL)
+    {
+        let text = token.text();
+        let start = token.text_range().start();
+        for bug_ref in iter_bug_refs(text) {
+            let Bug::Debian(id) = bug_ref.bug else {
+                continue;
+            };
+            let ref_start = if text[..bug_ref.start].ends_with('#') {
+                bug_ref.start - 1
+            } else {
+                bug_ref.start
+            };
+            let range = TextRange::new(
+                start + TextSize::from(ref_start as u32),
+                start + TextSize::from(bug_ref.end as u32),
+            );
+            bugs.push((id, range));
+        }
+    }
+    bugs
+}
+
+fn format_bug_list(ids: &[u32]) -> String {
+    ids.iter()
+        .map(|id| format!("#{}", id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Diagnostics for the Debian bugs closed by the entries being prepared.
+pub fn get_diagnostics(
+    parsed: &debian_changelog::Parse<ChangeLog>,
+    src: Source<'_>,
+    known: &KnownBugs,
+) -> Vec<Diagnostic> {
+    let changelog = parsed.tree();
+    let Some(package) = changelog.iter().next().and_then(|e| e.package()) else {
+        return Vec::new();
+    };
+    let pending = pending_entries(&changelog);
+
+    // Bugs closed by the entries that are not being prepared, with the
+    // version that closed them.
+    let mut closed_before: HashMap<u32, String> = HashMap::new();
+    for entry in changelog.iter().skip(pending.len()) {
+        let version = entry.version().map(|v| v.to_string()).unwrap_or_default();
+        for (id, _) in closed_bugs(&entry) {
+            closed_before.entry(id).or_insert_with(|| version.clone());
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    let mut seen = HashSet::new();
+    for entry in &pending {
+        let unreleased = entry.is_unreleased() == Some(true);
+        for (id, range) in closed_bugs(entry) {
+            let summary = known.summaries.get(&id);
+            let open = summary.map(|s| !s.done);
+            let mut report = |severity, code, message| {
+                diagnostics.push(finding(src, range, severity, code, message).diagnostic);
+            };
+
+            if !seen.insert(id) {
+                report(
+                    DiagnosticSeverity::WARNING,
+                    CLOSED_TWICE_CODE,
+                    format!("Bug #{} is closed more than once", id),
+                );
+            } else if let Some(version) = closed_before.get(&id) {
+                // A bug that is open again was reopened, so closing it
+                // again is expected.
+                if open != Some(true) {
+                    report(
+                        DiagnosticSeverity::WARNING,
+                        CLOSED_TWICE_CODE,
+                        format!("Bug #{} was already closed in version {}", id, version),
+                    );
+                }
+            }
+
+            match summary.and_then(|s| s.source.as_deref()) {
+                Some(source) if source != package => report(
+                    DiagnosticSeverity::WARNING,
+                    OTHER_PACKAGE_CODE,
+                    format!("Bug #{} is filed against {}, not {}", id, source, package),
+                ),
+                Some(_) => {}
+                None => {
+                    if known
+                        .package_bugs
+                        .as_ref()
+                        .is_some_and(|bugs| !bugs.contains(&id))
+                    {
+                        report(
+                            DiagnosticSeverity::WARNING,
+                            OTHER_PACKAGE_CODE,
+                            format!("Bug #{} is not a bug of {}", id, package),
+                        );
+                    }
+                }
+            }
+
+            let Some(summary) = summary else {
+                continue;
+            };
+            if summary.done {
+                // Bugs closed by a released entry are expected to be done.
+                if unreleased {
+                    if summary.archived {
+                        report(
+                            DiagnosticSeverity::WARNING,
+                            ARCHIVED_CODE,
+                            format!("Bug #{} is already closed and archived", id),
+                        );
+                    } else {
+                        report(
+                            DiagnosticSeverity::WARNING,
+                            ALREADY_CLOSED_CODE,
+                            format!("Bug #{} is already closed", id),
+                        );
+                    }
+                }
+                continue;
+            }
+            if !summary.merged_with.is_empty() {
+                report(
+                    DiagnosticSeverity::INFORMATION,
+                    MERGED_CODE,
+                    format!(
+                        "Bug #{} is merged with {}, which will be closed as well",
+                        id,
+                        format_bug_list(&summary.merged_with)
+                    ),
+                );
+            }
+            if let Some(severity) = summary
+                .severity
+                .as_deref()
+                .filter(|s| RC_SEVERITIES.contains(s))
+            {
+                report(
+                    DiagnosticSeverity::INFORMATION,
+                    RELEASE_CRITICAL_CODE,
+                    format!("Bug #{} is release-critical (severity {})", id, severity),
+                );
+            }
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+    use tower_lsp_server::ls_types::NumberOrString;
+
+    const CHANGELOG: &str = "foo (1.1-1) UNRELEASED; urgency=medium\n\n  * Fix things. (Closes: #100, #200)\n\n -- Foo <foo@example.com>  Tue, 02 Jan 2024 00:00:00 +0000\n\nfoo (1.0-1) unstable; urgency=medium\n\n  * Initial release. (Closes: #50)\n\n -- Foo <foo@example.com>  Mon, 01 Jan 2024 00:00:00 +0000\n";
+
+    fn summary(id: u32) -> DebbugsBugSummary {
+        DebbugsBugSummary {
+            id,
+            title: None,
+            severity: Some("normal".to_string()),
+            done: false,
+            tags: None,
+            forwarded: None,
+            originator: None,
+            source: Some("foo".to_string()),
+            archived: false,
+            merged_with: Vec::new(),
+            stale: false,
+        }
+    }
+
+    fn known(summaries: Vec<DebbugsBugSummary>) -> KnownBugs {
+        KnownBugs {
+            package_bugs: Some(summaries.iter().map(|s| s.id).collect()),
+            summaries: summaries.into_iter().map(|s| (s.id, s)).collect(),
+        }
+    }
+
+    fn diagnose(text: &str, known: &KnownBugs) -> Vec<(String, String)> {
+        let parsed = ChangeLog::parse(text);
+        let idx = LineIndex::new(text);
+        let src = Source::new(text, &idx);
+        get_diagnostics(&parsed, src, known)
+            .into_iter()
+            .map(|d| {
+                let Some(NumberOrString::String(code)) = d.code else {
+                    panic!("missing code");
+                };
+                let range = src.try_lsp_range_to_text_range(&d.range).unwrap();
+                (code, text[range].to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_open_bugs_of_package() {
+        assert_eq!(
+            diagnose(CHANGELOG, &known(vec![summary(100), summary(200)])),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_without_bug_data() {
+        assert_eq!(diagnose(CHANGELOG, &KnownBugs::default()), vec![]);
+    }
+
+    #[test]
+    fn test_other_package() {
+        let mut other = summary(200);
+        other.source = Some("bar".to_string());
+        let mut known = known(vec![summary(100), other]);
+        assert_eq!(
+            diagnose(CHANGELOG, &known),
+            vec![(OTHER_PACKAGE_CODE.to_string(), "#200".to_string())]
+        );
+
+        known.summaries.remove(&200);
+        known.package_bugs.as_mut().unwrap().remove(&200);
+        assert_eq!(
+            diagnose(CHANGELOG, &known),
+            vec![(OTHER_PACKAGE_CODE.to_string(), "#200".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_already_closed_and_archived() {
+        let mut done = summary(100);
+        done.done = true;
+        let mut archived = summary(200);
+        archived.done = true;
+        archived.archived = true;
+        assert_eq!(
+            diagnose(CHANGELOG, &known(vec![done.clone(), archived.clone()])),
+            vec![
+                (ALREADY_CLOSED_CODE.to_string(), "#100".to_string()),
+                (ARCHIVED_CODE.to_string(), "#200".to_string()),
+            ]
+        );
+
+        // Once released, the bugs the entry closes are expected to be done.
+        let released = CHANGELOG.replacen("UNRELEASED", "unstable", 1);
+        assert_eq!(diagnose(&released, &known(vec![done, archived])), vec![]);
+    }
+
+    #[test]
+    fn test_closed_twice() {
+        let text = CHANGELOG.replace("#200", "#50, #100");
+        let mut reopened = summary(50);
+        reopened.done = false;
+        assert_eq!(
+            diagnose(&text, &known(vec![summary(100)])),
+            vec![
+                (CLOSED_TWICE_CODE.to_string(), "#50".to_string()),
+                (OTHER_PACKAGE_CODE.to_string(), "#50".to_string()),
+                (CLOSED_TWICE_CODE.to_string(), "#100".to_string()),
+            ]
+        );
+        // A reopened bug may be closed again.
+        assert_eq!(
+            diagnose(&text, &known(vec![summary(100), reopened])),
+            vec![(CLOSED_TWICE_CODE.to_string(), "#100".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_merged_and_release_critical() {
+        let mut merged = summary(100);
+        merged.merged_with = vec![101, 102];
+        let mut rc = summary(200);
+        rc.severity = Some("serious".to_string());
+        let known = known(vec![merged, rc]);
+        assert_eq!(
+            diagnose(CHANGELOG, &known),
+            vec![
+                (MERGED_CODE.to_string(), "#100".to_string()),
+                (RELEASE_CRITICAL_CODE.to_string(), "#200".to_string()),
+            ]
+        );
+        let text = CHANGELOG.replacen("UNRELEASED", "unstable", 1);
+        assert_eq!(diagnose(&text, &known).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_load_fetches_bugs_missing_from_the_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = crate::udd::shared_pool();
+        pool.configure(crate::udd::DEFAULT_UDD_URL, true);
+        let mut cache = crate::bugs::BugCache::new(pool);
+        cache.insert_cached_open_bugs_for_package("foo", vec![(100, None)]);
+        // Filed against another package, so not in the prefetched list.
+        cache.insert_disk_cached_bug(dir.path(), 200, "bar").await;
+        let cache: SharedBugCache = std::sync::Arc::new(tokio::sync::RwLock::new(cache));
+
+        let known = KnownBugs::load(&cache, "foo", &[100, 200], false).await;
+        assert_eq!(known.package_bugs, Some(HashSet::from([100])));
+        assert!(known.summaries.contains_key(&100));
+        assert!(!known.summaries.contains_key(&200));
+
+        let known = KnownBugs::load(&cache, "foo", &[100, 200], true).await;
+        assert_eq!(
+            known.summaries[&200].source.as_deref(),
+            Some("bar"),
+            "a bug missing from the package's list is looked up"
+        );
+    }
+}
@@ -73,12 +73,14 @@ pub fn get_completions(
 /// Returns `None` when the cursor is not in bug-number context. When the
 /// tracker cache is cold the local-only completions are returned immediately
 /// with `is_incomplete = true` so the client re-requests once the background
-/// prefetch has warmed the cache.
+/// prefetch has warmed the cache. Only local completions are offered for a
+/// tracker whose lookups are turned off in `settings`.
 pub async fn get_async_bug_completions(
     parse: &debian_changelog::Parse<debian_changelog::ChangeLog>,
     src: Source<'_>,
     position: Position,
     bug_cache: &SharedBugCache,
+    settings: &crate::settings::Settings,
 ) -> Option<(Vec<CompletionItem>, bool)> {
     // Keep all CST-backed values in a short scope and drop them before await
     // so this future remains Send for tower-lsp.
@@ -104,6 +106,13 @@ pub async fn get_async_bug_completions(
     let Some(package_name) = package_name else {
         return Some((local, false));
     };
+    let remote_enabled = match tracker {
+        BugTracker::Debian => settings.udd,
+        BugTracker::Launchpad => settings.launchpad_enabled(),
+    };
+    if !remote_enabled {
+        return Some((local, false));
+    }
 
     // Check whether the tracker cache is already warm for this package.
     // If cold, return local results immediately and let the client re-request
@@ -126,8 +135,7 @@ pub async fn get_async_bug_completions(
             let mut summaries = bug_cache
                 .write()
                 .await
-                .get_bug_summaries_with_prefix(&package_name, &value_prefix)
-                .await;
+                .get_bug_summaries_with_prefix(&package_name, &value_prefix);
             // Sort: open bugs first, then by bug ID descending (highest/newest first).
             summaries.sort_by(|a, b| a.done.cmp(&b.done).then(b.id.cmp(&a.id)));
             summaries
@@ -938,6 +946,7 @@ foo (1.0-1) unstable; urgency=medium
             Source::new(text, &idx),
             position_at(text, offset),
             &bug_cache,
+            &crate::settings::Settings::default(),
         )
         .await
         .expect("bug context should return Some");
@@ -1009,6 +1018,7 @@ foo (1.0-1) unstable; urgency=medium
             Source::new(text, &idx),
             position_at(text, offset),
             &bug_cache,
+            &crate::settings::Settings::default(),
         )
         .await
         .expect("bug context should return Some");
@@ -1062,6 +1072,7 @@ foo (1.0-1) unstable; urgency=medium
             Source::new(text, &idx),
             position_at(text, offset),
             &bug_cache,
+            &crate::settings::Settings::default(),
         )
         .await
         .expect("bug context should return Some");
@@ -1087,6 +1098,7 @@ foo (1.0-1) unstable; urgency=medium
             Source::new(text, &idx),
             position_at(text, offset),
             &bug_cache,
+            &crate::settings::Settings::default(),
         )
         .await;
         assert!(completions.is_none());
@@ -0,0 +1,408 @@
+//! Cross-file checks of the latest changelog version.
+//!
+//! The version in `debian/changelog` has to agree with the rest of the
+//! packaging: a `3.0 (native)` package has no Debian revision and a
+//! `3.0 (quilt)` one needs one, a `+dfsg`-style repack suffix goes together
+//! with `Files-Excluded` in `debian/copyright`, and `debian/watch` has to strip
+//! that suffix again before comparing against upstream. Only the topmost entry
+//! is checked; older versions are history.
+
+use debian_changelog::ChangeLog;
+use debversion::Version;
+use tower_lsp_server::ls_types::{
+    CodeActionOrCommand, Diagnostic, DiagnosticSeverity, TextEdit, Uri,
+};
+
+use super::diagnostics::{finding, fix_actions, Finding};
+use crate::position::Source;
+use crate::workspace::Workspace;
+
+const NATIVE_CODE: &str = "native-version-with-revision";
+const NON_NATIVE_CODE: &str = "non-native-version-without-revision";
+const REPACK_CODE: &str = "repack-suffix-without-files-excluded";
+const MISSING_REPACK_CODE: &str = "files-excluded-without-repack-suffix";
+const WATCH_CODE: &str = "watch-keeps-repack-suffix";
+
+/// Suffix used when adding a repack suffix to the upstream version.
+const DEFAULT_REPACK_SUFFIX: &str = "+dfsg";
+
+/// What the other packaging files of a package say about its version.
+#[derive(Debug, Default)]
+pub struct PackageFiles {
+    /// The contents of `debian/source/format`.
+    pub source_format: Option<String>,
+    /// Whether the machine-readable `debian/copyright` has `Files-Excluded`;
+    /// `None` without such a file.
+    pub files_excluded: Option<bool>,
+    /// Whether `debian/watch` strips a repack suffix with `dversionmangle` or
+    /// `repacksuffix`; `None` without a watch file.
+    pub watch_strips_repack: Option<bool>,
+}
+
+impl PackageFiles {
+    /// Read the sibling packaging files of `changelog_uri` from the workspace.
+    pub fn load(workspace: &Workspace, changelog_uri: &Uri) -> Self {
+        let mut files = Self::default();
+        let Some(debian_dir) = changelog_uri.as_str().strip_suffix("changelog") else {
+            return files;
+        };
+        for (uri, file) in workspace.files() {
+            match uri.as_str().strip_prefix(debian_dir) {
+                Some("source/format") => {
+                    files.source_format = Some(workspace.source_text(file).trim().to_string());
+                }
+                Some("copyright") => {
+                    let header = workspace.get_parsed_copyright(file).tree().header();
+                    files.files_excluded = header
+                        .filter(|h| h.as_deb822().get("Format").is_some())
+                        .map(|h| {
+                            h.as_deb822()
+                                .get("Files-Excluded")
+                                .is_some_and(|v| !v.trim().is_empty())
+                        });
+                }
+                Some("watch") => {
+                    let watch = workspace.get_parsed_watch(file).to_watch_file();
+                    files.watch_strips_repack =
+                        Some(watch.entries().any(|e| {
+                            e.has_option("dversionmangle") || e.has_option("repacksuffix")
+                        }));
+                }
+                _ => {}
+            }
+        }
+        files
+    }
+
+    fn is_native(&self) -> Option<bool> {
+        match self.source_format.as_deref()? {
+            "3.0 (native)" => Some(true),
+            "3.0 (quilt)" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// The byte offset where a repack suffix such as `+dfsg1` or `~ds` starts in
+/// an upstream version.
+fn repack_suffix_start(upstream: &str) -> Option<usize> {
+    upstream.rmatch_indices(['+', '~']).find_map(|(i, _)| {
+        let rest = &upstream[i + 1..];
+        ["dfsg", "ds", "repack"]
+            .iter()
+            .any(|word| {
+                rest.strip_prefix(word)
+                    .is_some_and(|tail| tail.chars().all(|c| c.is_ascii_digit() || c == '.'))
+            })
+            .then_some(i)
+    })
+}
+
+fn with_upstream(version: &Version, upstream_version: String) -> Version {
+    Version {
+        upstream_version,
+        ..version.clone()
+    }
+}
+
+fn consistency_findings(
+    parsed: &debian_changelog::Parse<ChangeLog>,
+    src: Source<'_>,
+    files: &PackageFiles,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let Some(entry) = parsed.tree().iter().next() else {
+        return findings;
+    };
+    let (Some(version), Some(range)) = (entry.version(), entry.version_range()) else {
+        return findings;
+    };
+    let lsp_range = src.text_range_to_lsp_range(range);
+    let replace = |new: Version| {
+        (
+            format!("Change version to {}", new),
+            vec![TextEdit {
+                range: lsp_range,
+                new_text: new.to_string(),
+            }],
+        )
+    };
+
+    let native = files.is_native();
+    match (native, &version.debian_revision) {
+        (Some(true), Some(_)) => {
+            let (title, edits) = replace(Version {
+                debian_revision: None,
+                ..version.clone()
+            });
+            findings.push(
+                finding(
+                    src,
+                    range,
+                    DiagnosticSeverity::ERROR,
+                    NATIVE_CODE,
+                    format!(
+                        "Version {} has a Debian revision, but the source format is 3.0 (native)",
+                        version
+                    ),
+                )
+                .with_fix(title, edits),
+            );
+        }
+        (Some(false), None) => {
+            let (title, edits) = replace(Version {
+                debian_revision: Some("1".to_string()),
+                ..version.clone()
+            });
+            findings.push(
+                finding(
+                    src,
+                    range,
+                    DiagnosticSeverity::ERROR,
+                    NON_NATIVE_CODE,
+                    format!(
+                        "Version {} has no Debian revision, but the source format is 3.0 (quilt)",
+                        version
+                    ),
+                )
+                .with_fix(title, edits),
+            );
+        }
+        _ => {}
+    }
+
+    let upstream = &version.upstream_version;
+    match repack_suffix_start(upstream) {
+        Some(start) => {
+            let suffix = &upstream[start..];
+            if files.files_excluded == Some(false) {
+                let (title, edits) =
+                    replace(with_upstream(&version, upstream[..start].to_string()));
+                findings.push(
+                    finding(
+                        src,
+                        range,
+                        DiagnosticSeverity::WARNING,
+                        REPACK_CODE,
+                        format!(
+                            "Version has the repack suffix '{}', but debian/copyright has no Files-Excluded field",
+                            suffix
+                        ),
+                    )
+                    .with_fix(title, edits),
+                );
+            }
+            if files.watch_strips_repack == Some(false) {
+                findings.push(finding(
+                    src,
+                    range,
+                    DiagnosticSeverity::WARNING,
+                    WATCH_CODE,
+                    format!(
+                        "debian/watch does not strip the repack suffix '{}' with dversionmangle or repacksuffix",
+                        suffix
+                    ),
+                ));
+            }
+        }
+        None => {
+            if files.files_excluded == Some(true) && native != Some(true) {
+                let (title, edits) = replace(with_upstream(
+                    &version,
+                    format!("{}{}", upstream, DEFAULT_REPACK_SUFFIX),
+                ));
+                findings.push(
+                    finding(
+                        src,
+                        range,
+                        DiagnosticSeverity::INFORMATION,
+                        MISSING_REPACK_CODE,
+                        "debian/copyright has Files-Excluded, but the version has no repack suffix such as +dfsg".to_string(),
+                    )
+                    .with_fix(title, edits),
+                );
+            }
+        }
+    }
+
+    findings
+}
+
+/// Diagnostics for a latest changelog version that disagrees with the other
+/// packaging files.
+pub fn get_diagnostics(
+    parsed: &debian_changelog::Parse<ChangeLog>,
+    src: Source<'_>,
+    files: &PackageFiles,
+) -> Vec<Diagnostic> {
+    consistency_findings(parsed, src, files)
+        .into_iter()
+        .map(|f| f.diagnostic)
+        .collect()
+}
+
+/// Quick fixes changing the latest version to agree with the other packaging
+/// files.
+///
+/// When `diagnostics` is non-empty, only fixes for those diagnostics are
+/// returned.
+pub fn get_code_actions(
+    uri: &Uri,
+    parsed: &debian_changelog::Parse<ChangeLog>,
+    src: Source<'_>,
+    files: &PackageFiles,
+    diagnostics: &[Diagnostic],
+) -> Vec<CodeActionOrCommand> {
+    fix_actions(uri, consistency_findings(parsed, src, files), diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+    use tower_lsp_server::ls_types::NumberOrString;
+
+    fn changelog(version: &str) -> String {
+        format!(
+            "foo ({}) unstable; urgency=medium\n\n  * Change.\n\n -- Foo <foo@example.com>  Mon, 01 Jan 2024 00:00:00 +0000\n",
+            version
+        )
+    }
+
+    fn check(version: &str, files: &PackageFiles) -> Vec<String> {
+        let text = changelog(version);
+        let parsed = ChangeLog::parse(&text);
+        let idx = LineIndex::new(&text);
+        get_diagnostics(&parsed, Source::new(&text, &idx), files)
+            .into_iter()
+            .map(|d| match d.code {
+                Some(NumberOrString::String(code)) => code,
+                _ => panic!("missing code"),
+            })
+            .collect()
+    }
+
+    fn fix_titles(version: &str, files: &PackageFiles) -> Vec<String> {
+        let text = changelog(version);
+        let parsed = ChangeLog::parse(&text);
+        let idx = LineIndex::new(&text);
+        let uri: Uri = str::parse("file:///debian/changelog").unwrap();
+        get_code_actions(&uri, &parsed, Source::new(&text, &idx), files, &[])
+            .into_iter()
+            .map(|a| match a {
+                CodeActionOrCommand::CodeAction(a) => a.title,
+                CodeActionOrCommand::Command(c) => c.title,
+            })
+            .collect()
+    }
+
+    fn format(source_format: &str) -> PackageFiles {
+        PackageFiles {
+            source_format: Some(source_format.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_repack_suffix_start() {
+        assert_eq!(repack_suffix_start("1.2+dfsg"), Some(3));
+        assert_eq!(repack_suffix_start("1.2+dfsg1"), Some(3));
+        assert_eq!(repack_suffix_start("1.2~ds.1"), Some(3));
+        assert_eq!(repack_suffix_start("1.2+git20240101+repack"), Some(15));
+        assert_eq!(repack_suffix_start("1.2+git20240101"), None);
+        assert_eq!(repack_suffix_start("1.2+dsx"), None);
+    }
+
+    #[test]
+    fn test_native_format() {
+        let files = format("3.0 (native)");
+        assert_eq!(check("1.2-1", &files), vec![NATIVE_CODE]);
+        assert_eq!(
+            fix_titles("1:1.2-1", &files),
+            vec!["Change version to 1:1.2"]
+        );
+        assert_eq!(check("1.2", &files), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_quilt_format() {
+        let files = format("3.0 (quilt)");
+        assert_eq!(check("1.2", &files), vec![NON_NATIVE_CODE]);
+        assert_eq!(fix_titles("1.2", &files), vec!["Change version to 1.2-1"]);
+        assert_eq!(check("1.2-1", &files), Vec::<String>::new());
+        // Format 1.0 allows both.
+        assert_eq!(check("1.2", &format("1.0")), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_repack_suffix_and_copyright() {
+        let files = PackageFiles {
+            files_excluded: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(check("1.2+dfsg-1", &files), vec![REPACK_CODE]);
+        assert_eq!(
+            fix_titles("1.2+dfsg-1", &files),
+            vec!["Change version to 1.2-1"]
+        );
+
+        let files = PackageFiles {
+            files_excluded: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(check("1.2+dfsg-1", &files), Vec::<String>::new());
+        assert_eq!(check("1.2-1", &files), vec![MISSING_REPACK_CODE]);
+        assert_eq!(
+            fix_titles("1.2-1", &files),
+            vec!["Change version to 1.2+dfsg-1"]
+        );
+
+        // No machine-readable copyright file: nothing to compare against.
+        assert_eq!(
+            check("1.2+dfsg-1", &PackageFiles::default()),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_repack_suffix_and_watch() {
+        let files = PackageFiles {
+            watch_strips_repack: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(check("1.2+ds-1", &files), vec![WATCH_CODE]);
+        assert_eq!(fix_titles("1.2+ds-1", &files), Vec::<String>::new());
+        assert_eq!(check("1.2-1", &files), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_load_package_files() {
+        let mut workspace = Workspace::new();
+        let changelog_uri: Uri = str::parse("file:///pkg/debian/changelog").unwrap();
+        workspace.update_file(changelog_uri.clone(), changelog("1.2+dfsg-1"));
+        workspace.update_file(
+            str::parse("file:///pkg/debian/source/format").unwrap(),
+            "3.0 (quilt)\n".to_string(),
+        );
+        workspace.update_file(
+            str::parse("file:///pkg/debian/copyright").unwrap(),
+            "Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/\nFiles-Excluded: vendor/*\n\nFiles: *\nCopyright: 2024 Foo\nLicense: MIT\n".to_string(),
+        );
+        workspace.update_file(
+            str::parse("file:///pkg/debian/watch").unwrap(),
+            "version=4\nopts=dversionmangle=auto https://example.com/ foo-(.*)\\.tar\\.gz\n"
+                .to_string(),
+        );
+        // A different package's files are ignored.
+        workspace.update_file(
+            str::parse("file:///other/debian/source/format").unwrap(),
+            "3.0 (native)\n".to_string(),
+        );
+
+        let files = PackageFiles::load(&workspace, &changelog_uri);
+        assert_eq!(files.source_format.as_deref(), Some("3.0 (quilt)"));
+        assert_eq!(files.files_excluded, Some(true));
+        assert_eq!(files.watch_strips_repack, Some(true));
+        assert_eq!(check("1.2+dfsg-1", &files), Vec::<String>::new());
+    }
+}
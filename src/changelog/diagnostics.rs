@@ -0,0 +1,792 @@
+//! Structural diagnostics for debian/changelog files.
+//!
+//! Checks the parsed changelog for problems that `dpkg-parsechangelog` and
+//! lintian complain about: versions and dates that don't descend, malformed
+//! signature lines, unknown target distributions, UNRELEASED entries below
+//! released ones, missing blank lines and overlong change lines. Where the fix
+//! is mechanical (spacing, blank lines, day of week, rewrapping, misspelled
+//! distributions) a quick fix is offered through [`get_code_actions`].
+
+use chrono::{DateTime, FixedOffset, Utc};
+use debian_changelog::{Entry, SyntaxKind};
+use rowan::ast::AstNode;
+use text_size::{TextRange, TextSize};
+use tower_lsp_server::ls_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Diagnostic, DiagnosticSeverity,
+    NumberOrString, TextEdit, Uri, WorkspaceEdit,
+};
+
+use crate::position::Source;
+use crate::text::edit_distance;
+
+const VERSION_CODE: &str = "version-not-decreasing";
+const DATE_ORDER_CODE: &str = "date-out-of-order";
+const FUTURE_DATE_CODE: &str = "date-in-future";
+const MAINTAINER_CODE: &str = "malformed-maintainer-line";
+const DISTRIBUTION_CODE: &str = "unknown-distribution";
+const UNRELEASED_CODE: &str = "unreleased-below-released";
+const BLANK_LINE_CODE: &str = "missing-blank-line";
+const LINE_LENGTH_CODE: &str = "line-too-long";
+
+/// Maximum width of a change line, as enforced by lintian.
+const MAX_LINE_WIDTH: usize = 80;
+
+/// Suffixes that turn a suite into one of its update or backport pockets.
+const POCKET_SUFFIXES: &[&str] = &[
+    "-backports-sloppy",
+    "-backports",
+    "-proposed-updates",
+    "-security",
+    "-updates",
+    "-lts",
+];
+
+/// A mechanical fix for a finding.
+struct Fix {
+    title: String,
+    edits: Vec<TextEdit>,
+}
+
+/// A diagnostic together with its quick fix, if there is one.
+pub(super) struct Finding {
+    pub(super) diagnostic: Diagnostic,
+    fix: Option<Fix>,
+}
+
+pub(super) fn finding(
+    src: Source<'_>,
+    range: TextRange,
+    severity: DiagnosticSeverity,
+    code: &str,
+    message: String,
+) -> Finding {
+    Finding {
+        diagnostic: Diagnostic {
+            range: src.text_range_to_lsp_range(range),
+            severity: Some(severity),
+            code: Some(NumberOrString::String(code.to_string())),
+            source: Some("debian-lsp".to_string()),
+            message,
+            ..Default::default()
+        },
+        fix: None,
+    }
+}
+
+impl Finding {
+    pub(super) fn with_fix(mut self, title: String, edits: Vec<TextEdit>) -> Self {
+        self.fix = Some(Fix { title, edits });
+        self
+    }
+}
+
+/// The range of a node without its trailing newline.
+fn line_range<L: rowan::Language>(node: &rowan::SyntaxNode<L>) -> TextRange {
+    let range = node.text_range();
+    let text = node.text().to_string();
+    let trimmed = text.trim_end_matches('\n').len();
+    TextRange::at(range.start(), TextSize::from(trimmed as u32))
+}
+
+fn insert_blank_line(src: Source<'_>, offset: TextSize) -> Vec<TextEdit> {
+    vec![TextEdit {
+        range: src.text_range_to_lsp_range(TextRange::empty(offset)),
+        new_text: "\n".to_string(),
+    }]
+}
+
+/// The distribution tokens of an entry header, with their ranges.
+fn distribution_tokens(entry: &Entry) -> Vec<(String, TextRange)> {
+    let Some(header) = entry.header() else {
+        return Vec::new();
+    };
+    header
+        .syntax()
+        .children()
+        .filter(|n| n.kind() == SyntaxKind::DISTRIBUTIONS)
+        .flat_map(|n| n.children_with_tokens())
+        .filter_map(|t| t.into_token())
+        .filter(|t| t.kind() == SyntaxKind::IDENTIFIER)
+        .map(|t| (t.text().to_string(), t.text_range()))
+        .collect()
+}
+
+fn is_known_distribution(name: &str, known: &[String]) -> bool {
+    if known.iter().any(|k| k == name) {
+        return true;
+    }
+    POCKET_SUFFIXES.iter().any(|suffix| {
+        name.strip_suffix(suffix)
+            .is_some_and(|base| known.iter().any(|k| k == base))
+    })
+}
+
+/// The known distribution an unknown `name` was probably meant to be.
+fn distribution_suggestion<'a>(name: &str, known: &'a [String]) -> Option<&'a str> {
+    let lower = name.to_lowercase();
+    known
+        .iter()
+        .find(|k| k.to_lowercase() == lower)
+        .or_else(|| {
+            if name.len() < 5 {
+                return None;
+            }
+            known
+                .iter()
+                .find(|k| edit_distance(&k.to_lowercase(), &lower) == 1)
+        })
+        .map(|k| k.as_str())
+}
+
+fn check_distributions(entry: &Entry, src: Source<'_>, findings: &mut Vec<Finding>) {
+    // Only Debian suites are known; don't flag Ubuntu uploads.
+    if !crate::distros::has_distro_info()
+        || entry
+            .version()
+            .is_some_and(|v| v.to_string().contains("ubuntu"))
+    {
+        return;
+    }
+    let known = crate::distros::get_all_distributions();
+    for (name, range) in distribution_tokens(entry) {
+        if is_known_distribution(&name, known) {
+            continue;
+        }
+        let mut f = finding(
+            src,
+            range,
+            DiagnosticSeverity::WARNING,
+            DISTRIBUTION_CODE,
+            format!("Unknown distribution '{}'", name),
+        );
+        if let Some(suggestion) = distribution_suggestion(&name, known) {
+            f = f.with_fix(
+                format!("Replace '{}' with '{}'", name, suggestion),
+                vec![TextEdit {
+                    range: src.text_range_to_lsp_range(range),
+                    new_text: suggestion.to_string(),
+                }],
+            );
+        }
+        findings.push(f);
+    }
+}
+
+/// Parse an RFC 2822 date, returning the corrected date text if only the day
+/// of the week is wrong.
+fn check_date(date: &str) -> Result<(), Option<String>> {
+    if DateTime::parse_from_rfc2822(date).is_ok() {
+        return Ok(());
+    }
+    let Some((_, rest)) = date.split_once(", ") else {
+        return Err(None);
+    };
+    match DateTime::parse_from_rfc2822(rest) {
+        Ok(dt) => Err(Some(dt.format("%a, %d %b %Y %H:%M:%S %z").to_string())),
+        Err(_) => Err(None),
+    }
+}
+
+/// Check the ` -- Name <email>  date` signature line of an entry.
+fn check_footer(entry: &Entry, src: Source<'_>, findings: &mut Vec<Finding>) {
+    let Some(footer) = entry.footer() else {
+        return;
+    };
+    let node = footer.syntax();
+    let whole = line_range(node);
+    let text = node.text().to_string();
+    let line = text.trim_end_matches('\n');
+    let Some(rest) = line.strip_prefix(" -- ") else {
+        return;
+    };
+    let start = whole.start() + TextSize::from(4);
+    let at = |offset: usize, len: usize| {
+        TextRange::at(
+            start + TextSize::from(offset as u32),
+            TextSize::from(len as u32),
+        )
+    };
+    let malformed = |range: TextRange, message: &str| {
+        finding(
+            src,
+            range,
+            DiagnosticSeverity::WARNING,
+            MAINTAINER_CODE,
+            message.to_string(),
+        )
+    };
+
+    let Some((lt, gt)) = rest
+        .find('<')
+        .and_then(|lt| rest[lt..].find('>').map(|gt| (lt, lt + gt)))
+    else {
+        findings.push(malformed(
+            whole,
+            "Maintainer line lacks an email address in angle brackets",
+        ));
+        return;
+    };
+    if rest[..lt].trim().is_empty() {
+        findings.push(malformed(whole, "Maintainer line lacks a name"));
+    }
+    if !rest[lt + 1..gt].contains('@') {
+        findings.push(malformed(
+            at(lt, gt + 1 - lt),
+            "Maintainer email address is not valid",
+        ));
+    }
+
+    let after = &rest[gt + 1..];
+    let date = after.trim_start();
+    let sep_len = after.len() - date.len();
+    if date.is_empty() {
+        findings.push(malformed(whole, "Maintainer line lacks a date"));
+        return;
+    }
+    if &after[..sep_len] != "  " {
+        let sep = at(gt + 1, sep_len);
+        findings.push(
+            malformed(
+                sep,
+                "Expected two spaces between the email address and the date",
+            )
+            .with_fix(
+                "Use two spaces before the date".to_string(),
+                vec![TextEdit {
+                    range: src.text_range_to_lsp_range(sep),
+                    new_text: "  ".to_string(),
+                }],
+            ),
+        );
+    }
+
+    let date = date.trim_end();
+    let date_range = at(gt + 1 + sep_len, date.len());
+    match check_date(date) {
+        Ok(()) => {}
+        Err(Some(corrected)) => findings.push(
+            malformed(date_range, "Date has the wrong day of the week").with_fix(
+                format!("Replace with '{}'", corrected),
+                vec![TextEdit {
+                    range: src.text_range_to_lsp_range(date_range),
+                    new_text: corrected,
+                }],
+            ),
+        ),
+        Err(None) => findings.push(malformed(
+            date_range,
+            &format!("Invalid date '{}', expected RFC 2822 format", date),
+        )),
+    }
+}
+
+/// Check the blank lines around the change details of an entry.
+fn check_entry_layout(entry: &Entry, src: Source<'_>, findings: &mut Vec<Finding>) {
+    let children: Vec<_> = entry.syntax().children().collect();
+    for pair in children.windows(2) {
+        let (prev, next) = (&pair[0], &pair[1]);
+        let message = match (prev.kind(), next.kind()) {
+            (SyntaxKind::ENTRY_HEADER, SyntaxKind::ENTRY_BODY) => {
+                "Missing blank line after the entry header"
+            }
+            (SyntaxKind::ENTRY_BODY, SyntaxKind::ENTRY_FOOTER) => {
+                "Missing blank line before the maintainer line"
+            }
+            _ => continue,
+        };
+        findings.push(
+            finding(
+                src,
+                line_range(next),
+                DiagnosticSeverity::WARNING,
+                BLANK_LINE_CODE,
+                message.to_string(),
+            )
+            .with_fix(
+                "Insert blank line".to_string(),
+                insert_blank_line(src, next.text_range().start()),
+            ),
+        );
+    }
+}
+
+/// Split a change line into its indentation-plus-bullet prefix and its text.
+fn split_change_line(line: &str) -> (&str, &str) {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let rest = &line[indent..];
+    let prefix_len = match rest.as_bytes() {
+        [b'*' | b'+' | b'-', b' ', ..] => indent + 2,
+        _ => indent,
+    };
+    line.split_at(prefix_len)
+}
+
+fn check_line_lengths(entry: &Entry, src: Source<'_>, findings: &mut Vec<Finding>) {
+    for body in entry
+        .syntax()
+        .children()
+        .filter(|n| n.kind() == SyntaxKind::ENTRY_BODY)
+    {
+        let range = line_range(&body);
+        let line = &src.text[range];
+        let width = line.chars().count();
+        if width <= MAX_LINE_WIDTH {
+            continue;
+        }
+        let (cut, _) = line.char_indices().nth(MAX_LINE_WIDTH).unwrap();
+        let excess = TextRange::new(range.start() + TextSize::from(cut as u32), range.end());
+        let mut f = finding(
+            src,
+            excess,
+            DiagnosticSeverity::INFORMATION,
+            LINE_LENGTH_CODE,
+            format!(
+                "Line is {} characters long, more than {}",
+                width, MAX_LINE_WIDTH
+            ),
+        );
+
+        let (prefix, text) = split_change_line(line);
+        let subsequent = " ".repeat(prefix.len());
+        let wrapped = debian_changelog::textwrap::textwrap(
+            text,
+            Some(MAX_LINE_WIDTH),
+            Some(prefix),
+            Some(&subsequent),
+        );
+        if wrapped.len() > 1 {
+            f = f.with_fix(
+                "Rewrap line".to_string(),
+                vec![TextEdit {
+                    range: src.text_range_to_lsp_range(range),
+                    new_text: wrapped.join("\n"),
+                }],
+            );
+        }
+        findings.push(f);
+    }
+}
+
+fn changelog_findings(
+    parsed: &debian_changelog::Parse<debian_changelog::ChangeLog>,
+    src: Source<'_>,
+) -> Vec<Finding> {
+    let changelog = parsed.tree();
+    let mut findings = Vec::new();
+
+    // Entries must be separated by a blank line.
+    let top_level: Vec<_> = changelog.syntax().children().collect();
+    for pair in top_level.windows(2) {
+        if pair[0].kind() == SyntaxKind::ENTRY && pair[1].kind() == SyntaxKind::ENTRY {
+            findings.push(
+                finding(
+                    src,
+                    line_range(&pair[1].first_child().unwrap_or_else(|| pair[1].clone())),
+                    DiagnosticSeverity::WARNING,
+                    BLANK_LINE_CODE,
+                    "Missing blank line between entries".to_string(),
+                )
+                .with_fix(
+                    "Insert blank line".to_string(),
+                    insert_blank_line(src, pair[1].text_range().start()),
+                ),
+            );
+        }
+    }
+
+    let entries: Vec<Entry> = changelog.iter().collect();
+    let now: DateTime<FixedOffset> = Utc::now().into();
+    let mut seen_released = false;
+
+    for (i, entry) in entries.iter().enumerate() {
+        check_entry_layout(entry, src, &mut findings);
+        check_footer(entry, src, &mut findings);
+        check_distributions(entry, src, &mut findings);
+        check_line_lengths(entry, src, &mut findings);
+
+        let unreleased = entry.is_unreleased() == Some(true);
+        if unreleased && seen_released {
+            for (name, range) in distribution_tokens(entry) {
+                if name == "UNRELEASED" {
+                    findings.push(finding(
+                        src,
+                        range,
+                        DiagnosticSeverity::WARNING,
+                        UNRELEASED_CODE,
+                        "UNRELEASED entry below a released entry".to_string(),
+                    ));
+                }
+            }
+        }
+        seen_released |= !unreleased;
+
+        let next = entries.get(i + 1);
+
+        if let (Some(version), Some(range)) = (entry.version(), entry.version_range()) {
+            if let Some(older) = next.and_then(|n| n.version()) {
+                if version <= older {
+                    findings.push(finding(
+                        src,
+                        range,
+                        DiagnosticSeverity::ERROR,
+                        VERSION_CODE,
+                        format!(
+                            "Version {} is not greater than {} of the entry below",
+                            version, older
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if let (Some(date), Some(node)) = (entry.datetime(), entry.timestamp_node()) {
+            let range = line_range(node.syntax());
+            if date > now {
+                findings.push(finding(
+                    src,
+                    range,
+                    DiagnosticSeverity::WARNING,
+                    FUTURE_DATE_CODE,
+                    "Date is in the future".to_string(),
+                ));
+            }
+            if let Some(older) = next.and_then(|n| n.datetime()) {
+                if date < older {
+                    findings.push(finding(
+                        src,
+                        range,
+                        DiagnosticSeverity::WARNING,
+                        DATE_ORDER_CODE,
+                        format!(
+                            "Date is earlier than {} of the entry below",
+                            older.to_rfc2822()
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    findings
+}
+
+/// Produce structural diagnostics for a changelog.
+pub fn get_diagnostics(
+    parsed: &debian_changelog::Parse<debian_changelog::ChangeLog>,
+    src: Source<'_>,
+) -> Vec<Diagnostic> {
+    changelog_findings(parsed, src)
+        .into_iter()
+        .map(|f| f.diagnostic)
+        .collect()
+}
+
+/// Produce quick fixes for the structural diagnostics that have a mechanical
+/// fix.
+///
+/// When `diagnostics` is non-empty, only fixes for those diagnostics are
+/// returned.
+pub fn get_code_actions(
+    uri: &Uri,
+    parsed: &debian_changelog::Parse<debian_changelog::ChangeLog>,
+    src: Source<'_>,
+    diagnostics: &[Diagnostic],
+) -> Vec<CodeActionOrCommand> {
+    fix_actions(uri, changelog_findings(parsed, src), diagnostics)
+}
+
+/// Turn the fixes of `findings` into quick fixes, restricted to the requested
+/// `diagnostics` when there are any.
+pub(super) fn fix_actions(
+    uri: &Uri,
+    findings: Vec<Finding>,
+    diagnostics: &[Diagnostic],
+) -> Vec<CodeActionOrCommand> {
+    let mut actions = Vec::new();
+    for finding in findings {
+        let Some(fix) = finding.fix else {
+            continue;
+        };
+        let matching: Vec<Diagnostic> = diagnostics
+            .iter()
+            .filter(|d| d.range == finding.diagnostic.range && d.code == finding.diagnostic.code)
+            .cloned()
+            .collect();
+        if !diagnostics.is_empty() && matching.is_empty() {
+            continue;
+        }
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: fix.title,
+            kind: Some(CodeActionKind::QUICKFIX),
+            edit: Some(WorkspaceEdit {
+                changes: Some(vec![(uri.clone(), fix.edits)].into_iter().collect()),
+                ..Default::default()
+            }),
+            diagnostics: (!matching.is_empty()).then_some(matching),
+            is_preferred: Some(true),
+            ..Default::default()
+        }));
+    }
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+
+    const FOOTER: &str = " -- Foo <foo@example.com>  Mon, 01 Jan 2024 00:00:00 +0000\n";
+
+    fn check(text: &str) -> Vec<(String, String)> {
+        let parsed = debian_changelog::ChangeLog::parse(text);
+        let idx = LineIndex::new(text);
+        get_diagnostics(&parsed, Source::new(text, &idx))
+            .into_iter()
+            .map(|d| match d.code {
+                Some(NumberOrString::String(code)) => (code, d.message),
+                _ => panic!("missing code"),
+            })
+            .collect()
+    }
+
+    fn codes(text: &str) -> Vec<String> {
+        check(text).into_iter().map(|(code, _)| code).collect()
+    }
+
+    /// Apply the single fix for `code` to `text`.
+    fn apply_fix(text: &str, code: &str) -> String {
+        let parsed = debian_changelog::ChangeLog::parse(text);
+        let idx = LineIndex::new(text);
+        let src = Source::new(text, &idx);
+        let uri: Uri = str::parse("file:///debian/changelog").unwrap();
+        let diagnostics: Vec<_> = get_diagnostics(&parsed, src)
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String(code.to_string())))
+            .collect();
+        let actions = get_code_actions(&uri, &parsed, src, &diagnostics);
+        assert_eq!(actions.len(), 1, "{:?}", actions);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("expected code action");
+        };
+        let mut edits = action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri].clone();
+        edits.sort_by_key(|e| std::cmp::Reverse(e.range.start));
+        let mut result = text.to_string();
+        for edit in edits {
+            let range = src.try_lsp_range_to_text_range(&edit.range).unwrap();
+            result.replace_range(std::ops::Range::<usize>::from(range), &edit.new_text);
+        }
+        result
+    }
+
+    fn entry(version: &str, dist: &str, footer: &str) -> String {
+        format!(
+            "foo ({}) {}; urgency=medium\n\n  * Change.\n\n{}",
+            version, dist, footer
+        )
+    }
+
+    #[test]
+    fn test_clean_changelog() {
+        let text = format!(
+            "{}\n{}",
+            entry(
+                "1.0-2",
+                "UNRELEASED",
+                " -- Foo <foo@example.com>  Tue, 02 Jan 2024 00:00:00 +0000\n"
+            ),
+            entry("1.0-1", "unstable", FOOTER)
+        );
+        assert_eq!(check(&text), vec![]);
+    }
+
+    #[test]
+    fn test_version_not_decreasing() {
+        let text = format!(
+            "{}\n{}",
+            entry(
+                "1.0-1",
+                "unstable",
+                " -- Foo <foo@example.com>  Tue, 02 Jan 2024 00:00:00 +0000\n"
+            ),
+            entry("1.0-2", "unstable", FOOTER)
+        );
+        assert_eq!(
+            check(&text),
+            vec![(
+                VERSION_CODE.to_string(),
+                "Version 1.0-1 is not greater than 1.0-2 of the entry below".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_dates() {
+        let text = format!(
+            "{}\n{}",
+            entry("1.0-2", "unstable", FOOTER),
+            entry(
+                "1.0-1",
+                "unstable",
+                " -- Foo <foo@example.com>  Tue, 02 Jan 2024 00:00:00 +0000\n"
+            )
+        );
+        assert_eq!(codes(&text), vec![DATE_ORDER_CODE]);
+
+        let text = entry(
+            "1.0-1",
+            "unstable",
+            " -- Foo <foo@example.com>  2024-01-01 00:00:00\n",
+        );
+        assert_eq!(codes(&text), vec![MAINTAINER_CODE]);
+
+        let text = entry(
+            "1.0-1",
+            "unstable",
+            " -- Foo <foo@example.com>  Tue, 01 Jan 2999 00:00:00 +0000\n",
+        );
+        assert_eq!(codes(&text), vec![FUTURE_DATE_CODE]);
+    }
+
+    #[test]
+    fn test_maintainer_line() {
+        let text = entry(
+            "1.0-1",
+            "unstable",
+            " -- Foo <foo@example.com> Mon, 01 Jan 2024 00:00:00 +0000\n",
+        );
+        assert_eq!(
+            check(&text),
+            vec![(
+                MAINTAINER_CODE.to_string(),
+                "Expected two spaces between the email address and the date".to_string()
+            )]
+        );
+        assert_eq!(
+            apply_fix(&text, MAINTAINER_CODE),
+            entry("1.0-1", "unstable", FOOTER)
+        );
+
+        let text = entry(
+            "1.0-1",
+            "unstable",
+            " -- Foo <foo.example.com>  Mon, 01 Jan 2024 00:00:00 +0000\n",
+        );
+        assert_eq!(
+            check(&text),
+            vec![(
+                MAINTAINER_CODE.to_string(),
+                "Maintainer email address is not valid".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_wrong_day_of_week() {
+        let text = entry(
+            "1.0-1",
+            "unstable",
+            " -- Foo <foo@example.com>  Fri, 01 Jan 2024 00:00:00 +0000\n",
+        );
+        assert_eq!(
+            check(&text),
+            vec![(
+                MAINTAINER_CODE.to_string(),
+                "Date has the wrong day of the week".to_string()
+            )]
+        );
+        assert_eq!(
+            apply_fix(&text, MAINTAINER_CODE),
+            entry("1.0-1", "unstable", FOOTER)
+        );
+    }
+
+    #[test]
+    fn test_unknown_distribution() {
+        if !crate::distros::has_distro_info() {
+            return;
+        }
+        let text = entry("1.0-1", "unstabel", FOOTER);
+        assert_eq!(
+            check(&text),
+            vec![(
+                DISTRIBUTION_CODE.to_string(),
+                "Unknown distribution 'unstabel'".to_string()
+            )]
+        );
+        assert_eq!(
+            apply_fix(&text, DISTRIBUTION_CODE),
+            entry("1.0-1", "unstable", FOOTER)
+        );
+
+        assert_eq!(check(&entry("1.0-1", "bookworm-backports", FOOTER)), vec![]);
+        assert_eq!(check(&entry("1.0-1ubuntu1", "jammy", FOOTER)), vec![]);
+    }
+
+    #[test]
+    fn test_unreleased_below_released() {
+        let text = format!(
+            "{}\n{}",
+            entry(
+                "1.0-2",
+                "unstable",
+                " -- Foo <foo@example.com>  Tue, 02 Jan 2024 00:00:00 +0000\n"
+            ),
+            entry("1.0-1", "UNRELEASED", FOOTER)
+        );
+        assert_eq!(codes(&text), vec![UNRELEASED_CODE]);
+    }
+
+    #[test]
+    fn test_missing_blank_lines() {
+        let text = format!(
+            "foo (1.0-1) unstable; urgency=medium\n  * Change.\n{}",
+            FOOTER
+        );
+        assert_eq!(codes(&text), vec![BLANK_LINE_CODE, BLANK_LINE_CODE]);
+
+        let text = format!(
+            "foo (1.0-1) unstable; urgency=medium\n  * Change.\n\n{}",
+            FOOTER
+        );
+        assert_eq!(
+            apply_fix(&text, BLANK_LINE_CODE),
+            entry("1.0-1", "unstable", FOOTER)
+        );
+
+        let text = format!(
+            "{}{}",
+            entry(
+                "1.0-2",
+                "unstable",
+                " -- Foo <foo@example.com>  Tue, 02 Jan 2024 00:00:00 +0000\n"
+            ),
+            entry("1.0-1", "unstable", FOOTER)
+        );
+        assert_eq!(
+            check(&text),
+            vec![(
+                BLANK_LINE_CODE.to_string(),
+                "Missing blank line between entries".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_line_too_long() {
+        let long = "  * Fix a very long problem that happens to take more than eighty characters to describe.";
+        let text = format!(
+            "foo (1.0-1) unstable; urgency=medium\n\n{}\n\n{}",
+            long, FOOTER
+        );
+        assert_eq!(codes(&text), vec![LINE_LENGTH_CODE]);
+        assert_eq!(
+            apply_fix(&text, LINE_LENGTH_CODE),
+            format!(
+                "foo (1.0-1) unstable; urgency=medium\n\n{}\n{}\n\n{}",
+                "  * Fix a very long problem that happens to take more than eighty characters",
+                "    to describe.",
+                FOOTER
+            )
+        );
+    }
+}
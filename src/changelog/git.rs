@@ -0,0 +1,453 @@
+//! Drafting changelog entries from git history, in the style of `gbp dch`.
+//!
+//! The commits since the last released version are read from git: the range
+//! starts at the `debian/<version>` tag of the newest released entry (with
+//! DEP-14 mangling of `:` and `~`), falling back to the newest `debian/*` tag
+//! and then to the last commit that touched `debian/changelog`. Each commit
+//! subject becomes a bullet; `Closes: #NNN` in the message is carried over,
+//! and commits by several authors are grouped under `[ Name ]` lines as for
+//! team uploads. Commits with `Gbp-Dch: Ignore` are skipped.
+
+use std::path::Path;
+use std::process::Command as ProcessCommand;
+
+use debian_changelog::bugs::{iter_bug_refs, Bug};
+use debian_changelog::{ChangeLog, SyntaxKind, Urgency};
+use debversion::Version;
+use rowan::ast::AstNode;
+use text_size::{TextRange, TextSize};
+use tower_lsp_server::ls_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Command, TextEdit, Uri, WorkspaceEdit,
+};
+
+use crate::position::Source;
+
+pub const GENERATE_CHANGELOG_ENTRY_COMMAND: &str = "debian-lsp.generateChangelogEntry";
+
+/// Width of a change line's text, leaving room for the two-space indent.
+const CHANGE_WIDTH: usize = 78;
+
+/// A commit to describe in the changelog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub author: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Commit {
+    fn is_ignored(&self) -> bool {
+        self.body.lines().any(|line| {
+            line.split_once(':').is_some_and(|(key, value)| {
+                key.trim().eq_ignore_ascii_case("Gbp-Dch")
+                    && value.trim().eq_ignore_ascii_case("ignore")
+            })
+        })
+    }
+
+    /// The Debian bugs the commit message closes.
+    fn closes(&self) -> Vec<u32> {
+        let mut bugs = Vec::new();
+        for line in std::iter::once(self.subject.as_str()).chain(self.body.lines()) {
+            for bug_ref in iter_bug_refs(line) {
+                if let Bug::Debian(id) = bug_ref.bug {
+                    if !bugs.contains(&id) {
+                        bugs.push(id);
+                    }
+                }
+            }
+        }
+        bugs
+    }
+
+    /// The text of the bullet for this commit.
+    fn change(&self) -> String {
+        let closes = self.closes();
+        if closes.is_empty() || self.subject.contains("Closes:") {
+            return self.subject.clone();
+        }
+        let bugs: Vec<String> = closes.iter().map(|id| format!("#{}", id)).collect();
+        format!("{} (Closes: {})", self.subject, bugs.join(", "))
+    }
+}
+
+/// The git tag of a released version, as named by DEP-14.
+pub fn release_tag(version: &Version) -> String {
+    format!(
+        "debian/{}",
+        version.to_string().replace(':', "%").replace('~', "_")
+    )
+}
+
+fn git(root: &Path, args: &[&str]) -> Option<String> {
+    let output = match ProcessCommand::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+    {
+        Ok(o) => o,
+        Err(e) => {
+            tracing::warn!("failed to run git in {}: {e}", root.display());
+            return None;
+        }
+    };
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/// The revision the new entry's commits start after.
+fn since_revision(root: &Path, released: Option<&Version>) -> Option<String> {
+    if let Some(version) = released {
+        let tag = release_tag(version);
+        let reference = format!("refs/tags/{}", tag);
+        if git(root, &["rev-parse", "--verify", "--quiet", &reference]).is_some() {
+            return Some(tag);
+        }
+    }
+    if let Some(tag) = git(
+        root,
+        &["describe", "--tags", "--abbrev=0", "--match", "debian/*"],
+    ) {
+        return Some(tag.trim().to_string());
+    }
+    git(
+        root,
+        &["log", "-1", "--format=%H", "--", "debian/changelog"],
+    )
+    .map(|rev| rev.trim().to_string())
+    .filter(|rev| !rev.is_empty())
+}
+
+/// Read the commits made since `released` was tagged, oldest first.
+///
+/// Returns `None` if `root` is not a git checkout.
+pub fn read_commits(root: &Path, released: Option<&Version>) -> Option<Vec<Commit>> {
+    git(root, &["rev-parse", "--git-dir"])?;
+    let range = match since_revision(root, released) {
+        Some(since) => format!("{}..HEAD", since),
+        None => "HEAD".to_string(),
+    };
+    let log = git(
+        root,
+        &["log", "--no-merges", "--format=%an%x1f%s%x1f%b%x1e", &range],
+    )
+    .unwrap_or_default();
+
+    let mut commits: Vec<Commit> = log
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(3, '\x1f');
+            let author = fields.next()?.to_string();
+            let subject = fields.next()?.to_string();
+            let body = fields.next().unwrap_or_default().trim().to_string();
+            Some(Commit {
+                author,
+                subject,
+                body,
+            })
+        })
+        .collect();
+    commits.reverse();
+    Some(commits)
+}
+
+fn push_change(lines: &mut Vec<String>, change: &str) {
+    let wrapped =
+        debian_changelog::textwrap::textwrap(change, Some(CHANGE_WIDTH), Some("* "), Some("  "));
+    lines.extend(wrapped.into_iter().map(|line| line.into_owned()));
+}
+
+/// Draft the change lines describing `commits`, without the two-space indent.
+///
+/// Changes already described in `existing` are left out.
+pub fn draft_changes(commits: &[Commit], existing: &str) -> Vec<String> {
+    let commits: Vec<&Commit> = commits
+        .iter()
+        .filter(|c| !c.is_ignored() && !existing.contains(&c.subject))
+        .collect();
+
+    let mut authors: Vec<&str> = Vec::new();
+    for commit in &commits {
+        if !authors.contains(&commit.author.as_str()) {
+            authors.push(&commit.author);
+        }
+    }
+
+    let mut lines = Vec::new();
+    if authors.len() <= 1 {
+        for commit in &commits {
+            push_change(&mut lines, &commit.change());
+        }
+        return lines;
+    }
+    for author in authors {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!("[ {} ]", author));
+        for commit in commits.iter().filter(|c| c.author == author) {
+            push_change(&mut lines, &commit.change());
+        }
+    }
+    lines
+}
+
+fn indent(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| {
+            if line.is_empty() {
+                "\n".to_string()
+            } else {
+                format!("  {}\n", line)
+            }
+        })
+        .collect()
+}
+
+/// The version of the newest released entry, whose tag the commits are read
+/// from.
+pub fn latest_released_version(changelog: &ChangeLog) -> Option<Version> {
+    changelog
+        .iter()
+        .find(|entry| entry.is_unreleased() == Some(false))
+        .and_then(|entry| entry.version())
+}
+
+/// Build the edit adding changes for `commits` to the changelog.
+///
+/// The changes are appended to the topmost entry while it is unreleased
+/// (replacing an empty `*` placeholder); otherwise a new entry is created.
+pub fn build_entry_edit(
+    uri: &Uri,
+    src: Source<'_>,
+    parsed: &debian_changelog::Parse<ChangeLog>,
+    commits: &[Commit],
+) -> Option<WorkspaceEdit> {
+    let changelog = parsed.tree();
+    let top = changelog.iter().next();
+
+    let edit = match top.filter(|entry| entry.is_unreleased() == Some(true)) {
+        Some(entry) => {
+            let lines = draft_changes(commits, &entry.to_string());
+            if lines.is_empty() {
+                return None;
+            }
+            let bodies: Vec<_> = entry
+                .syntax()
+                .children()
+                .filter(|n| n.kind() == SyntaxKind::ENTRY_BODY)
+                .collect();
+            let placeholder = bodies
+                .iter()
+                .all(|n| matches!(n.text().to_string().trim(), "" | "*"));
+            let range = match bodies.last() {
+                Some(last) if placeholder => {
+                    TextRange::new(bodies[0].text_range().start(), last.text_range().end())
+                }
+                Some(last) => TextRange::empty(last.text_range().end()),
+                None => {
+                    let offset = entry
+                        .footer()
+                        .map(|f| f.syntax().text_range().start())
+                        .unwrap_or_else(|| entry.syntax().text_range().end());
+                    TextRange::empty(offset)
+                }
+            };
+            let mut new_text = indent(&lines);
+            if bodies.is_empty() && entry.footer().is_some() {
+                new_text.push('\n');
+            }
+            TextEdit {
+                range: src.text_range_to_lsp_range(range),
+                new_text,
+            }
+        }
+        None => {
+            let lines = draft_changes(commits, "");
+            if lines.is_empty() {
+                return None;
+            }
+            let mut changelog = changelog.clone();
+            let mut builder = changelog.new_entry().urgency(Urgency::Medium);
+            for line in &lines {
+                builder = builder.change_line(line.clone());
+            }
+            // Separator lines come out as bare indentation; drop it.
+            let entry: String = builder
+                .finish()
+                .to_string()
+                .lines()
+                .map(|line| format!("{}\n", line.trim_end()))
+                .collect();
+            TextEdit {
+                range: src.text_range_to_lsp_range(TextRange::empty(TextSize::from(0))),
+                new_text: format!("{}\n", entry),
+            }
+        }
+    };
+
+    Some(WorkspaceEdit {
+        changes: Some(vec![(uri.clone(), vec![edit])].into_iter().collect()),
+        ..Default::default()
+    })
+}
+
+/// A source action running [`GENERATE_CHANGELOG_ENTRY_COMMAND`] on the
+/// changelog at `uri`.
+pub fn generate_action(uri: &Uri) -> CodeActionOrCommand {
+    let title = "Add changes from git history".to_string();
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title: title.clone(),
+        kind: Some(CodeActionKind::SOURCE),
+        command: Some(Command {
+            title,
+            command: GENERATE_CHANGELOG_ENTRY_COMMAND.to_string(),
+            arguments: Some(vec![serde_json::Value::String(uri.to_string())]),
+        }),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+
+    fn commit(author: &str, subject: &str, body: &str) -> Commit {
+        Commit {
+            author: author.to_string(),
+            subject: subject.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    fn apply(text: &str, commits: &[Commit]) -> Option<String> {
+        let parsed = ChangeLog::parse(text);
+        let idx = LineIndex::new(text);
+        let src = Source::new(text, &idx);
+        let uri: Uri = str::parse("file:///debian/changelog").unwrap();
+        let edit = build_entry_edit(&uri, src, &parsed, commits)?;
+        let edit = edit.changes.unwrap()[&uri][0].clone();
+        let range = src.try_lsp_range_to_text_range(&edit.range).unwrap();
+        let mut result = text.to_string();
+        result.replace_range(std::ops::Range::<usize>::from(range), &edit.new_text);
+        Some(result)
+    }
+
+    #[test]
+    fn test_release_tag() {
+        let version: Version = "1:2.0~rc1-1".parse().unwrap();
+        assert_eq!(release_tag(&version), "debian/1%2.0_rc1-1");
+    }
+
+    #[test]
+    fn test_draft_changes_single_author() {
+        let commits = vec![
+            commit("Jane", "Fix the build with GCC 14", "Closes: #1066000"),
+            commit("Jane", "Bump Standards-Version", "Gbp-Dch: Ignore"),
+            commit(
+                "Jane",
+                "Refresh the patches that no longer apply to the new upstream release of the library",
+                "",
+            ),
+        ];
+        assert_eq!(
+            draft_changes(&commits, ""),
+            vec![
+                "* Fix the build with GCC 14 (Closes: #1066000)",
+                "* Refresh the patches that no longer apply to the new upstream release of",
+                "  the library",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_draft_changes_groups_authors() {
+        let commits = vec![
+            commit("Jane", "First", ""),
+            commit("John", "Second", ""),
+            commit("Jane", "Third", ""),
+        ];
+        assert_eq!(
+            draft_changes(&commits, ""),
+            vec!["[ Jane ]", "* First", "* Third", "", "[ John ]", "* Second"]
+        );
+    }
+
+    #[test]
+    fn test_build_entry_edit_extends_unreleased() {
+        let text = "foo (1.0-2) UNRELEASED; urgency=medium\n\n  * First\n\n -- Jane <jane@example.com>  Mon, 01 Jan 2024 00:00:00 +0000\n";
+        let commits = vec![commit("Jane", "First", ""), commit("Jane", "Second", "")];
+        assert_eq!(
+            apply(text, &commits).unwrap(),
+            "foo (1.0-2) UNRELEASED; urgency=medium\n\n  * First\n  * Second\n\n -- Jane <jane@example.com>  Mon, 01 Jan 2024 00:00:00 +0000\n"
+        );
+        assert_eq!(apply(text, &commits[..1]), None);
+    }
+
+    #[test]
+    fn test_build_entry_edit_replaces_placeholder() {
+        let text = "foo (1.0-2) UNRELEASED; urgency=medium\n\n  * \n\n -- Jane <jane@example.com>  Mon, 01 Jan 2024 00:00:00 +0000\n";
+        assert_eq!(
+            apply(text, &[commit("Jane", "Second", "")]).unwrap(),
+            "foo (1.0-2) UNRELEASED; urgency=medium\n\n  * Second\n\n -- Jane <jane@example.com>  Mon, 01 Jan 2024 00:00:00 +0000\n"
+        );
+    }
+
+    #[test]
+    fn test_build_entry_edit_new_entry() {
+        let text = "foo (1.0-1) unstable; urgency=medium\n\n  * Initial release.\n\n -- Jane <jane@example.com>  Mon, 01 Jan 2024 00:00:00 +0000\n";
+        let commits = vec![commit("Jane", "One", ""), commit("John", "Two", "")];
+        let result = apply(text, &commits).unwrap();
+        assert!(
+            result.starts_with(
+                "foo (1.0-2) UNRELEASED; urgency=medium\n\n  [ Jane ]\n  * One\n\n  [ John ]\n  * Two\n\n -- "
+            ),
+            "{}",
+            result
+        );
+        assert!(result.ends_with(&format!("\n\n{}", text)), "{}", result);
+    }
+
+    #[test]
+    fn test_read_commits_since_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let run = |args: &[&str]| {
+            let status = ProcessCommand::new("git")
+                .args(["-c", "user.name=Jane", "-c", "user.email=jane@example.com"])
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        run(&["init", "-q"]);
+        run(&["commit", "-q", "--allow-empty", "-m", "Release 1.0-1"]);
+        run(&["tag", "debian/1.0-1"]);
+        run(&[
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "Fix crash\n\nCloses: #123",
+        ]);
+        run(&["commit", "-q", "--allow-empty", "-m", "Update homepage"]);
+
+        let version: Version = "1.0-1".parse().unwrap();
+        assert_eq!(
+            read_commits(root, Some(&version)).unwrap(),
+            vec![
+                commit("Jane", "Fix crash", "Closes: #123"),
+                commit("Jane", "Update homepage", ""),
+            ]
+        );
+
+        let not_git = tempfile::tempdir().unwrap();
+        assert_eq!(read_commits(not_git.path(), None), None);
+    }
+}
@@ -19,6 +19,7 @@ use crate::bugs::{DebbugsBugSummary, LaunchpadBugSummary, SharedBugCache};
 use crate::cve::{self, SharedCveCache};
 use crate::ghsa;
 use crate::position::Source;
+use crate::settings::Settings;
 
 /// A reference found under the cursor in a changelog file.
 enum Reference {
@@ -38,13 +39,15 @@ enum Reference {
 /// For bug references, fetches bug details from the cache (populating it from
 /// UDD/Launchpad on first access for the package).  For CVE identifiers, fetches
 /// security tracker details from UDD, falling back to a plain link.  Returns
-/// `None` when the cursor is not on a reference.
+/// `None` when the cursor is not on a reference. Lookups turned off in
+/// `settings` fall back to the plain links.
 pub async fn get_hover(
     parse: &debian_changelog::Parse<debian_changelog::ChangeLog>,
     src: Source<'_>,
     position: Position,
     bug_cache: &SharedBugCache,
     cve_cache: &SharedCveCache,
+    settings: &Settings,
 ) -> Option<Hover> {
     // Extract the reference in a non-Send scope, then drop all CST values
     // before the first await so the future remains Send.
@@ -65,9 +68,23 @@ pub async fn get_hover(
     };
 
     match reference {
-        Reference::Bug(bug) => Some(make_bug_hover(&bug, bug_cache).await),
+        Reference::Bug(bug) => {
+            let enabled = match bug {
+                Bug::Debian(_) => settings.udd,
+                Bug::Launchpad(_) => settings.launchpad_enabled(),
+            };
+            Some(if enabled {
+                make_bug_hover(&bug, bug_cache).await
+            } else {
+                make_fallback_hover(&bug)
+            })
+        }
         Reference::Cve { id, source } => {
-            let summary = cve::cve_summary(cve_cache, &id, &source).await;
+            let summary = if settings.udd {
+                cve::cve_summary(cve_cache, &id, &source).await
+            } else {
+                None
+            };
             Some(match summary {
                 Some(s) => make_cve_hover(&s),
                 None => make_cve_link_hover(&id),
@@ -229,6 +246,9 @@ pub fn debian_bug_markdown(summary: &DebbugsBugSummary) -> String {
             lines.push(format!("**Forwarded:** {}", forwarded));
         }
     }
+    if summary.stale {
+        lines.push(crate::disk_cache::STALE_NOTE.to_string());
+    }
 
     lines.join("\n\n")
 }
@@ -355,6 +375,10 @@ mod tests {
             tags: Some("patch".to_string()),
             forwarded: None,
             originator: Some("someone@example.com".to_string()),
+            source: None,
+            archived: false,
+            merged_with: Vec::new(),
+            stale: false,
         };
         let hover = make_debian_hover(&summary);
         assert_eq!(
@@ -381,6 +405,10 @@ mod tests {
             tags: None,
             forwarded: None,
             originator: None,
+            source: None,
+            archived: false,
+            merged_with: Vec::new(),
+            stale: false,
         };
         let hover = make_debian_hover(&summary);
         assert_eq!(
@@ -1,10 +1,14 @@
 pub mod actions;
+pub mod closes;
 pub mod completion;
+pub mod consistency;
 pub mod detection;
+pub mod diagnostics;
 pub mod document_link;
 pub mod fields;
 pub mod file_refs;
 pub mod folding;
+pub mod git;
 pub mod hover;
 pub mod inlay_hints;
 pub mod on_type_formatting;
@@ -13,6 +17,7 @@ pub mod semantic;
 #[cfg(feature = "spellcheck")]
 pub mod spelling;
 pub mod symbols;
+pub mod templates;
 
 pub use actions::*;
 pub use completion::*;
@@ -0,0 +1,306 @@
+//! Templates for the common kinds of changelog entry.
+//!
+//! Each template is a variant of [`super::ADD_CHANGELOG_ENTRY_COMMAND`] that
+//! picks the version, distribution and first change line for a particular
+//! kind of upload: a new upstream release, a non-maintainer upload, a team
+//! upload or a backport. The entry is signed with the identity from
+//! [`crate::maintainers::get_user_identity`].
+
+use debian_changelog::{ChangeLog, Urgency};
+use debversion::Version;
+use tower_lsp_server::ls_types::{CodeAction, CodeActionKind, CodeActionOrCommand, Command, Uri};
+
+pub const NEW_UPSTREAM_COMMAND: &str = "debian-lsp.addNewUpstreamEntry";
+pub const NMU_COMMAND: &str = "debian-lsp.addNmuEntry";
+pub const TEAM_UPLOAD_COMMAND: &str = "debian-lsp.addTeamUploadEntry";
+pub const BACKPORT_COMMAND: &str = "debian-lsp.addBackportEntry";
+
+/// All the entry template commands.
+pub const COMMANDS: &[&str] = &[
+    NEW_UPSTREAM_COMMAND,
+    NMU_COMMAND,
+    TEAM_UPLOAD_COMMAND,
+    BACKPORT_COMMAND,
+];
+
+/// The kind of entry to add.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryTemplate {
+    /// A new upstream release, with the new upstream version.
+    NewUpstream(String),
+    Nmu,
+    TeamUpload,
+    /// A backport to the stable release with the given codename and version
+    /// number.
+    Backport {
+        codename: String,
+        release: String,
+    },
+}
+
+impl EntryTemplate {
+    /// The template for an entry template `command`, taking the arguments
+    /// that follow the changelog URI. `None` if `command` is not one of
+    /// [`COMMANDS`].
+    pub fn from_command(
+        command: &str,
+        arguments: &[serde_json::Value],
+    ) -> Option<Result<Self, String>> {
+        Some(match command {
+            NEW_UPSTREAM_COMMAND => match arguments.first().and_then(|v| v.as_str()) {
+                Some(version) if !version.trim().is_empty() => {
+                    Ok(Self::NewUpstream(version.trim().to_string()))
+                }
+                _ => Err("A new upstream version is required".to_string()),
+            },
+            NMU_COMMAND => Ok(Self::Nmu),
+            TEAM_UPLOAD_COMMAND => Ok(Self::TeamUpload),
+            BACKPORT_COMMAND => crate::distros::get_stable_release()
+                .map(|(codename, release)| Self::Backport { codename, release })
+                .ok_or_else(|| "Cannot determine the current stable release".to_string()),
+            _ => return None,
+        })
+    }
+
+    /// The version of the new entry, following `previous`.
+    pub fn version(&self, previous: &Version) -> Result<Version, String> {
+        match self {
+            Self::NewUpstream(upstream) => {
+                let version = Version {
+                    epoch: previous.epoch,
+                    upstream_version: upstream.clone(),
+                    debian_revision: previous.debian_revision.as_ref().map(|_| "1".to_string()),
+                };
+                if version <= *previous {
+                    return Err(format!(
+                        "New version {} is not greater than {}",
+                        version, previous
+                    ));
+                }
+                Ok(version)
+            }
+            Self::Nmu => Ok(nmu_version(previous)),
+            Self::TeamUpload => {
+                let mut version = previous.clone();
+                version.increment_debian();
+                Ok(version)
+            }
+            Self::Backport { release, .. } => Ok(backport_version(previous, release)),
+        }
+    }
+
+    fn distribution(&self) -> String {
+        match self {
+            Self::Backport { codename, .. } => format!("{}-backports", codename),
+            _ => debian_changelog::UNRELEASED.to_string(),
+        }
+    }
+
+    fn change(&self) -> String {
+        match self {
+            Self::NewUpstream(_) => "* New upstream release.".to_string(),
+            Self::Nmu => "* Non-maintainer upload.".to_string(),
+            Self::TeamUpload => "* Team upload.".to_string(),
+            Self::Backport { codename, .. } => format!("* Rebuild for {}-backports.", codename),
+        }
+    }
+}
+
+/// Increment the number after the last `marker` in `s`, or append `marker`
+/// followed by `1`.
+fn bump_suffix(s: &str, marker: &str) -> String {
+    if let Some((head, count)) = s.rsplit_once(marker) {
+        if let Ok(n) = count.parse::<u32>() {
+            return format!("{}{}{}", head, marker, n + 1);
+        }
+    }
+    format!("{}{}1", s, marker)
+}
+
+/// The version of a non-maintainer upload after `previous`: `1.2-3` becomes
+/// `1.2-3.1` and native `1.2` becomes `1.2+nmu1`.
+fn nmu_version(previous: &Version) -> Version {
+    let mut version = previous.clone();
+    match &mut version.debian_revision {
+        Some(revision) => *revision = bump_suffix(revision, "."),
+        None => version.upstream_version = bump_suffix(&version.upstream_version, "+nmu"),
+    }
+    version
+}
+
+/// The version of a backport of `previous` to the stable release numbered
+/// `release`: `1.2-3` becomes `1.2-3~bpo12+1`.
+fn backport_version(previous: &Version, release: &str) -> Version {
+    let marker = format!("~bpo{}+", release);
+    let mut version = previous.clone();
+    let part = match &mut version.debian_revision {
+        Some(revision) => revision,
+        None => &mut version.upstream_version,
+    };
+    *part = bump_suffix(part, &marker);
+    version
+}
+
+/// The identity from `DEBFULLNAME`/`DEBEMAIL`, split into name and email.
+fn identity() -> Option<(String, String)> {
+    let identity = crate::maintainers::get_user_identity()?;
+    let (name, email) = identity.rsplit_once(" <")?;
+    Some((name.to_string(), email.trim_end_matches('>').to_string()))
+}
+
+/// Generate the text of a new entry for `template`, to be inserted at the top
+/// of `changelog`.
+pub fn generate_entry(changelog: &ChangeLog, template: &EntryTemplate) -> Result<String, String> {
+    let previous = changelog
+        .iter()
+        .next()
+        .and_then(|entry| entry.version())
+        .ok_or_else(|| "The changelog has no version to start from".to_string())?;
+    let version = template.version(&previous)?;
+
+    let mut changelog = changelog.clone();
+    let mut builder = changelog
+        .new_entry()
+        .version(version)
+        .distributions(vec![template.distribution()])
+        .urgency(Urgency::Medium)
+        .change_line(template.change());
+    if let Some(identity) = identity() {
+        builder = builder.maintainer(identity);
+    }
+    Ok(format!("{}\n", builder.finish()))
+}
+
+/// Source actions adding an entry from each template that needs no further
+/// input to the changelog at `uri`. A new upstream release needs the new
+/// version, which the client has to ask for.
+pub fn template_actions(uri: &Uri) -> Vec<CodeActionOrCommand> {
+    [
+        (NMU_COMMAND, "Add non-maintainer upload entry"),
+        (TEAM_UPLOAD_COMMAND, "Add team upload entry"),
+        (BACKPORT_COMMAND, "Add backport entry"),
+    ]
+    .into_iter()
+    .map(|(command, title)| {
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title: title.to_string(),
+            kind: Some(CodeActionKind::SOURCE),
+            command: Some(Command {
+                title: title.to_string(),
+                command: command.to_string(),
+                arguments: Some(vec![serde_json::Value::String(uri.to_string())]),
+            }),
+            ..Default::default()
+        })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG: &str = "foo (1.0-2) unstable; urgency=medium\n\n  * Change.\n\n -- Foo <foo@example.com>  Mon, 01 Jan 2024 00:00:00 +0000\n";
+
+    fn v(s: &str) -> Version {
+        s.parse().unwrap()
+    }
+
+    fn next(template: EntryTemplate, previous: &str) -> String {
+        template.version(&v(previous)).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_new_upstream_version() {
+        let template = EntryTemplate::NewUpstream("1.1".to_string());
+        assert_eq!(next(template.clone(), "1.0-2"), "1.1-1");
+        assert_eq!(next(template.clone(), "2:1.0-2"), "2:1.1-1");
+        assert_eq!(next(template.clone(), "1.0"), "1.1");
+        assert!(template.version(&v("1.2-1")).is_err());
+    }
+
+    #[test]
+    fn test_nmu_version() {
+        assert_eq!(next(EntryTemplate::Nmu, "1.0-2"), "1.0-2.1");
+        assert_eq!(next(EntryTemplate::Nmu, "1.0-2.1"), "1.0-2.2");
+        assert_eq!(next(EntryTemplate::Nmu, "1.0"), "1.0+nmu1");
+        assert_eq!(next(EntryTemplate::Nmu, "1.0+nmu1"), "1.0+nmu2");
+    }
+
+    #[test]
+    fn test_team_upload_version() {
+        assert_eq!(next(EntryTemplate::TeamUpload, "1.0-2"), "1.0-3");
+    }
+
+    #[test]
+    fn test_backport_version() {
+        let template = EntryTemplate::Backport {
+            codename: "bookworm".to_string(),
+            release: "12".to_string(),
+        };
+        assert_eq!(next(template.clone(), "1.0-2"), "1.0-2~bpo12+1");
+        assert_eq!(next(template.clone(), "1.0-2~bpo12+1"), "1.0-2~bpo12+2");
+        assert_eq!(next(template, "1.0"), "1.0~bpo12+1");
+    }
+
+    #[test]
+    fn test_from_command() {
+        assert_eq!(EntryTemplate::from_command("debian-lsp.other", &[]), None);
+        assert_eq!(
+            EntryTemplate::from_command(NMU_COMMAND, &[]),
+            Some(Ok(EntryTemplate::Nmu))
+        );
+        assert_eq!(
+            EntryTemplate::from_command(NEW_UPSTREAM_COMMAND, &["2.0".into()]),
+            Some(Ok(EntryTemplate::NewUpstream("2.0".to_string())))
+        );
+        assert!(matches!(
+            EntryTemplate::from_command(NEW_UPSTREAM_COMMAND, &[]),
+            Some(Err(_))
+        ));
+    }
+
+    #[test]
+    fn test_template_actions() {
+        let uri: Uri = "file:///debian/changelog".parse().unwrap();
+        let commands: Vec<_> = template_actions(&uri)
+            .into_iter()
+            .map(|action| {
+                let CodeActionOrCommand::CodeAction(action) = action else {
+                    panic!("expected a code action");
+                };
+                let command = action.command.unwrap();
+                assert_eq!(command.arguments, Some(vec![uri.to_string().into()]));
+                command.command
+            })
+            .collect();
+        assert_eq!(
+            commands,
+            [NMU_COMMAND, TEAM_UPLOAD_COMMAND, BACKPORT_COMMAND]
+        );
+    }
+
+    #[test]
+    fn test_generate_entry() {
+        let changelog = ChangeLog::parse(CHANGELOG).tree();
+        let entry = generate_entry(&changelog, &EntryTemplate::Nmu).unwrap();
+        assert!(
+            entry.starts_with(
+                "foo (1.0-2.1) UNRELEASED; urgency=medium\n\n  * Non-maintainer upload.\n"
+            ),
+            "{}",
+            entry
+        );
+
+        let template = EntryTemplate::Backport {
+            codename: "bookworm".to_string(),
+            release: "12".to_string(),
+        };
+        let entry = generate_entry(&changelog, &template).unwrap();
+        assert!(
+            entry.starts_with("foo (1.0-2~bpo12+1) bookworm-backports; urgency=medium\n\n  * Rebuild for bookworm-backports.\n"),
+            "{}",
+            entry
+        );
+    }
+}
@@ -5,6 +5,7 @@
 //! - Vcs-Git: shows packaged version from UDD vcswatch
 
 use crate::position::Source;
+use crate::settings::CodeLensSettings;
 use debian_control::relations::VersionConstraint;
 use tower_lsp_server::ls_types::{CodeLens, Command, Range};
 
@@ -52,6 +53,8 @@ pub struct LensContext<'a> {
     pub popcon_cache: &'a crate::popcon::SharedPopconCache,
     /// Cache for reverse dependency lookups from UDD.
     pub rdeps_cache: &'a crate::rdeps::SharedRdepsCache,
+    /// Which lenses to produce.
+    pub categories: &'a CodeLensSettings,
 }
 
 /// Info about a Standards-Version field found in the control file.
@@ -269,6 +272,15 @@ fn find_vcs_git_field(
     Some((parsed_vcs.repo_url, range))
 }
 
+/// Suffix marking a lens title whose data is an expired on-disk copy.
+fn stale_suffix(stale: bool) -> &'static str {
+    if stale {
+        crate::disk_cache::STALE_LENS_SUFFIX
+    } else {
+        ""
+    }
+}
+
 /// Format a count for display, using k/M suffixes for large numbers.
 fn format_count(count: u32) -> String {
     if count >= 1_000_000 {
@@ -321,7 +333,7 @@ pub async fn generate_code_lenses(
     let mut uncached = UncachedLensData::default();
 
     // Standards-Version lens (cache-only read for policy version)
-    if !data.standards_versions.is_empty() {
+    if ctx.categories.standards_version && !data.standards_versions.is_empty() {
         let cache = ctx.package_cache.read().await;
         let latest_standards = cache.get_cached_versions("debian-policy").and_then(|vs| {
             vs.first()
@@ -350,7 +362,7 @@ pub async fn generate_code_lenses(
     }
 
     // debhelper-compat lens (local dh_assistant call, always awaited)
-    if !data.debhelper_compats.is_empty() {
+    if ctx.categories.debhelper_compat && !data.debhelper_compats.is_empty() {
         if let Some(levels) = get_compat_levels().await {
             for dh in &data.debhelper_compats {
                 let title = if levels.max == levels.highest_stable {
@@ -364,18 +376,19 @@ pub async fn generate_code_lenses(
     }
 
     // Vcs-Git lens (cache-only)
-    if let Some((url, range)) = find_vcs_git_field(parsed, src) {
+    if let Some((url, range)) = find_vcs_git_field(parsed, src).filter(|_| ctx.categories.vcs_git) {
         let cache = ctx.vcswatch_cache.read().await;
         let version = cache
             .get_cached_version_for_url(&url)
             .map(|s| s.to_string());
         let is_cached = cache.is_cached(&url);
+        let suffix = stale_suffix(cache.is_stale(&url));
         drop(cache);
 
         if let Some(version) = version {
             lenses.push(make_link_lens(
                 range,
-                format!("git: {}", version),
+                format!("git: {}{}", version, suffix),
                 url.clone(),
             ));
         } else if !is_cached {
@@ -384,16 +397,22 @@ pub async fn generate_code_lenses(
     }
 
     // Source package bug count lens (cache-only)
-    if let Some(source) = &data.source_package {
+    if let Some(source) = data.source_package.as_ref().filter(|_| ctx.categories.bugs) {
         let cache = ctx.bug_cache.read().await;
         let bug_count = cache.get_cached_open_bug_count(&source.name);
+        let suffix = stale_suffix(cache.is_source_package_stale(&source.name));
         drop(cache);
 
         match bug_count {
             Some(count) if count > 0 => {
                 lenses.push(make_link_lens(
                     source.range,
-                    format!("{} open {}", count, if count == 1 { "bug" } else { "bugs" }),
+                    format!(
+                        "{} open {}{}",
+                        count,
+                        if count == 1 { "bug" } else { "bugs" },
+                        suffix
+                    ),
                     format!("https://bugs.debian.org/src:{}", source.name),
                 ));
             }
@@ -408,14 +427,20 @@ pub async fn generate_code_lenses(
     for pkg in &data.binary_packages {
         let mut needs_fetch = false;
 
-        {
+        if ctx.categories.bugs {
             let cache = ctx.bug_cache.read().await;
             let bug_count = cache.get_cached_open_binary_bug_count(&pkg.name);
+            let suffix = stale_suffix(cache.is_binary_package_stale(&pkg.name));
             match bug_count {
                 Some(count) if count > 0 => {
                     lenses.push(make_link_lens(
                         pkg.range,
-                        format!("{} open {}", count, if count == 1 { "bug" } else { "bugs" }),
+                        format!(
+                            "{} open {}{}",
+                            count,
+                            if count == 1 { "bug" } else { "bugs" },
+                            suffix
+                        ),
                         format!("https://bugs.debian.org/{}", pkg.name),
                     ));
                 }
@@ -426,12 +451,16 @@ pub async fn generate_code_lenses(
             }
         }
 
-        {
+        if ctx.categories.popcon {
             let cache = ctx.popcon_cache.read().await;
             if let Some(count) = cache.get_cached_inst_count(&pkg.name) {
                 lenses.push(make_link_lens(
                     pkg.range,
-                    format!("popcon: {} installs", format_count(count)),
+                    format!(
+                        "popcon: {} installs{}",
+                        format_count(count),
+                        stale_suffix(cache.is_stale(&pkg.name))
+                    ),
                     format!(
                         "https://qa.debian.org/popcon-graph.php?packages={}",
                         pkg.name
@@ -442,13 +471,17 @@ pub async fn generate_code_lenses(
             }
         }
 
-        {
+        if ctx.categories.reverse_dependencies {
             let cache = ctx.rdeps_cache.read().await;
             if let Some(count) = cache.get_cached_rdeps_count(&pkg.name) {
                 if count > 0 {
                     lenses.push(make_link_lens(
                         pkg.range,
-                        format!("{} reverse deps", format_count(count)),
+                        format!(
+                            "{} reverse deps{}",
+                            format_count(count),
+                            stale_suffix(cache.is_stale(&pkg.name))
+                        ),
                         format!("https://tracker.debian.org/pkg/{}", pkg.name),
                     ));
                 }
@@ -519,6 +552,7 @@ mod tests {
             bug_cache: &bug_cache,
             popcon_cache: &popcon_cache,
             rdeps_cache: &rdeps_cache,
+            categories: &CodeLensSettings::default(),
         };
         let idx = crate::position::LineIndex::new(content);
         let (lenses, _uncached) =
@@ -528,6 +562,49 @@ mod tests {
         assert_eq!(lenses[0].command.as_ref().unwrap().title, "latest: 4.7.3");
     }
 
+    #[tokio::test]
+    async fn test_code_lens_category_disabled() {
+        use crate::package_cache::{TestPackageCache, VersionInfo};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+
+        let mut cache = TestPackageCache::default();
+        cache.versions.insert(
+            "debian-policy".to_string(),
+            vec![VersionInfo {
+                version: "4.7.3.0".to_string(),
+                suites: vec!["unstable".to_string()],
+            }],
+        );
+        let shared_cache: crate::package_cache::SharedPackageCache = Arc::new(RwLock::new(cache));
+        let vcswatch_cache = make_shared_vcswatch_cache();
+        let bug_cache = make_shared_bug_cache();
+        let popcon_cache = make_shared_popcon_cache();
+        let rdeps_cache = make_shared_rdeps_cache();
+        let categories = CodeLensSettings {
+            standards_version: false,
+            bugs: false,
+            ..Default::default()
+        };
+        let ctx = LensContext {
+            package_cache: &shared_cache,
+            vcswatch_cache: &vcswatch_cache,
+            bug_cache: &bug_cache,
+            popcon_cache: &popcon_cache,
+            rdeps_cache: &rdeps_cache,
+            categories: &categories,
+        };
+
+        let content = "Source: test-package\nStandards-Version: 4.6.2\n";
+        let parsed = debian_control::lossless::Control::parse(content);
+        let idx = crate::position::LineIndex::new(content);
+        let (lenses, uncached) =
+            generate_code_lenses(&parsed, Source::new(content, &idx), &ctx).await;
+
+        assert!(lenses.is_empty());
+        assert!(uncached.is_empty());
+    }
+
     #[tokio::test]
     async fn test_no_code_lens_when_current() {
         use crate::package_cache::{TestPackageCache, VersionInfo};
@@ -558,6 +635,7 @@ mod tests {
             bug_cache: &bug_cache,
             popcon_cache: &popcon_cache,
             rdeps_cache: &rdeps_cache,
+            categories: &CodeLensSettings::default(),
         };
         let idx = crate::position::LineIndex::new(content);
         let (lenses, _uncached) =
@@ -602,6 +680,7 @@ Maintainer: Test <test@example.com>
             bug_cache: &bug_cache,
             popcon_cache: &popcon_cache,
             rdeps_cache: &rdeps_cache,
+            categories: &CodeLensSettings::default(),
         };
         let idx = crate::position::LineIndex::new(content);
         let (lenses, _uncached) =
@@ -660,6 +739,7 @@ Maintainer: Test <test@example.com>
             bug_cache: &bug_cache,
             popcon_cache: &popcon_cache,
             rdeps_cache: &rdeps_cache,
+            categories: &CodeLensSettings::default(),
         };
         let idx = crate::position::LineIndex::new(content);
         let (lenses, _uncached) =
@@ -703,6 +783,7 @@ Maintainer: Test <test@example.com>
             bug_cache: &bug_cache,
             popcon_cache: &popcon_cache,
             rdeps_cache: &rdeps_cache,
+            categories: &CodeLensSettings::default(),
         };
         let idx = crate::position::LineIndex::new(content);
         let (lenses, _uncached) =
@@ -738,6 +819,7 @@ Maintainer: Test <test@example.com>
             bug_cache: &bug_cache,
             popcon_cache: &popcon_cache,
             rdeps_cache: &rdeps_cache,
+            categories: &CodeLensSettings::default(),
         };
         let idx = crate::position::LineIndex::new(content);
         let (lenses, _uncached) =
@@ -769,6 +851,7 @@ Maintainer: Test <test@example.com>
             bug_cache: &bug_cache,
             popcon_cache: &popcon_cache,
             rdeps_cache: &rdeps_cache,
+            categories: &CodeLensSettings::default(),
         };
         let idx = crate::position::LineIndex::new(content);
         let (lenses, _uncached) =
@@ -811,6 +894,7 @@ Maintainer: Test <test@example.com>
             bug_cache: &bug_cache,
             popcon_cache: &popcon_cache,
             rdeps_cache: &rdeps_cache,
+            categories: &CodeLensSettings::default(),
         };
         let idx = crate::position::LineIndex::new(content);
         let (lenses, _uncached) =
@@ -862,6 +946,7 @@ Description: Foo library
             bug_cache: &bug_cache,
             popcon_cache: &popcon_cache,
             rdeps_cache: &rdeps_cache,
+            categories: &CodeLensSettings::default(),
         };
         let idx = crate::position::LineIndex::new(content);
         let (lenses, _uncached) =
@@ -923,6 +1008,7 @@ Description: Foo library
             bug_cache: &bug_cache,
             popcon_cache: &popcon_cache,
             rdeps_cache: &rdeps_cache,
+            categories: &CodeLensSettings::default(),
         };
         let idx = crate::position::LineIndex::new(content);
         let (lenses, _uncached) =
@@ -126,13 +126,15 @@ pub fn get_field_value_completions(field_name: &str, prefix: &str) -> Vec<Comple
 /// Get async value completions for control file fields that need the package cache.
 ///
 /// Returns `Some` with completions for relationship fields, `None` for other fields.
+/// Maintainer completions need `maintainer_cache`; without it (UDD lookups
+/// turned off) those fields fall back to the synchronous completions too.
 pub async fn get_async_field_value_completions(
     field_name: &str,
     prefix: &str,
     position: tower_lsp_server::ls_types::Position,
     package_cache: &SharedPackageCache,
     architecture_list: &SharedArchitectureList,
-    maintainer_cache: &SharedMaintainerCache,
+    maintainer_cache: Option<&SharedMaintainerCache>,
 ) -> Option<Vec<CompletionItem>> {
     if relation_completion::is_relationship_field(field_name) {
         Some(
@@ -149,7 +151,7 @@ pub async fn get_async_field_value_completions(
     } else if field_name.eq_ignore_ascii_case("Maintainer")
         || field_name.eq_ignore_ascii_case("Uploaders")
     {
-        Some(get_maintainer_completions(prefix, maintainer_cache).await)
+        Some(get_maintainer_completions(prefix, maintainer_cache?).await)
     } else {
         None
     }
@@ -704,7 +706,7 @@ mod tests {
             Position::new(0, 2),
             &cache,
             &test_arch_list(),
-            &test_maintainer_cache(),
+            Some(&test_maintainer_cache()),
         )
         .await
         .expect("Should return completions");
@@ -721,7 +723,7 @@ mod tests {
             Position::new(0, 0),
             &cache,
             &test_arch_list(),
-            &test_maintainer_cache(),
+            Some(&test_maintainer_cache()),
         )
         .await
         .expect("Should return completions");
@@ -737,7 +739,7 @@ mod tests {
             Position::new(0, 4),
             &cache,
             &test_arch_list(),
-            &test_maintainer_cache(),
+            Some(&test_maintainer_cache()),
         )
         .await;
         assert!(completions.is_none());
@@ -771,7 +773,7 @@ mod tests {
                     tower_lsp_server::ls_types::Position::new(0, 15),
                     &cache,
                     &test_arch_list(),
-                    &test_maintainer_cache(),
+                    Some(&test_maintainer_cache()),
                 )
                 .await
                 .expect("Should return completions for relationship field");
@@ -808,7 +810,7 @@ mod tests {
                     tower_lsp_server::ls_types::Position::new(0, 17),
                     &cache,
                     &test_arch_list(),
-                    &test_maintainer_cache(),
+                    Some(&test_maintainer_cache()),
                 )
                 .await
                 .expect("Should return completions for relationship field");
@@ -847,7 +849,7 @@ mod tests {
                     position,
                     &cache,
                     &test_arch_list(),
-                    &test_maintainer_cache(),
+                    Some(&test_maintainer_cache()),
                 )
                 .await
                 .expect("Should return completions");
@@ -1000,7 +1002,7 @@ mod tests {
             Position::new(0, 3),
             &cache,
             &test_arch_list(),
-            &test_maintainer_cache(),
+            Some(&test_maintainer_cache()),
         )
         .await
         .expect("Should return completions for Maintainer field");
@@ -1017,7 +1019,7 @@ mod tests {
             Position::new(0, 0),
             &cache,
             &test_arch_list(),
-            &test_maintainer_cache(),
+            Some(&test_maintainer_cache()),
         )
         .await
         .expect("Should return completions for Uploaders field");
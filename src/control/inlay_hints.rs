@@ -11,6 +11,7 @@ use text_size::TextSize;
 use tower_lsp_server::ls_types::{InlayHint, InlayHintKind, InlayHintLabel};
 
 use crate::position::Source;
+use crate::settings::InlayHintSettings;
 
 /// Create an inlay hint at the given source offset with the given label.
 ///
@@ -277,6 +278,8 @@ pub struct HintContext<'a> {
     pub package_cache: &'a crate::package_cache::SharedPackageCache,
     /// Resolved substvar values (e.g. `"binary:Version"` → `"1.2.3-1"`).
     pub resolved_substvars: &'a HashMap<String, String>,
+    /// Which hints to produce.
+    pub categories: &'a InlayHintSettings,
 }
 
 /// Generate inlay hints for a control file.
@@ -318,20 +321,23 @@ pub async fn generate_inlay_hints(
             if let Some(versions) = cached_versions {
                 if !versions.is_empty() {
                     // Real package with version info — show archive versions
+                    if !ctx.categories.archive_versions {
+                        continue;
+                    }
                     if let Some(label) = format_version_hint(versions) {
                         hints.push(make_hint(src, rel.relation_end, label));
                     }
                 } else if let Some(providers) = cached_providers {
                     // Versions cached but empty = virtual package; show providers
                     // with their available versions
-                    if !providers.is_empty() {
+                    if ctx.categories.virtual_packages && !providers.is_empty() {
                         let label =
                             format_provider_hint(providers, &*cache, &mut uncached_packages, 80);
                         hints.push(make_hint(src, rel.relation_end, label));
                     }
                 }
                 // else: versions empty, no providers cached — will be loaded in background
-            } else {
+            } else if ctx.categories.archive_versions || ctx.categories.virtual_packages {
                 // Versions not cached yet
                 uncached_packages.push(rel.name.clone());
             }
@@ -344,7 +350,7 @@ pub async fn generate_inlay_hints(
     uncached_packages.dedup();
 
     // Substvar hints
-    for sv in &data.substvars {
+    for sv in data.substvars.iter().filter(|_| ctx.categories.substvars) {
         if let Some(value) = ctx.resolved_substvars.get(&sv.name) {
             hints.push(make_hint(src, sv.substvar_end, format!("[= {}]", value)));
         }
@@ -357,6 +363,13 @@ pub async fn generate_inlay_hints(
 mod tests {
     use super::*;
 
+    const DEFAULT_CATEGORIES: InlayHintSettings = InlayHintSettings {
+        archive_versions: true,
+        virtual_packages: true,
+        substvars: true,
+        distributions: true,
+    };
+
     fn default_ctx<'a>(
         package_cache: &'a crate::package_cache::SharedPackageCache,
         resolved_substvars: &'a HashMap<String, String>,
@@ -364,6 +377,7 @@ mod tests {
         HintContext {
             package_cache,
             resolved_substvars,
+            categories: &DEFAULT_CATEGORIES,
         }
     }
 
@@ -767,4 +781,36 @@ Description: A test
             _ => panic!("Expected string label"),
         }
     }
+
+    #[tokio::test]
+    async fn test_inlay_hint_substvar_disabled() {
+        use crate::package_cache::TestPackageCache;
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+
+        let shared_cache: crate::package_cache::SharedPackageCache =
+            Arc::new(RwLock::new(TestPackageCache::default()));
+        let mut resolved = HashMap::new();
+        resolved.insert("shlibs:Depends".to_string(), "libc6".to_string());
+
+        let content = "Package: test-package\nDepends: ${shlibs:Depends}\n";
+        let parsed = debian_control::lossless::Control::parse(content);
+        let range = tower_lsp_server::ls_types::Range {
+            start: tower_lsp_server::ls_types::Position::new(0, 0),
+            end: tower_lsp_server::ls_types::Position::new(2, 0),
+        };
+        let categories = InlayHintSettings {
+            substvars: false,
+            ..DEFAULT_CATEGORIES
+        };
+        let ctx = HintContext {
+            categories: &categories,
+            ..default_ctx(&shared_cache, &resolved)
+        };
+
+        let idx = crate::position::LineIndex::new(content);
+        let (hints, _uncached) =
+            generate_inlay_hints(&parsed, Source::new(content, &idx), &range, &ctx).await;
+        assert!(hints.is_empty());
+    }
 }
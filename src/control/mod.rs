@@ -9,6 +9,7 @@ pub mod hover;
 pub mod inlay_hints;
 pub mod references;
 pub(crate) mod relation_completion;
+pub mod relation_diagnostics;
 pub mod rename;
 pub mod semantic;
 #[cfg(feature = "spellcheck")]
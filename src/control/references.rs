@@ -2,16 +2,72 @@
 //!
 //! When the cursor is on a `Package:` field value, finds all locations in the
 //! same control file where that package name appears in relationship fields
-//! (Depends, Build-Depends, etc.).
+//! (Depends, Build-Depends, etc.). The other packaging files that refer to a
+//! binary package — `<package>.install` and friends, package specs in
+//! lintian-overrides and `Depends` in debian/tests/control — are searched by
+//! the `find_*_references` helpers below.
 
 use debian_control::lossless::relations::Relations;
 use debian_control::lossless::{Control, Parse};
 use debian_control::relations::SyntaxKind as RelSyntaxKind;
-use tower_lsp_server::ls_types::{Location, Position, Uri};
+use lintian_overrides::{AstNode as _, LintianOverrides, SyntaxKind};
+use tower_lsp_server::ls_types::{Location, Position, Range, Uri};
 
 use super::relation_completion::is_relationship_field;
+use super::rename::PACKAGE_FILE_EXTENSIONS;
 use crate::position::Source;
 
+/// Byte ranges, relative to `raw_value`, of every relation on `package_name`
+/// in a relationship field value.
+fn relation_name_ranges(raw_value: &str, package_name: &str) -> Vec<rowan::TextRange> {
+    let (relations, _errors) = Relations::parse_relaxed(raw_value, false);
+    let syntax = relations.syntax();
+
+    let mut ranges = Vec::new();
+    let mut tok = syntax.first_token();
+    while let Some(t) = tok {
+        if t.kind() == RelSyntaxKind::IDENT
+            && t.text() == package_name
+            && t.parent().map(|p| p.kind()) == Some(RelSyntaxKind::RELATION)
+        {
+            ranges.push(t.text_range());
+        }
+        tok = t.next_token();
+    }
+    ranges
+}
+
+/// Locations of `package_name` in the relationship fields of a deb822 file.
+fn find_relation_references(
+    deb822: &deb822_lossless::Deb822,
+    src: Source<'_>,
+    package_name: &str,
+    uri: &Uri,
+) -> Vec<Location> {
+    let mut locations = Vec::new();
+    for paragraph in deb822.paragraphs() {
+        for entry in paragraph.entries() {
+            let Some(field_name) = entry.key() else {
+                continue;
+            };
+            if !is_relationship_field(&field_name) {
+                continue;
+            }
+            let Some(value_range) = entry.value_range() else {
+                continue;
+            };
+            let raw_value = &src.text[value_range];
+            for range in relation_name_ranges(raw_value, package_name) {
+                locations.push(Location {
+                    uri: uri.clone(),
+                    range: src.text_range_to_lsp_range(range + value_range.start()),
+                });
+            }
+        }
+    }
+    locations
+}
+
 /// Find all locations in a control file where the given package name appears
 /// in relationship fields.
 fn find_package_references_in_control(
@@ -43,48 +99,12 @@ fn find_package_references_in_control(
     }
 
     // Scan all relationship fields for references to the package name.
-    for paragraph in control.as_deb822().paragraphs() {
-        for entry in paragraph.entries() {
-            let Some(field_name) = entry.key() else {
-                continue;
-            };
-            if !is_relationship_field(&field_name) {
-                continue;
-            }
-
-            let Some(value_range) = entry.value_range() else {
-                continue;
-            };
-            let value_start: usize = value_range.start().into();
-            let value_end: usize = value_range.end().into();
-            let raw_value = &src.text[value_start..value_end];
-
-            let (relations, _errors) = Relations::parse_relaxed(raw_value, false);
-            let syntax = relations.syntax();
-
-            let mut tok = syntax.first_token();
-            while let Some(t) = tok {
-                if t.kind() == RelSyntaxKind::IDENT {
-                    if let Some(parent) = t.parent() {
-                        if parent.kind() == RelSyntaxKind::RELATION && t.text() == package_name {
-                            let rel_start: usize = t.text_range().start().into();
-                            let rel_end: usize = t.text_range().end().into();
-                            let abs_range = rowan::TextRange::new(
-                                ((value_start + rel_start) as u32).into(),
-                                ((value_start + rel_end) as u32).into(),
-                            );
-                            let range = src.text_range_to_lsp_range(abs_range);
-                            locations.push(Location {
-                                uri: uri.clone(),
-                                range,
-                            });
-                        }
-                    }
-                }
-                tok = t.next_token();
-            }
-        }
-    }
+    locations.extend(find_relation_references(
+        control.as_deb822(),
+        src,
+        package_name,
+        uri,
+    ));
 
     locations
 }
@@ -101,10 +121,24 @@ pub fn find_references(
     uri: &Uri,
     include_declaration: bool,
 ) -> Vec<Location> {
+    match referenced_package_at(parse, src, position) {
+        Some(name) => {
+            find_package_references_in_control(parse, src, &name, uri, include_declaration)
+        }
+        None => Vec::new(),
+    }
+}
+
+/// The binary package of this control file named at the cursor position:
+/// either the value of a `Package:` field or a relation on one of the
+/// file's own binary packages.
+pub fn referenced_package_at(
+    parse: &Parse<Control>,
+    src: Source<'_>,
+    position: Position,
+) -> Option<String> {
     let control = parse.tree();
-    let Some(offset) = src.try_position_to_offset(position) else {
-        return Vec::new();
-    };
+    let offset = src.try_position_to_offset(position)?;
 
     // Check if cursor is on a Package: field value.
     for binary in control.binaries() {
@@ -117,13 +151,7 @@ pub fn find_references(
         };
         if value_range.contains(offset) || value_range.end() == offset {
             if let Some(name) = binary.name() {
-                return find_package_references_in_control(
-                    parse,
-                    src,
-                    &name,
-                    uri,
-                    include_declaration,
-                );
+                return Some(name);
             }
         }
     }
@@ -136,23 +164,14 @@ pub fn find_references(
         .find(|entry| {
             let r = entry.text_range();
             r.start() <= offset && offset < r.end()
-        });
-
-    let Some(entry) = entry else {
-        return Vec::new();
-    };
-
-    let Some(field_name) = entry.key() else {
-        return Vec::new();
-    };
+        })?;
 
+    let field_name = entry.key()?;
     if !is_relationship_field(&field_name) {
-        return Vec::new();
+        return None;
     }
 
-    let Some(value_range) = entry.value_range() else {
-        return Vec::new();
-    };
+    let value_range = entry.value_range()?;
     let value_start: usize = value_range.start().into();
     let value_end: usize = value_range.end().into();
     let raw_value = &src.text[value_start..value_end];
@@ -180,19 +199,62 @@ pub fn find_references(
         tok = t.next_token();
     }
 
-    let Some(name) = package_name else {
-        return Vec::new();
-    };
+    let name = package_name?;
 
     // Only find references for packages defined in this control file.
     let is_local = control
         .binaries()
         .any(|b| b.name().as_deref() == Some(&name));
-    if !is_local {
-        return Vec::new();
-    }
+    is_local.then_some(name)
+}
 
-    find_package_references_in_control(parse, src, &name, uri, include_declaration)
+/// A per-package file such as `debian/<package>.install` refers to its
+/// package by name; the location is the start of the file.
+pub fn find_package_file_reference(uri: &Uri, package_name: &str) -> Option<Location> {
+    let file_name = uri.as_str().rsplit('/').next()?;
+    let extension = file_name.strip_prefix(package_name)?.strip_prefix('.')?;
+    PACKAGE_FILE_EXTENSIONS
+        .contains(&extension)
+        .then(|| Location {
+            uri: uri.clone(),
+            range: Range::default(),
+        })
+}
+
+/// Locations of `package_name` in the package specs (`package:` or
+/// `package binary:`) of a lintian-overrides file.
+pub fn find_lintian_overrides_references(
+    overrides: &LintianOverrides,
+    src: Source<'_>,
+    package_name: &str,
+    uri: &Uri,
+) -> Vec<Location> {
+    overrides
+        .lines()
+        .filter_map(|line| line.package_spec())
+        .filter(|spec| spec.package_name().as_deref() == Some(package_name))
+        .filter_map(|spec| {
+            spec.syntax()
+                .children_with_tokens()
+                .filter_map(|it| it.into_token())
+                .find(|t| t.kind() == SyntaxKind::PACKAGE_NAME)
+        })
+        .map(|token| Location {
+            uri: uri.clone(),
+            range: src.text_range_to_lsp_range(token.text_range()),
+        })
+        .collect()
+}
+
+/// Locations of `package_name` in the `Depends` fields of
+/// debian/tests/control.
+pub fn find_tests_control_references(
+    deb822: &deb822_lossless::Deb822,
+    src: Source<'_>,
+    package_name: &str,
+    uri: &Uri,
+) -> Vec<Location> {
+    find_relation_references(deb822, src, package_name, uri)
 }
 
 #[cfg(test)]
@@ -389,4 +451,63 @@ Description: Dev files
         let refs = find_references(&parsed, src, Position::new(7, 10), &uri, false);
         assert!(refs.is_empty());
     }
+
+    #[test]
+    fn test_package_file_reference() {
+        let uri = Uri::from_file_path("/tmp/debian/foo-dev.install").unwrap();
+        let location = find_package_file_reference(&uri, "foo-dev").unwrap();
+        assert_eq!(location.range, Range::default());
+
+        assert!(find_package_file_reference(&uri, "foo").is_none());
+        let docs = Uri::from_file_path("/tmp/debian/foo-dev.README").unwrap();
+        assert!(find_package_file_reference(&docs, "foo-dev").is_none());
+    }
+
+    #[test]
+    fn test_lintian_overrides_references() {
+        let text = "\
+foo: some-tag
+foo-dev binary: other-tag
+# foo: commented-tag
+some-tag-without-package
+";
+        let parsed = LintianOverrides::parse(text);
+        let idx = crate::position::LineIndex::new(text);
+        let src = Source::new(text, &idx);
+        let uri = Uri::from_file_path("/tmp/debian/foo.lintian-overrides").unwrap();
+
+        let refs = find_lintian_overrides_references(&parsed.tree(), src, "foo", &uri);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(
+            refs[0].range,
+            Range::new(Position::new(0, 0), Position::new(0, 3))
+        );
+
+        let refs = find_lintian_overrides_references(&parsed.tree(), src, "foo-dev", &uri);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].range.start, Position::new(1, 0));
+    }
+
+    #[test]
+    fn test_tests_control_references() {
+        let text = "\
+Tests: smoke
+Depends: foo, foo-dev (>= 1.0) | bar
+
+Test-Command: foo --version
+Depends: @, foo
+";
+        let parsed = deb822_lossless::Deb822::parse(text);
+        let idx = crate::position::LineIndex::new(text);
+        let src = Source::new(text, &idx);
+        let uri = Uri::from_file_path("/tmp/debian/tests/control").unwrap();
+
+        let refs = find_tests_control_references(&parsed.tree(), src, "foo", &uri);
+        let lines: Vec<u32> = refs.iter().map(|r| r.range.start.line).collect();
+        assert_eq!(lines, vec![1, 4]);
+        assert_eq!(
+            refs[0].range,
+            Range::new(Position::new(1, 9), Position::new(1, 12))
+        );
+    }
 }
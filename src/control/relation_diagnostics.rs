@@ -0,0 +1,769 @@
+//! Semantic diagnostics for relationship fields in debian/control.
+//!
+//! The relations parser only reports syntax errors. This module checks the
+//! parsed relations for problems that are still valid syntax: packages
+//! missing from the apt cache, version constraints that can never be
+//! satisfied together, duplicated relations, binary packages that depend
+//! on themselves and unknown architecture qualifiers.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use debian_control::lossless::relations::{Relation, Relations};
+use debian_control::lossless::{Control, Parse};
+use debian_control::relations::{SyntaxKind as RelSyntaxKind, VersionConstraint};
+use rowan::NodeOrToken;
+use text_size::TextRange;
+use tower_lsp_server::ls_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+use super::inlay_hints::joined_offset_to_source_offset;
+use super::relation_completion::is_relationship_field;
+use crate::package_cache::PackageCache;
+use crate::position::Source;
+
+/// Fields that pull in other packages. Only these are checked against the
+/// package cache: `Conflicts`, `Breaks` and `Replaces` routinely name
+/// packages that no longer exist, and `Provides` declares new names.
+const DEPENDENCY_FIELDS: &[&str] = &[
+    "Depends",
+    "Pre-Depends",
+    "Recommends",
+    "Suggests",
+    "Enhances",
+    "Build-Depends",
+    "Build-Depends-Indep",
+    "Build-Depends-Arch",
+];
+
+/// Binary package fields in which naming the package itself is a mistake.
+const SELF_DEPENDENCY_FIELDS: &[&str] = &["Depends", "Pre-Depends", "Recommends", "Suggests"];
+
+/// Architecture qualifiers that are valid regardless of the architecture list.
+const SPECIAL_ARCH_QUALIFIERS: &[&str] = &["any", "native"];
+
+/// External data the relationship checks consult.
+///
+/// Both lookups are optional: without a package cache the unknown-package
+/// check is skipped, and with an empty architecture list only the special
+/// `:any` and `:native` qualifiers are known.
+#[derive(Clone, Copy, Default)]
+pub struct RelationLookup<'a> {
+    /// Package cache to resolve package names against.
+    pub packages: Option<&'a dyn PackageCache>,
+    /// Known architecture names, as listed by `dpkg-architecture -L`.
+    pub architectures: &'a [String],
+}
+
+/// The kind of problem found in a relationship field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelationIssueKind {
+    /// The package is not known to the package cache.
+    UnknownPackage { package: String },
+    /// The version constraints on a package exclude every version.
+    ImpossibleConstraint { package: String },
+    /// The same relation appears more than once in a field.
+    Duplicate { relation: String },
+    /// A binary package depends on itself.
+    SelfDependency { package: String },
+    /// The architecture qualifier is not `any`, `native` or a known architecture.
+    UnknownArchQualifier { archqual: String },
+}
+
+/// A problem found in a relationship field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelationIssue {
+    /// What is wrong.
+    pub kind: RelationIssueKind,
+    /// The source range to highlight.
+    pub range: TextRange,
+}
+
+/// A relation with its version constraint, located in the source.
+struct LocatedRelation {
+    name: String,
+    version: Option<(VersionConstraint, debversion::Version)>,
+    /// Whether the relation is restricted by architecture or build profile,
+    /// so it only applies to some builds.
+    restricted: bool,
+    range: TextRange,
+    name_range: TextRange,
+}
+
+/// Map a range in the joined field value back to the source text.
+fn to_source_range(line_ranges: &[TextRange], range: TextRange) -> Option<TextRange> {
+    let start = joined_offset_to_source_offset(line_ranges, range.start().into())?;
+    let end = joined_offset_to_source_offset(line_ranges, range.end().into())?;
+    Some(TextRange::new(start, end))
+}
+
+/// Read the version constraint of a relation.
+///
+/// Unlike `Relation::version`, this does not panic on a version string
+/// that fails to parse; such relations are treated as unversioned.
+fn relation_version(relation: &Relation) -> Option<(VersionConstraint, debversion::Version)> {
+    let version_node = relation
+        .syntax()
+        .children()
+        .find(|n| n.kind() == RelSyntaxKind::VERSION)?;
+    let constraint = version_node
+        .children()
+        .find(|n| n.kind() == RelSyntaxKind::CONSTRAINT)?
+        .text()
+        .to_string()
+        .parse()
+        .ok()?;
+    let version: String = version_node
+        .children_with_tokens()
+        .filter_map(|it| match it {
+            NodeOrToken::Token(t)
+                if t.kind() == RelSyntaxKind::IDENT || t.kind() == RelSyntaxKind::COLON =>
+            {
+                Some(t.text().to_string())
+            }
+            _ => None,
+        })
+        .collect();
+    Some((constraint, version.parse().ok()?))
+}
+
+/// A bound on the versions that satisfy a set of constraints.
+#[derive(Clone)]
+struct Bound {
+    version: debversion::Version,
+    inclusive: bool,
+}
+
+/// The range of versions allowed by all constraints seen so far.
+#[derive(Default)]
+struct VersionRange {
+    lower: Option<Bound>,
+    upper: Option<Bound>,
+}
+
+impl VersionRange {
+    /// Narrow the range by one more constraint.
+    fn constrain(&mut self, constraint: VersionConstraint, version: &debversion::Version) {
+        let bound = |inclusive| Bound {
+            version: version.clone(),
+            inclusive,
+        };
+        match constraint {
+            VersionConstraint::GreaterThanEqual => self.raise_lower(bound(true)),
+            VersionConstraint::GreaterThan => self.raise_lower(bound(false)),
+            VersionConstraint::LessThanEqual => self.lower_upper(bound(true)),
+            VersionConstraint::LessThan => self.lower_upper(bound(false)),
+            VersionConstraint::Equal => {
+                self.raise_lower(bound(true));
+                self.lower_upper(bound(true));
+            }
+        }
+    }
+
+    fn raise_lower(&mut self, new: Bound) {
+        let tighter = match &self.lower {
+            None => true,
+            Some(old) => match new.version.cmp(&old.version) {
+                Ordering::Greater => true,
+                Ordering::Equal => old.inclusive && !new.inclusive,
+                Ordering::Less => false,
+            },
+        };
+        if tighter {
+            self.lower = Some(new);
+        }
+    }
+
+    fn lower_upper(&mut self, new: Bound) {
+        let tighter = match &self.upper {
+            None => true,
+            Some(old) => match new.version.cmp(&old.version) {
+                Ordering::Less => true,
+                Ordering::Equal => old.inclusive && !new.inclusive,
+                Ordering::Greater => false,
+            },
+        };
+        if tighter {
+            self.upper = Some(new);
+        }
+    }
+
+    /// Whether no version satisfies the range.
+    fn is_empty(&self) -> bool {
+        let (Some(lower), Some(upper)) = (&self.lower, &self.upper) else {
+            return false;
+        };
+        match lower.version.cmp(&upper.version) {
+            Ordering::Greater => true,
+            Ordering::Equal => !(lower.inclusive && upper.inclusive),
+            Ordering::Less => false,
+        }
+    }
+}
+
+/// The packages named in dependency fields that are neither built from this
+/// source nor in the package list, and whose providers haven't been looked up
+/// yet. These have to be resolved before the unknown-package check can tell
+/// virtual packages from missing ones.
+pub fn unresolved_packages(parsed: &Parse<Control>, packages: &dyn PackageCache) -> Vec<String> {
+    let control = parsed.tree();
+    let local_packages: HashSet<String> = control.binaries().filter_map(|b| b.name()).collect();
+    let mut names = Vec::new();
+    for paragraph in control.as_deb822().paragraphs() {
+        for entry in paragraph.entries() {
+            let is_dependency_field = entry.key().is_some_and(|key| {
+                DEPENDENCY_FIELDS
+                    .iter()
+                    .any(|f| f.eq_ignore_ascii_case(&key))
+            });
+            if !is_dependency_field {
+                continue;
+            }
+            let (relations, _errors) = Relations::parse_relaxed(&entry.value(), true);
+            for relation in relations
+                .entries()
+                .flat_map(|e| e.relations().collect::<Vec<_>>())
+            {
+                let Some(name) = relation.try_name() else {
+                    continue;
+                };
+                if !local_packages.contains(&name)
+                    && !packages.has_package(&name)
+                    && packages.get_cached_providers(&name).is_none()
+                    && !names.contains(&name)
+                {
+                    names.push(name);
+                }
+            }
+        }
+    }
+    names
+}
+
+/// Find semantic problems in the relationship fields of a control file.
+pub fn find_relation_issues(
+    parsed: &Parse<Control>,
+    lookup: RelationLookup<'_>,
+) -> Vec<RelationIssue> {
+    let control = parsed.tree();
+    let mut issues = Vec::new();
+
+    // Packages built from this source are valid dependency targets even
+    // before they reach the archive.
+    let local_packages: HashSet<String> = control.binaries().filter_map(|b| b.name()).collect();
+    let packages = lookup.packages.filter(|cache| cache.is_complete());
+
+    for paragraph in control.as_deb822().paragraphs() {
+        let own_name = paragraph.get("Package").map(|n| n.trim().to_string());
+
+        for entry in paragraph.entries() {
+            let Some(field_name) = entry.key() else {
+                continue;
+            };
+            if !is_relationship_field(&field_name) {
+                continue;
+            }
+            let is_dependency_field = DEPENDENCY_FIELDS
+                .iter()
+                .any(|f| f.eq_ignore_ascii_case(&field_name));
+            let is_self_dependency_field = own_name.is_some()
+                && SELF_DEPENDENCY_FIELDS
+                    .iter()
+                    .any(|f| f.eq_ignore_ascii_case(&field_name));
+
+            let value = entry.value();
+            let (relations, _errors) = Relations::parse_relaxed(&value, true);
+            let line_ranges = entry.value_line_ranges();
+
+            let mut seen_entries = HashSet::new();
+            let mut ranges: HashMap<String, VersionRange> = HashMap::new();
+
+            for rel_entry in relations.entries() {
+                let alternatives: Vec<LocatedRelation> = rel_entry
+                    .relations()
+                    .filter_map(|relation| {
+                        let name = relation.try_name()?;
+                        let restricted = relation.architectures().is_some()
+                            || relation.profiles().next().is_some();
+                        let range = to_source_range(&line_ranges, relation.syntax().text_range())?;
+                        let name_range = to_source_range(&line_ranges, relation.name_range()?)?;
+
+                        if let Some(archqual) = relation.archqual() {
+                            let known = SPECIAL_ARCH_QUALIFIERS.contains(&archqual.as_str())
+                                || lookup.architectures.is_empty()
+                                || lookup.architectures.contains(&archqual);
+                            if !known {
+                                let archqual_range = relation
+                                    .syntax()
+                                    .children()
+                                    .find(|n| n.kind() == RelSyntaxKind::ARCHQUAL)
+                                    .and_then(|n| to_source_range(&line_ranges, n.text_range()));
+                                issues.push(RelationIssue {
+                                    kind: RelationIssueKind::UnknownArchQualifier { archqual },
+                                    range: archqual_range.unwrap_or(name_range),
+                                });
+                            }
+                        }
+
+                        Some(LocatedRelation {
+                            version: relation_version(&relation),
+                            name,
+                            restricted,
+                            range,
+                            name_range,
+                        })
+                    })
+                    .collect();
+
+                if alternatives.is_empty() {
+                    continue;
+                }
+
+                // Whitespace and line wrapping don't make two relations different.
+                let normalized: String = rel_entry
+                    .to_string()
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if !seen_entries.insert(normalized.clone()) {
+                    let start = alternatives[0].range.start();
+                    let end = alternatives[alternatives.len() - 1].range.end();
+                    issues.push(RelationIssue {
+                        kind: RelationIssueKind::Duplicate {
+                            relation: normalized,
+                        },
+                        range: TextRange::new(start, end),
+                    });
+                    continue;
+                }
+
+                for relation in &alternatives {
+                    if is_self_dependency_field && own_name.as_deref() == Some(&relation.name) {
+                        issues.push(RelationIssue {
+                            kind: RelationIssueKind::SelfDependency {
+                                package: relation.name.clone(),
+                            },
+                            range: relation.name_range,
+                        });
+                    }
+
+                    if is_dependency_field {
+                        if let Some(cache) = packages {
+                            // Virtual packages are missing from the package
+                            // list, so only warn once the providers are known
+                            // to be none.
+                            let unprovided = cache
+                                .get_cached_providers(&relation.name)
+                                .is_some_and(|p| p.is_empty());
+                            if !local_packages.contains(&relation.name)
+                                && !cache.has_package(&relation.name)
+                                && unprovided
+                            {
+                                issues.push(RelationIssue {
+                                    kind: RelationIssueKind::UnknownPackage {
+                                        package: relation.name.clone(),
+                                    },
+                                    range: relation.name_range,
+                                });
+                            }
+                        }
+                    }
+                }
+
+                // Only unconditional, non-alternative relations must all hold
+                // at once; `foo (>= 2) [amd64], foo (<< 1) [i386]` is fine.
+                if let [relation] = alternatives.as_slice() {
+                    if let (Some((constraint, version)), false) =
+                        (&relation.version, relation.restricted)
+                    {
+                        let range = ranges.entry(relation.name.clone()).or_default();
+                        let was_empty = range.is_empty();
+                        range.constrain(constraint.clone(), version);
+                        if !was_empty && range.is_empty() {
+                            issues.push(RelationIssue {
+                                kind: RelationIssueKind::ImpossibleConstraint {
+                                    package: relation.name.clone(),
+                                },
+                                range: relation.range,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+/// Convert a relation issue to an LSP diagnostic.
+fn issue_to_diagnostic(issue: RelationIssue, src: Source<'_>) -> Diagnostic {
+    let (severity, code, message) = match issue.kind {
+        RelationIssueKind::UnknownPackage { package } => (
+            DiagnosticSeverity::WARNING,
+            "unknown-package",
+            format!("Package '{}' is not known to apt", package),
+        ),
+        RelationIssueKind::ImpossibleConstraint { package } => (
+            DiagnosticSeverity::ERROR,
+            "impossible-version-constraint",
+            format!(
+                "Version constraints on '{}' cannot be satisfied together",
+                package
+            ),
+        ),
+        RelationIssueKind::Duplicate { relation } => (
+            DiagnosticSeverity::WARNING,
+            "duplicate-relation",
+            format!("Duplicate relation '{}'", relation),
+        ),
+        RelationIssueKind::SelfDependency { package } => (
+            DiagnosticSeverity::WARNING,
+            "self-dependency",
+            format!("Package '{}' depends on itself", package),
+        ),
+        RelationIssueKind::UnknownArchQualifier { archqual } => (
+            DiagnosticSeverity::WARNING,
+            "unknown-arch-qualifier",
+            format!("Unknown architecture qualifier ':{}'", archqual),
+        ),
+    };
+
+    Diagnostic {
+        range: src.text_range_to_lsp_range(issue.range),
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some("debian-lsp".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Get LSP diagnostics for the relationship fields of a control file.
+pub fn get_relation_diagnostics(
+    src: Source<'_>,
+    parsed: &Parse<Control>,
+    lookup: RelationLookup<'_>,
+) -> Vec<Diagnostic> {
+    find_relation_issues(parsed, lookup)
+        .into_iter()
+        .map(|issue| issue_to_diagnostic(issue, src))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package_cache::TestPackageCache;
+
+    fn issues(text: &str, lookup: RelationLookup<'_>) -> Vec<RelationIssue> {
+        find_relation_issues(&Control::parse(text), lookup)
+    }
+
+    fn kinds(text: &str) -> Vec<RelationIssueKind> {
+        issues(text, RelationLookup::default())
+            .into_iter()
+            .map(|i| i.kind)
+            .collect()
+    }
+
+    fn range_text(text: &str, range: TextRange) -> &str {
+        &text[usize::from(range.start())..usize::from(range.end())]
+    }
+
+    #[test]
+    fn test_no_issues() {
+        let text = "\
+Source: foo
+Build-Depends: debhelper-compat (= 13), libbar-dev (>= 1.0), libbar-dev (<< 2.0)
+
+Package: foo
+Architecture: any
+Depends: ${misc:Depends}, libbar1 (>= 1.0) | libbar2
+Description: Foo
+";
+        assert_eq!(kinds(text), vec![]);
+    }
+
+    #[test]
+    fn test_impossible_constraint() {
+        let text = "\
+Source: foo
+Build-Depends: libbar-dev (>= 2), libbar-dev (<< 1)
+";
+        let found = issues(text, RelationLookup::default());
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].kind,
+            RelationIssueKind::ImpossibleConstraint {
+                package: "libbar-dev".to_string()
+            }
+        );
+        assert_eq!(range_text(text, found[0].range), "libbar-dev (<< 1)");
+    }
+
+    #[test]
+    fn test_impossible_constraint_exclusive_bounds() {
+        let text = "\
+Source: foo
+Build-Depends: libbar-dev (>> 1.0), libbar-dev (<= 1.0)
+";
+        assert_eq!(
+            kinds(text),
+            vec![RelationIssueKind::ImpossibleConstraint {
+                package: "libbar-dev".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_exact_version_within_range() {
+        let text = "\
+Source: foo
+Build-Depends: libbar-dev (>= 1.0), libbar-dev (= 1.0), libbar-dev (<= 1.0)
+";
+        assert_eq!(kinds(text), vec![]);
+    }
+
+    #[test]
+    fn test_conflicting_constraints_on_different_architectures() {
+        let text = "\
+Source: foo
+Build-Depends: libbar-dev (>= 2) [amd64], libbar-dev (<< 1) [i386]
+";
+        assert_eq!(kinds(text), vec![]);
+    }
+
+    #[test]
+    fn test_alternatives_are_not_impossible() {
+        let text = "\
+Source: foo
+Build-Depends: libbar-dev (>= 2) | libbar-dev (<< 1)
+";
+        assert_eq!(kinds(text), vec![]);
+    }
+
+    #[test]
+    fn test_duplicate_relation() {
+        let text = "\
+Source: foo
+Build-Depends: debhelper-compat (= 13),
+               libbar-dev,
+               libbar-dev
+";
+        let found = issues(text, RelationLookup::default());
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].kind,
+            RelationIssueKind::Duplicate {
+                relation: "libbar-dev".to_string()
+            }
+        );
+        assert_eq!(found[0].range.start(), 94.into());
+        assert_eq!(range_text(text, found[0].range), "libbar-dev");
+    }
+
+    #[test]
+    fn test_self_dependency() {
+        let text = "\
+Source: foo
+
+Package: foo
+Architecture: any
+Depends: libc6, foo
+Description: Foo
+";
+        let found = issues(text, RelationLookup::default());
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].kind,
+            RelationIssueKind::SelfDependency {
+                package: "foo".to_string()
+            }
+        );
+        assert_eq!(range_text(text, found[0].range), "foo");
+        assert_eq!(found[0].range.start(), 60.into());
+    }
+
+    #[test]
+    fn test_self_conflict_is_allowed() {
+        let text = "\
+Source: foo
+
+Package: foo
+Architecture: any
+Provides: bar
+Conflicts: bar, foo
+Description: Foo
+";
+        assert_eq!(kinds(text), vec![]);
+    }
+
+    #[test]
+    fn test_unknown_arch_qualifier() {
+        let architectures = vec!["amd64".to_string(), "i386".to_string()];
+        let lookup = RelationLookup {
+            packages: None,
+            architectures: &architectures,
+        };
+        let text = "\
+Source: foo
+Build-Depends: python3:any, libbar-dev:amd64, libbaz-dev:nonsense
+";
+        let found = issues(text, lookup);
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].kind,
+            RelationIssueKind::UnknownArchQualifier {
+                archqual: "nonsense".to_string()
+            }
+        );
+        assert_eq!(range_text(text, found[0].range), ":nonsense");
+    }
+
+    #[test]
+    fn test_arch_qualifier_without_architecture_list() {
+        let text = "\
+Source: foo
+Build-Depends: libbaz-dev:nonsense
+";
+        assert_eq!(kinds(text), vec![]);
+    }
+
+    #[test]
+    fn test_unknown_package() {
+        let mut cache = TestPackageCache::default();
+        cache.packages.push(("libc6".to_string(), None));
+        cache.providers.insert(
+            "debhelper-compat".to_string(),
+            vec!["debhelper".to_string()],
+        );
+        cache
+            .providers
+            .insert("libdoesnotexist-dev".to_string(), vec![]);
+        let lookup = RelationLookup {
+            packages: Some(&cache),
+            architectures: &[],
+        };
+        let text = "\
+Source: foo
+Build-Depends: debhelper-compat (= 13), libdoesnotexist-dev
+
+Package: foo
+Architecture: any
+Depends: libc6, foo-data
+Conflicts: oldpackage
+Description: Foo
+
+Package: foo-data
+Architecture: all
+Description: Foo data
+";
+        let found = issues(text, lookup);
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].kind,
+            RelationIssueKind::UnknownPackage {
+                package: "libdoesnotexist-dev".to_string()
+            }
+        );
+        assert_eq!(range_text(text, found[0].range), "libdoesnotexist-dev");
+    }
+
+    #[test]
+    fn test_virtual_package_with_cached_providers() {
+        let mut cache = TestPackageCache::default();
+        cache.providers.insert(
+            "mail-transport-agent".to_string(),
+            vec!["postfix".to_string()],
+        );
+        let lookup = RelationLookup {
+            packages: Some(&cache),
+            architectures: &[],
+        };
+        let text = "\
+Source: foo
+
+Package: foo
+Architecture: all
+Depends: mail-transport-agent
+Description: Foo
+";
+        assert_eq!(issues(text, lookup), vec![]);
+    }
+
+    #[test]
+    fn test_virtual_package_with_unresolved_providers() {
+        let cache = TestPackageCache::new_shared(&[("libc6", None)]);
+        let cache = cache.try_read().unwrap();
+        let lookup = RelationLookup {
+            packages: Some(&*cache),
+            architectures: &[],
+        };
+        let text = "\
+Source: foo
+Build-Depends: debhelper-compat (= 13)
+
+Package: foo
+Architecture: all
+Depends: libc6, default-mta | mail-transport-agent, foo-data
+Description: Foo
+
+Package: foo-data
+Architecture: all
+Description: Foo data
+";
+        // Until the providers are looked up, virtual packages are not
+        // reported.
+        assert_eq!(issues(text, lookup), vec![]);
+        assert_eq!(
+            unresolved_packages(&Control::parse(text), &*cache),
+            vec!["debhelper-compat", "default-mta", "mail-transport-agent"]
+        );
+    }
+
+    #[test]
+    fn test_unknown_package_multiline_range() {
+        let mut cache = TestPackageCache::default();
+        cache.packages.push(("libc6".to_string(), None));
+        cache.providers.insert("libmissing-dev".to_string(), vec![]);
+        let lookup = RelationLookup {
+            packages: Some(&cache),
+            architectures: &[],
+        };
+        let text = "\
+Source: foo
+Build-Depends: libc6,
+ libmissing-dev (>= 1.0)
+";
+        let found = issues(text, lookup);
+        assert_eq!(found.len(), 1);
+        assert_eq!(range_text(text, found[0].range), "libmissing-dev");
+    }
+
+    #[test]
+    fn test_get_relation_diagnostics() {
+        let text = "\
+Source: foo
+Build-Depends: libbar-dev (>= 2), libbar-dev (<< 1)
+";
+        let parsed = Control::parse(text);
+        let idx = crate::position::LineIndex::new(text);
+        let src = Source::new(text, &idx);
+        let diags = get_relation_diagnostics(src, &parsed, RelationLookup::default());
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(
+            diags[0].code,
+            Some(NumberOrString::String(
+                "impossible-version-constraint".to_string()
+            ))
+        );
+        assert_eq!(
+            diags[0].range,
+            tower_lsp_server::ls_types::Range::new(
+                tower_lsp_server::ls_types::Position::new(1, 34),
+                tower_lsp_server::ls_types::Position::new(1, 51),
+            )
+        );
+    }
+}
@@ -11,7 +11,7 @@ use std::path::Path;
 use tower_lsp_server::ls_types::*;
 
 /// File extensions that are named after binary packages in the debian/ directory.
-const PACKAGE_FILE_EXTENSIONS: &[&str] = &[
+pub(crate) const PACKAGE_FILE_EXTENSIONS: &[&str] = &[
     "install",
     "docs",
     "dirs",
@@ -29,11 +29,10 @@ pub(crate) struct CachedFileCounts {
     git_files: Vec<String>,
     /// When the git file list was fetched.
     git_fetched_at: Instant,
-    /// The patterns (Files: + Files-Excluded:) that produced the cached counts.
-    /// Used to detect when the copyright file changes and we need to recompute.
-    pattern_key: Vec<String>,
-    /// The computed file-count lenses.
-    lenses: Vec<CodeLens>,
+    /// The patterns (Files: + Files-Excluded:) that produced the cached counts,
+    /// and the computed file-count lenses. The patterns are used to detect when
+    /// the copyright file changes and we need to recompute.
+    lenses: Option<(Vec<String>, Vec<CodeLens>)>,
 }
 
 /// Shared, per-root cache of file-count code lenses.
@@ -67,6 +66,35 @@ fn build_pattern_key(file_lens_data: &FileLensData) -> Vec<String> {
     key
 }
 
+/// The git-tracked files under `root`, reusing the cached list while it is
+/// fresh. The listing is offloaded to a blocking thread.
+pub async fn tracked_files(cache: &SharedGitFileCache, root: &Path) -> Option<Vec<String>> {
+    {
+        let map = cache.lock().await;
+        if let Some(entry) = map.get(root) {
+            if entry.git_fetched_at.elapsed() < GIT_FILE_LIST_TTL {
+                return Some(entry.git_files.clone());
+            }
+        }
+    }
+
+    let root_buf = root.to_path_buf();
+    let git_files =
+        tokio::task::spawn_blocking(move || crate::source_scan::git_ls_files(&root_buf))
+            .await
+            .ok()
+            .flatten()?;
+    cache.lock().await.insert(
+        root.to_path_buf(),
+        CachedFileCounts {
+            git_files: git_files.clone(),
+            git_fetched_at: Instant::now(),
+            lenses: None,
+        },
+    );
+    Some(git_files)
+}
+
 /// Get file-count lenses, using cached results when the patterns and
 /// git file list haven't changed.
 async fn get_file_count_lenses(
@@ -80,32 +108,15 @@ async fn get_file_count_lenses(
     {
         let map = cache.lock().await;
         if let Some(entry) = map.get(root) {
-            if entry.pattern_key == pattern_key
-                && entry.git_fetched_at.elapsed() < GIT_FILE_LIST_TTL
-            {
-                return Some(entry.lenses.clone());
+            if let Some((key, lenses)) = &entry.lenses {
+                if *key == pattern_key && entry.git_fetched_at.elapsed() < GIT_FILE_LIST_TTL {
+                    return Some(lenses.clone());
+                }
             }
         }
     }
 
-    // Fetch git file list (possibly reusing a still-fresh cached list).
-    let git_files = {
-        let map = cache.lock().await;
-        map.get(root)
-            .filter(|e| e.git_fetched_at.elapsed() < GIT_FILE_LIST_TTL)
-            .map(|e| e.git_files.clone())
-    };
-
-    let git_files = match git_files {
-        Some(files) => files,
-        None => {
-            let root_buf = root.to_path_buf();
-            tokio::task::spawn_blocking(move || crate::source_scan::git_ls_files(&root_buf))
-                .await
-                .ok()
-                .flatten()?
-        }
-    };
+    let git_files = tracked_files(cache, root).await?;
 
     // Apply Files-Excluded then Files-Included (re-include from excluded set).
     let included_files: Vec<&str> = git_files
@@ -159,18 +170,9 @@ async fn get_file_count_lenses(
         })
         .collect();
 
-    // Store in cache.
-    {
-        let mut map = cache.lock().await;
-        map.insert(
-            root.to_path_buf(),
-            CachedFileCounts {
-                git_files,
-                git_fetched_at: Instant::now(),
-                pattern_key,
-                lenses: lenses.clone(),
-            },
-        );
+    // Store in cache, alongside the git file list they were computed from.
+    if let Some(entry) = cache.lock().await.get_mut(root) {
+        entry.lenses = Some((pattern_key, lenses.clone()));
     }
 
     Some(lenses)
@@ -0,0 +1,514 @@
+//! `/usr/share/common-licenses` references in `debian/copyright`.
+//!
+//! Debian ships the texts of common licenses, so a `License:` paragraph may
+//! point at `/usr/share/common-licenses/<name>` instead of quoting the text.
+//! This module resolves those references for hovers and offers to swap a
+//! pasted license body for the reference, and back.
+
+use std::path::Path;
+
+use debian_copyright::LicenseExpr;
+use text_size::{TextRange, TextSize};
+use tower_lsp_server::ls_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Hover, HoverContents, MarkupContent,
+    MarkupKind, Position, TextEdit, Uri, WorkspaceEdit,
+};
+
+use crate::position::Source;
+
+use super::fields::COMMON_LICENSES_DIR;
+
+/// How many lines of a license text a hover shows.
+const MAX_HOVER_LINES: usize = 200;
+
+/// Share of the words of a common license, in percent, that a pasted body
+/// has to reproduce (in order, from the start) to count as a copy of it.
+/// Allows for differences in the trailing "How to apply" appendix.
+const MIN_MATCH_PERCENT: usize = 90;
+
+/// A `/usr/share/common-licenses/<name>` reference.
+struct Reference {
+    name: String,
+    range: TextRange,
+}
+
+/// Find the common-licenses references in `text`, which starts at `start`.
+fn references(text: &str, start: TextSize) -> Vec<Reference> {
+    let prefix = format!("{COMMON_LICENSES_DIR}/");
+    text.match_indices(&prefix)
+        .filter_map(|(pos, _)| {
+            let rest = &text[pos + prefix.len()..];
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '+')))
+                .unwrap_or(rest.len());
+            // A reference often ends a sentence.
+            let name = rest[..len].trim_end_matches('.');
+            (!name.is_empty()).then(|| Reference {
+                name: name.to_string(),
+                range: TextRange::at(
+                    start + TextSize::from(pos as u32),
+                    TextSize::from((prefix.len() + name.len()) as u32),
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Read the text of common license `name` from `dir`.
+fn read_license(dir: &Path, name: &str) -> Option<String> {
+    if name.contains('/') || name.starts_with('.') {
+        return None;
+    }
+    std::fs::read_to_string(dir.join(name)).ok()
+}
+
+/// A line of license text in a `License:` field.
+struct BodyLine {
+    /// The whole line, including its indentation.
+    range: TextRange,
+    /// The line without indentation; empty for a `.` separator.
+    content: String,
+}
+
+/// A `License:` field of a Files or stand-alone License paragraph.
+struct LicenseField {
+    /// Whether this is a stand-alone License paragraph.
+    standalone: bool,
+    synopsis: String,
+    entry_range: TextRange,
+    body: Vec<BodyLine>,
+}
+
+impl LicenseField {
+    /// The common license named by the synopsis, if it is a single name with
+    /// a text in `dir`. `GPL-2+` is covered by the `GPL-2` text.
+    fn common_license(&self, dir: &Path) -> Option<String> {
+        let names = LicenseExpr::name_ranges(&self.synopsis);
+        let [(name, _)] = names.as_slice() else {
+            return None;
+        };
+        let name = name.strip_suffix('+').unwrap_or(name);
+        dir.join(name).is_file().then(|| name.to_string())
+    }
+}
+
+fn license_fields(parsed: &debian_copyright::lossless::Parse, text: &str) -> Vec<LicenseField> {
+    let copyright = parsed.tree();
+    let paragraphs: Vec<(deb822_lossless::Paragraph, bool)> = copyright
+        .iter_files()
+        .map(|para| (para.as_deb822().clone(), false))
+        .chain(
+            copyright
+                .iter_licenses()
+                .map(|para| (para.as_deb822().clone(), true)),
+        )
+        .collect();
+
+    paragraphs
+        .iter()
+        .filter_map(|(para, standalone)| {
+            let entry = para
+                .entries()
+                .find(|e| e.key().is_some_and(|k| k.eq_ignore_ascii_case("License")))?;
+            let value_range = entry.value_range()?;
+            let mut lines = text[value_range].split('\n');
+            let synopsis = lines.next().unwrap_or_default();
+            let mut offset = value_range.start() + TextSize::from(synopsis.len() as u32 + 1);
+            let body = lines
+                .map(|line| {
+                    let range = TextRange::at(offset, TextSize::from(line.len() as u32));
+                    offset += TextSize::from(line.len() as u32 + 1);
+                    let content = line.trim();
+                    BodyLine {
+                        range,
+                        content: if content == "." { "" } else { content }.to_string(),
+                    }
+                })
+                .collect();
+            Some(LicenseField {
+                standalone: *standalone,
+                synopsis: synopsis.trim().to_string(),
+                entry_range: entry.text_range(),
+                body,
+            })
+        })
+        .collect()
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split_whitespace().map(str::to_lowercase).collect()
+}
+
+/// The index of the body line where a pasted copy of `canonical` starts.
+fn find_pasted(body: &[BodyLine], canonical: &str) -> Option<usize> {
+    let first = words(canonical.lines().find(|l| !l.trim().is_empty())?);
+    let canonical_words = words(canonical);
+    (0..body.len()).find(|&i| {
+        if words(&body[i].content) != first {
+            return false;
+        }
+        let pasted = words(
+            &body[i..]
+                .iter()
+                .map(|line| line.content.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+        let common = pasted
+            .iter()
+            .zip(&canonical_words)
+            .take_while(|(a, b)| a == b)
+            .count();
+        common * 100 >= canonical_words.len() * MIN_MATCH_PERCENT
+    })
+}
+
+/// Format a license text as the continuation lines of a field.
+fn format_body(text: &str) -> String {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(|l| l.trim_end_matches(['\u{c}', ' ', '\t']))
+        .collect();
+    let start = lines.iter().position(|l| !l.trim().is_empty()).unwrap_or(0);
+    let end = lines
+        .iter()
+        .rposition(|l| !l.trim().is_empty())
+        .map_or(start, |i| i + 1);
+    lines[start..end]
+        .iter()
+        .map(|line| {
+            if line.trim().is_empty() {
+                " .".to_string()
+            } else {
+                format!(" {line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn hover_for(dir: &Path, name: &str, range: Option<TextRange>, src: Source<'_>) -> Option<Hover> {
+    let text = read_license(dir, name)?;
+    let lines: Vec<&str> = text.lines().collect();
+    let mut shown = lines[..lines.len().min(MAX_HOVER_LINES)].join("\n");
+    if lines.len() > MAX_HOVER_LINES {
+        shown.push_str(&format!(
+            "\n… ({} more lines)",
+            lines.len() - MAX_HOVER_LINES
+        ));
+    }
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!("**{COMMON_LICENSES_DIR}/{name}**\n\n```text\n{shown}\n```"),
+        }),
+        range: range.map(|r| src.text_range_to_lsp_range(r)),
+    })
+}
+
+/// Show the text of a common license when hovering over a reference to it,
+/// or over a license name in a Files paragraph whose stand-alone License
+/// paragraph references it.
+pub(crate) fn get_hover_in(
+    dir: &Path,
+    parsed: &debian_copyright::lossless::Parse,
+    src: Source<'_>,
+    position: Position,
+) -> Option<Hover> {
+    let offset = src.try_position_to_offset(position)?;
+    if let Some(reference) = references(src.text, TextSize::from(0))
+        .into_iter()
+        .find(|r| r.range.contains_inclusive(offset))
+    {
+        return hover_for(dir, &reference.name, Some(reference.range), src);
+    }
+
+    let copyright = parsed.tree();
+    let name = super::definition::license_name_at_offset(&copyright, src.text, offset.into())?;
+    let field = license_fields(parsed, src.text)
+        .into_iter()
+        .find(|field| field.standalone && field.synopsis.eq_ignore_ascii_case(&name))?;
+    let reference = field.body.iter().find_map(|line| {
+        references(&line.content, TextSize::from(0))
+            .into_iter()
+            .next()
+    })?;
+    hover_for(dir, &reference.name, None, src)
+}
+
+/// See [`get_hover_in`]; reads the system's common licenses.
+pub fn get_hover(
+    parsed: &debian_copyright::lossless::Parse,
+    src: Source<'_>,
+    position: Position,
+) -> Option<Hover> {
+    get_hover_in(Path::new(COMMON_LICENSES_DIR), parsed, src, position)
+}
+
+fn replace_action(
+    uri: &Uri,
+    title: String,
+    range: tower_lsp_server::ls_types::Range,
+    new_text: String,
+) -> CodeActionOrCommand {
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title,
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        edit: Some(WorkspaceEdit {
+            changes: Some(
+                vec![(uri.clone(), vec![TextEdit { range, new_text }])]
+                    .into_iter()
+                    .collect(),
+            ),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+}
+
+/// Offer to replace a pasted common license body with a reference to
+/// `/usr/share/common-licenses`, or a reference with the full text, in the
+/// `License:` fields overlapping `range`.
+pub(crate) fn license_text_actions_in(
+    dir: &Path,
+    uri: &Uri,
+    parsed: &debian_copyright::lossless::Parse,
+    src: Source<'_>,
+    range: TextRange,
+) -> Vec<CodeActionOrCommand> {
+    let mut actions = Vec::new();
+    for field in license_fields(parsed, src.text) {
+        if field.entry_range.intersect(range).is_none() {
+            continue;
+        }
+
+        if let Some(name) = field.common_license(dir) {
+            if let Some(canonical) = read_license(dir, &name) {
+                if let Some(start) = find_pasted(&field.body, &canonical) {
+                    let separator = match start.checked_sub(1).map(|i| &field.body[i]) {
+                        Some(previous) if !previous.content.is_empty() => " .\n",
+                        _ => "",
+                    };
+                    let new_text = format!(
+                        "{separator} On Debian systems, the complete text of this license can be found in\n {COMMON_LICENSES_DIR}/{name}."
+                    );
+                    let pasted = TextRange::new(
+                        field.body[start].range.start(),
+                        field
+                            .body
+                            .last()
+                            .map_or(field.body[start].range.end(), |l| l.range.end()),
+                    );
+                    actions.push(replace_action(
+                        uri,
+                        format!(
+                            "Replace license text with a reference to {COMMON_LICENSES_DIR}/{name}"
+                        ),
+                        src.text_range_to_lsp_range(pasted),
+                        new_text,
+                    ));
+                    continue;
+                }
+            }
+        }
+
+        // The reference sentence may span lines: replace the block of lines
+        // between separators that holds it.
+        for (i, line) in field.body.iter().enumerate() {
+            let Some(reference) = references(&line.content, TextSize::from(0))
+                .into_iter()
+                .next()
+            else {
+                continue;
+            };
+            let Some(text) = read_license(dir, &reference.name) else {
+                continue;
+            };
+            let first = field.body[..i]
+                .iter()
+                .rposition(|l| l.content.is_empty())
+                .map_or(0, |sep| sep + 1);
+            let last = field.body[i..]
+                .iter()
+                .position(|l| l.content.is_empty())
+                .map_or(field.body.len(), |sep| i + sep);
+            let block = TextRange::new(
+                field.body[first].range.start(),
+                field.body[last - 1].range.end(),
+            );
+            actions.push(replace_action(
+                uri,
+                format!(
+                    "Insert the full text of {COMMON_LICENSES_DIR}/{}",
+                    reference.name
+                ),
+                src.text_range_to_lsp_range(block),
+                format_body(&text),
+            ));
+            break;
+        }
+    }
+    actions
+}
+
+/// See [`license_text_actions_in`]; reads the system's common licenses.
+pub fn license_text_actions(
+    uri: &Uri,
+    parsed: &debian_copyright::lossless::Parse,
+    src: Source<'_>,
+    range: TextRange,
+) -> Vec<CodeActionOrCommand> {
+    license_text_actions_in(Path::new(COMMON_LICENSES_DIR), uri, parsed, src, range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+
+    const LICENSE_TEXT: &str = "                    EXAMPLE PUBLIC LICENSE
+                       Version 2
+
+  Everyone may copy this software, as long as
+they keep this notice.\x0c
+
+  There is no warranty.
+";
+
+    fn license_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("EPL-2"), LICENSE_TEXT).unwrap();
+        dir
+    }
+
+    fn apply(text: &str, action: &CodeActionOrCommand, uri: &Uri) -> String {
+        let CodeActionOrCommand::CodeAction(action) = action else {
+            panic!("expected a code action");
+        };
+        let edit = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[uri][0];
+        let idx = LineIndex::new(text);
+        let range = Source::new(text, &idx)
+            .try_lsp_range_to_text_range(&edit.range)
+            .unwrap();
+        let mut result = text.to_string();
+        result.replace_range(std::ops::Range::<usize>::from(range), &edit.new_text);
+        result
+    }
+
+    fn actions(dir: &Path, text: &str) -> Vec<CodeActionOrCommand> {
+        let parsed = debian_copyright::lossless::Parse::parse_relaxed(text);
+        let idx = LineIndex::new(text);
+        let uri: Uri = "file:///debian/copyright".parse().unwrap();
+        let whole = TextRange::up_to(TextSize::from(text.len() as u32));
+        license_text_actions_in(dir, &uri, &parsed, Source::new(text, &idx), whole)
+    }
+
+    const PASTED: &str = "\
+Files: *
+Copyright: 2024 Foo
+License: EPL-2+
+
+License: EPL-2+
+ This program may be redistributed under the license below, or any later
+ version.
+ .
+ EXAMPLE PUBLIC LICENSE
+ Version 2
+ .
+ Everyone may copy this software, as long as
+ they keep this notice.
+ .
+ There is no warranty.
+";
+
+    const REFERENCED: &str = "\
+Files: *
+Copyright: 2024 Foo
+License: EPL-2+
+
+License: EPL-2+
+ This program may be redistributed under the license below, or any later
+ version.
+ .
+ On Debian systems, the complete text of this license can be found in
+ /usr/share/common-licenses/EPL-2.
+";
+
+    #[test]
+    fn test_replace_pasted_text_with_reference() {
+        let dir = license_dir();
+        let found = actions(dir.path(), PASTED);
+        assert_eq!(found.len(), 1);
+        let uri: Uri = "file:///debian/copyright".parse().unwrap();
+        assert_eq!(apply(PASTED, &found[0], &uri), REFERENCED);
+    }
+
+    #[test]
+    fn test_replace_reference_with_text() {
+        let dir = license_dir();
+        let found = actions(dir.path(), REFERENCED);
+        assert_eq!(found.len(), 1);
+        let uri: Uri = "file:///debian/copyright".parse().unwrap();
+        assert_eq!(
+            apply(REFERENCED, &found[0], &uri),
+            "\
+Files: *
+Copyright: 2024 Foo
+License: EPL-2+
+
+License: EPL-2+
+ This program may be redistributed under the license below, or any later
+ version.
+ .
+                     EXAMPLE PUBLIC LICENSE
+                        Version 2
+ .
+   Everyone may copy this software, as long as
+ they keep this notice.
+ .
+   There is no warranty.
+"
+        );
+    }
+
+    #[test]
+    fn test_no_actions_for_unrelated_text() {
+        let dir = license_dir();
+        let text = "Files: *\nCopyright: 2024 Foo\nLicense: Custom\n You may do anything.\n";
+        assert!(actions(dir.path(), text).is_empty());
+    }
+
+    fn hover_text(dir: &Path, text: &str, position: Position) -> Option<String> {
+        let parsed = debian_copyright::lossless::Parse::parse_relaxed(text);
+        let idx = LineIndex::new(text);
+        let hover = get_hover_in(dir, &parsed, Source::new(text, &idx), position)?;
+        match hover.contents {
+            HoverContents::Markup(markup) => Some(markup.value),
+            _ => panic!("expected markup"),
+        }
+    }
+
+    #[test]
+    fn test_hover_on_reference() {
+        let dir = license_dir();
+        let value = hover_text(dir.path(), REFERENCED, Position::new(9, 10)).unwrap();
+        assert!(value.starts_with("**/usr/share/common-licenses/EPL-2**"));
+        assert!(value.contains("There is no warranty."));
+    }
+
+    #[test]
+    fn test_hover_on_license_name_in_files_paragraph() {
+        let dir = license_dir();
+        let value = hover_text(dir.path(), REFERENCED, Position::new(2, 10)).unwrap();
+        assert!(value.contains("EXAMPLE PUBLIC LICENSE"));
+    }
+
+    #[test]
+    fn test_no_hover_elsewhere() {
+        let dir = license_dir();
+        assert_eq!(
+            hover_text(dir.path(), REFERENCED, Position::new(1, 3)),
+            None
+        );
+    }
+}
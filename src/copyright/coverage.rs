@@ -0,0 +1,437 @@
+//! Coverage diagnostics for debian/copyright against the source tree.
+//!
+//! Matches the `Files:` globs against the git-tracked files (the same list
+//! the file-count code lenses use) and reports:
+//! - `Files:` paragraphs that match no files
+//! - `Files:` paragraphs whose files are all claimed by later paragraphs
+//! - tracked files matched by no paragraph, when there is no `Files: *`
+//! - `Files-Excluded` patterns that still match tracked files
+//!
+//! The first two come with a quick fix that deletes the dead paragraph.
+
+use std::collections::HashMap;
+
+use debian_copyright::lossless::Header;
+use debian_copyright::GlobPattern;
+use rowan::ast::AstNode;
+use text_size::{TextRange, TextSize};
+use tower_lsp_server::ls_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Diagnostic, DiagnosticSeverity,
+    NumberOrString, TextEdit, Uri, WorkspaceEdit,
+};
+
+use crate::position::Source;
+
+/// Diagnostic code for a `Files:` paragraph that matches no files.
+const NO_MATCH_CODE: &str = "files-paragraph-matches-nothing";
+/// Diagnostic code for a `Files:` paragraph whose files are all claimed by
+/// later paragraphs.
+const SUPERSEDED_CODE: &str = "files-paragraph-superseded";
+/// Diagnostic code for tracked files that no `Files:` paragraph covers.
+const UNCOVERED_CODE: &str = "files-not-covered";
+/// Diagnostic code for a `Files-Excluded` pattern matching tracked files.
+const EXCLUDED_PRESENT_CODE: &str = "files-excluded-still-present";
+
+/// How many uncovered files to name in the diagnostic message.
+const MAX_LISTED_FILES: usize = 5;
+
+/// A `Files:` paragraph, as needed for coverage checks.
+struct FilesParagraph {
+    patterns: Vec<GlobPattern>,
+    /// Whether one of the patterns is the catch-all `*`.
+    is_catch_all: bool,
+    /// Range of the `Files` field, where diagnostics are reported.
+    files_range: TextRange,
+    /// Range of the whole paragraph.
+    range: TextRange,
+}
+
+fn files_paragraphs(parsed: &debian_copyright::lossless::Parse) -> Vec<FilesParagraph> {
+    parsed
+        .tree()
+        .iter_files()
+        .map(|files_para| {
+            let para = files_para.as_deb822();
+            let range = para.syntax().text_range();
+            let files_range = para
+                .entries()
+                .find(|e| e.key().is_some_and(|k| k.eq_ignore_ascii_case("Files")))
+                .map_or(range, |e| e.text_range());
+            let raw = files_para.files();
+            FilesParagraph {
+                patterns: raw.iter().map(|p| GlobPattern::new(p)).collect(),
+                is_catch_all: raw.iter().any(|p| p == "*"),
+                files_range,
+                range,
+            }
+        })
+        .collect()
+}
+
+/// The whitespace-separated patterns of a header field such as
+/// `Files-Excluded`.
+fn header_patterns(header: Option<&Header>, field: &str) -> Vec<String> {
+    header
+        .and_then(|h| h.as_deb822().get(field))
+        .map(|value| value.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// The tracked files that end up in the upstream source: those not removed
+/// by `Files-Excluded`, or re-added by `Files-Included`.
+pub fn shipped_files<'a>(
+    parsed: &debian_copyright::lossless::Parse,
+    tracked_files: &'a [String],
+) -> Vec<&'a str> {
+    let header = parsed.tree().header();
+    let excluded: Vec<GlobPattern> = header_patterns(header.as_ref(), "Files-Excluded")
+        .iter()
+        .map(|p| GlobPattern::new(p))
+        .collect();
+    let included: Vec<GlobPattern> = header_patterns(header.as_ref(), "Files-Included")
+        .iter()
+        .map(|p| GlobPattern::new(p))
+        .collect();
+    tracked_files
+        .iter()
+        .map(String::as_str)
+        .filter(|f| {
+            !excluded.iter().any(|p| p.is_match(f)) || included.iter().any(|p| p.is_match(f))
+        })
+        .collect()
+}
+
+fn make_diagnostic(src: Source<'_>, range: TextRange, code: &str, message: String) -> Diagnostic {
+    Diagnostic {
+        range: src.text_range_to_lsp_range(range),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some("debian-lsp".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Check the `Files:` paragraphs of a copyright file against `tracked_files`,
+/// the files in the source tree relative to its root.
+pub fn coverage_diagnostics(
+    parsed: &debian_copyright::lossless::Parse,
+    src: Source<'_>,
+    tracked_files: &[String],
+) -> Vec<Diagnostic> {
+    let copyright = parsed.tree();
+    let header = copyright.header();
+    let excluded_raw = header_patterns(header.as_ref(), "Files-Excluded");
+    let excluded: Vec<GlobPattern> = excluded_raw.iter().map(|p| GlobPattern::new(p)).collect();
+    let included: Vec<GlobPattern> = header_patterns(header.as_ref(), "Files-Included")
+        .iter()
+        .map(|p| GlobPattern::new(p))
+        .collect();
+    let is_reincluded = |f: &str| included.iter().any(|p| p.is_match(f));
+
+    let mut diagnostics = Vec::new();
+
+    // Files-Excluded patterns should match nothing in a repacked tree.
+    if let Some(field_range) = header.as_ref().and_then(|h| {
+        h.as_deb822()
+            .entries()
+            .find(|e| {
+                e.key()
+                    .is_some_and(|k| k.eq_ignore_ascii_case("Files-Excluded"))
+            })
+            .map(|e| e.text_range())
+    }) {
+        for (raw, pattern) in excluded_raw.iter().zip(&excluded) {
+            let count = tracked_files
+                .iter()
+                .filter(|f| pattern.is_match(f) && !is_reincluded(f))
+                .count();
+            if count > 0 {
+                diagnostics.push(make_diagnostic(
+                    src,
+                    field_range,
+                    EXCLUDED_PRESENT_CODE,
+                    format!(
+                        "Files-Excluded pattern '{}' still matches {} tracked {}",
+                        raw,
+                        count,
+                        if count == 1 { "file" } else { "files" }
+                    ),
+                ));
+            }
+        }
+    }
+
+    let files = shipped_files(parsed, tracked_files);
+
+    let paragraphs = files_paragraphs(parsed);
+    let mut matched = vec![0usize; paragraphs.len()];
+    let mut claimed = vec![0usize; paragraphs.len()];
+    let mut uncovered = Vec::new();
+    for f in &files {
+        let mut winner = None;
+        for (i, para) in paragraphs.iter().enumerate() {
+            if para.patterns.iter().any(|p| p.is_match(f)) {
+                matched[i] += 1;
+                winner = Some(i);
+            }
+        }
+        match winner {
+            Some(i) => claimed[i] += 1,
+            None => uncovered.push(*f),
+        }
+    }
+
+    for (i, para) in paragraphs.iter().enumerate() {
+        if matched[i] == 0 {
+            diagnostics.push(make_diagnostic(
+                src,
+                para.files_range,
+                NO_MATCH_CODE,
+                "Files paragraph matches no files in the source tree".to_string(),
+            ));
+        } else if claimed[i] == 0 {
+            diagnostics.push(make_diagnostic(
+                src,
+                para.files_range,
+                SUPERSEDED_CODE,
+                "Files paragraph is superseded: every file it matches is claimed by a later paragraph"
+                    .to_string(),
+            ));
+        }
+    }
+
+    if !uncovered.is_empty() && !paragraphs.iter().any(|p| p.is_catch_all) {
+        let range = header
+            .as_ref()
+            .map(|h| h.as_deb822().syntax().text_range())
+            .or_else(|| paragraphs.first().map(|p| p.files_range))
+            .unwrap_or_default();
+        let mut listed = uncovered
+            .iter()
+            .take(MAX_LISTED_FILES)
+            .copied()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if uncovered.len() > MAX_LISTED_FILES {
+            listed.push_str(&format!(" and {} more", uncovered.len() - MAX_LISTED_FILES));
+        }
+        diagnostics.push(make_diagnostic(
+            src,
+            range,
+            UNCOVERED_CODE,
+            format!(
+                "{} tracked {} not covered by any Files paragraph: {}",
+                uncovered.len(),
+                if uncovered.len() == 1 {
+                    "file is"
+                } else {
+                    "files are"
+                },
+                listed
+            ),
+        ));
+    }
+
+    diagnostics
+}
+
+/// The range to delete to drop `range`, a paragraph, along with the blank
+/// line separating it from its neighbours.
+fn paragraph_deletion_range(text: &str, range: TextRange) -> TextRange {
+    let start = usize::from(range.start());
+    let end = usize::from(range.end());
+    let after = text[end..].len() - text[end..].trim_start_matches('\n').len();
+    if end + after < text.len() {
+        return TextRange::new(range.start(), TextSize::from((end + after) as u32));
+    }
+    // Last paragraph: take the blank line before it instead, keeping the
+    // newline that ends the previous paragraph.
+    let before = text[..start].len() - text[..start].trim_end_matches('\n').len();
+    let start = if before > 0 {
+        start - before + 1
+    } else {
+        start
+    };
+    TextRange::new(
+        TextSize::from(start as u32),
+        TextSize::from(text.len() as u32),
+    )
+}
+
+/// Quick fixes deleting the `Files:` paragraphs that `diagnostics` report as
+/// dead.
+pub fn coverage_actions(
+    uri: &Uri,
+    parsed: &debian_copyright::lossless::Parse,
+    src: Source<'_>,
+    diagnostics: &[Diagnostic],
+) -> Vec<CodeActionOrCommand> {
+    let paragraphs = files_paragraphs(parsed);
+    diagnostics
+        .iter()
+        .filter(|d| {
+            matches!(&d.code, Some(NumberOrString::String(c)) if c == NO_MATCH_CODE || c == SUPERSEDED_CODE)
+        })
+        .filter_map(|diagnostic| {
+            let para = paragraphs
+                .iter()
+                .find(|p| src.text_range_to_lsp_range(p.files_range) == diagnostic.range)?;
+            let edit = TextEdit {
+                range: src
+                    .text_range_to_lsp_range(paragraph_deletion_range(src.text, para.range)),
+                new_text: String::new(),
+            };
+            Some(CodeActionOrCommand::CodeAction(CodeAction {
+                title: "Remove unused Files paragraph".to_string(),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::Workspace;
+
+    const HEADER: &str =
+        "Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/\n";
+
+    fn check(content: &str, files: &[&str]) -> Vec<(String, String)> {
+        let mut workspace = Workspace::new();
+        let url = str::parse("file:///debian/copyright").unwrap();
+        let file = workspace.update_file(url, content.to_string());
+        let parsed = workspace.get_parsed_copyright(file);
+        let text = workspace.source_text(file);
+        let idx = workspace.get_line_index(file);
+        let files: Vec<String> = files.iter().map(|f| f.to_string()).collect();
+        coverage_diagnostics(&parsed, Source::new(&text, &idx), &files)
+            .into_iter()
+            .map(|d| match d.code {
+                Some(NumberOrString::String(code)) => (code, d.message),
+                _ => panic!("missing code"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fully_covered() {
+        let content = format!(
+            "{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: MIT\n\nFiles: debian/*\nCopyright: 2024 Bar\nLicense: MIT\n"
+        );
+        assert_eq!(check(&content, &["src/main.c", "debian/rules"]), vec![]);
+    }
+
+    #[test]
+    fn test_paragraph_matching_nothing() {
+        let content = format!(
+            "{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: MIT\n\nFiles: docs/legacy/*\nCopyright: 2024 Bar\nLicense: MIT\n"
+        );
+        assert_eq!(
+            check(&content, &["src/main.c"]),
+            vec![(
+                NO_MATCH_CODE.to_string(),
+                "Files paragraph matches no files in the source tree".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_superseded_paragraph() {
+        let content = format!(
+            "{HEADER}\nFiles: src/*\nCopyright: 2024 Foo\nLicense: MIT\n\nFiles: *\nCopyright: 2024 Bar\nLicense: MIT\n"
+        );
+        let diags = check(&content, &["src/main.c", "README"]);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].0, SUPERSEDED_CODE);
+    }
+
+    #[test]
+    fn test_uncovered_files_without_catch_all() {
+        let content = format!("{HEADER}\nFiles: src/*\nCopyright: 2024 Foo\nLicense: MIT\n");
+        assert_eq!(
+            check(&content, &["src/main.c", "README", "Makefile"]),
+            vec![(
+                UNCOVERED_CODE.to_string(),
+                "2 tracked files are not covered by any Files paragraph: README, Makefile"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_files_excluded_still_present() {
+        let content = format!(
+            "{HEADER}Files-Excluded: build/* vendor/*\n\nFiles: *\nCopyright: 2024 Foo\nLicense: MIT\n"
+        );
+        assert_eq!(
+            check(&content, &["src/main.c", "vendor/lib.c"]),
+            vec![(
+                EXCLUDED_PRESENT_CODE.to_string(),
+                "Files-Excluded pattern 'vendor/*' still matches 1 tracked file".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_excluded_files_are_not_uncovered() {
+        let content = format!(
+            "{HEADER}Files-Excluded: vendor/*\n\nFiles: src/*\nCopyright: 2024 Foo\nLicense: MIT\n"
+        );
+        let diags = check(&content, &["src/main.c", "vendor/lib.c"]);
+        assert!(diags.iter().all(|(code, _)| code != UNCOVERED_CODE));
+    }
+
+    fn apply_fix(content: &str, files: &[&str]) -> String {
+        let mut workspace = Workspace::new();
+        let url: Uri = str::parse("file:///debian/copyright").unwrap();
+        let file = workspace.update_file(url.clone(), content.to_string());
+        let parsed = workspace.get_parsed_copyright(file);
+        let text = workspace.source_text(file);
+        let idx = workspace.get_line_index(file);
+        let src = Source::new(&text, &idx);
+        let files: Vec<String> = files.iter().map(|f| f.to_string()).collect();
+        let diags = coverage_diagnostics(&parsed, src, &files);
+        let actions = coverage_actions(&url, &parsed, src, &diags);
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("expected a code action");
+        };
+        let edit = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&url][0];
+        let range = src.try_lsp_range_to_text_range(&edit.range).unwrap();
+        let mut result = content.to_string();
+        result.replace_range(std::ops::Range::<usize>::from(range), &edit.new_text);
+        result
+    }
+
+    #[test]
+    fn test_remove_dead_paragraph_in_the_middle() {
+        let content = format!(
+            "{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: MIT\n\nFiles: gone/*\nCopyright: 2024 Bar\nLicense: MIT\n\nFiles: debian/*\nCopyright: 2024 Baz\nLicense: MIT\n"
+        );
+        assert_eq!(
+            apply_fix(&content, &["src/main.c", "debian/rules"]),
+            format!(
+                "{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: MIT\n\nFiles: debian/*\nCopyright: 2024 Baz\nLicense: MIT\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_remove_dead_last_paragraph() {
+        let content = format!(
+            "{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: MIT\n\nFiles: gone/*\nCopyright: 2024 Bar\nLicense: MIT\n"
+        );
+        assert_eq!(
+            apply_fix(&content, &["src/main.c"]),
+            format!("{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: MIT\n")
+        );
+    }
+}
@@ -42,7 +42,9 @@ pub fn goto_definition(
     None
 }
 
-fn license_name_at_offset(
+/// The license name under `offset` in the License field of a Files
+/// paragraph.
+pub(super) fn license_name_at_offset(
     copyright: &debian_copyright::lossless::Copyright,
     text: &str,
     offset: u32,
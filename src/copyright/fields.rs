@@ -64,13 +64,14 @@ pub fn get_standard_field_name(field_name: &str) -> Option<&'static str> {
     crate::deb822::completion::get_standard_field_name(COPYRIGHT_FIELDS, field_name)
 }
 
+/// Where Debian ships the texts of common licenses.
+pub const COMMON_LICENSES_DIR: &str = "/usr/share/common-licenses";
+
 /// Cache for common license names loaded from the system
 static COMMON_LICENSES_CACHE: OnceLock<Vec<String>> = OnceLock::new();
 
 /// Load common license names from /usr/share/common-licenses
 fn load_common_licenses() -> Vec<String> {
-    const COMMON_LICENSES_DIR: &str = "/usr/share/common-licenses";
-
     let mut licenses = Vec::new();
 
     if let Ok(entries) = fs::read_dir(COMMON_LICENSES_DIR) {
@@ -94,6 +95,56 @@ pub fn get_common_licenses() -> &'static [String] {
     COMMON_LICENSES_CACHE.get_or_init(load_common_licenses)
 }
 
+/// SPDX license identifiers whose Debian short name differs, mapped to the
+/// Debian name. Includes the deprecated SPDX forms still common in headers.
+const SPDX_TO_DEBIAN: &[(&str, &str)] = &[
+    ("AGPL-3.0", "AGPL-3"),
+    ("AGPL-3.0-only", "AGPL-3"),
+    ("AGPL-3.0-or-later", "AGPL-3+"),
+    ("AGPL-3.0+", "AGPL-3+"),
+    ("Artistic-1.0-Perl", "Artistic"),
+    ("BSD-2-Clause", "BSD-2-clause"),
+    ("BSD-3-Clause", "BSD-3-clause"),
+    ("BSD-4-Clause", "BSD-4-clause"),
+    ("GFDL-1.2-only", "GFDL-1.2"),
+    ("GFDL-1.2-or-later", "GFDL-1.2+"),
+    ("GFDL-1.3-only", "GFDL-1.3"),
+    ("GFDL-1.3-or-later", "GFDL-1.3+"),
+    ("GPL-1.0", "GPL-1"),
+    ("GPL-1.0-only", "GPL-1"),
+    ("GPL-1.0-or-later", "GPL-1+"),
+    ("GPL-1.0+", "GPL-1+"),
+    ("GPL-2.0", "GPL-2"),
+    ("GPL-2.0-only", "GPL-2"),
+    ("GPL-2.0-or-later", "GPL-2+"),
+    ("GPL-2.0+", "GPL-2+"),
+    ("GPL-3.0", "GPL-3"),
+    ("GPL-3.0-only", "GPL-3"),
+    ("GPL-3.0-or-later", "GPL-3+"),
+    ("GPL-3.0+", "GPL-3+"),
+    ("LGPL-2.0", "LGPL-2"),
+    ("LGPL-2.0-only", "LGPL-2"),
+    ("LGPL-2.0-or-later", "LGPL-2+"),
+    ("LGPL-2.0+", "LGPL-2+"),
+    ("LGPL-2.1-only", "LGPL-2.1"),
+    ("LGPL-2.1-or-later", "LGPL-2.1+"),
+    ("LGPL-2.1+", "LGPL-2.1+"),
+    ("LGPL-3.0", "LGPL-3"),
+    ("LGPL-3.0-only", "LGPL-3"),
+    ("LGPL-3.0-or-later", "LGPL-3+"),
+    ("LGPL-3.0+", "LGPL-3+"),
+    ("MIT", "Expat"),
+];
+
+/// The Debian short name for an SPDX license identifier, if it differs from
+/// the SPDX one.
+pub fn debian_license_name(spdx_id: &str) -> Option<&'static str> {
+    SPDX_TO_DEBIAN
+        .iter()
+        .find(|(spdx, _)| *spdx == spdx_id)
+        .map(|(_, debian)| *debian)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,6 +196,14 @@ mod tests {
         assert_eq!(get_standard_field_name("random"), None);
     }
 
+    #[test]
+    fn test_debian_license_name() {
+        assert_eq!(debian_license_name("GPL-2.0-or-later"), Some("GPL-2+"));
+        assert_eq!(debian_license_name("LGPL-2.1-only"), Some("LGPL-2.1"));
+        assert_eq!(debian_license_name("MIT"), Some("Expat"));
+        assert_eq!(debian_license_name("Apache-2.0"), None);
+    }
+
     #[test]
     fn test_get_common_licenses() {
         let licenses = get_common_licenses();
@@ -0,0 +1,472 @@
+//! License expression checks for `debian/copyright`.
+//!
+//! The synopsis of every `License:` field is parsed as a DEP-5 license
+//! expression (`or`, `and`, `with ... exception`) and each short name in it
+//! is checked:
+//! - SPDX identifiers with a different Debian short name (`GPL-2.0-or-later`
+//!   rather than `GPL-2+`) get a quick fix to the Debian name
+//! - names that look like a misspelled or miscased Debian short name get a
+//!   quick fix to the likely intended name
+//! - names cited by a `Files:` paragraph without license text need a
+//!   stand-alone `License:` paragraph
+//!
+//! Stand-alone `License:` paragraphs without license text are reported too.
+
+use std::collections::HashSet;
+
+use debian_copyright::LicenseExpr;
+use text_size::{TextRange, TextSize};
+use tower_lsp_server::ls_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Diagnostic, DiagnosticSeverity,
+    NumberOrString, TextEdit, Uri, WorkspaceEdit,
+};
+
+use crate::position::Source;
+use crate::text::edit_distance;
+
+use super::fields::{debian_license_name, get_common_licenses};
+
+/// Diagnostic code for an SPDX identifier with a different Debian short name.
+const SPDX_NAME_CODE: &str = "spdx-license-name";
+/// Diagnostic code for a misspelled license short name.
+const UNKNOWN_NAME_CODE: &str = "unknown-license-name";
+/// Diagnostic code for a license cited without text or a License paragraph.
+const UNDEFINED_CODE: &str = "undefined-license";
+/// Diagnostic code for a stand-alone License paragraph without text.
+const EMPTY_TEXT_CODE: &str = "empty-license-text";
+
+/// The short names defined by the DEP-5 specification. Most take a version
+/// suffix, e.g. `GPL-2` or `CC-BY-SA-4.0`, and an optional `+`.
+const DEBIAN_SHORT_NAMES: &[&str] = &[
+    "public-domain",
+    "Apache",
+    "Artistic",
+    "BSD-2-clause",
+    "BSD-3-clause",
+    "BSD-4-clause",
+    "ISC",
+    "CC-BY",
+    "CC-BY-SA",
+    "CC-BY-ND",
+    "CC-BY-NC",
+    "CC-BY-NC-SA",
+    "CC-BY-NC-ND",
+    "CC0",
+    "CDDL",
+    "CPL",
+    "EFL",
+    "Expat",
+    "GPL",
+    "LGPL",
+    "GFDL",
+    "GFDL-NIV",
+    "LPPL",
+    "MPL",
+    "Perl",
+    "Python",
+    "QPL",
+    "W3C",
+    "Zlib",
+    "Zope",
+];
+
+/// Names shorter than this are too ambiguous to guess at: `EPL` is one edit
+/// away from `CPL` and `EFL`.
+const MIN_FUZZY_LENGTH: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Problem {
+    /// An SPDX identifier, with the Debian short name to use instead.
+    Spdx(String),
+    /// An unknown name, with the Debian short name it probably means.
+    Misspelled(String),
+    /// A name cited without license text or a stand-alone License paragraph.
+    Undefined,
+    /// A stand-alone License paragraph without license text.
+    EmptyText,
+}
+
+struct LicenseFinding {
+    name: String,
+    range: TextRange,
+    problem: Problem,
+}
+
+/// Split the version suffix off a short name: `("GPL", "-2")` for `GPL-2`.
+fn split_version(name: &str) -> (&str, &str) {
+    match name.rsplit_once('-') {
+        Some((base, version))
+            if version.starts_with(|c: char| c.is_ascii_digit())
+                && version
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '.') =>
+        {
+            (base, &name[base.len()..])
+        }
+        _ => (name, ""),
+    }
+}
+
+fn is_known(name: &str) -> bool {
+    if get_common_licenses().iter().any(|l| l == name) {
+        return true;
+    }
+    let (base, _) = split_version(name.strip_suffix('+').unwrap_or(name));
+    DEBIAN_SHORT_NAMES.contains(&base)
+}
+
+/// The Debian short name an unknown `name` probably stands for.
+fn suggestion(name: &str) -> Option<String> {
+    if is_known(name) {
+        return None;
+    }
+    let (rest, plus) = match name.strip_suffix('+') {
+        Some(rest) => (rest, "+"),
+        None => (name, ""),
+    };
+    let (base, version) = split_version(rest);
+    let lower = base.to_lowercase();
+    let candidate = DEBIAN_SHORT_NAMES
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(base))
+        .or_else(|| {
+            if base.len() < MIN_FUZZY_LENGTH {
+                return None;
+            }
+            DEBIAN_SHORT_NAMES
+                .iter()
+                .find(|known| edit_distance(&known.to_lowercase(), &lower) == 1)
+        })?;
+    Some(format!("{candidate}{version}{plus}"))
+}
+
+/// Check the `License:` fields of a copyright file.
+fn license_findings(
+    parsed: &debian_copyright::lossless::Parse,
+    src: Source<'_>,
+) -> Vec<LicenseFinding> {
+    let copyright = parsed.tree();
+    // Read the synopsis directly: `LicenseParagraph::name` is `None` for a
+    // paragraph without text, which still defines the name.
+    let defined: HashSet<String> = copyright
+        .iter_licenses()
+        .filter_map(|para| para.as_deb822().get("License"))
+        .filter_map(|value| value.lines().next().map(|name| name.trim().to_lowercase()))
+        .collect();
+
+    let paragraphs = copyright
+        .iter_files()
+        .map(|para| (para.as_deb822().clone(), true))
+        .chain(
+            copyright
+                .iter_licenses()
+                .map(|para| (para.as_deb822().clone(), false)),
+        );
+
+    let mut findings = Vec::new();
+    for (para, is_files) in paragraphs {
+        let Some(value_range) = para
+            .entries()
+            .find(|e| e.key().is_some_and(|k| k.eq_ignore_ascii_case("License")))
+            .and_then(|e| e.value_range())
+        else {
+            continue;
+        };
+        let value = &src.text[value_range];
+        let (synopsis, text) = value.split_once('\n').unwrap_or((value, ""));
+        let has_text = text.lines().any(|line| {
+            let line = line.trim();
+            !line.is_empty() && line != "."
+        });
+
+        let names = LicenseExpr::name_ranges(synopsis);
+        for (name, range) in &names {
+            let range = TextRange::new(
+                value_range.start() + TextSize::from(range.start as u32),
+                value_range.start() + TextSize::from(range.end as u32),
+            );
+            let problem = if let Some(debian) = debian_license_name(name) {
+                Problem::Spdx(debian.to_string())
+            } else if let Some(suggested) = suggestion(name) {
+                Problem::Misspelled(suggested)
+            } else if is_files && !has_text && !defined.contains(&name.to_lowercase()) {
+                Problem::Undefined
+            } else {
+                continue;
+            };
+            findings.push(LicenseFinding {
+                name: name.to_string(),
+                range,
+                problem,
+            });
+        }
+
+        if !is_files && !has_text {
+            if let Some((name, _)) = names.first() {
+                let synopsis_end = value_range.start() + TextSize::from(synopsis.len() as u32);
+                findings.push(LicenseFinding {
+                    name: name.to_string(),
+                    range: TextRange::new(value_range.start(), synopsis_end),
+                    problem: Problem::EmptyText,
+                });
+            }
+        }
+    }
+    findings
+}
+
+/// Produce diagnostics for the license expressions of a `debian/copyright`
+/// file.
+pub fn license_diagnostics(
+    parsed: &debian_copyright::lossless::Parse,
+    src: Source<'_>,
+) -> Vec<Diagnostic> {
+    license_findings(parsed, src)
+        .into_iter()
+        .map(|finding| {
+            let (severity, code, message) = match &finding.problem {
+                Problem::Spdx(debian) => (
+                    DiagnosticSeverity::INFORMATION,
+                    SPDX_NAME_CODE,
+                    format!(
+                        "'{}' is an SPDX identifier; the Debian short name is '{}'",
+                        finding.name, debian
+                    ),
+                ),
+                Problem::Misspelled(suggested) => (
+                    DiagnosticSeverity::WARNING,
+                    UNKNOWN_NAME_CODE,
+                    format!(
+                        "Unknown license short name '{}', did you mean '{}'?",
+                        finding.name, suggested
+                    ),
+                ),
+                Problem::Undefined => (
+                    DiagnosticSeverity::WARNING,
+                    UNDEFINED_CODE,
+                    format!(
+                        "License '{}' has neither license text nor a stand-alone License paragraph",
+                        finding.name
+                    ),
+                ),
+                Problem::EmptyText => (
+                    DiagnosticSeverity::WARNING,
+                    EMPTY_TEXT_CODE,
+                    format!(
+                        "License paragraph for '{}' has no license text",
+                        finding.name
+                    ),
+                ),
+            };
+            Diagnostic {
+                range: src.text_range_to_lsp_range(finding.range),
+                severity: Some(severity),
+                code: Some(NumberOrString::String(code.to_string())),
+                source: Some("debian-lsp".to_string()),
+                message,
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Produce quick fixes replacing SPDX identifiers and misspelled names with
+/// Debian short names.
+///
+/// When `diagnostics` is non-empty, only fixes for those diagnostics are
+/// returned.
+pub fn license_actions(
+    uri: &Uri,
+    parsed: &debian_copyright::lossless::Parse,
+    src: Source<'_>,
+    diagnostics: &[Diagnostic],
+) -> Vec<CodeActionOrCommand> {
+    let mut actions = Vec::new();
+    for finding in license_findings(parsed, src) {
+        let (Problem::Spdx(replacement) | Problem::Misspelled(replacement)) = finding.problem
+        else {
+            continue;
+        };
+        let range = src.text_range_to_lsp_range(finding.range);
+        let matching: Vec<Diagnostic> = diagnostics
+            .iter()
+            .filter(|d| {
+                d.range == range
+                    && matches!(&d.code, Some(NumberOrString::String(c)) if c == SPDX_NAME_CODE || c == UNKNOWN_NAME_CODE)
+            })
+            .cloned()
+            .collect();
+        if !diagnostics.is_empty() && matching.is_empty() {
+            continue;
+        }
+
+        let edit = TextEdit {
+            range,
+            new_text: replacement.clone(),
+        };
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Replace '{}' with '{}'", finding.name, replacement),
+            kind: Some(CodeActionKind::QUICKFIX),
+            edit: Some(WorkspaceEdit {
+                changes: Some(vec![(uri.clone(), vec![edit])].into_iter().collect()),
+                ..Default::default()
+            }),
+            diagnostics: (!matching.is_empty()).then_some(matching),
+            is_preferred: Some(true),
+            ..Default::default()
+        }));
+    }
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+    use tower_lsp_server::ls_types::{Position, Range};
+
+    const HEADER: &str =
+        "Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/\n";
+
+    fn diags(text: &str) -> Vec<(String, String)> {
+        let parsed = debian_copyright::lossless::Parse::parse_relaxed(text);
+        let idx = LineIndex::new(text);
+        license_diagnostics(&parsed, Source::new(text, &idx))
+            .into_iter()
+            .map(|d| match d.code {
+                Some(NumberOrString::String(code)) => (code, d.message),
+                _ => panic!("missing code"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_expressions() {
+        let text = format!(
+            "{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: GPL-2+ with OpenSSL exception or BSD-3-clause\n\nLicense: GPL-2+\n See /usr/share/common-licenses/GPL-2.\n\nLicense: BSD-3-clause\n Redistribution and use...\n"
+        );
+        assert_eq!(diags(&text), vec![]);
+    }
+
+    #[test]
+    fn test_spdx_identifier() {
+        let text = format!(
+            "{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: GPL-2.0-or-later\n\nLicense: GPL-2.0-or-later\n See /usr/share/common-licenses/GPL-2.\n"
+        );
+        let found = diags(&text);
+        assert_eq!(found.len(), 2);
+        assert_eq!(
+            found[0],
+            (
+                SPDX_NAME_CODE.to_string(),
+                "'GPL-2.0-or-later' is an SPDX identifier; the Debian short name is 'GPL-2+'"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_misspelled_names() {
+        assert_eq!(suggestion("Apche-2.0"), Some("Apache-2.0".to_string()));
+        assert_eq!(suggestion("gpl-3+"), Some("GPL-3+".to_string()));
+        assert_eq!(suggestion("bsd-3-clause"), Some("BSD-3-clause".to_string()));
+        assert_eq!(suggestion("Expta"), Some("Expat".to_string()));
+        assert_eq!(suggestion("LGPL-2.1+"), None);
+        assert_eq!(suggestion("CC-BY-SA-4.0"), None);
+        assert_eq!(suggestion("EPL-2.0"), None);
+        assert_eq!(suggestion("Custom-License"), None);
+    }
+
+    #[test]
+    fn test_undefined_license() {
+        let text = format!("{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: Custom or Expat\n");
+        assert_eq!(
+            diags(&text),
+            vec![
+                (
+                    UNDEFINED_CODE.to_string(),
+                    "License 'Custom' has neither license text nor a stand-alone License paragraph"
+                        .to_string()
+                ),
+                (
+                    UNDEFINED_CODE.to_string(),
+                    "License 'Expat' has neither license text nor a stand-alone License paragraph"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inline_text_defines_license() {
+        let text = format!(
+            "{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: Custom\n You may do anything.\n"
+        );
+        assert_eq!(diags(&text), vec![]);
+    }
+
+    #[test]
+    fn test_defined_case_insensitively() {
+        let text = format!(
+            "{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: custom\n\nLicense: Custom\n You may do anything.\n"
+        );
+        assert_eq!(diags(&text), vec![]);
+    }
+
+    #[test]
+    fn test_empty_license_text() {
+        let text =
+            format!("{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: Expat\n\nLicense: Expat\n");
+        assert_eq!(
+            diags(&text),
+            vec![(
+                EMPTY_TEXT_CODE.to_string(),
+                "License paragraph for 'Expat' has no license text".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_spdx_quick_fix() {
+        let text = format!(
+            "{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: MIT\n Permission is hereby granted...\n"
+        );
+        let parsed = debian_copyright::lossless::Parse::parse_relaxed(&text);
+        let idx = LineIndex::new(&text);
+        let src = Source::new(&text, &idx);
+        let uri: Uri = "file:///debian/copyright".parse().unwrap();
+        let diagnostics = license_diagnostics(&parsed, src);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(4, 9), Position::new(4, 12))
+        );
+
+        let actions = license_actions(&uri, &parsed, src, &diagnostics);
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("expected a code action");
+        };
+        assert_eq!(action.title, "Replace 'MIT' with 'Expat'");
+        let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits[0].new_text, "Expat");
+        assert_eq!(edits[0].range, diagnostics[0].range);
+    }
+
+    #[test]
+    fn test_actions_filtered_by_diagnostics() {
+        let text = format!(
+            "{HEADER}\nFiles: *\nCopyright: 2024 Foo\nLicense: MIT\n Permission is hereby granted...\n"
+        );
+        let parsed = debian_copyright::lossless::Parse::parse_relaxed(&text);
+        let idx = LineIndex::new(&text);
+        let src = Source::new(&text, &idx);
+        let uri: Uri = "file:///debian/copyright".parse().unwrap();
+        let unrelated = Diagnostic {
+            range: Range::new(Position::new(0, 0), Position::new(0, 6)),
+            message: "something else".to_string(),
+            ..Default::default()
+        };
+        assert!(license_actions(&uri, &parsed, src, &[unrelated]).is_empty());
+    }
+}
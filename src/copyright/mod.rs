@@ -1,10 +1,14 @@
 pub mod actions;
 pub mod code_lens;
+pub mod common_licenses;
 pub mod completion;
+pub mod coverage;
 pub mod definition;
 pub mod detection;
 pub mod fields;
 pub mod hover;
+pub mod licenses;
+pub mod scan;
 pub mod semantic;
 #[cfg(feature = "spellcheck")]
 pub mod spelling;
@@ -0,0 +1,935 @@
+//! Licensecheck-style scanning of the source tree to draft `Files:`
+//! paragraphs.
+//!
+//! The headers of the git-tracked files are searched for
+//! `SPDX-License-Identifier` tags, well-known license boilerplate and
+//! copyright statements. Files are then grouped by license and copyright
+//! holders: the largest group becomes the `Files: *` paragraph and every
+//! other group gets a paragraph of its own, with whole directories collapsed
+//! into `dir/*` where possible. An existing copyright file is only added to:
+//! paragraphs are drafted for the files it does not cover yet, and
+//! placeholder values are filled in.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Read;
+use std::path::Path;
+
+use debian_copyright::{GlobPattern, LicenseExpr};
+use rowan::ast::AstNode;
+use tower_lsp_server::ls_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Command, TextEdit, Uri, WorkspaceEdit,
+};
+
+use crate::position::Source;
+
+use super::fields::debian_license_name;
+
+pub const SCAN_COPYRIGHT_COMMAND: &str = "debian-lsp.scanCopyright";
+
+/// How much of each file is searched. License headers live at the top.
+const HEADER_BYTES: u64 = 16 * 1024;
+
+/// Placeholder for a license or copyright holder that couldn't be found.
+const UNKNOWN: &str = "UNKNOWN";
+
+const FORMAT_URL: &str = "https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/";
+
+/// Leading comment markers stripped from header lines.
+const COMMENT_MARKERS: &[&str] = &["<!--", "/*", "//", "dnl ", "#", "*", "--", ";", "%", "\""];
+
+/// License boilerplate phrases, checked in order against the normalized
+/// header. The GNU licenses and BSD variants are handled separately.
+const BOILERPLATE: &[(&str, &str)] = &[
+    ("apache license, version 2.0", "Apache-2.0"),
+    ("apache license version 2.0", "Apache-2.0"),
+    ("mozilla public license, v. 2.0", "MPL-2.0"),
+    ("mozilla public license version 2.0", "MPL-2.0"),
+    (
+        "permission is hereby granted, free of charge, to any person obtaining a copy",
+        "Expat",
+    ),
+    (
+        "permission to use, copy, modify, and/or distribute this software for any purpose",
+        "ISC",
+    ),
+    (
+        "permission to use, copy, modify, and distribute this software for any purpose",
+        "ISC",
+    ),
+    ("altered source versions must be plainly marked", "Zlib"),
+    (
+        "this is free and unencumbered software released into the public domain",
+        "Unlicense",
+    ),
+    ("the artistic license 2.0", "Artistic-2.0"),
+];
+
+/// What was found in the header of a single file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileFindings {
+    /// The license, as a Debian short name or expression.
+    pub license: Option<String>,
+    /// Copyright statements, without the leading "Copyright (C)".
+    pub holders: Vec<String>,
+}
+
+impl FileFindings {
+    fn is_empty(&self) -> bool {
+        self.license.is_none() && self.holders.is_empty()
+    }
+}
+
+/// Strip comment markers and surrounding whitespace from a line.
+fn strip_comment(line: &str) -> &str {
+    let mut s = line.trim();
+    'markers: loop {
+        for marker in COMMENT_MARKERS {
+            if let Some(rest) = s.strip_prefix(marker) {
+                s = rest.trim_start();
+                continue 'markers;
+            }
+        }
+        break;
+    }
+    for marker in ["*/", "-->"] {
+        s = s.strip_suffix(marker).unwrap_or(s);
+    }
+    s.trim_end()
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    s.get(..prefix.len())
+        .filter(|head| head.eq_ignore_ascii_case(prefix))
+        .map(|_| &s[prefix.len()..])
+}
+
+/// Translate an SPDX license expression into Debian short names.
+fn debian_expression(spdx: &str) -> String {
+    spdx.split_whitespace()
+        .map(|token| {
+            let core = token.trim_matches(|c| c == '(' || c == ')');
+            let replacement = match core {
+                "AND" => "and",
+                "OR" => "or",
+                "WITH" => "with",
+                _ => debian_license_name(core).unwrap_or(core),
+            };
+            token.replacen(core, replacement, 1)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The license from an `SPDX-License-Identifier` tag.
+fn spdx_license(text: &str) -> Option<String> {
+    const TAG: &str = "SPDX-License-Identifier:";
+    text.lines().find_map(|line| {
+        let pos = line.find(TAG)?;
+        let expression = strip_comment(&line[pos + TAG.len()..]);
+        (!expression.is_empty()).then(|| debian_expression(expression))
+    })
+}
+
+/// The version following a GNU license name, e.g. "2.1" in "version 2.1 of
+/// the License". A trailing ".0" is dropped, as in Debian short names.
+fn gnu_version(after_name: &str) -> Option<String> {
+    let pos = after_name.find("version ")?;
+    // The version belongs to this license only if it follows closely.
+    if pos > 80 {
+        return None;
+    }
+    let rest = &after_name[pos + "version ".len()..];
+    let version: String = rest
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let version = version.trim_end_matches('.');
+    let version = version.strip_suffix(".0").unwrap_or(version);
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+/// The license from well-known boilerplate in `normalized`, the lowercased
+/// header text with comment markers removed and whitespace collapsed.
+fn boilerplate_license(normalized: &str) -> Option<String> {
+    for (name, short) in [
+        ("gnu affero general public license", "AGPL"),
+        ("gnu lesser general public license", "LGPL"),
+        ("gnu library general public license", "LGPL"),
+        ("gnu general public license", "GPL"),
+    ] {
+        if let Some(pos) = normalized.find(name) {
+            let after = &normalized[pos + name.len()..];
+            let later = if after.contains("any later version") {
+                "+"
+            } else {
+                ""
+            };
+            return Some(match gnu_version(after) {
+                Some(version) => format!("{short}-{version}{later}"),
+                None => short.to_string(),
+            });
+        }
+    }
+
+    if normalized.contains("redistribution and use in source and binary forms") {
+        let license = if normalized.contains("advertising materials") {
+            "BSD-4-clause"
+        } else if normalized.contains("neither the name")
+            || normalized.contains("may not be used to endorse")
+        {
+            "BSD-3-clause"
+        } else {
+            "BSD-2-clause"
+        };
+        return Some(license.to_string());
+    }
+
+    BOILERPLATE
+        .iter()
+        .find(|(phrase, _)| normalized.contains(phrase))
+        .map(|(_, license)| license.to_string())
+}
+
+/// The copyright statement on `line`, if any.
+fn copyright_statement(line: &str) -> Option<String> {
+    let line = strip_comment(line);
+    let (mut rest, mut marked) =
+        if let Some(rest) = strip_prefix_ignore_case(line, "SPDX-FileCopyrightText:") {
+            (rest, true)
+        } else {
+            // Only a "Copyright" followed by a (C) sign or a year starts a
+            // statement; this skips prose such as "the above copyright notice".
+            let pos = line.to_ascii_lowercase().find("copyright")?;
+            (&line[pos + "copyright".len()..], false)
+        };
+    loop {
+        rest = rest.trim_start().trim_start_matches(':').trim_start();
+        if let Some(r) = strip_prefix_ignore_case(rest, "copyright") {
+            rest = r;
+        } else if let Some(r) = strip_prefix_ignore_case(rest, "(c)") {
+            rest = r;
+            marked = true;
+        } else if let Some(r) = rest.strip_prefix('©') {
+            rest = r;
+            marked = true;
+        } else {
+            break;
+        }
+    }
+    if !marked && !rest.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut statement = rest.split_whitespace().collect::<Vec<_>>().join(" ");
+    let lower = statement.to_ascii_lowercase();
+    if let Some(pos) = lower.find("all rights reserved") {
+        statement.truncate(pos);
+    }
+    let statement = statement.trim_end_matches([' ', ',', ';']);
+    (!statement.is_empty()).then(|| statement.to_string())
+}
+
+/// Find the license and copyright holders in the header of a file.
+pub fn scan_text(text: &str) -> FileFindings {
+    let mut holders: Vec<String> = Vec::new();
+    for statement in text.lines().filter_map(copyright_statement) {
+        if !holders.contains(&statement) {
+            holders.push(statement);
+        }
+    }
+
+    let license = spdx_license(text).or_else(|| {
+        let normalized = text
+            .lines()
+            .map(strip_comment)
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        boilerplate_license(&normalized)
+    });
+
+    FileFindings { license, holders }
+}
+
+/// Read the header of `path`. `None` for unreadable or binary files.
+fn read_header(path: &Path) -> Option<String> {
+    let mut data = Vec::new();
+    std::fs::File::open(path)
+        .and_then(|f| f.take(HEADER_BYTES).read_to_end(&mut data))
+        .ok()?;
+    if data.contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&data).into_owned())
+}
+
+/// Scan the headers of `files`, relative to `root`. Files without findings
+/// are left out.
+pub fn scan_tree(root: &Path, files: &[String]) -> HashMap<String, FileFindings> {
+    files
+        .iter()
+        .filter_map(|file| {
+            let findings = scan_text(&read_header(&root.join(file))?);
+            (!findings.is_empty()).then(|| (file.clone(), findings))
+        })
+        .collect()
+}
+
+/// The files a scan should cover: everything shipped upstream, minus the
+/// packaging under `debian/`.
+pub fn files_to_scan(
+    parsed: &debian_copyright::lossless::Parse,
+    tracked_files: &[String],
+) -> Vec<String> {
+    super::coverage::shipped_files(parsed, tracked_files)
+        .into_iter()
+        .filter(|f| !f.starts_with("debian/"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// The directories containing `path`, outermost first.
+fn ancestor_dirs(path: &str) -> impl Iterator<Item = &str> {
+    path.match_indices('/').map(move |(i, _)| &path[..i])
+}
+
+/// Count the files under each directory.
+fn dir_counts<'a>(files: impl Iterator<Item = &'a str>) -> HashMap<&'a str, usize> {
+    let mut counts = HashMap::new();
+    for file in files {
+        for dir in ancestor_dirs(file) {
+            *counts.entry(dir).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// Patterns matching exactly `group`: the outermost directory holding only
+/// files of the group becomes `dir/*`, other files are listed as they are.
+fn compress(group: &[&str], totals: &HashMap<&str, usize>) -> Vec<String> {
+    let counts = dir_counts(group.iter().copied());
+    let patterns: BTreeSet<String> = group
+        .iter()
+        .map(|file| {
+            ancestor_dirs(file)
+                .find(|dir| counts.get(dir) == totals.get(dir))
+                .map_or_else(|| file.to_string(), |dir| format!("{dir}/*"))
+        })
+        .collect();
+    patterns.into_iter().collect()
+}
+
+fn format_paragraph(patterns: &[String], holders: &[String], license: &str) -> String {
+    let copyright = if holders.is_empty() {
+        UNKNOWN.to_string()
+    } else {
+        holders.join("\n ")
+    };
+    format!(
+        "Files: {}\nCopyright: {}\nLicense: {}\n",
+        patterns.join("\n "),
+        copyright,
+        license
+    )
+}
+
+/// Draft `Files:` paragraphs for `files` from the scan results.
+pub fn draft_paragraphs(files: &[String], findings: &HashMap<String, FileFindings>) -> Vec<String> {
+    let all: Vec<&str> = files.iter().map(String::as_str).collect();
+    draft(files, &all, findings, true)
+        .into_iter()
+        .map(|(_, paragraph)| paragraph)
+        .collect()
+}
+
+/// Draft paragraphs for `drafted`, a subset of `files`, paired with their
+/// license. With `catch_all`, the largest group becomes the `Files: *`
+/// paragraph, which also covers files without findings; otherwise every
+/// file gets a pattern, so the paragraphs cover exactly `drafted`.
+fn draft(
+    files: &[String],
+    drafted: &[&str],
+    findings: &HashMap<String, FileFindings>,
+    catch_all: bool,
+) -> Vec<(String, String)> {
+    let mut groups: BTreeMap<(String, Vec<String>), Vec<&str>> = BTreeMap::new();
+    for &file in drafted {
+        let found = findings.get(file);
+        if found.is_none() && catch_all {
+            continue;
+        }
+        let mut holders = found.map(|f| f.holders.clone()).unwrap_or_default();
+        holders.sort();
+        holders.dedup();
+        let license = found
+            .and_then(|f| f.license.clone())
+            .unwrap_or_else(|| UNKNOWN.to_string());
+        groups.entry((license, holders)).or_default().push(file);
+    }
+
+    let mut paragraphs = Vec::new();
+    let mut largest: Option<&(String, Vec<String>)> = None;
+    if catch_all {
+        // The largest group becomes the catch-all; files without findings
+        // fall under it too.
+        for (key, group) in &groups {
+            if largest.is_none_or(|best| group.len() > groups[best].len()) {
+                largest = Some(key);
+            }
+        }
+        paragraphs.push(match largest {
+            Some((license, holders)) => (
+                license.clone(),
+                format_paragraph(&["*".to_string()], holders, license),
+            ),
+            None => (
+                UNKNOWN.to_string(),
+                format_paragraph(&["*".to_string()], &[], UNKNOWN),
+            ),
+        });
+    }
+
+    let totals = dir_counts(files.iter().map(String::as_str));
+    let mut others: Vec<(Vec<String>, String, String)> = groups
+        .iter()
+        .filter(|(key, _)| Some(*key) != largest)
+        .map(|((license, holders), group)| {
+            let patterns = compress(group, &totals);
+            let paragraph = format_paragraph(&patterns, holders, license);
+            (patterns, license.clone(), paragraph)
+        })
+        .collect();
+    others.sort();
+    paragraphs.extend(
+        others
+            .into_iter()
+            .map(|(_, license, paragraph)| (license, paragraph)),
+    );
+    paragraphs
+}
+
+/// The source action running [`SCAN_COPYRIGHT_COMMAND`] on `uri`.
+pub fn scan_action(uri: &Uri) -> CodeActionOrCommand {
+    let title = "Draft Files paragraphs from source file headers".to_string();
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title: title.clone(),
+        kind: Some(CodeActionKind::SOURCE),
+        command: Some(Command {
+            title,
+            command: SCAN_COPYRIGHT_COMMAND.to_string(),
+            arguments: Some(vec![serde_json::Value::String(uri.to_string())]),
+        }),
+        ..Default::default()
+    })
+}
+
+/// Whether a `Copyright` or `License` value is missing or a placeholder
+/// that the scan may fill in.
+fn is_placeholder(value: Option<&str>) -> bool {
+    value.is_none_or(|v| v.trim().is_empty() || v.trim().eq_ignore_ascii_case(UNKNOWN))
+}
+
+/// The order of the fields of a `Files:` paragraph.
+const FILES_FIELD_ORDER: &[&str] = &["Files", "Copyright", "License", "Comment"];
+
+/// The edit setting field `name` of `para` to `value`, replacing the field
+/// or adding it before the fields that follow it in [`FILES_FIELD_ORDER`].
+fn set_field_edit(
+    src: Source<'_>,
+    para: &deb822_lossless::Paragraph,
+    name: &str,
+    value: &str,
+) -> TextEdit {
+    let new_text = format!("{name}: {value}\n");
+    let order = |key: &str| {
+        FILES_FIELD_ORDER
+            .iter()
+            .position(|f| f.eq_ignore_ascii_case(key))
+    };
+    let range = match para
+        .entries()
+        .find(|e| e.key().is_some_and(|k| k.eq_ignore_ascii_case(name)))
+    {
+        Some(entry) => entry.syntax().text_range(),
+        None => {
+            let offset = para
+                .entries()
+                .find(|e| e.key().and_then(|k| order(&k)) > order(name))
+                .map_or(para.syntax().text_range().end(), |e| {
+                    e.syntax().text_range().start()
+                });
+            text_size::TextRange::empty(offset)
+        }
+    };
+    TextEdit {
+        range: src.text_range_to_lsp_range(range),
+        new_text,
+    }
+}
+
+/// Insert `paragraphs` at `offset`, which is either the start of an existing
+/// paragraph or the end of one.
+fn insert_paragraphs(
+    src: Source<'_>,
+    offset: text_size::TextSize,
+    paragraphs: &[String],
+) -> TextEdit {
+    let block = paragraphs.join("\n");
+    let at = usize::from(offset);
+    let new_text = if at == 0 && src.text.trim().is_empty() {
+        block
+    } else if at < src.text.len() && src.text[..at].ends_with("\n\n") {
+        format!("{block}\n")
+    } else if src.text[..at].ends_with('\n') {
+        format!("\n{block}")
+    } else {
+        format!("\n\n{block}")
+    };
+    TextEdit {
+        range: src.text_range_to_lsp_range(text_size::TextRange::empty(offset)),
+        new_text,
+    }
+}
+
+/// Build the edit merging the scan results into a copyright file.
+///
+/// Existing paragraphs are kept as written, except that a missing or
+/// `UNKNOWN` `Copyright` or `License` is filled in from the files the
+/// paragraph covers. Files no paragraph covers get drafted paragraphs of
+/// their own, headed by a `Files: *` catch-all when nothing outside
+/// `debian/` is covered yet. Every license cited by the scan that has no
+/// stand-alone `License:` paragraph gets a stub to add the text to.
+pub fn build_scan_edit(
+    uri: &Uri,
+    src: Source<'_>,
+    parsed: &debian_copyright::lossless::Parse,
+    files: &[String],
+    findings: &HashMap<String, FileFindings>,
+) -> Option<WorkspaceEdit> {
+    let copyright = parsed.tree();
+    let paragraphs: Vec<_> = copyright.iter_files().collect();
+    let patterns: Vec<Vec<GlobPattern>> = paragraphs
+        .iter()
+        .map(|para| para.files().iter().map(|p| GlobPattern::new(p)).collect())
+        .collect();
+
+    // As in coverage checks, the last matching paragraph claims a file.
+    let mut claimed: Vec<Vec<&str>> = vec![Vec::new(); paragraphs.len()];
+    let mut uncovered = Vec::new();
+    for file in files {
+        match patterns
+            .iter()
+            .rposition(|para| para.iter().any(|p| p.is_match(file)))
+        {
+            Some(i) => claimed[i].push(file.as_str()),
+            None => uncovered.push(file.as_str()),
+        }
+    }
+
+    let mut edits = Vec::new();
+    let mut cited: Vec<String> = Vec::new();
+    for (para, claimed) in paragraphs.iter().zip(&claimed) {
+        let para = para.as_deb822();
+        let found: Vec<&FileFindings> = claimed.iter().filter_map(|f| findings.get(*f)).collect();
+
+        if is_placeholder(para.get("Copyright").as_deref()) {
+            let holders: BTreeSet<&String> = found.iter().flat_map(|f| &f.holders).collect();
+            if !holders.is_empty() {
+                let holders: Vec<&str> = holders.into_iter().map(String::as_str).collect();
+                edits.push(set_field_edit(src, para, "Copyright", &holders.join("\n ")));
+            }
+        }
+
+        // A license with text of its own is not a placeholder, whatever
+        // its name.
+        let license = para.get("License");
+        if is_placeholder(license.as_deref()) {
+            let licenses: BTreeSet<&String> =
+                found.iter().filter_map(|f| f.license.as_ref()).collect();
+            if let [license] = licenses.into_iter().collect::<Vec<_>>()[..] {
+                edits.push(set_field_edit(src, para, "License", license));
+                cited.push(license.clone());
+            }
+        }
+    }
+
+    let covers_upstream = paragraphs.iter().any(|para| {
+        let patterns = para.files();
+        !patterns.is_empty() && !patterns.iter().all(|p| p.starts_with("debian/"))
+    });
+    let drafted = if uncovered.is_empty() {
+        Vec::new()
+    } else {
+        draft(files, &uncovered, findings, !covers_upstream)
+    };
+    cited.extend(drafted.iter().map(|(license, _)| license.clone()));
+    let mut new_paragraphs: Vec<String> = drafted.into_iter().map(|(_, p)| p).collect();
+
+    let defined: BTreeSet<String> = copyright
+        .iter_licenses()
+        .filter_map(|para| para.as_deb822().get("License"))
+        .filter_map(|value| value.lines().next().map(|name| name.trim().to_lowercase()))
+        .collect();
+    let mut stubs: Vec<String> = Vec::new();
+    for license in &cited {
+        for (name, _) in LicenseExpr::name_ranges(license) {
+            let lower = name.to_lowercase();
+            if name != UNKNOWN
+                && !defined.contains(&lower)
+                && !stubs.iter().any(|s| s.to_lowercase() == lower)
+            {
+                stubs.push(name.to_string());
+            }
+        }
+    }
+    let stubs: Vec<String> = stubs
+        .into_iter()
+        .map(|name| format!("License: {name}\n"))
+        .collect();
+
+    let end = text_size::TextSize::from(src.text.len() as u32);
+    let header = copyright.header();
+    if header.is_none() {
+        new_paragraphs.insert(0, format!("Format: {FORMAT_URL}\n"));
+    }
+    // The catch-all has to precede the paragraphs it is an exception to;
+    // other drafts cover files no paragraph matches, so they simply follow.
+    let offset = if header.is_none() {
+        text_size::TextSize::from(0)
+    } else if !covers_upstream {
+        paragraphs
+            .first()
+            .map(|para| para.as_deb822().syntax().text_range().start())
+            .or_else(|| {
+                copyright
+                    .iter_licenses()
+                    .next()
+                    .map(|para| para.as_deb822().syntax().text_range().start())
+            })
+            .unwrap_or(end)
+    } else {
+        paragraphs
+            .last()
+            .map(|para| para.as_deb822().syntax().text_range().end())
+            .unwrap_or(end)
+    };
+    if offset == end {
+        new_paragraphs.extend(stubs);
+        if !new_paragraphs.is_empty() {
+            edits.push(insert_paragraphs(src, offset, &new_paragraphs));
+        }
+    } else {
+        if !new_paragraphs.is_empty() {
+            edits.push(insert_paragraphs(src, offset, &new_paragraphs));
+        }
+        if !stubs.is_empty() {
+            edits.push(insert_paragraphs(src, end, &stubs));
+        }
+    }
+
+    if edits.is_empty() {
+        return None;
+    }
+    Some(WorkspaceEdit {
+        changes: Some(HashMap::from([(uri.clone(), edits)])),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::Workspace;
+
+    #[test]
+    fn test_scan_spdx_header() {
+        let text = "// SPDX-FileCopyrightText: 2023 Jane Doe <jane@example.com>\n// SPDX-License-Identifier: GPL-2.0-or-later OR MIT\n\nint main() {}\n";
+        assert_eq!(
+            scan_text(text),
+            FileFindings {
+                license: Some("GPL-2+ or Expat".to_string()),
+                holders: vec!["2023 Jane Doe <jane@example.com>".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_scan_gpl_boilerplate() {
+        let text = "\
+/*
+ * Copyright (C) 2019-2021 John Smith. All rights reserved.
+ * Copyright (c) 2022 Acme, Inc.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ */
+";
+        assert_eq!(
+            scan_text(text),
+            FileFindings {
+                license: Some("GPL-2+".to_string()),
+                holders: vec![
+                    "2019-2021 John Smith.".to_string(),
+                    "2022 Acme, Inc.".to_string()
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_scan_lgpl_boilerplate() {
+        let text = "# This library is free software; you can redistribute it under the terms\n# of the GNU Lesser General Public License version 2.1 as published by\n# the Free Software Foundation.\n";
+        assert_eq!(scan_text(text).license.as_deref(), Some("LGPL-2.1"));
+    }
+
+    #[test]
+    fn test_scan_bsd_boilerplate() {
+        let text = "Redistribution and use in source and binary forms, with or without\nmodification, are permitted provided that the following conditions are met:\n3. Neither the name of the copyright holder nor the names of its contributors\n";
+        assert_eq!(scan_text(text).license.as_deref(), Some("BSD-3-clause"));
+    }
+
+    #[test]
+    fn test_scan_ignores_copyright_prose() {
+        let text = "The above copyright notice and this permission notice shall be included\n";
+        assert_eq!(scan_text(text).holders, Vec::<String>::new());
+    }
+
+    fn findings(license: &str, holder: &str) -> FileFindings {
+        FileFindings {
+            license: Some(license.to_string()),
+            holders: vec![holder.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_draft_paragraphs() {
+        let files: Vec<String> = [
+            "README",
+            "src/a.c",
+            "src/b.c",
+            "src/c.c",
+            "src/d.c",
+            "lib/x/one.c",
+            "lib/x/two.c",
+            "lib/y.c",
+            "tools/gen.py",
+        ]
+        .iter()
+        .map(|f| f.to_string())
+        .collect();
+        let found = HashMap::from([
+            ("src/a.c".to_string(), findings("GPL-2+", "2020 Jane")),
+            ("src/b.c".to_string(), findings("GPL-2+", "2020 Jane")),
+            ("src/c.c".to_string(), findings("GPL-2+", "2020 Jane")),
+            ("src/d.c".to_string(), findings("GPL-2+", "2020 Jane")),
+            ("lib/x/one.c".to_string(), findings("Expat", "2019 Bob")),
+            ("lib/x/two.c".to_string(), findings("Expat", "2019 Bob")),
+            ("tools/gen.py".to_string(), findings("Expat", "2019 Bob")),
+        ]);
+        assert_eq!(
+            draft_paragraphs(&files, &found),
+            vec![
+                "Files: *\nCopyright: 2020 Jane\nLicense: GPL-2+\n".to_string(),
+                "Files: lib/x/*\n tools/*\nCopyright: 2019 Bob\nLicense: Expat\n".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_draft_paragraphs_without_findings() {
+        let files = vec!["README".to_string()];
+        assert_eq!(
+            draft_paragraphs(&files, &HashMap::new()),
+            vec!["Files: *\nCopyright: UNKNOWN\nLicense: UNKNOWN\n".to_string()]
+        );
+    }
+
+    /// Apply the scan edit for `content` and return the new text.
+    fn scan_edit(
+        content: &str,
+        files: &[&str],
+        found: &HashMap<String, FileFindings>,
+    ) -> Option<String> {
+        let mut workspace = Workspace::new();
+        let uri: Uri = str::parse("file:///debian/copyright").unwrap();
+        let file = workspace.update_file(uri.clone(), content.to_string());
+        let parsed = workspace.get_parsed_copyright(file);
+        let text = workspace.source_text(file);
+        let idx = workspace.get_line_index(file);
+        let src = Source::new(&text, &idx);
+
+        let files: Vec<String> = files.iter().map(|f| f.to_string()).collect();
+        let edit = build_scan_edit(&uri, src, &parsed, &files, found)?;
+        let mut ranges: Vec<_> = edit.changes.unwrap()[&uri]
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                let range = src.try_lsp_range_to_text_range(&e.range).unwrap();
+                (range, i, e.new_text.clone())
+            })
+            .collect();
+        // Inserts at the same position keep their order.
+        ranges.sort_by_key(|(range, i, _)| std::cmp::Reverse((range.start(), *i)));
+        let mut result = content.to_string();
+        for (range, _, new_text) in ranges {
+            result.replace_range(std::ops::Range::<usize>::from(range), &new_text);
+        }
+        Some(result)
+    }
+
+    #[test]
+    fn test_build_scan_edit_keeps_existing_paragraphs() {
+        let content = "\
+Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/
+Upstream-Name: foo
+
+# Checked by hand.
+Files: *
+Copyright: 2010 Someone
+License: GPL-2+
+
+Files: debian/*
+Copyright: 2024 Packager
+License: GPL-2+
+
+License: GPL-2+
+ On Debian systems, see /usr/share/common-licenses/GPL-2.
+";
+        let found = HashMap::from([("src/main.c".to_string(), findings("Expat", "2023 Jane"))]);
+        // Everything is covered already and nothing is a placeholder.
+        assert_eq!(scan_edit(content, &["src/main.c"], &found), None);
+
+        let content = content.replace("Files: *", "Files: src/*");
+        let found = HashMap::from([
+            ("src/main.c".to_string(), findings("Expat", "2023 Jane")),
+            ("lib/util.c".to_string(), findings("Expat", "2023 Jane")),
+        ]);
+        assert_eq!(
+            scan_edit(&content, &["src/main.c", "lib/util.c"], &found).unwrap(),
+            "\
+Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/
+Upstream-Name: foo
+
+# Checked by hand.
+Files: src/*
+Copyright: 2010 Someone
+License: GPL-2+
+
+Files: debian/*
+Copyright: 2024 Packager
+License: GPL-2+
+
+Files: lib/*
+Copyright: 2023 Jane
+License: Expat
+
+License: GPL-2+
+ On Debian systems, see /usr/share/common-licenses/GPL-2.
+
+License: Expat
+"
+        );
+    }
+
+    #[test]
+    fn test_build_scan_edit_adds_catch_all_before_debian_paragraph() {
+        let content = "\
+Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/
+
+Files: debian/*
+Copyright: 2024 Packager
+License: GPL-2+
+";
+        let found = HashMap::from([("src/main.c".to_string(), findings("Expat", "2023 Jane"))]);
+        assert_eq!(
+            scan_edit(content, &["src/main.c", "README"], &found).unwrap(),
+            "\
+Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/
+
+Files: *
+Copyright: 2023 Jane
+License: Expat
+
+Files: debian/*
+Copyright: 2024 Packager
+License: GPL-2+
+
+License: Expat
+"
+        );
+    }
+
+    #[test]
+    fn test_build_scan_edit_fills_placeholders() {
+        let content = "\
+Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/
+
+Files: *
+Copyright: UNKNOWN
+License: UNKNOWN
+Comment: Drafted earlier.
+
+Files: data/*
+License: CC0-1.0
+";
+        let found = HashMap::from([
+            ("src/a.c".to_string(), findings("GPL-2+", "2020 Jane")),
+            ("src/b.c".to_string(), findings("GPL-2+", "2021 Bob")),
+            ("data/x.svg".to_string(), findings("CC-BY-4.0", "2022 Ann")),
+        ]);
+        assert_eq!(
+            scan_edit(content, &["src/a.c", "src/b.c", "data/x.svg"], &found).unwrap(),
+            "\
+Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/
+
+Files: *
+Copyright: 2020 Jane
+ 2021 Bob
+License: GPL-2+
+Comment: Drafted earlier.
+
+Files: data/*
+Copyright: 2022 Ann
+License: CC0-1.0
+
+License: GPL-2+
+"
+        );
+    }
+
+    #[test]
+    fn test_build_scan_edit_without_header() {
+        let found = HashMap::from([("src/main.c".to_string(), findings("Expat", "2023 Jane"))]);
+        assert_eq!(
+            scan_edit("", &["src/main.c"], &found).unwrap(),
+            "\
+Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/
+
+Files: *
+Copyright: 2023 Jane
+License: Expat
+
+License: Expat
+"
+        );
+    }
+
+    #[test]
+    fn test_files_to_scan_skips_debian_and_excluded() {
+        let content = "Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/\nFiles-Excluded: vendor/*\n";
+        let parsed = debian_copyright::lossless::Parse::parse_relaxed(content);
+        let tracked: Vec<String> = ["src/a.c", "vendor/b.c", "debian/rules"]
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+        assert_eq!(
+            files_to_scan(&parsed, &tracked),
+            vec!["src/a.c".to_string()]
+        );
+    }
+}
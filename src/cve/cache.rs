@@ -2,10 +2,14 @@
 
 use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Duration;
 
 use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
 use tokio::sync::RwLock;
 
+use crate::disk_cache::DiskCache;
 use crate::udd::SharedPool;
 
 /// Thread-safe shared cache for CVE lookups.
@@ -15,8 +19,12 @@ pub type SharedCveCache = Arc<RwLock<CveCache>>;
 /// cap exists to bound long-session growth.
 const CVE_CACHE_CAPACITY: usize = 8192;
 
+/// How long CVE details are kept on disk before being re-queried. The
+/// per-release status changes as fixes land, so this is kept short.
+const CVE_DISK_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
 /// Per-release status for a CVE in a given source package.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CveReleaseStatus {
     /// Debian release codename (e.g. `bookworm`, `sid`).
     pub release: String,
@@ -27,7 +35,7 @@ pub struct CveReleaseStatus {
 }
 
 /// Details for a CVE, drawn from UDD.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CveSummary {
     /// The CVE identifier, e.g. `CVE-2024-1234`.
     pub id: String,
@@ -39,6 +47,10 @@ pub struct CveSummary {
     pub bug: Option<u32>,
     /// Per-release status for the source package, ordered by release name.
     pub releases: Vec<CveReleaseStatus>,
+    /// Whether this is an expired on-disk copy, used because UDD could not
+    /// be reached.
+    #[serde(skip)]
+    pub stale: bool,
 }
 
 /// Cache of CVE summaries keyed by `(cve-id, source-package)`.
@@ -48,6 +60,7 @@ pub struct CveSummary {
 pub struct CveCache {
     pool: SharedPool,
     by_key: LruCache<(String, String), Option<CveSummary>>,
+    disk: DiskCache,
 }
 
 impl CveCache {
@@ -58,6 +71,7 @@ impl CveCache {
             by_key: LruCache::new(
                 NonZeroUsize::new(CVE_CACHE_CAPACITY).expect("non-zero capacity"),
             ),
+            disk: DiskCache::open("cve", CVE_DISK_TTL),
         }
     }
 }
@@ -86,6 +100,8 @@ struct ReleaseRow {
 /// `source` scopes the per-release status; pass the source package the CVE is
 /// referenced from. Returns `None` when the CVE is unknown to UDD (or the
 /// lookup fails), in which case callers should fall back to a plain link.
+/// When UDD can't be reached an expired on-disk copy is returned, marked
+/// stale.
 pub async fn cve_summary(cache: &SharedCveCache, id: &str, source: &str) -> Option<CveSummary> {
     let key = (id.to_owned(), source.to_owned());
 
@@ -94,34 +110,57 @@ pub async fn cve_summary(cache: &SharedCveCache, id: &str, source: &str) -> Opti
         return cached.clone();
     }
 
-    // Slow path: fetch from UDD with the lock released.
-    let pool = cache.read().await.pool.clone();
-    let summary = fetch_cve(&pool, id, source).await;
+    let (pool, disk) = {
+        let guard = cache.read().await;
+        (guard.pool.clone(), guard.disk.clone())
+    };
+    // Slow path: read the on-disk cache or fetch from UDD with the lock
+    // released.
+    let disk_key = format!("{id}/{source}");
+    let query = async {
+        let udd = pool.get()?;
+        match fetch_cve(&udd, id, source).await {
+            Ok(summary) => Some(summary),
+            Err(e) => {
+                tracing::warn!(cve = id, error = %e, "UDD CVE query failed");
+                pool.report_error(&e);
+                None
+            }
+        }
+    };
+    let (summary, stale) = disk.fetch(&pool.url(), &disk_key, query).await?;
+    if stale {
+        // Not cached in memory, so the lookup is retried once UDD is
+        // reachable again.
+        return summary.map(|summary| CveSummary {
+            stale: true,
+            ..summary
+        });
+    }
 
     cache.write().await.by_key.put(key, summary.clone());
     summary
 }
 
 /// Query UDD for a single CVE's details scoped to a source package.
-async fn fetch_cve(pool: &SharedPool, id: &str, source: &str) -> Option<CveSummary> {
-    let issue: Option<IssueRow> = match sqlx::query_as(
+async fn fetch_cve(
+    udd: &PgPool,
+    id: &str,
+    source: &str,
+) -> Result<Option<CveSummary>, sqlx::Error> {
+    let issue: Option<IssueRow> = sqlx::query_as(
         "SELECT description, scope::text AS scope, bug \
          FROM security_issues WHERE issue = $1 \
          ORDER BY (source = $2) DESC LIMIT 1",
     )
     .bind(id)
     .bind(source)
-    .fetch_optional(&**pool)
-    .await
-    {
-        Ok(row) => row,
-        Err(e) => {
-            tracing::warn!(cve = id, error = %e, "UDD CVE query failed");
-            return None;
-        }
-    };
+    .fetch_optional(udd)
+    .await?;
 
-    let issue = issue?;
+    let Some(issue) = issue else {
+        return Ok(None);
+    };
 
     let releases: Vec<ReleaseRow> = match sqlx::query_as(
         "SELECT release, status::text AS status, fixed_version \
@@ -130,7 +169,7 @@ async fn fetch_cve(pool: &SharedPool, id: &str, source: &str) -> Option<CveSumma
     )
     .bind(id)
     .bind(source)
-    .fetch_all(&**pool)
+    .fetch_all(udd)
     .await
     {
         Ok(rows) => rows,
@@ -140,7 +179,7 @@ async fn fetch_cve(pool: &SharedPool, id: &str, source: &str) -> Option<CveSumma
         }
     };
 
-    Some(CveSummary {
+    Ok(Some(CveSummary {
         id: id.to_owned(),
         description: issue.description,
         scope: issue.scope,
@@ -153,7 +192,8 @@ async fn fetch_cve(pool: &SharedPool, id: &str, source: &str) -> Option<CveSumma
                 fixed_version: r.fixed_version,
             })
             .collect(),
-    })
+        stale: false,
+    }))
 }
 
 #[cfg(test)]
@@ -184,6 +224,7 @@ mod tests {
                 status: Some("resolved".to_owned()),
                 fixed_version: Some("2.15.0-1".to_owned()),
             }],
+            stale: false,
         };
         cache.write().await.by_key.put(
             ("CVE-2021-44228".to_owned(), "apache-log4j2".to_owned()),
@@ -195,6 +236,38 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn expired_disk_entry_is_used_offline() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = crate::udd::shared_pool();
+        pool.configure(crate::udd::DEFAULT_UDD_URL, true);
+        let cache = new_shared_cve_cache(pool);
+        let disk = DiskCache::in_dir(dir.path().to_path_buf(), Duration::ZERO);
+        let summary = CveSummary {
+            id: "CVE-2021-44228".to_owned(),
+            description: Some("Log4Shell".to_owned()),
+            scope: None,
+            bug: None,
+            releases: vec![],
+            stale: false,
+        };
+        disk.store(
+            crate::udd::DEFAULT_UDD_URL,
+            "CVE-2021-44228/apache-log4j2",
+            &Some(summary.clone()),
+        )
+        .await;
+        cache.write().await.disk = disk;
+
+        assert_eq!(
+            cve_summary(&cache, "CVE-2021-44228", "apache-log4j2").await,
+            Some(CveSummary {
+                stale: true,
+                ..summary
+            })
+        );
+    }
+
     #[tokio::test]
     #[ignore] // requires network access to UDD
     async fn fetches_known_cve_from_udd() {
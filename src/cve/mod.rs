@@ -69,6 +69,9 @@ pub fn summary_markdown(summary: &CveSummary) -> String {
         }
         lines.push(status_line);
     }
+    if summary.stale {
+        lines.push(crate::disk_cache::STALE_NOTE.to_owned());
+    }
 
     lines.join("\n\n")
 }
@@ -251,6 +254,7 @@ mod tests {
                     fixed_version: None,
                 },
             ],
+            stale: false,
         };
         assert_eq!(
             summary_markdown(&summary),
@@ -276,6 +280,7 @@ mod tests {
             scope: None,
             bug: None,
             releases: vec![],
+            stale: false,
         };
         assert_eq!(
             summary_markdown(&summary),
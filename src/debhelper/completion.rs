@@ -28,7 +28,7 @@ const COMMON_DIRS: &[&str] = &[
 ];
 
 /// Substitution variables expanded in debhelper config files.
-const SUBSTITUTION_VARS: &[(&str, &str)] = &[
+pub(crate) const SUBSTITUTION_VARS: &[(&str, &str)] = &[
     ("DEB_HOST_ARCH", "dpkg-architecture host architecture"),
     (
         "DEB_HOST_MULTIARCH",
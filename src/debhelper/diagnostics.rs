@@ -0,0 +1,147 @@
+//! Diagnostics shared by the line-oriented debhelper files.
+//!
+//! Every helper reports the lexer's substitution errors and repeated entries
+//! the same way; the per-file modules add their own checks on top and build
+//! their diagnostics through [`make_diagnostic`] so ranges and wording stay
+//! consistent.
+
+use std::collections::HashSet;
+use std::ops::Range;
+
+use tower_lsp_server::ls_types::{
+    Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range as LspRange,
+};
+
+use crate::debhelper::parser::{parse_line, ParseErrorKind};
+use crate::position::{utf16_len, Source};
+
+/// The LSP range of a byte range within a single line.
+pub(crate) fn line_range(line_num: usize, line: &str, range: Range<usize>) -> LspRange {
+    LspRange::new(
+        Position::new(line_num as u32, utf16_len(&line[..range.start])),
+        Position::new(line_num as u32, utf16_len(&line[..range.end])),
+    )
+}
+
+/// Build a diagnostic for a byte range within a single line.
+pub(crate) fn make_diagnostic(
+    line_num: usize,
+    line: &str,
+    range: Range<usize>,
+    severity: DiagnosticSeverity,
+    code: &str,
+    message: String,
+) -> Diagnostic {
+    Diagnostic {
+        range: line_range(line_num, line, range),
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some("debian-lsp".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Substitution errors and duplicate entries in a debhelper file.
+///
+/// Two lines are duplicates when their tokens match after collapsing
+/// whitespace; the later one is flagged with `duplicate-entry`, which the
+/// quick fix in [`crate::debhelper::actions`] deletes.
+pub(crate) fn common_diagnostics(src: Source<'_>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (line_num, line) in src.text.lines().enumerate() {
+        let parsed = parse_line(line);
+        if parsed.comment.is_some() {
+            continue;
+        }
+
+        for error in parsed.errors() {
+            let message = match error.kind {
+                ParseErrorKind::UnterminatedSubstitution => {
+                    "Unterminated substitution, missing '}'".to_string()
+                }
+                ParseErrorKind::EmptySubstitution => "Empty substitution '${}'".to_string(),
+            };
+            diagnostics.push(make_diagnostic(
+                line_num,
+                line,
+                error.range,
+                DiagnosticSeverity::ERROR,
+                "invalid-substitution",
+                message,
+            ));
+        }
+
+        let (Some(first), Some(last)) = (parsed.words.first(), parsed.words.last()) else {
+            continue;
+        };
+        let Some(rendered) = parsed.render(line) else {
+            continue;
+        };
+        if !seen.insert(rendered.clone()) {
+            diagnostics.push(make_diagnostic(
+                line_num,
+                line,
+                first.range.start..last.range.end,
+                DiagnosticSeverity::WARNING,
+                "duplicate-entry",
+                format!("Duplicate entry '{}'", rendered),
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+
+    fn diagnostics(text: &str) -> Vec<Diagnostic> {
+        let idx = LineIndex::new(text);
+        common_diagnostics(Source::new(text, &idx))
+    }
+
+    fn codes(diags: &[Diagnostic]) -> Vec<String> {
+        diags
+            .iter()
+            .map(|d| match &d.code {
+                Some(NumberOrString::String(s)) => s.clone(),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn clean_file_has_no_diagnostics() {
+        assert_eq!(diagnostics("usr/bin/foo\n# note\nusr/lib/foo\n"), vec![]);
+    }
+
+    #[test]
+    fn flags_a_duplicate_line() {
+        let diags = diagnostics("usr/bin/foo usr/bin\nusr/lib\nusr/bin/foo   usr/bin\n");
+        assert_eq!(codes(&diags), vec!["duplicate-entry"]);
+        assert_eq!(
+            diags[0].range,
+            LspRange::new(Position::new(2, 0), Position::new(2, 21))
+        );
+    }
+
+    #[test]
+    fn flags_an_unterminated_substitution() {
+        let diags = diagnostics("usr/lib/${DEB_HOST_MULTIARCH\n");
+        assert_eq!(codes(&diags), vec!["invalid-substitution"]);
+        assert_eq!(
+            diags[0].range,
+            LspRange::new(Position::new(0, 8), Position::new(0, 28))
+        );
+    }
+
+    #[test]
+    fn repeated_comments_are_not_duplicates() {
+        assert_eq!(diagnostics("# note\n# note\n"), vec![]);
+    }
+}
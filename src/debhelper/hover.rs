@@ -0,0 +1,91 @@
+//! Hover building blocks shared by the line-oriented debhelper files.
+
+use tower_lsp_server::ls_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+
+use crate::debhelper::completion::SUBSTITUTION_VARS;
+use crate::debhelper::parser::{parse_line, Part, Word};
+use crate::position::Source;
+
+/// A markdown hover with no explicit range.
+pub(crate) fn markdown(value: String) -> Hover {
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: None,
+    }
+}
+
+/// The line under `position` and the byte offset of the cursor within it.
+pub(crate) fn line_at<'a>(src: Source<'a>, position: Position) -> Option<(&'a str, usize)> {
+    let line = src.text.lines().nth(position.line as usize)?;
+    let line_start = src.try_position_to_offset(Position::new(position.line, 0))?;
+    let offset = src.try_position_to_offset(position)?;
+    Some((line, usize::from(offset - line_start)))
+}
+
+/// The index and token under `offset`, if the cursor is on one.
+pub(crate) fn word_at(words: &[Word], offset: usize) -> Option<(usize, &Word)> {
+    words
+        .iter()
+        .enumerate()
+        .find(|(_, w)| w.range.start <= offset && offset <= w.range.end)
+}
+
+/// Hover for a `${...}` substitution under the cursor.
+pub(crate) fn substitution_hover(line: &str, offset: usize) -> Option<Hover> {
+    let parsed = parse_line(line);
+    let (_, word) = word_at(&parsed.words, offset)?;
+    word.parts.iter().find_map(|part| match part {
+        Part::Substitution(sub) if sub.range.start <= offset && offset < sub.range.end => {
+            let name = &line[sub.name.clone()];
+            let (var, detail) = SUBSTITUTION_VARS.iter().find(|(var, _)| {
+                if var.ends_with(':') {
+                    name.starts_with(var)
+                } else {
+                    *var == name
+                }
+            })?;
+            Some(markdown(format!("**${{{var}}}**\n\n{detail}")))
+        }
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn md(h: &Hover) -> &str {
+        match &h.contents {
+            HoverContents::Markup(m) => &m.value,
+            _ => panic!("expected markup"),
+        }
+    }
+
+    #[test]
+    fn hovers_a_known_substitution() {
+        let h = substitution_hover("usr/lib/${DEB_HOST_MULTIARCH}/foo", 12).unwrap();
+        assert_eq!(
+            md(&h),
+            "**${DEB_HOST_MULTIARCH}**\n\ndpkg-architecture host multiarch tuple"
+        );
+    }
+
+    #[test]
+    fn hovers_an_env_substitution() {
+        let h = substitution_hover("${env:HOME}/foo", 3).unwrap();
+        assert!(md(&h).starts_with("**${env:}**"));
+    }
+
+    #[test]
+    fn nothing_on_a_literal() {
+        assert!(substitution_hover("usr/lib/${DEB_HOST_MULTIARCH}/foo", 2).is_none());
+    }
+
+    #[test]
+    fn nothing_on_an_unknown_substitution() {
+        assert!(substitution_hover("${NOPE}", 3).is_none());
+    }
+}
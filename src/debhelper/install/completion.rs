@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use tower_lsp_server::ls_types::{CompletionItem, Position};
+
+use crate::debhelper::completion::{self, dir_items};
+use crate::debhelper::source::source_candidates;
+
+/// Completions for a debian/install file at the given cursor position.
+pub fn get_completions(
+    text: &str,
+    position: Position,
+    debian_dir: Option<&Path>,
+) -> Vec<CompletionItem> {
+    completion::get_completions(text, position, |index, prefix| {
+        let sources = match debian_dir {
+            Some(dir) => source_candidates(dir, prefix),
+            None => Vec::new(),
+        };
+        if index == 0 {
+            return sources;
+        }
+        // Any later token is either another source or, if it ends up last,
+        // the destination directory. Offer install locations first.
+        let mut items = dir_items(prefix, &HashSet::new());
+        items.extend(sources);
+        items
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source_scan::git_tree;
+    use tower_lsp_server::ls_types::CompletionItemKind;
+
+    fn labels(items: &[CompletionItem]) -> Vec<String> {
+        items.iter().map(|i| i.label.clone()).collect()
+    }
+
+    #[test]
+    fn first_token_completes_sources() {
+        let dir = git_tree(
+            &["debian/install", "foo.conf", "debian/tmp/usr/bin/foo"],
+            &[],
+        );
+        let debian = dir.path().join("debian");
+        let items = labels(&get_completions("", Position::new(0, 0), Some(&debian)));
+        assert!(items.contains(&"foo.conf".to_string()));
+        assert!(items.contains(&"usr/".to_string()));
+        assert!(!items.contains(&"etc/default/".to_string()));
+    }
+
+    #[test]
+    fn later_token_offers_destinations() {
+        let items = get_completions("foo.conf etc/", Position::new(0, 13), None);
+        assert!(items.iter().any(|i| i.label == "etc/default/"));
+        assert!(items
+            .iter()
+            .all(|i| i.kind == Some(CompletionItemKind::FOLDER)));
+    }
+
+    #[test]
+    fn later_token_also_offers_sources() {
+        let dir = git_tree(&["debian/install", "a.conf", "b.conf"], &[]);
+        let debian = dir.path().join("debian");
+        let items = labels(&get_completions(
+            "a.conf b",
+            Position::new(0, 8),
+            Some(&debian),
+        ));
+        assert!(items.contains(&"b.conf".to_string()));
+    }
+
+    #[test]
+    fn nothing_in_a_comment() {
+        assert!(get_completions("# etc/", Position::new(0, 6), None).is_empty());
+    }
+}
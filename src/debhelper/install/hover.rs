@@ -0,0 +1,68 @@
+use tower_lsp_server::ls_types::{Hover, Position};
+
+use crate::debhelper::hover::{line_at, markdown, substitution_hover, word_at};
+use crate::debhelper::parser::parse_line;
+use crate::position::Source;
+
+/// Get hover information for a debian/install file at the given cursor position.
+pub fn get_hover(src: Source<'_>, position: Position) -> Option<Hover> {
+    let (line, offset) = line_at(src, position)?;
+    if let Some(hover) = substitution_hover(line, offset) {
+        return Some(hover);
+    }
+
+    let parsed = parse_line(line);
+    let (index, _) = word_at(&parsed.words, offset)?;
+    let is_destination = parsed.words.len() > 1 && index == parsed.words.len() - 1;
+    let text = if is_destination {
+        "**Destination**\n\nDirectory inside the package to install the sources into."
+    } else {
+        "**Source**\n\nFile, directory or glob to install, relative to the top of the \
+         source tree. Paths not found there are looked up in `debian/tmp`."
+    };
+    Some(markdown(text.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+    use tower_lsp_server::ls_types::HoverContents;
+
+    fn hover(text: &str, line: u32, col: u32) -> Option<String> {
+        let idx = LineIndex::new(text);
+        let src = Source::new(text, &idx);
+        get_hover(src, Position::new(line, col)).map(|h| match h.contents {
+            HoverContents::Markup(m) => m.value,
+            _ => panic!("expected markup"),
+        })
+    }
+
+    #[test]
+    fn single_token_is_a_source() {
+        assert!(hover("usr/bin/foo\n", 0, 3)
+            .unwrap()
+            .starts_with("**Source**"));
+    }
+
+    #[test]
+    fn last_of_several_tokens_is_the_destination() {
+        let text = "foo.conf bar.conf etc/foo\n";
+        assert!(hover(text, 0, 12).unwrap().starts_with("**Source**"));
+        assert!(hover(text, 0, 20).unwrap().starts_with("**Destination**"));
+    }
+
+    #[test]
+    fn substitution_takes_precedence() {
+        let text = "usr/lib/${DEB_HOST_MULTIARCH}/libfoo.so.*\n";
+        assert!(hover(text, 0, 12)
+            .unwrap()
+            .starts_with("**${DEB_HOST_MULTIARCH}**"));
+    }
+
+    #[test]
+    fn nothing_on_whitespace_or_comment() {
+        assert_eq!(hover("foo   bar\n", 0, 4), None);
+        assert_eq!(hover("# foo\n", 0, 3), None);
+    }
+}
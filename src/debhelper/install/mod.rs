@@ -0,0 +1,9 @@
+//! Support for debian/install and debian/<package>.install files.
+
+pub mod completion;
+pub mod detection;
+pub mod hover;
+
+pub use completion::get_completions;
+pub use detection::is_install_file;
+pub use hover::get_hover;
@@ -0,0 +1,39 @@
+use std::collections::HashSet;
+
+use tower_lsp_server::ls_types::{CompletionItem, Position};
+
+use crate::debhelper::completion::{self, dir_items};
+
+/// Completions for a debian/links file at the given cursor position.
+///
+/// Both the link target and the link name are paths inside the package, so
+/// every token completes against the common install locations.
+pub fn get_completions(text: &str, position: Position) -> Vec<CompletionItem> {
+    completion::get_completions(text, position, |_, prefix| {
+        dir_items(prefix, &HashSet::new())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completes_the_target() {
+        let items = get_completions("usr/sh", Position::new(0, 6));
+        assert!(items.iter().any(|i| i.label == "usr/share/"));
+        assert!(items.iter().all(|i| i.label.starts_with("usr/sh")));
+    }
+
+    #[test]
+    fn completes_the_link_name() {
+        let items = get_completions("usr/share/foo/foo.1 usr/share/man/", Position::new(0, 34));
+        assert!(items.iter().any(|i| i.label == "usr/share/man/man1/"));
+    }
+
+    #[test]
+    fn dollar_offers_substitution_vars() {
+        let items = get_completions("usr/lib/$", Position::new(0, 9));
+        assert!(items.iter().any(|i| i.label == "${DEB_HOST_MULTIARCH}"));
+    }
+}
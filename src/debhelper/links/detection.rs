@@ -0,0 +1,31 @@
+use tower_lsp_server::ls_types::Uri;
+
+use crate::debhelper::detection::is_debhelper_file;
+
+/// Whether the URI is a debian/links, debian/<package>.links or
+/// debian/<package>.symlinks file.
+pub fn is_links_file(uri: &Uri) -> bool {
+    is_debhelper_file(uri, "links") || is_debhelper_file(uri, "symlinks")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri(s: &str) -> Uri {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn detects_qualified_and_unqualified() {
+        assert!(is_links_file(&uri("file:///p/debian/links")));
+        assert!(is_links_file(&uri("file:///p/debian/mypkg.links")));
+        assert!(is_links_file(&uri("file:///p/debian/mypkg.symlinks")));
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(!is_links_file(&uri("file:///p/debian/control")));
+        assert!(!is_links_file(&uri("file:///p/debian/links.bak")));
+    }
+}
@@ -0,0 +1,104 @@
+use tower_lsp_server::ls_types::{Diagnostic, DiagnosticSeverity};
+
+use crate::debhelper::diagnostics::{common_diagnostics, make_diagnostic};
+use crate::debhelper::parser::parse_line;
+use crate::position::Source;
+
+/// Get all LSP diagnostics for a debian/links file.
+///
+/// Each line holds pairs of a link target followed by the link name. A
+/// trailing target without a name and a link pointing at itself are errors.
+pub fn get_diagnostics(src: Source<'_>) -> Vec<Diagnostic> {
+    let mut diagnostics = common_diagnostics(src);
+
+    for (line_num, line) in src.text.lines().enumerate() {
+        let parsed = parse_line(line);
+        for pair in parsed.words.chunks(2) {
+            match pair {
+                [target] => diagnostics.push(make_diagnostic(
+                    line_num,
+                    line,
+                    target.range.clone(),
+                    DiagnosticSeverity::ERROR,
+                    "missing-link-name",
+                    format!(
+                        "Link target '{}' has no link name",
+                        &line[target.range.clone()]
+                    ),
+                )),
+                [target, name] => {
+                    let target_path = line[target.range.clone()].trim_start_matches('/');
+                    let name_path = line[name.range.clone()].trim_start_matches('/');
+                    if target_path == name_path {
+                        diagnostics.push(make_diagnostic(
+                            line_num,
+                            line,
+                            target.range.start..name.range.end,
+                            DiagnosticSeverity::ERROR,
+                            "link-to-itself",
+                            format!("Link '{}' points to itself", name_path),
+                        ));
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+    use tower_lsp_server::ls_types::{NumberOrString, Position, Range};
+
+    fn diagnostics(text: &str) -> Vec<Diagnostic> {
+        let idx = LineIndex::new(text);
+        get_diagnostics(Source::new(text, &idx))
+    }
+
+    fn code(d: &Diagnostic) -> &str {
+        match &d.code {
+            Some(NumberOrString::String(s)) => s,
+            _ => "",
+        }
+    }
+
+    #[test]
+    fn valid_links_have_no_diagnostics() {
+        let text = "usr/share/foo/foo.sh usr/bin/foo\n# a comment\n\n";
+        assert_eq!(diagnostics(text), vec![]);
+    }
+
+    #[test]
+    fn flags_a_link_to_itself() {
+        let diags = diagnostics("usr/bin/foo /usr/bin/foo\n");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(code(&diags[0]), "link-to-itself");
+        assert_eq!(
+            diags[0].range,
+            Range::new(Position::new(0, 0), Position::new(0, 24))
+        );
+    }
+
+    #[test]
+    fn flags_a_missing_link_name() {
+        let diags = diagnostics("usr/share/foo/a usr/bin/a usr/share/foo/b\n");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(code(&diags[0]), "missing-link-name");
+        assert_eq!(
+            diags[0].range,
+            Range::new(Position::new(0, 26), Position::new(0, 41))
+        );
+    }
+
+    #[test]
+    fn includes_common_diagnostics() {
+        let diags = diagnostics("a b\na b\n");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(code(&diags[0]), "duplicate-entry");
+    }
+}
@@ -0,0 +1,52 @@
+use tower_lsp_server::ls_types::{Hover, Position};
+
+use crate::debhelper::hover::{line_at, markdown, substitution_hover, word_at};
+use crate::debhelper::parser::parse_line;
+use crate::position::Source;
+
+/// Get hover information for a debian/links file at the given cursor position.
+pub fn get_hover(src: Source<'_>, position: Position) -> Option<Hover> {
+    let (line, offset) = line_at(src, position)?;
+    if let Some(hover) = substitution_hover(line, offset) {
+        return Some(hover);
+    }
+
+    let parsed = parse_line(line);
+    let (index, _) = word_at(&parsed.words, offset)?;
+    let text = if index % 2 == 0 {
+        "**Link target**\n\nExisting path inside the package that the link points to."
+    } else {
+        "**Link name**\n\nPath of the symbolic link to create. dh_link makes the link \
+         relative as Debian policy requires."
+    };
+    Some(markdown(text.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+    use tower_lsp_server::ls_types::HoverContents;
+
+    fn hover(text: &str, line: u32, col: u32) -> Option<String> {
+        let idx = LineIndex::new(text);
+        let src = Source::new(text, &idx);
+        get_hover(src, Position::new(line, col)).map(|h| match h.contents {
+            HoverContents::Markup(m) => m.value,
+            _ => panic!("expected markup"),
+        })
+    }
+
+    #[test]
+    fn tokens_alternate_between_target_and_name() {
+        let text = "usr/share/foo/a usr/bin/a usr/share/foo/b usr/bin/b\n";
+        assert!(hover(text, 0, 2).unwrap().starts_with("**Link target**"));
+        assert!(hover(text, 0, 18).unwrap().starts_with("**Link name**"));
+        assert!(hover(text, 0, 30).unwrap().starts_with("**Link target**"));
+    }
+
+    #[test]
+    fn nothing_on_a_comment() {
+        assert_eq!(hover("# usr/bin/a\n", 0, 4), None);
+    }
+}
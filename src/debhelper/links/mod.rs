@@ -0,0 +1,14 @@
+//! Support for debian/links and debian/<package>.links files.
+//!
+//! `.symlinks` files use the same pairs-of-paths syntax and are handled here
+//! too.
+
+pub mod completion;
+pub mod detection;
+pub mod diagnostics;
+pub mod hover;
+
+pub use completion::get_completions;
+pub use detection::is_links_file;
+pub use diagnostics::get_diagnostics;
+pub use hover::get_hover;
@@ -0,0 +1,60 @@
+use tower_lsp_server::ls_types::{CompletionItem, CompletionItemKind, Position};
+
+use super::SUBCOMMANDS;
+use crate::debhelper::completion;
+
+/// Completions for a debian/maintscript file at the given cursor position.
+pub fn get_completions(text: &str, position: Position) -> Vec<CompletionItem> {
+    completion::get_completions(text, position, |index, prefix| {
+        if index != 0 {
+            return Vec::new();
+        }
+        SUBCOMMANDS
+            .iter()
+            .filter(|s| s.name.starts_with(prefix))
+            .map(|s| CompletionItem {
+                label: s.name.to_string(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                detail: Some(s.description.to_string()),
+                ..Default::default()
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offers_subcommands_on_an_empty_line() {
+        let items = get_completions("\n", Position::new(0, 0));
+        let labels: Vec<_> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "rm_conffile",
+                "mv_conffile",
+                "symlink_to_dir",
+                "dir_to_symlink"
+            ]
+        );
+    }
+
+    #[test]
+    fn filters_by_prefix() {
+        let items = get_completions("rm\n", Position::new(0, 2));
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].label, "rm_conffile");
+    }
+
+    #[test]
+    fn nothing_for_arguments() {
+        assert!(get_completions("rm_conffile /etc/", Position::new(0, 17)).is_empty());
+    }
+
+    #[test]
+    fn nothing_in_a_comment() {
+        assert!(get_completions("# rm", Position::new(0, 4)).is_empty());
+    }
+}
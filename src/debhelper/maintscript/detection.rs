@@ -0,0 +1,33 @@
+use tower_lsp_server::ls_types::Uri;
+
+use crate::debhelper::detection::is_debhelper_file;
+
+/// Whether the URI is a debian/maintscript or debian/<package>.maintscript file.
+pub fn is_maintscript_file(uri: &Uri) -> bool {
+    is_debhelper_file(uri, "maintscript")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri(s: &str) -> Uri {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn detects_qualified_and_unqualified() {
+        assert!(is_maintscript_file(&uri("file:///p/debian/maintscript")));
+        assert!(is_maintscript_file(&uri(
+            "file:///p/debian/mypkg.maintscript"
+        )));
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(!is_maintscript_file(&uri("file:///p/debian/control")));
+        assert!(!is_maintscript_file(&uri(
+            "file:///p/debian/mypkg.postinst"
+        )));
+    }
+}
@@ -0,0 +1,190 @@
+use tower_lsp_server::ls_types::{Diagnostic, DiagnosticSeverity};
+
+use super::{find_subcommand, OPTIONAL_ARGS};
+use crate::debhelper::diagnostics::{common_diagnostics, make_diagnostic};
+use crate::debhelper::parser::parse_line;
+use crate::position::Source;
+
+/// Get all LSP diagnostics for a debian/maintscript file.
+pub fn get_diagnostics(src: Source<'_>) -> Vec<Diagnostic> {
+    let mut diagnostics = common_diagnostics(src);
+
+    for (line_num, line) in src.text.lines().enumerate() {
+        let parsed = parse_line(line);
+        let Some((first, rest)) = parsed.words.split_first() else {
+            continue;
+        };
+        let name = &line[first.range.clone()];
+        let Some(subcommand) = find_subcommand(name) else {
+            diagnostics.push(make_diagnostic(
+                line_num,
+                line,
+                first.range.clone(),
+                DiagnosticSeverity::ERROR,
+                "unknown-subcommand",
+                format!("Unknown dpkg-maintscript-helper sub-command '{}'", name),
+            ));
+            continue;
+        };
+
+        // dh_installdeb appends `-- "$@"` itself; anything after an explicit
+        // `--` is passed through and not counted.
+        let args: Vec<_> = rest
+            .iter()
+            .take_while(|w| &line[w.range.clone()] != "--")
+            .collect();
+        let min = subcommand.args.len();
+        let max = min + OPTIONAL_ARGS.len();
+        if args.len() < min || args.len() > max {
+            let end = args.last().map_or(first.range.end, |w| w.range.end);
+            diagnostics.push(make_diagnostic(
+                line_num,
+                line,
+                first.range.start..end,
+                DiagnosticSeverity::ERROR,
+                "wrong-argument-count",
+                format!(
+                    "{} takes {} to {} arguments, got {}: {}",
+                    subcommand.name,
+                    min,
+                    max,
+                    args.len(),
+                    subcommand.synopsis()
+                ),
+            ));
+            continue;
+        }
+
+        for (word, (arg, absolute)) in args.iter().zip(subcommand.args) {
+            let value = &line[word.range.clone()];
+            if *absolute && !value.starts_with('/') {
+                diagnostics.push(make_diagnostic(
+                    line_num,
+                    line,
+                    word.range.clone(),
+                    DiagnosticSeverity::ERROR,
+                    "relative-path",
+                    format!("{} '{}' must be an absolute path", arg, value),
+                ));
+            }
+        }
+
+        if let Some(word) = args.get(min) {
+            let value = &line[word.range.clone()];
+            if value.parse::<debversion::Version>().is_err() {
+                diagnostics.push(make_diagnostic(
+                    line_num,
+                    line,
+                    word.range.clone(),
+                    DiagnosticSeverity::ERROR,
+                    "invalid-prior-version",
+                    format!("'{}' is not a valid Debian version", value),
+                ));
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+    use tower_lsp_server::ls_types::{NumberOrString, Position, Range};
+
+    fn diagnostics(text: &str) -> Vec<Diagnostic> {
+        let idx = LineIndex::new(text);
+        get_diagnostics(Source::new(text, &idx))
+    }
+
+    fn codes(text: &str) -> Vec<String> {
+        diagnostics(text)
+            .iter()
+            .map(|d| match &d.code {
+                Some(NumberOrString::String(s)) => s.clone(),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn valid_lines_have_no_diagnostics() {
+        let text = "\
+rm_conffile /etc/foo/old.conf 1.2-3~
+mv_conffile /etc/foo/a.conf /etc/foo/b.conf 1.2-3~ foo
+symlink_to_dir /usr/share/doc/foo foo-common 2.0~
+dir_to_symlink /usr/share/foo ../bar 2.0~ -- \"$@\"
+# comment
+";
+        assert_eq!(codes(text), Vec::<String>::new());
+    }
+
+    #[test]
+    fn flags_an_unknown_subcommand() {
+        let diags = diagnostics("rm_conf /etc/foo\n");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(
+            diags[0].code,
+            Some(NumberOrString::String("unknown-subcommand".to_string()))
+        );
+        assert_eq!(
+            diags[0].range,
+            Range::new(Position::new(0, 0), Position::new(0, 7))
+        );
+    }
+
+    #[test]
+    fn flags_too_few_arguments() {
+        let diags = diagnostics("mv_conffile /etc/foo\n");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(
+            diags[0].message,
+            "mv_conffile takes 2 to 4 arguments, got 1: \
+             mv_conffile old-conffile new-conffile [prior-version [package]]"
+        );
+        assert_eq!(
+            diags[0].range,
+            Range::new(Position::new(0, 0), Position::new(0, 20))
+        );
+    }
+
+    #[test]
+    fn flags_too_many_arguments() {
+        assert_eq!(
+            codes("rm_conffile /etc/foo 1.0 foo bar\n"),
+            vec!["wrong-argument-count"]
+        );
+    }
+
+    #[test]
+    fn flags_a_relative_conffile() {
+        let diags = diagnostics("rm_conffile etc/foo 1.0~\n");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(
+            diags[0].message,
+            "conffile 'etc/foo' must be an absolute path"
+        );
+        assert_eq!(
+            diags[0].range,
+            Range::new(Position::new(0, 12), Position::new(0, 19))
+        );
+    }
+
+    #[test]
+    fn relative_symlink_target_is_fine() {
+        assert_eq!(
+            codes("dir_to_symlink /usr/share/foo ../bar\n"),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn flags_an_invalid_prior_version() {
+        assert_eq!(
+            codes("rm_conffile /etc/foo not_a+version!\n"),
+            vec!["invalid-prior-version"]
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use tower_lsp_server::ls_types::{Hover, Position};
+
+use super::find_subcommand;
+use crate::debhelper::hover::{line_at, markdown, substitution_hover, word_at};
+use crate::debhelper::parser::parse_line;
+use crate::position::Source;
+
+/// Get hover information for a debian/maintscript file at the given cursor position.
+pub fn get_hover(src: Source<'_>, position: Position) -> Option<Hover> {
+    let (line, offset) = line_at(src, position)?;
+    if let Some(hover) = substitution_hover(line, offset) {
+        return Some(hover);
+    }
+
+    let parsed = parse_line(line);
+    let (index, _) = word_at(&parsed.words, offset)?;
+    let first = parsed.words.first()?;
+    let subcommand = find_subcommand(&line[first.range.clone()])?;
+
+    if index == 0 {
+        return Some(markdown(format!(
+            "**{}**\n\n{}\n\n`{}`",
+            subcommand.name,
+            subcommand.description,
+            subcommand.synopsis()
+        )));
+    }
+    let arg = subcommand.arg_name(index - 1)?;
+    Some(markdown(format!(
+        "**{}** argument of `{}`\n\n`{}`",
+        arg,
+        subcommand.name,
+        subcommand.synopsis()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+    use tower_lsp_server::ls_types::HoverContents;
+
+    fn hover(text: &str, line: u32, col: u32) -> Option<String> {
+        let idx = LineIndex::new(text);
+        let src = Source::new(text, &idx);
+        get_hover(src, Position::new(line, col)).map(|h| match h.contents {
+            HoverContents::Markup(m) => m.value,
+            _ => panic!("expected markup"),
+        })
+    }
+
+    #[test]
+    fn hovers_a_subcommand() {
+        assert_eq!(
+            hover("rm_conffile /etc/foo.conf 1.2~\n", 0, 3).unwrap(),
+            "**rm_conffile**\n\nRemove an obsolete conffile on upgrade\n\n\
+             `rm_conffile conffile [prior-version [package]]`"
+        );
+    }
+
+    #[test]
+    fn hovers_an_argument() {
+        let text = "mv_conffile /etc/old /etc/new 1.2~ foo\n";
+        assert!(hover(text, 0, 23)
+            .unwrap()
+            .starts_with("**new-conffile** argument of `mv_conffile`"));
+        assert!(hover(text, 0, 31).unwrap().starts_with("**prior-version**"));
+        assert!(hover(text, 0, 36).unwrap().starts_with("**package**"));
+    }
+
+    #[test]
+    fn nothing_for_an_unknown_subcommand() {
+        assert_eq!(hover("frobnicate /etc/foo\n", 0, 3), None);
+    }
+
+    #[test]
+    fn nothing_past_the_last_argument() {
+        assert_eq!(hover("rm_conffile /etc/a 1.0 foo extra\n", 0, 29), None);
+    }
+}
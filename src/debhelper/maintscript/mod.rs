@@ -0,0 +1,80 @@
+//! Support for debian/maintscript and debian/<package>.maintscript files.
+//!
+//! Each line is a `dpkg-maintscript-helper` invocation without the script
+//! arguments, which dh_installdeb appends when it generates the maintainer
+//! scripts.
+
+pub mod completion;
+pub mod detection;
+pub mod diagnostics;
+pub mod hover;
+pub mod semantic;
+
+pub use completion::get_completions;
+pub use detection::is_maintscript_file;
+pub use diagnostics::get_diagnostics;
+pub use hover::get_hover;
+pub use semantic::generate_semantic_tokens;
+
+/// A `dpkg-maintscript-helper` sub-command usable in a maintscript file.
+pub struct Subcommand {
+    pub name: &'static str,
+    /// The required arguments, with whether each must be an absolute path.
+    pub args: &'static [(&'static str, bool)],
+    pub description: &'static str,
+}
+
+/// Optional arguments every sub-command accepts after its required ones.
+pub const OPTIONAL_ARGS: &[&str] = &["prior-version", "package"];
+
+/// The sub-commands from dpkg-maintscript-helper(1).
+pub const SUBCOMMANDS: &[Subcommand] = &[
+    Subcommand {
+        name: "rm_conffile",
+        args: &[("conffile", true)],
+        description: "Remove an obsolete conffile on upgrade",
+    },
+    Subcommand {
+        name: "mv_conffile",
+        args: &[("old-conffile", true), ("new-conffile", true)],
+        description: "Rename a conffile, keeping local modifications",
+    },
+    Subcommand {
+        name: "symlink_to_dir",
+        args: &[("pathname", true), ("old-target", false)],
+        description: "Replace a symlink with a real directory",
+    },
+    Subcommand {
+        name: "dir_to_symlink",
+        args: &[("pathname", true), ("new-target", false)],
+        description: "Replace a real directory with a symlink",
+    },
+];
+
+/// Look up a sub-command by name.
+pub fn find_subcommand(name: &str) -> Option<&'static Subcommand> {
+    SUBCOMMANDS.iter().find(|s| s.name == name)
+}
+
+impl Subcommand {
+    /// The usage line, e.g. `rm_conffile conffile [prior-version [package]]`.
+    pub fn synopsis(&self) -> String {
+        let required: Vec<&str> = self.args.iter().map(|(name, _)| *name).collect();
+        format!(
+            "{} {} [{} [{}]]",
+            self.name,
+            required.join(" "),
+            OPTIONAL_ARGS[0],
+            OPTIONAL_ARGS[1]
+        )
+    }
+
+    /// The name of the argument at `index`, counting from the first argument
+    /// after the sub-command.
+    pub fn arg_name(&self, index: usize) -> Option<&'static str> {
+        match self.args.get(index) {
+            Some((name, _)) => Some(name),
+            None => OPTIONAL_ARGS.get(index - self.args.len()).copied(),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use tower_lsp_server::ls_types::SemanticToken;
+
+use super::find_subcommand;
+use crate::deb822::semantic::{SemanticTokensBuilder, TokenType};
+use crate::debhelper::parser::parse_line;
+use crate::position::{utf16_len, Source};
+
+/// Semantic tokens for a debian/maintscript file.
+pub fn generate_semantic_tokens(src: Source<'_>) -> Vec<SemanticToken> {
+    let mut builder = SemanticTokensBuilder::new();
+
+    for (line_num, line) in src.text.lines().enumerate() {
+        let line_num = line_num as u32;
+        let parsed = parse_line(line);
+
+        if let Some(comment) = parsed.comment {
+            push(&mut builder, line, line_num, comment, TokenType::Comment);
+            continue;
+        }
+
+        for (index, word) in parsed.words.iter().enumerate() {
+            let token_type = match index {
+                0 if find_subcommand(&line[word.range.clone()]).is_some() => TokenType::Field,
+                0 => TokenType::UnknownField,
+                _ => TokenType::Value,
+            };
+            push(&mut builder, line, line_num, word.range.clone(), token_type);
+        }
+    }
+
+    builder.build()
+}
+
+/// Push one token for a byte range inside a line, in UTF-16 columns.
+fn push(
+    builder: &mut SemanticTokensBuilder,
+    line: &str,
+    line_num: u32,
+    range: std::ops::Range<usize>,
+    token_type: TokenType,
+) {
+    let start_col = utf16_len(&line[..range.start]);
+    let length = utf16_len(&line[range]);
+    builder.push(line_num, start_col, length, token_type, 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+
+    fn types(text: &str) -> Vec<u32> {
+        let idx = LineIndex::new(text);
+        generate_semantic_tokens(Source::new(text, &idx))
+            .iter()
+            .map(|t| t.token_type)
+            .collect()
+    }
+
+    #[test]
+    fn subcommand_then_arguments() {
+        assert_eq!(
+            types("rm_conffile /etc/foo 1.0~\n"),
+            vec![
+                TokenType::Field as u32,
+                TokenType::Value as u32,
+                TokenType::Value as u32,
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_subcommand() {
+        assert_eq!(
+            types("rm_conf /etc/foo\n"),
+            vec![TokenType::UnknownField as u32, TokenType::Value as u32]
+        );
+    }
+
+    #[test]
+    fn comment() {
+        assert_eq!(types("# note\n"), vec![TokenType::Comment as u32]);
+    }
+}
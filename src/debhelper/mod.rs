@@ -2,10 +2,15 @@ pub mod actions;
 pub mod clean;
 pub mod completion;
 pub mod detection;
+pub mod diagnostics;
 pub mod dirs;
 pub mod docs;
 pub mod examples;
+pub mod hover;
 pub mod info;
+pub mod install;
+pub mod links;
+pub mod maintscript;
 pub mod manpages;
 pub mod parser;
 pub mod semantic;
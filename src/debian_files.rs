@@ -0,0 +1,144 @@
+//! Enumerating the packaging files under a `debian/` directory, for the
+//! features that look beyond the files open in the editor.
+
+use std::path::{Path, PathBuf};
+
+/// Subdirectories of `debian/` that hold packaging files. Everything else
+/// below `debian/` is build output (`debian/tmp`, `debian/<package>`) or
+/// helper state (`debian/.debhelper`) and is not scanned.
+const PACKAGING_SUBDIRS: &[&str] = &["patches", "source", "tests", "upstream"];
+
+/// The `debian/` directory of each workspace root that has one.
+pub fn debian_dirs(roots: &[PathBuf]) -> Vec<PathBuf> {
+    roots
+        .iter()
+        .map(|root| {
+            if root.file_name().and_then(|n| n.to_str()) == Some("debian") {
+                root.clone()
+            } else {
+                root.join("debian")
+            }
+        })
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// Every regular file directly in `debian_dir` or in one of its packaging
+/// subdirectories, sorted. `debian/patches` is scanned recursively since
+/// quilt allows patches in nested directories.
+///
+/// The caller decides which of these it understands, typically with
+/// `FileType::detect`.
+pub fn scan(debian_dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    collect_files(debian_dir, false, &mut files);
+    for subdir in PACKAGING_SUBDIRS {
+        collect_files(&debian_dir.join(subdir), *subdir == "patches", &mut files);
+    }
+    files.sort();
+    files
+}
+
+/// Whether `path` is one of the files [`scan`] would find in `debian_dir`.
+pub fn is_packaging_file(debian_dir: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(debian_dir) else {
+        return false;
+    };
+    let components: Vec<_> = relative.components().collect();
+    match components.as_slice() {
+        [_] => true,
+        [dir, rest @ ..] => {
+            let dir = dir.as_os_str();
+            PACKAGING_SUBDIRS.iter().any(|s| dir == *s) && (rest.len() == 1 || dir == "patches")
+        }
+        [] => false,
+    }
+}
+
+fn collect_files(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_file() {
+            files.push(path);
+        } else if file_type.is_dir() && recursive {
+            collect_files(&path, true, files);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(path: &Path) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+
+    #[test]
+    fn scans_packaging_files_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let debian = dir.path().join("debian");
+        for file in [
+            "control",
+            "foo.install",
+            "source/format",
+            "tests/control",
+            "patches/series",
+            "patches/upstream/fix.patch",
+            "tmp/usr/bin/foo",
+            "foo/DEBIAN/postinst",
+            ".debhelper/generated/foo/installed-by-dh_install",
+        ] {
+            touch(&debian.join(file));
+        }
+
+        let found: Vec<_> = scan(&debian)
+            .into_iter()
+            .map(|p| p.strip_prefix(&debian).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            found,
+            [
+                "control",
+                "foo.install",
+                "patches/series",
+                "patches/upstream/fix.patch",
+                "source/format",
+                "tests/control",
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn recognises_packaging_paths() {
+        let debian = Path::new("/src/foo/debian");
+        assert!(is_packaging_file(debian, &debian.join("control")));
+        assert!(is_packaging_file(debian, &debian.join("source/format")));
+        assert!(is_packaging_file(debian, &debian.join("patches/a/b.patch")));
+        assert!(!is_packaging_file(debian, &debian.join("tmp/usr/bin/foo")));
+        assert!(!is_packaging_file(debian, &debian.join("tests/data/x")));
+        assert!(!is_packaging_file(debian, Path::new("/src/foo/README")));
+    }
+
+    #[test]
+    fn finds_the_debian_dir_of_each_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let with = dir.path().join("with");
+        let without = dir.path().join("without");
+        std::fs::create_dir_all(with.join("debian")).unwrap();
+        std::fs::create_dir_all(&without).unwrap();
+
+        assert_eq!(
+            debian_dirs(&[with.clone(), without, with.join("debian")]),
+            vec![with.join("debian"), with.join("debian")]
+        );
+    }
+}
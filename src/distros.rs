@@ -97,7 +97,6 @@ fn resolve_suites(debian_info: &DebianDistroInfo, date: NaiveDate) -> SuiteResol
 }
 
 /// Whether distro-info-data is available on this system.
-#[cfg(test)]
 pub fn has_distro_info() -> bool {
     cached().debian_info.is_some()
 }
@@ -218,6 +217,20 @@ pub fn get_distribution_detail(distribution: &str) -> Option<String> {
     }
 }
 
+/// The codename and version number of the current stable release, e.g.
+/// `("trixie", "13")`, as used for naming backports.
+pub fn get_stable_release() -> Option<(String, String)> {
+    let debian_info = cached().debian_info.as_ref()?;
+    let today = chrono::Local::now().date_naive();
+    let stable = resolve_suites(debian_info, today).stable?;
+    let version = debian_info
+        .iter()
+        .find(|r| *r.series() == stable)?
+        .version()
+        .clone()?;
+    Some((stable, version))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -407,6 +420,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_stable_release() {
+        if let Some((codename, version)) = get_stable_release() {
+            assert_eq!(
+                get_distribution_mapping(&codename).as_deref(),
+                Some("stable")
+            );
+            assert!(version.chars().all(|c| c.is_ascii_digit()), "{}", version);
+        }
+    }
+
     #[test]
     fn test_unreleased_has_no_detail() {
         assert_eq!(get_distribution_detail("UNRELEASED"), None);
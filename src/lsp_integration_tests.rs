@@ -1,5 +1,4 @@
 use super::*;
-#[cfg(feature = "lintian-brush")]
 use futures::StreamExt;
 use serde_json::json;
 use tower_lsp_server::jsonrpc::Request;
@@ -15,7 +14,7 @@ async fn setup_server() -> (LspService<Backend>, tower_lsp_server::ClientSocket)
     let vcswatch_cache = vcswatch::new_shared_vcswatch_cache(udd_pool.clone());
     let popcon_cache = popcon::new_shared_popcon_cache(udd_pool.clone());
     let maintainer_cache = maintainers::new_shared_maintainer_cache(udd_pool.clone());
-    let rdeps_cache = rdeps::new_shared_rdeps_cache(udd_pool);
+    let rdeps_cache = rdeps::new_shared_rdeps_cache(udd_pool.clone());
 
     let (service, socket) = LspService::new(|client| Backend {
         client,
@@ -29,6 +28,7 @@ async fn setup_server() -> (LspService<Backend>, tower_lsp_server::ClientSocket)
         vcswatch_cache,
         popcon_cache,
         rdeps_cache,
+        udd_pool,
         git_file_cache: copyright::code_lens::new_shared_git_file_cache(),
         lintian_tag_cache: Arc::new(tokio::sync::RwLock::new(
             lintian_overrides::LintianTagCache::new(),
@@ -37,6 +37,12 @@ async fn setup_server() -> (LspService<Backend>, tower_lsp_server::ClientSocket)
         #[cfg(feature = "multiarch-hints")]
         multiarch_hints_store: multiarch_hints::hints::HintsStore::default(),
         settings: Arc::new(Mutex::new(Settings::default())),
+        settings_generation: Default::default(),
+        data_generation: Default::default(),
+        workspace_roots: Default::default(),
+        pull_diagnostics: Default::default(),
+        watch_files: Default::default(),
+        pull_configuration: Default::default(),
     });
     (service, socket)
 }
@@ -552,3 +558,460 @@ async fn test_overrides_change_refreshes_sibling_changelog() {
         "un-overridden diagnostic should carry no tags"
     );
 }
+
+#[tokio::test]
+async fn test_pull_diagnostics_for_unopened_files() {
+    let temp = tempfile::tempdir().unwrap();
+    let debian_dir = temp.path().join("debian");
+    std::fs::create_dir(&debian_dir).unwrap();
+    std::fs::write(debian_dir.join("conffiles"), "etc/foo.conf\n").unwrap();
+    std::fs::write(debian_dir.join("not-a-packaging-file"), "").unwrap();
+    let conffiles_uri = Uri::from_file_path(debian_dir.join("conffiles")).unwrap();
+    let root_uri = Uri::from_file_path(temp.path()).unwrap();
+
+    let (mut service, _) = setup_server().await;
+
+    let response = service
+        .call(
+            serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "rootUri": root_uri.as_str(),
+                    "capabilities": { "textDocument": { "diagnostic": {} } }
+                }
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    let res = serde_json::to_value(response.unwrap()).unwrap();
+    assert_eq!(
+        res["result"]["capabilities"]["diagnosticProvider"]["workspaceDiagnostics"],
+        true
+    );
+
+    let workspace_pull = |id: i64, previous: serde_json::Value| -> Request {
+        serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "workspace/diagnostic",
+            "params": { "previousResultIds": previous }
+        }))
+        .unwrap()
+    };
+
+    let response = service.call(workspace_pull(2, json!([]))).await.unwrap();
+    let res = serde_json::to_value(response.unwrap()).unwrap();
+    let items = res["result"]["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["uri"], conffiles_uri.as_str());
+    assert_eq!(items[0]["kind"], "full");
+    assert_eq!(items[0]["items"][0]["code"], "relative-path");
+    let result_id = items[0]["resultId"].clone();
+
+    // Nothing changed on disk: the client's copy is still current.
+    let response = service
+        .call(workspace_pull(
+            3,
+            json!([{ "uri": conffiles_uri.as_str(), "value": result_id }]),
+        ))
+        .await
+        .unwrap();
+    let res = serde_json::to_value(response.unwrap()).unwrap();
+    assert_eq!(res["result"]["items"][0]["kind"], "unchanged");
+
+    // A document pull sees the file change on disk.
+    std::fs::write(debian_dir.join("conffiles"), "/etc/foo.conf\n").unwrap();
+    let response = service
+        .call(
+            serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "id": 4,
+                "method": "textDocument/diagnostic",
+                "params": {
+                    "textDocument": { "uri": conffiles_uri.as_str() },
+                    "previousResultId": result_id
+                }
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    let res = serde_json::to_value(response.unwrap()).unwrap();
+    assert_eq!(res["result"]["kind"], "full");
+    assert_eq!(res["result"]["items"], json!([]));
+    assert_ne!(res["result"]["resultId"], result_id);
+}
+
+#[tokio::test]
+async fn test_pull_diagnostics_after_sibling_change() {
+    let temp = tempfile::tempdir().unwrap();
+    let debian_dir = temp.path().join("debian");
+    std::fs::create_dir(&debian_dir).unwrap();
+    std::fs::write(debian_dir.join("conffiles"), "/etc/foo.conf\n").unwrap();
+    let conffiles_uri = Uri::from_file_path(debian_dir.join("conffiles")).unwrap();
+    let control_uri = Uri::from_file_path(debian_dir.join("control")).unwrap();
+
+    let (mut service, _) = setup_server().await;
+    service
+        .call(
+            serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "capabilities": { "textDocument": { "diagnostic": {} } }
+                }
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let pull = |id: i64, previous: &serde_json::Value| -> Request {
+        serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/diagnostic",
+            "params": {
+                "textDocument": { "uri": conffiles_uri.as_str() },
+                "previousResultId": previous
+            }
+        }))
+        .unwrap()
+    };
+
+    let response = service.call(pull(2, &json!(null))).await.unwrap();
+    let res = serde_json::to_value(response.unwrap()).unwrap();
+    assert_eq!(res["result"]["kind"], "full");
+    let result_id = res["result"]["resultId"].clone();
+
+    let response = service.call(pull(3, &result_id)).await.unwrap();
+    let res = serde_json::to_value(response.unwrap()).unwrap();
+    assert_eq!(res["result"]["kind"], "unchanged");
+
+    // Opening another file of the package may change what is reported
+    // for conffiles, so its diagnostics are no longer current.
+    service
+        .call(
+            serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didOpen",
+                "params": {
+                    "textDocument": {
+                        "uri": control_uri.as_str(),
+                        "languageId": "debian-control",
+                        "version": 1,
+                        "text": "Source: foo\n\nPackage: foo\n"
+                    }
+                }
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let response = service.call(pull(4, &result_id)).await.unwrap();
+    let res = serde_json::to_value(response.unwrap()).unwrap();
+    assert_eq!(res["result"]["kind"], "full");
+    assert_ne!(res["result"]["resultId"], result_id);
+    let result_id = res["result"]["resultId"].clone();
+
+    // So may a change of settings.
+    service
+        .call(
+            serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "method": "workspace/didChangeConfiguration",
+                "params": { "settings": { "debian": { "spellcheck": false } } }
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let response = service.call(pull(5, &result_id)).await.unwrap();
+    let res = serde_json::to_value(response.unwrap()).unwrap();
+    assert_eq!(res["result"]["kind"], "full");
+    assert_ne!(res["result"]["resultId"], result_id);
+    let result_id = res["result"]["resultId"].clone();
+
+    // And so may background data arriving, like the package list.
+    service.inner().data_generation.bump();
+
+    let response = service.call(pull(6, &result_id)).await.unwrap();
+    let res = serde_json::to_value(response.unwrap()).unwrap();
+    assert_eq!(res["result"]["kind"], "full");
+    assert_ne!(res["result"]["resultId"], result_id);
+}
+
+#[tokio::test]
+async fn test_references_include_unopened_files() {
+    let temp = tempfile::tempdir().unwrap();
+    let debian_dir = temp.path().join("debian");
+    std::fs::create_dir_all(debian_dir.join("tests")).unwrap();
+    let control = "Source: foo\nMaintainer: Alice <alice@example.com>\n\nPackage: foo\nArchitecture: all\nDescription: test\n";
+    std::fs::write(debian_dir.join("control"), control).unwrap();
+    std::fs::write(debian_dir.join("foo.install"), "usr/bin\n").unwrap();
+    std::fs::write(debian_dir.join("foo.lintian-overrides"), "foo: some-tag\n").unwrap();
+    std::fs::write(
+        debian_dir.join("tests/control"),
+        "Tests: smoke\nDepends: foo\n",
+    )
+    .unwrap();
+    let control_uri = Uri::from_file_path(debian_dir.join("control")).unwrap();
+    let root_uri = Uri::from_file_path(temp.path()).unwrap();
+
+    let (mut service, mut socket) = setup_server().await;
+    tokio::spawn(async move { while socket.next().await.is_some() {} });
+
+    let notify = |method: &str, params: serde_json::Value| -> Request {
+        serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params
+        }))
+        .unwrap()
+    };
+
+    service
+        .call(
+            serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "rootUri": root_uri.as_str(), "capabilities": {} }
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    service
+        .call(notify("initialized", json!({})))
+        .await
+        .unwrap();
+    service
+        .call(notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": control_uri.as_str(),
+                    "languageId": "debcontrol",
+                    "version": 1,
+                    "text": control
+                }
+            }),
+        ))
+        .await
+        .unwrap();
+
+    let response = service
+        .call(
+            serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "textDocument/references",
+                "params": {
+                    "textDocument": { "uri": control_uri.as_str() },
+                    "position": { "line": 3, "character": 10 },
+                    "context": { "includeDeclaration": false }
+                }
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    let res = serde_json::to_value(response.unwrap()).unwrap();
+    let uris: Vec<String> = res["result"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| l["uri"].as_str().unwrap().to_string())
+        .collect();
+    // The overrides file refers to the package by its name and in its
+    // `foo:` package spec.
+    let expected: Vec<String> = [
+        "foo.install",
+        "foo.lintian-overrides",
+        "foo.lintian-overrides",
+        "tests/control",
+    ]
+    .iter()
+    .map(|f| {
+        Uri::from_file_path(debian_dir.join(f))
+            .unwrap()
+            .as_str()
+            .to_string()
+    })
+    .collect();
+    assert_eq!(uris, expected);
+}
+
+#[tokio::test]
+async fn test_did_change_configuration_applies_settings() {
+    let temp = tempfile::tempdir().unwrap();
+    let changelog_path = temp.path().join("debian").join("changelog");
+    let changelog_uri = Uri::from_file_path(&changelog_path).unwrap();
+
+    let (mut service, mut socket) = setup_server().await;
+    tokio::spawn(async move { while socket.next().await.is_some() {} });
+
+    service
+        .call(
+            serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "capabilities": {},
+                    "initializationOptions": { "udd": false, "launchpad": false }
+                }
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert!(!service.inner().settings.lock().await.udd);
+
+    service
+        .call(
+            serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didOpen",
+                "params": {
+                    "textDocument": {
+                        "uri": changelog_uri.as_str(),
+                        "languageId": "debian-changelog",
+                        "version": 1,
+                        "text": "foo (1.0-1) UNRELEASED; urgency=medium\n\n  * Initial release.\n\n -- Test <test@example.com>  Mon, 01 Jan 2024 12:00:00 +0000\n"
+                    }
+                }
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let inlay_hints = |id: i64| -> Request {
+        serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/inlayHint",
+            "params": {
+                "textDocument": { "uri": changelog_uri.as_str() },
+                "range": {
+                    "start": { "line": 0, "character": 0 },
+                    "end": { "line": 5, "character": 0 }
+                }
+            }
+        }))
+        .unwrap()
+    };
+
+    let response = service.call(inlay_hints(2)).await.unwrap();
+    let res = serde_json::to_value(response.unwrap()).unwrap();
+    assert_eq!(res["result"].as_array().unwrap().len(), 1);
+
+    service
+        .call(
+            serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "method": "workspace/didChangeConfiguration",
+                "params": {
+                    "settings": {
+                        "debian": { "udd": false, "inlayHints": { "distributions": false } }
+                    }
+                }
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let settings = service.inner().settings.lock().await.clone();
+    assert!(!settings.inlay_hints.distributions);
+    // Settings missing from the notification keep their current values.
+    assert!(!settings.launchpad);
+
+    let response = service.call(inlay_hints(3)).await.unwrap();
+    let res = serde_json::to_value(response.unwrap()).unwrap();
+    assert_eq!(res["result"], serde_json::Value::Null);
+
+    // An empty notification leaves the initialization options in place.
+    service
+        .call(
+            serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "method": "workspace/didChangeConfiguration",
+                "params": { "settings": {} }
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(*service.inner().settings.lock().await, settings);
+}
+
+#[tokio::test]
+async fn test_pulled_configuration_replaces_settings() {
+    let (mut service, socket) = setup_server().await;
+
+    // Answer `workspace/configuration` with whatever the user's
+    // configuration currently is, and any other request with null.
+    let configuration = Arc::new(std::sync::Mutex::new(json!({ "udd": false })));
+    let answer = configuration.clone();
+    let (mut from_server, mut to_server) = socket.split();
+    tokio::spawn(async move {
+        while let Some(request) = from_server.next().await {
+            let Some(id) = request.id().cloned() else {
+                continue;
+            };
+            let result = if request.method() == "workspace/configuration" {
+                json!([answer.lock().unwrap().clone()])
+            } else {
+                serde_json::Value::Null
+            };
+            let response = tower_lsp_server::jsonrpc::Response::from_ok(id, result);
+            if futures::SinkExt::send(&mut to_server, response)
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    service
+        .call(
+            serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "capabilities": { "workspace": { "configuration": true } }
+                }
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let did_change_configuration = || -> Request {
+        serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "method": "workspace/didChangeConfiguration",
+            "params": { "settings": null }
+        }))
+        .unwrap()
+    };
+
+    service.call(did_change_configuration()).await.unwrap();
+    assert!(!service.inner().settings.lock().await.udd);
+
+    // The user removes the key again: it returns to its default.
+    *configuration.lock().unwrap() = json!({});
+    service.call(did_change_configuration()).await.unwrap();
+    assert_eq!(*service.inner().settings.lock().await, Settings::default());
+}
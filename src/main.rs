@@ -3,6 +3,7 @@
 #![deny(missing_docs)]
 #![deny(unsafe_code)]
 
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tower_lsp_server::jsonrpc::Result;
@@ -10,28 +11,7 @@ use tower_lsp_server::ls_types::*;
 use tower_lsp_server::{Client, LanguageServer, LspService, Server};
 
 use clap::{Parser, Subcommand};
-
-/// Server settings received from the client via initializationOptions.
-#[derive(Debug, Clone, serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-#[serde(default)]
-struct Settings {
-    /// Allow the upstream-ontologist to make network requests when guessing
-    /// upstream metadata values. Defaults to `false`.
-    upstream_ontologist_net_access: bool,
-    /// Surface lintian-brush issues that are suppressed by a lintian
-    /// override as faded, informational diagnostics. Defaults to `true`.
-    show_overridden_issues: bool,
-}
-
-impl Default for Settings {
-    fn default() -> Self {
-        Self {
-            upstream_ontologist_net_access: false,
-            show_overridden_issues: true,
-        }
-    }
-}
+use settings::Settings;
 
 mod architecture;
 mod bugs;
@@ -43,15 +23,18 @@ mod cve;
 mod deb822;
 mod debcargo;
 mod debhelper;
+mod debian_files;
 #[cfg(any(feature = "lintian-brush", feature = "multiarch-hints"))]
 mod debian_workspace;
 mod dep3;
+mod disk_cache;
 mod distros;
 mod ghsa;
 mod links;
 #[cfg(feature = "lintian-brush")]
 mod lintian_brush;
 mod lintian_overrides;
+mod maintainer_scripts;
 mod maintainers;
 #[cfg(feature = "multiarch-hints")]
 mod multiarch_hints;
@@ -64,18 +47,22 @@ mod rdeps;
 mod rules;
 #[cfg(feature = "scip")]
 mod scip;
+mod settings;
 mod source_format;
 mod source_options;
 mod source_scan;
 #[cfg(feature = "spellcheck")]
 mod spelling;
+mod templates;
 mod tests;
+mod text;
 mod triggers;
 mod udd;
 mod upstream_metadata;
 mod vcswatch;
 mod watch;
 mod workspace;
+mod workspace_symbols;
 
 #[cfg(test)]
 mod lsp_integration_tests;
@@ -148,6 +135,16 @@ enum FileType {
     Info,
     /// debian/manpages or debian/<package>.manpages file
     Manpages,
+    /// debian/install or debian/<package>.install file
+    Install,
+    /// debian/links, debian/<package>.links or debian/<package>.symlinks file
+    Links,
+    /// debian/maintscript or debian/<package>.maintscript file
+    Maintscript,
+    /// Maintainer script (debian/postinst, debian/<package>.prerm, ...)
+    MaintainerScript,
+    /// debian/templates or debian/<package>.templates file (debconf)
+    Templates,
 }
 
 impl FileType {
@@ -195,6 +192,16 @@ impl FileType {
             Some(Self::Info)
         } else if debhelper::manpages::is_manpages_file(uri) {
             Some(Self::Manpages)
+        } else if debhelper::install::is_install_file(uri) {
+            Some(Self::Install)
+        } else if debhelper::links::is_links_file(uri) {
+            Some(Self::Links)
+        } else if debhelper::maintscript::is_maintscript_file(uri) {
+            Some(Self::Maintscript)
+        } else if maintainer_scripts::is_maintainer_script_file(uri) {
+            Some(Self::MaintainerScript)
+        } else if templates::is_templates_file(uri) {
+            Some(Self::Templates)
         } else {
             None
         }
@@ -284,6 +291,34 @@ fn build_fix_all_action(
     }))
 }
 
+/// Background data that diagnostics depend on but that arrives after the
+/// files are opened: the package list, package providers, and bug data.
+#[derive(Default)]
+struct DataGeneration {
+    generation: AtomicU64,
+    changed: tokio::sync::Notify,
+}
+
+impl DataGeneration {
+    /// Record that new data arrived, waking the task that brings the
+    /// diagnostics up to date.
+    fn bump(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        self.changed.notify_one();
+    }
+
+    /// The number of times data arrived so far.
+    fn get(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    /// Wait until data arrives. Arrivals while nobody waits are coalesced.
+    async fn changed(&self) {
+        self.changed.notified().await;
+    }
+}
+
+#[derive(Clone)]
 struct Backend {
     client: Client,
     workspace: Arc<Mutex<Workspace>>,
@@ -296,12 +331,30 @@ struct Backend {
     vcswatch_cache: vcswatch::SharedVcsWatchCache,
     popcon_cache: popcon::SharedPopconCache,
     rdeps_cache: rdeps::SharedRdepsCache,
+    /// The UDD connection shared by the caches above.
+    udd_pool: udd::SharedPool,
     git_file_cache: copyright::code_lens::SharedGitFileCache,
     lintian_tag_cache: lintian_overrides::SharedLintianTagCache,
     upstream_cache: upstream_metadata::SharedUpstreamCache,
     #[cfg(feature = "multiarch-hints")]
     multiarch_hints_store: multiarch_hints::hints::HintsStore,
     settings: Arc<Mutex<Settings>>,
+    /// Root folders of the workspace, as sent in `initialize`.
+    workspace_roots: Arc<Mutex<Vec<std::path::PathBuf>>>,
+    /// Bumped whenever the settings change, so that diagnostics pulled
+    /// under the old settings are no longer reported as unchanged.
+    settings_generation: Arc<AtomicU64>,
+    /// Bumped whenever background data used by diagnostics arrives, for
+    /// the same reason.
+    data_generation: Arc<DataGeneration>,
+    /// Whether the client pulls diagnostics with `textDocument/diagnostic`,
+    /// in which case they are not also pushed.
+    pull_diagnostics: Arc<AtomicBool>,
+    /// Whether the client lets us register `workspace/didChangeWatchedFiles`
+    /// watchers dynamically.
+    watch_files: Arc<AtomicBool>,
+    /// Whether the client answers `workspace/configuration` requests.
+    pull_configuration: Arc<AtomicBool>,
 }
 
 use phase::RunPhase;
@@ -326,20 +379,94 @@ impl Backend {
         uri: Uri,
         source_file: workspace::SourceFile,
         file_type: FileType,
-        workspace: Workspace,
+        mut workspace: Workspace,
         open_files: HashMap<Uri, FileInfo>,
         phase: RunPhase,
         changed_ranges: Option<Vec<rowan::TextRange>>,
-        show_overridden: bool,
+        settings: &Settings,
+        package_cache: Option<package_cache::SharedPackageCache>,
+        architecture_list: Option<architecture::SharedArchitectureList>,
+        git_file_cache: Option<copyright::code_lens::SharedGitFileCache>,
+        bug_cache: Option<bugs::SharedBugCache>,
         #[cfg(feature = "multiarch-hints")] multiarch_hints_store: Option<
             multiarch_hints::hints::HintsStore,
         >,
     ) -> tower_lsp_server::jsonrpc::Result<Option<Vec<Diagnostic>>> {
-        #[cfg_attr(not(feature = "spellcheck"), allow(unused_mut))]
-        let mut builtin = Self::builtin_diagnostics(&uri, source_file, file_type, &workspace);
+        let rules_context = if file_type == FileType::Rules {
+            Self::rules_context(&uri, &open_files, &mut workspace).await
+        } else {
+            Default::default()
+        };
+        let mut builtin =
+            Self::builtin_diagnostics(&uri, source_file, file_type, &workspace, &rules_context);
+
+        if file_type == FileType::Control {
+            let packages = match &package_cache {
+                Some(cache) => Some(cache.read().await),
+                None => None,
+            };
+            let architectures = match &architecture_list {
+                Some(list) => Some(list.read().await),
+                None => None,
+            };
+            let lookup = control::relation_diagnostics::RelationLookup {
+                packages: packages.as_deref(),
+                architectures: architectures.as_deref().map_or(&[], |a| a.as_slice()),
+            };
+            let source_text = workspace.source_text(source_file);
+            let idx = workspace.get_line_index(source_file);
+            let src = Source::new(&source_text, &idx);
+            let parsed = workspace.get_parsed_control(source_file);
+            let relation_diags =
+                control::relation_diagnostics::get_relation_diagnostics(src, &parsed, lookup);
+            builtin.get_or_insert_with(Vec::new).extend(relation_diags);
+        }
+
+        // Coverage of the source tree by the Files paragraphs, checked
+        // against the git-tracked files of the source root
+        // (debian/copyright -> debian/ -> source root).
+        if let (FileType::Copyright, Some(cache)) = (file_type, &git_file_cache) {
+            let source_root = uri.to_file_path().and_then(|p| {
+                p.parent()
+                    .and_then(|debian| debian.parent())
+                    .map(|root| root.to_path_buf())
+            });
+            if let Some(root) = source_root {
+                if let Some(files) = copyright::code_lens::tracked_files(cache, &root).await {
+                    let source_text = workspace.source_text(source_file);
+                    let idx = workspace.get_line_index(source_file);
+                    let src = Source::new(&source_text, &idx);
+                    let parsed = workspace.get_parsed_copyright(source_file);
+                    let coverage_diags =
+                        copyright::coverage::coverage_diagnostics(&parsed, src, &files);
+                    builtin.get_or_insert_with(Vec::new).extend(coverage_diags);
+                }
+            }
+        }
+
+        // Bugs closed by the entries being prepared, checked against the
+        // bug data of the package. UDD is only queried on explicit runs (the
+        // `check` and `scip` commands); otherwise whatever the background
+        // prefetch has cached is used.
+        if let (FileType::Changelog, Some(cache), true) = (file_type, &bug_cache, settings.udd) {
+            let pending = {
+                let parsed = workspace.get_parsed_changelog(source_file);
+                changelog::closes::pending_bugs(&parsed.tree())
+            };
+            if let Some((package, ids)) = pending {
+                let fetch = phase == RunPhase::Explicit;
+                let known = changelog::closes::KnownBugs::load(cache, &package, &ids, fetch).await;
+                let source_text = workspace.source_text(source_file);
+                let idx = workspace.get_line_index(source_file);
+                let src = Source::new(&source_text, &idx);
+                let parsed = workspace.get_parsed_changelog(source_file);
+                let closes_diags = changelog::closes::get_diagnostics(&parsed, src, &known);
+                builtin.get_or_insert_with(Vec::new).extend(closes_diags);
+            }
+        }
 
         #[cfg(feature = "spellcheck")]
-        {
+        if settings.spellcheck {
             let source_text = workspace.source_text(source_file);
             let idx = workspace.get_line_index(source_file);
             let src = Source::new(&source_text, &idx);
@@ -356,41 +483,42 @@ impl Backend {
         }
 
         #[cfg(feature = "lintian-brush")]
-        let lb = Self::lintian_brush_diagnostics(
-            uri.clone(),
-            file_type,
-            workspace.clone(),
-            open_files.clone(),
-            phase,
-            changed_ranges.clone(),
-            show_overridden,
-        )
-        .await?;
+        let lb = if settings.lintian_brush {
+            Self::lintian_brush_diagnostics(
+                uri.clone(),
+                file_type,
+                workspace.clone(),
+                open_files.clone(),
+                phase,
+                changed_ranges.clone(),
+                settings.show_overridden_issues,
+            )
+            .await?
+        } else {
+            None
+        };
         #[cfg(not(feature = "lintian-brush"))]
         let lb: Option<Vec<Diagnostic>> = None;
 
         #[cfg(feature = "multiarch-hints")]
-        let mh = Self::multiarch_hints_diagnostics(
-            uri.clone(),
-            file_type,
-            workspace.clone(),
-            open_files.clone(),
-            multiarch_hints_store,
-        )
-        .await?;
+        let mh = if settings.multiarch_hints {
+            Self::multiarch_hints_diagnostics(
+                uri.clone(),
+                file_type,
+                workspace.clone(),
+                open_files.clone(),
+                multiarch_hints_store,
+            )
+            .await?
+        } else {
+            None
+        };
         #[cfg(not(feature = "multiarch-hints"))]
         let mh: Option<Vec<Diagnostic>> = None;
 
         // Silence unused-variable warnings when neither extension feature
         // is enabled — the bindings are only consumed inside the cfg arms.
-        let _ = (
-            uri,
-            workspace,
-            open_files,
-            phase,
-            changed_ranges,
-            show_overridden,
-        );
+        let _ = (uri, workspace, open_files, phase, changed_ranges, settings);
 
         let combined = match (builtin, lb) {
             (None, None) => None,
@@ -417,6 +545,7 @@ impl Backend {
         source_file: workspace::SourceFile,
         file_type: FileType,
         workspace: &Workspace,
+        rules_context: &rules::diagnostics::Context,
     ) -> Option<Vec<Diagnostic>> {
         match file_type {
             FileType::Control => {
@@ -431,16 +560,14 @@ impl Backend {
                 Some(diags)
             }
             FileType::Copyright => {
-                #[cfg_attr(not(feature = "spellcheck"), allow(unused_mut))]
+                let source_text = workspace.source_text(source_file);
+                let idx = workspace.get_line_index(source_file);
+                let src = Source::new(&source_text, &idx);
+                let parsed = workspace.get_parsed_copyright(source_file);
                 let mut diags = workspace.get_copyright_diagnostics(source_file);
+                diags.extend(copyright::licenses::license_diagnostics(&parsed, src));
                 #[cfg(feature = "spellcheck")]
-                {
-                    let source_text = workspace.source_text(source_file);
-                    let idx = workspace.get_line_index(source_file);
-                    let src = Source::new(&source_text, &idx);
-                    let parsed = workspace.get_parsed_copyright(source_file);
-                    diags.extend(copyright::spelling::copyright_diagnostics(&parsed, src));
-                }
+                diags.extend(copyright::spelling::copyright_diagnostics(&parsed, src));
                 Some(diags)
             }
             FileType::Patch => {
@@ -464,16 +591,20 @@ impl Backend {
                 ))
             }
             FileType::Changelog => {
+                let source_text = workspace.source_text(source_file);
+                let idx = workspace.get_line_index(source_file);
+                let src = Source::new(&source_text, &idx);
+                let parsed = workspace.get_parsed_changelog(source_file);
+                let mut diagnostics = changelog::diagnostics::get_diagnostics(&parsed, src);
+                let package_files = changelog::consistency::PackageFiles::load(workspace, uri);
+                diagnostics.extend(changelog::consistency::get_diagnostics(
+                    &parsed,
+                    src,
+                    &package_files,
+                ));
                 #[cfg(feature = "spellcheck")]
-                {
-                    let source_text = workspace.source_text(source_file);
-                    let idx = workspace.get_line_index(source_file);
-                    let src = Source::new(&source_text, &idx);
-                    let parsed = workspace.get_parsed_changelog(source_file);
-                    Some(changelog::spelling::changelog_diagnostics(&parsed, src))
-                }
-                #[cfg(not(feature = "spellcheck"))]
-                None
+                diagnostics.extend(changelog::spelling::changelog_diagnostics(&parsed, src));
+                Some(diagnostics)
             }
             FileType::Conffiles => {
                 let source_text = workspace.source_text(source_file);
@@ -481,12 +612,69 @@ impl Backend {
                 let src = Source::new(&source_text, &idx);
                 Some(conffiles::diagnostics::get_diagnostics(src))
             }
-            FileType::Watch
-            | FileType::TestsControl
+            FileType::Install => {
+                let source_text = workspace.source_text(source_file);
+                let idx = workspace.get_line_index(source_file);
+                let src = Source::new(&source_text, &idx);
+                Some(debhelper::diagnostics::common_diagnostics(src))
+            }
+            FileType::Links => {
+                let source_text = workspace.source_text(source_file);
+                let idx = workspace.get_line_index(source_file);
+                let src = Source::new(&source_text, &idx);
+                Some(debhelper::links::get_diagnostics(src))
+            }
+            FileType::Maintscript => {
+                let source_text = workspace.source_text(source_file);
+                let idx = workspace.get_line_index(source_file);
+                let src = Source::new(&source_text, &idx);
+                Some(debhelper::maintscript::get_diagnostics(src))
+            }
+            FileType::MaintainerScript => {
+                let kind = maintainer_scripts::detection::script_kind(uri)?;
+                let source_text = workspace.source_text(source_file);
+                let idx = workspace.get_line_index(source_file);
+                let src = Source::new(&source_text, &idx);
+                Some(maintainer_scripts::get_diagnostics(src, kind))
+            }
+            FileType::Templates => {
+                let source_text = workspace.source_text(source_file);
+                let idx = workspace.get_line_index(source_file);
+                let src = Source::new(&source_text, &idx);
+                let parsed = workspace.get_parsed_deb822(source_file);
+                Some(templates::get_diagnostics(&parsed.tree(), src))
+            }
+            FileType::Watch => {
+                let source_text = workspace.source_text(source_file);
+                let idx = workspace.get_line_index(source_file);
+                let src = Source::new(&source_text, &idx);
+                let parsed = workspace.get_parsed_watch(source_file);
+                Some(watch::diagnostics::get_diagnostics(&parsed, src))
+            }
+            FileType::Rules => {
+                let source_text = workspace.source_text(source_file);
+                let idx = workspace.get_line_index(source_file);
+                let src = Source::new(&source_text, &idx);
+                let parsed = workspace.get_parsed_rules(source_file);
+                // The control file as the workspace has it, which may be an
+                // unsaved buffer.
+                let control = uri.as_str().strip_suffix("rules").and_then(|debian_dir| {
+                    workspace
+                        .files()
+                        .find(|(u, _)| u.as_str().strip_prefix(debian_dir) == Some("control"))
+                        .map(|(_, file)| workspace.get_parsed_control(file).tree())
+                });
+                Some(rules::diagnostics::get_diagnostics(
+                    &parsed.tree(),
+                    src,
+                    rules_context.debhelper(control.as_ref()),
+                    rules_context.executable,
+                ))
+            }
+            FileType::TestsControl
             | FileType::SourceFormat
             | FileType::SourceOptions
             | FileType::UpstreamMetadata
-            | FileType::Rules
             | FileType::LintianOverrides
             | FileType::DebcargoToml
             | FileType::Dirs
@@ -513,7 +701,10 @@ impl Backend {
         use crate::spelling::comments;
 
         match file_type {
-            FileType::Control | FileType::TestsControl | FileType::SourceOptions => {
+            FileType::Control
+            | FileType::TestsControl
+            | FileType::SourceOptions
+            | FileType::Templates => {
                 let parsed = workspace.get_parsed_deb822(source_file);
                 comments::deb822_comment_findings(&parsed.tree(), src)
             }
@@ -560,7 +751,11 @@ impl Backend {
             | FileType::Triggers
             | FileType::Clean
             | FileType::Info
-            | FileType::Manpages => Vec::new(),
+            | FileType::Manpages
+            | FileType::Install
+            | FileType::Links
+            | FileType::Maintscript
+            | FileType::MaintainerScript => Vec::new(),
         }
     }
 
@@ -665,6 +860,48 @@ impl Backend {
         Ok(Some(diags))
     }
 
+    /// Gather what the checks of the rules file at `uri` read besides the
+    /// file itself. `debian/compat` and `debian/control` are taken from the
+    /// open files, or else read from disk off the async workers; a control
+    /// file read from disk is added to the workspace.
+    async fn rules_context(
+        uri: &Uri,
+        open_files: &HashMap<Uri, FileInfo>,
+        workspace: &mut Workspace,
+    ) -> rules::diagnostics::Context {
+        let Some(path) = uri.to_file_path().map(|p| p.to_path_buf()) else {
+            return Default::default();
+        };
+        let Some(debian_dir) = path.parent().map(|p| p.to_path_buf()) else {
+            return Default::default();
+        };
+        let open_compat = Uri::from_file_path(debian_dir.join("compat"))
+            .and_then(|compat_uri| open_files.get(&compat_uri))
+            .map(|info| info.source_file);
+        let control_uri = Uri::from_file_path(debian_dir.join("control"));
+        let read_control = control_uri
+            .as_ref()
+            .is_some_and(|control_uri| !open_files.contains_key(control_uri));
+        let read = tokio::task::spawn_blocking(move || {
+            let context = rules::diagnostics::Context::read(&path, open_compat.is_none());
+            let control = read_control
+                .then(|| std::fs::read_to_string(debian_dir.join("control")).ok())
+                .flatten();
+            (context, control)
+        })
+        .await;
+        let Ok((mut context, control)) = read else {
+            return Default::default();
+        };
+        if let Some(compat) = open_compat {
+            context.compat = Some(workspace.source_text(compat).to_string());
+        }
+        if let (Some(control_uri), Some(text)) = (control_uri, control) {
+            workspace.update_file(control_uri, text);
+        }
+        context
+    }
+
     /// Find the `debian/` directory by walking up from the given URI.
     fn find_debian_dir(uri: &Uri) -> Option<std::path::PathBuf> {
         let path = uri.to_file_path()?;
@@ -707,40 +944,538 @@ impl Backend {
             return;
         }
 
-        let show_overridden = self.settings.lock().await.show_overridden_issues;
+        // A pulling client re-requests everything after a refresh.
+        if self.pull_diagnostics.load(Ordering::Relaxed) {
+            if let Err(e) = self.client.workspace_diagnostic_refresh().await {
+                tracing::debug!("workspace/diagnostic/refresh failed: {e}");
+            }
+            return;
+        }
+
         for (uri, info) in siblings {
             // Lock `files` and `workspace` one at a time, never nested —
             // other handlers take them in the opposite order, and holding
             // both at once risks an AB-BA deadlock.
             let open_files_snapshot = self.files.lock().await.clone();
             let workspace = self.workspace.lock().await.clone();
-            let diagnostics = match Self::collect_diagnostics(
-                uri.clone(),
-                info.source_file,
-                info.file_type,
-                workspace,
-                open_files_snapshot,
-                RunPhase::Open,
-                None,
-                show_overridden,
-                #[cfg(feature = "multiarch-hints")]
-                Some(self.multiarch_hints_store.clone()),
-            )
-            .await
+            self.push_diagnostics(&uri, info, workspace, open_files_snapshot, RunPhase::Open)
+                .await;
+        }
+    }
+
+    /// Scan the headers of the files in the source tree and merge what was
+    /// found into the copyright file at `uri`.
+    async fn scan_copyright(&self, uri: &Uri) {
+        let Some(file_info) = self.files.lock().await.get(uri).cloned() else {
+            return;
+        };
+        // debian/copyright -> debian/ -> source root
+        let Some(root) = uri.to_file_path().and_then(|p| {
+            p.parent()
+                .and_then(|debian| debian.parent())
+                .map(|root| root.to_path_buf())
+        }) else {
+            return;
+        };
+        let Some(tracked) = copyright::code_lens::tracked_files(&self.git_file_cache, &root).await
+        else {
+            self.client
+                .show_message(
+                    MessageType::WARNING,
+                    "Cannot scan the source tree: it is not a git checkout",
+                )
+                .await;
+            return;
+        };
+
+        let workspace = self.workspace_clone().await;
+        let parsed = workspace.get_parsed_copyright(file_info.source_file);
+        let files = copyright::scan::files_to_scan(&parsed, &tracked);
+        let scanned = files.clone();
+        let findings =
+            match tokio::task::spawn_blocking(move || copyright::scan::scan_tree(&root, &scanned))
+                .await
             {
-                Ok(Some(d)) => d,
-                Ok(None) => continue,
+                Ok(findings) => findings,
                 Err(e) => {
-                    self.client.log_message(MessageType::ERROR, &e).await;
-                    continue;
+                    tracing::warn!("copyright scan failed: {e}");
+                    return;
                 }
             };
+
+        let source_text = workspace.source_text(file_info.source_file);
+        let idx = workspace.get_line_index(file_info.source_file);
+        let src = Source::new(&source_text, &idx);
+        if let Some(edit) = copyright::scan::build_scan_edit(uri, src, &parsed, &files, &findings) {
+            let _ = self.client.apply_edit(edit).await;
+        }
+    }
+
+    /// Simulate the version check of an entry of the watch file at `uri`
+    /// against a saved upstream page. `arguments` are the index of the entry
+    /// and the path or `file://` URL of the page, which defaults to the
+    /// entry's own URL. Returns the outcome as JSON.
+    async fn simulate_watch(
+        &self,
+        uri: &Uri,
+        arguments: &[serde_json::Value],
+    ) -> Option<serde_json::Value> {
+        let file_info = self.files.lock().await.get(uri).cloned()?;
+        // debian/watch -> debian/ -> source root
+        let root = uri.to_file_path().and_then(|p| {
+            p.parent()
+                .and_then(|debian| debian.parent())
+                .map(|root| root.to_path_buf())
+        })?;
+        let index = arguments.first().and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let changelog = Uri::from_file_path(root.join("debian").join("changelog"));
+        let changelog = match changelog {
+            Some(changelog_uri) => self.load_file(&changelog_uri).await,
+            None => None,
+        };
+
+        let workspace = self.workspace_clone().await;
+        let package = changelog
+            .and_then(|info| {
+                watch::simulate::package_name(
+                    &workspace.get_parsed_changelog(info.source_file).tree(),
+                )
+            })
+            .unwrap_or_default();
+        let rule = {
+            let parsed = workspace.get_parsed_watch(file_info.source_file);
+            match parsed.to_watch_file().entries().nth(index) {
+                Some(entry) => watch::simulate::Rule::from_entry(&entry, &package),
+                None => Err(format!("The watch file has no entry {}", index)),
+            }
+        };
+        let fixture = arguments
+            .get(1)
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        // Reading the page, or walking a directory, blocks.
+        let result = match rule {
+            Ok(rule) => tokio::task::spawn_blocking(move || {
+                let fixture = fixture.as_deref().unwrap_or(&rule.url);
+                let path = watch::simulate::fixture_path(fixture, &root).ok_or_else(|| {
+                    format!(
+                        "{} is not a local file or directory; pass the path of a saved copy of the page",
+                        fixture
+                    )
+                })?;
+                let fixture = watch::simulate::Fixture::read(&path)
+                    .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+                watch::simulate::simulate(&rule, &fixture)
+            })
+            .await
+            .unwrap_or_else(|e| Err(format!("The simulation failed: {}", e))),
+            Err(message) => Err(message),
+        };
+
+        match result {
+            Ok(simulation) => {
+                self.client
+                    .log_message(MessageType::INFO, simulation.report())
+                    .await;
+                self.client
+                    .show_message(MessageType::INFO, simulation.summary())
+                    .await;
+                serde_json::to_value(&simulation).ok()
+            }
+            Err(message) => {
+                self.client
+                    .show_message(MessageType::WARNING, message)
+                    .await;
+                None
+            }
+        }
+    }
+
+    /// Add the changes made in git since the last release to the changelog
+    /// at `uri`, as `gbp dch` would.
+    async fn generate_changelog_entry(&self, uri: &Uri) {
+        let Some(file_info) = self.files.lock().await.get(uri).cloned() else {
+            return;
+        };
+        // debian/changelog -> debian/ -> source root
+        let Some(root) = uri.to_file_path().and_then(|p| {
+            p.parent()
+                .and_then(|debian| debian.parent())
+                .map(|root| root.to_path_buf())
+        }) else {
+            return;
+        };
+
+        let workspace = self.workspace_clone().await;
+        let parsed = workspace.get_parsed_changelog(file_info.source_file);
+        let released = changelog::git::latest_released_version(&parsed.tree());
+        let commits = match tokio::task::spawn_blocking(move || {
+            changelog::git::read_commits(&root, released.as_ref())
+        })
+        .await
+        {
+            Ok(Some(commits)) => commits,
+            Ok(None) => {
+                self.client
+                    .show_message(
+                        MessageType::WARNING,
+                        "Cannot read the git history: the package is not a git checkout",
+                    )
+                    .await;
+                return;
+            }
+            Err(e) => {
+                tracing::warn!("reading git history failed: {e}");
+                return;
+            }
+        };
+
+        let source_text = workspace.source_text(file_info.source_file);
+        let idx = workspace.get_line_index(file_info.source_file);
+        let src = Source::new(&source_text, &idx);
+        match changelog::git::build_entry_edit(uri, src, &parsed, &commits) {
+            Some(edit) => {
+                let _ = self.client.apply_edit(edit).await;
+            }
+            None => {
+                self.client
+                    .show_message(MessageType::INFO, "No new commits since the last release")
+                    .await;
+            }
+        }
+    }
+
+    /// Compute the diagnostics for a file, logging rather than failing on
+    /// errors. `None` means the file type has no diagnostics.
+    async fn file_diagnostics(
+        &self,
+        uri: &Uri,
+        info: FileInfo,
+        workspace: Workspace,
+        open_files: HashMap<Uri, FileInfo>,
+        phase: RunPhase,
+    ) -> Option<Vec<Diagnostic>> {
+        // Names missing from the package list may be virtual packages, so
+        // they are only reported once their providers are known. The lookup
+        // runs apt-cache, too slow for every keystroke; the diagnostics are
+        // refreshed when it completes.
+        if info.file_type == FileType::Control && phase != RunPhase::Keystroke {
+            let parsed = workspace.get_parsed_control(info.source_file);
+            let unresolved = self.unresolved_packages(&parsed).await;
+            if !unresolved.is_empty() {
+                let cache = self.package_cache.clone();
+                let data_generation = self.data_generation.clone();
+                tokio::spawn(async move {
+                    if package_cache::resolve_providers(&cache, &unresolved).await {
+                        data_generation.bump();
+                    }
+                });
+            }
+        }
+
+        // changed_ranges is intentionally `None`: narrowing by touched
+        // fields would skip detectors for unchanged fields and wipe
+        // their already-published diagnostics from the rest of the file.
+        let settings = self.settings.lock().await.clone();
+        match Self::collect_diagnostics(
+            uri.clone(),
+            info.source_file,
+            info.file_type,
+            workspace,
+            open_files,
+            phase,
+            None,
+            &settings,
+            Some(self.package_cache.clone()),
+            Some(self.architecture_list.clone()),
+            Some(self.git_file_cache.clone()),
+            Some(self.bug_cache.clone()),
+            #[cfg(feature = "multiarch-hints")]
+            Some(self.multiarch_hints_store.clone()),
+        )
+        .await
+        {
+            Ok(d) => d,
+            Err(e) => {
+                self.client.log_message(MessageType::ERROR, &e).await;
+                None
+            }
+        }
+    }
+
+    /// The names in the dependency fields of the control file whose
+    /// providers must be looked up before they can be reported as unknown.
+    /// Empty until the package list is complete.
+    async fn unresolved_packages(
+        &self,
+        parsed: &debian_control::lossless::Parse<debian_control::lossless::Control>,
+    ) -> Vec<String> {
+        let cache = self.package_cache.read().await;
+        if !cache.is_complete() {
+            return Vec::new();
+        }
+        control::relation_diagnostics::unresolved_packages(parsed, &*cache)
+    }
+
+    /// Look up the providers of the unknown names in every open control
+    /// file, e.g. once the package list is complete.
+    async fn resolve_open_control_providers(&self) {
+        let open_files = self.files.lock().await.clone();
+        let workspace = self.workspace_clone().await;
+        let controls: Vec<_> = open_files
+            .values()
+            .filter(|info| info.file_type == FileType::Control)
+            .map(|info| workspace.get_parsed_control(info.source_file))
+            .collect();
+        let mut names = Vec::new();
+        for parsed in &controls {
+            names.extend(self.unresolved_packages(parsed).await);
+        }
+        names.sort();
+        names.dedup();
+        package_cache::resolve_providers(&self.package_cache, &names).await;
+    }
+
+    /// Compute and publish the diagnostics for an open file, unless the
+    /// client pulls them itself.
+    async fn push_diagnostics(
+        &self,
+        uri: &Uri,
+        info: FileInfo,
+        workspace: Workspace,
+        open_files: HashMap<Uri, FileInfo>,
+        phase: RunPhase,
+    ) {
+        if self.pull_diagnostics.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Some(diagnostics) = self
+            .file_diagnostics(uri, info, workspace, open_files, phase)
+            .await
+        {
             self.client
                 .publish_diagnostics(uri.clone(), diagnostics, None)
                 .await;
         }
     }
 
+    /// The workspace file for `uri`: the open buffer if there is one,
+    /// otherwise the file as read from disk. `None` if the file type is not
+    /// recognised or the file cannot be read.
+    async fn load_file(&self, uri: &Uri) -> Option<FileInfo> {
+        let files = self.files.lock().await;
+        if let Some(info) = files.get(uri) {
+            return Some(*info);
+        }
+        drop(files);
+
+        let file_type = FileType::detect(uri)?;
+        let text = std::fs::read_to_string(uri.to_file_path()?).ok()?;
+        let source_file = self.workspace.lock().await.update_file(uri.clone(), text);
+        Some(FileInfo {
+            source_file,
+            file_type,
+        })
+    }
+
+    /// Add every packaging file under the `debian/` directory of each
+    /// workspace root to the workspace, so that features spanning several
+    /// files see the ones that are not open too. Open files are left alone;
+    /// their buffers are newer than the disk.
+    async fn index_workspace(&self) {
+        let roots = self.workspace_roots.lock().await.clone();
+        let open: std::collections::HashSet<Uri> =
+            self.files.lock().await.keys().cloned().collect();
+
+        let mut loaded = Vec::new();
+        for debian_dir in debian_files::debian_dirs(&roots) {
+            for path in debian_files::scan(&debian_dir) {
+                let Some(uri) = Uri::from_file_path(&path) else {
+                    continue;
+                };
+                if open.contains(&uri) || FileType::detect(&uri).is_none() {
+                    continue;
+                }
+                if let Ok(text) = std::fs::read_to_string(&path) {
+                    loaded.push((uri, text));
+                }
+            }
+        }
+
+        let count = loaded.len();
+        let mut workspace = self.workspace.lock().await;
+        for (uri, text) in loaded {
+            workspace.update_file(uri, text);
+        }
+        drop(workspace);
+        tracing::debug!("Indexed {count} packaging files");
+    }
+
+    /// Ask the client to tell us about changes below `debian/`.
+    async fn register_file_watchers(&self) {
+        let options = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![FileSystemWatcher {
+                glob_pattern: GlobPattern::String("**/debian/**".to_string()),
+                kind: None,
+            }],
+        };
+        let registration = Registration {
+            id: "debian-lsp-watched-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(options).ok(),
+        };
+        if let Err(e) = self.client.register_capability(vec![registration]).await {
+            tracing::warn!("Failed to register file watchers: {e}");
+        }
+    }
+
+    /// Ask the client for the `debian` configuration section, if it
+    /// supports `workspace/configuration`. The answer is the whole section,
+    /// so what it leaves out has its default value.
+    async fn request_configuration(&self) -> Option<Settings> {
+        if !self.pull_configuration.load(Ordering::Relaxed) {
+            return None;
+        }
+        let items = vec![ConfigurationItem {
+            scope_uri: None,
+            section: Some(settings::SECTION.to_string()),
+        }];
+        match self.client.configuration(items).await {
+            Ok(values) => values
+                .into_iter()
+                .next()
+                .and_then(Settings::from_configuration),
+            Err(e) => {
+                tracing::warn!("workspace/configuration failed: {e}");
+                None
+            }
+        }
+    }
+
+    /// Replace the settings and bring everything they affect up to date:
+    /// diagnostics of the open files are recomputed, and the client is asked
+    /// to re-request inlay hints and code lenses.
+    async fn apply_settings(&self, settings: Settings) {
+        let mut current = self.settings.lock().await;
+        if *current == settings {
+            return;
+        }
+        let (url, offline) = settings.udd_connection();
+        self.udd_pool.configure(&url, offline);
+        *current = settings;
+        drop(current);
+        self.settings_generation.fetch_add(1, Ordering::Relaxed);
+
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            if let Err(e) = client.inlay_hint_refresh().await {
+                tracing::debug!("workspace/inlayHint/refresh failed: {e}");
+            }
+            if let Err(e) = client.code_lens_refresh().await {
+                tracing::debug!("workspace/codeLens/refresh failed: {e}");
+            }
+        });
+
+        self.refresh_diagnostics().await;
+    }
+
+    /// Bring the diagnostics of every open file up to date after something
+    /// they depend on changed outside the files themselves: a pulling client
+    /// is asked to re-request them, a pushing one gets them re-published.
+    async fn refresh_diagnostics(&self) {
+        if self.pull_diagnostics.load(Ordering::Relaxed) {
+            let client = self.client.clone();
+            tokio::spawn(async move {
+                if let Err(e) = client.workspace_diagnostic_refresh().await {
+                    tracing::debug!("workspace/diagnostic/refresh failed: {e}");
+                }
+            });
+            return;
+        }
+
+        let open_files = self.files.lock().await.clone();
+        for (uri, info) in &open_files {
+            let workspace = self.workspace.lock().await.clone();
+            self.push_diagnostics(uri, *info, workspace, open_files.clone(), RunPhase::Open)
+                .await;
+        }
+    }
+
+    /// Refresh the diagnostics each time background data arrives, for as
+    /// long as the server runs.
+    fn watch_data_generation(&self) {
+        let backend = self.clone();
+        tokio::spawn(async move {
+            loop {
+                backend.data_generation.changed().await;
+                // A pulling client re-requests at keystroke cost, which
+                // doesn't look up providers, so do it here.
+                backend.resolve_open_control_providers().await;
+                backend.refresh_diagnostics().await;
+            }
+        });
+    }
+
+    /// Whether `path` is a packaging file under one of the workspace roots.
+    async fn is_workspace_packaging_file(&self, path: &std::path::Path) -> bool {
+        let roots = self.workspace_roots.lock().await.clone();
+        debian_files::debian_dirs(&roots)
+            .iter()
+            .any(|debian_dir| debian_files::is_packaging_file(debian_dir, path))
+    }
+
+    /// A pulled diagnostics report for `uri`, or an unchanged report when
+    /// neither the workspace, the settings nor the background data changed
+    /// since `previous_result_id`.
+    ///
+    /// Diagnostics look at sibling files too, so the result id is the
+    /// revision of the whole workspace rather than that of `uri`.
+    async fn pull_file_diagnostics(
+        &self,
+        uri: &Uri,
+        previous_result_id: Option<&str>,
+    ) -> DocumentDiagnosticReport {
+        let Some(info) = self.load_file(uri).await else {
+            return DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport::default());
+        };
+
+        let workspace = self.workspace_clone().await;
+        let result_id = format!(
+            "{}-{}-{}",
+            workspace.revision(),
+            self.settings_generation.load(Ordering::Relaxed),
+            self.data_generation.get()
+        );
+        if previous_result_id == Some(result_id.as_str()) {
+            return DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                related_documents: None,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                    result_id,
+                },
+            });
+        }
+
+        // A client re-pulls after every edit; only the first pull of a file
+        // gets the budget of a fresh open.
+        let phase = if previous_result_id.is_some() {
+            RunPhase::Keystroke
+        } else {
+            RunPhase::Open
+        };
+        let open_files = self.files.lock().await.clone();
+        let items = self
+            .file_diagnostics(uri, info, workspace, open_files, phase)
+            .await
+            .unwrap_or_default();
+        DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+            related_documents: None,
+            full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                result_id: Some(result_id),
+                items,
+            },
+        })
+    }
+
     /// Get or load the control source file for the debian directory
     /// containing the given URI.
     fn get_control_for_uri(
@@ -764,6 +1499,36 @@ impl Backend {
         ))
     }
 
+    /// Get or load the debconf templates file for a maintainer script:
+    /// `debian/<package>.templates` if it exists, else `debian/templates`.
+    fn get_templates_for_uri(
+        uri: &Uri,
+        kind: maintainer_scripts::ScriptKind,
+        files: &HashMap<Uri, FileInfo>,
+        workspace: &mut Workspace,
+    ) -> Option<(workspace::SourceFile, Uri)> {
+        let debian_dir = Self::find_debian_dir(uri)?;
+        let script_name = uri.as_str().rsplit('/').next()?;
+
+        for name in templates::definition::templates_file_names(script_name, kind) {
+            let templates_path = debian_dir.join(name);
+            let Some(templates_uri) = Uri::from_file_path(&templates_path) else {
+                continue;
+            };
+            if let Some(info) = files.get(&templates_uri) {
+                return Some((info.source_file, templates_uri));
+            }
+            // Not open — read from disk and insert into the workspace.
+            if let Ok(text) = std::fs::read_to_string(&templates_path) {
+                return Some((
+                    workspace.update_file(templates_uri.clone(), text),
+                    templates_uri,
+                ));
+            }
+        }
+        None
+    }
+
     /// Get or load the changelog source file for the debian directory
     /// containing the given URI. If the changelog is already open, reuses the
     /// existing workspace entry; otherwise reads it from disk and inserts it
@@ -848,25 +1613,27 @@ impl Backend {
         let package_name = changelog.iter().next().and_then(|entry| entry.package());
         if let Some(package_name) = package_name {
             let bug_cache = self.bug_cache.clone();
-            let client = self.client.clone();
+            let settings = self.settings.clone();
+            let data_generation = self.data_generation.clone();
             tokio::spawn(async move {
-                {
-                    let mut cache = bug_cache.write().await;
-                    cache.prefetch_bugs_for_package(&package_name).await;
-                    if let Some(err) = cache.last_udd_error.take() {
-                        client
-                            .show_message(
-                                MessageType::WARNING,
-                                format!("UDD connection failed: {err}"),
-                            )
-                            .await;
-                    }
+                let (udd, launchpad) = {
+                    let settings = settings.lock().await;
+                    (settings.udd, settings.launchpad_enabled())
+                };
+                if udd {
+                    bugs::BugCache::prefetch_bugs_for_package(&bug_cache, &package_name).await;
+                }
+                if launchpad {
+                    bug_cache
+                        .write()
+                        .await
+                        .prefetch_launchpad_bugs_for_package(&package_name)
+                        .await;
+                }
+                if udd || launchpad {
+                    // The Closes checks use the prefetched bugs.
+                    data_generation.bump();
                 }
-                bug_cache
-                    .write()
-                    .await
-                    .prefetch_launchpad_bugs_for_package(&package_name)
-                    .await;
             });
         }
     }
@@ -923,17 +1690,56 @@ impl Backend {
 
 impl LanguageServer for Backend {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
-        if let Some(options) = params.initialization_options {
-            match serde_json::from_value::<Settings>(options) {
-                Ok(settings) => {
-                    *self.settings.lock().await = settings;
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to parse initializationOptions: {e}");
-                }
-            }
+        if let Some(settings) = params
+            .initialization_options
+            .and_then(Settings::from_configuration)
+        {
+            let (url, offline) = settings.udd_connection();
+            self.udd_pool.configure(&url, offline);
+            *self.settings.lock().await = settings;
         }
 
+        let pulls = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .is_some_and(|t| t.diagnostic.is_some());
+        self.pull_diagnostics.store(pulls, Ordering::Relaxed);
+
+        let watches = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|w| w.did_change_watched_files.as_ref())
+            .and_then(|w| w.dynamic_registration)
+            .unwrap_or(false);
+        self.watch_files.store(watches, Ordering::Relaxed);
+
+        let configuration = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|w| w.configuration)
+            .unwrap_or(false);
+        self.pull_configuration
+            .store(configuration, Ordering::Relaxed);
+
+        #[allow(deprecated)]
+        let roots: Vec<std::path::PathBuf> = match &params.workspace_folders {
+            Some(folders) => folders
+                .iter()
+                .filter_map(|f| f.uri.to_file_path())
+                .map(|p| p.into_owned())
+                .collect(),
+            None => params
+                .root_uri
+                .iter()
+                .filter_map(|uri| uri.to_file_path())
+                .map(|p| p.into_owned())
+                .collect(),
+        };
+        *self.workspace_roots.lock().await = roots;
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
@@ -964,6 +1770,7 @@ impl LanguageServer for Backend {
                 }),
                 folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
                 document_symbol_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
                 code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
                 semantic_tokens_provider: Some(
                     SemanticTokensServerCapabilities::SemanticTokensOptions(
@@ -1001,6 +1808,13 @@ impl LanguageServer for Backend {
                         control::code_lens::OPEN_URL_COMMAND.to_string(),
                         changelog::ADD_CHANGELOG_ENTRY_COMMAND.to_string(),
                         control::ADD_BINARY_PACKAGE_COMMAND.to_string(),
+                        copyright::scan::SCAN_COPYRIGHT_COMMAND.to_string(),
+                        changelog::git::GENERATE_CHANGELOG_ENTRY_COMMAND.to_string(),
+                        changelog::templates::NEW_UPSTREAM_COMMAND.to_string(),
+                        changelog::templates::NMU_COMMAND.to_string(),
+                        changelog::templates::TEAM_UPLOAD_COMMAND.to_string(),
+                        changelog::templates::BACKPORT_COMMAND.to_string(),
+                        watch::simulate::SIMULATE_WATCH_COMMAND.to_string(),
                     ],
                     ..Default::default()
                 }),
@@ -1022,6 +1836,14 @@ impl LanguageServer for Backend {
                 definition_provider: Some(OneOf::Left(true)),
                 references_provider: Some(OneOf::Left(true)),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
+                diagnostic_provider: Some(DiagnosticServerCapabilities::Options(
+                    DiagnosticOptions {
+                        identifier: Some("debian-lsp".to_string()),
+                        inter_file_dependencies: true,
+                        workspace_diagnostics: true,
+                        work_done_progress_options: Default::default(),
+                    },
+                )),
                 ..Default::default()
             },
             ..Default::default()
@@ -1032,6 +1854,15 @@ impl LanguageServer for Backend {
         self.client
             .log_message(MessageType::INFO, "Debian LSP initialized!")
             .await;
+
+        self.index_workspace().await;
+        self.watch_data_generation();
+        if self.watch_files.load(Ordering::Relaxed) {
+            self.register_file_watchers().await;
+        }
+        if let Some(settings) = self.request_configuration().await {
+            self.apply_settings(settings).await;
+        }
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -1083,33 +1914,17 @@ impl LanguageServer for Backend {
         let open_files_snapshot = files.clone();
         drop(files);
 
-        let show_overridden = self.settings.lock().await.show_overridden_issues;
-        let diagnostics = match Self::collect_diagnostics(
-            params.text_document.uri.clone(),
-            source_file,
-            file_type,
+        self.push_diagnostics(
+            &params.text_document.uri,
+            FileInfo {
+                source_file,
+                file_type,
+            },
             workspace,
             open_files_snapshot,
             RunPhase::Open,
-            None,
-            show_overridden,
-            #[cfg(feature = "multiarch-hints")]
-            Some(self.multiarch_hints_store.clone()),
         )
-        .await
-        {
-            Ok(d) => d,
-            Err(e) => {
-                self.client.log_message(MessageType::ERROR, &e).await;
-                None
-            }
-        };
-
-        if let Some(diagnostics) = diagnostics {
-            self.client
-                .publish_diagnostics(params.text_document.uri.clone(), diagnostics, None)
-                .await;
-        }
+        .await;
 
         // Opening a lintian-overrides file may change which sibling
         // diagnostics are suppressed (its on-disk content is now an open
@@ -1197,36 +2012,17 @@ impl LanguageServer for Backend {
         let open_files_snapshot = files.clone();
         drop(files);
 
-        // changed_ranges is intentionally `None`: narrowing by touched
-        // fields would skip detectors for unchanged fields and wipe
-        // their already-published diagnostics from the rest of the file.
-        let show_overridden = self.settings.lock().await.show_overridden_issues;
-        let diagnostics = match Self::collect_diagnostics(
-            params.text_document.uri.clone(),
-            source_file,
-            file_type,
+        self.push_diagnostics(
+            &params.text_document.uri,
+            FileInfo {
+                source_file,
+                file_type,
+            },
             workspace,
             open_files_snapshot,
             RunPhase::Keystroke,
-            None,
-            show_overridden,
-            #[cfg(feature = "multiarch-hints")]
-            Some(self.multiarch_hints_store.clone()),
         )
-        .await
-        {
-            Ok(d) => d,
-            Err(e) => {
-                self.client.log_message(MessageType::ERROR, &e).await;
-                None
-            }
-        };
-
-        if let Some(diagnostics) = diagnostics {
-            self.client
-                .publish_diagnostics(params.text_document.uri.clone(), diagnostics, None)
-                .await;
-        }
+        .await;
 
         // Editing a lintian-overrides file changes which sibling
         // diagnostics are suppressed — re-analyse the open files in the
@@ -1252,30 +2048,90 @@ impl LanguageServer for Backend {
             None => return Ok(None),
         };
 
-        if file_info.file_type != FileType::Changelog {
-            return Ok(None);
+        if file_info.file_type != FileType::Changelog {
+            return Ok(None);
+        }
+
+        let workspace = self.workspace_clone().await;
+        let source_text = workspace.source_text(file_info.source_file);
+        let idx = workspace.get_line_index(file_info.source_file);
+        let src = Source::new(&source_text, &idx);
+        let parsed = workspace.get_parsed_changelog(file_info.source_file);
+        let changelog = parsed.tree();
+
+        let edit = changelog::generate_timestamp_update_edit(&changelog, src);
+        Ok(edit.map(|e| vec![e]))
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = &params.text_document.uri;
+        let mut files = self.files.lock().await;
+        files.remove(uri);
+        drop(files);
+
+        // The buffer may have been closed without saving; fall back to what
+        // is on disk, or forget the file if it is gone.
+        match uri.to_file_path().map(std::fs::read_to_string) {
+            Some(Ok(text)) => {
+                self.workspace.lock().await.update_file(uri.clone(), text);
+            }
+            _ => self.workspace.lock().await.remove_file(uri),
+        }
+
+        // Clear diagnostics so stale squiggles don't linger after the file
+        // is closed. A pulling client manages its own.
+        if !self.pull_diagnostics.load(Ordering::Relaxed) {
+            self.client
+                .publish_diagnostics(params.text_document.uri, vec![], None)
+                .await;
+        }
+    }
+
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        // Clients that answer `workspace/configuration` often send an empty
+        // notification and expect the server to ask.
+        let settings = match self.request_configuration().await {
+            Some(settings) => Some(settings),
+            None => self
+                .settings
+                .lock()
+                .await
+                .with_configuration(params.settings),
+        };
+        if let Some(settings) = settings {
+            self.apply_settings(settings).await;
+        }
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let mut changed = false;
+        for event in params.changes {
+            let uri = event.uri;
+            if self.files.lock().await.contains_key(&uri) || FileType::detect(&uri).is_none() {
+                continue;
+            }
+            let Some(path) = uri.to_file_path().map(|p| p.into_owned()) else {
+                continue;
+            };
+            if !self.is_workspace_packaging_file(&path).await {
+                continue;
+            }
+
+            if event.typ == FileChangeType::DELETED {
+                self.workspace.lock().await.remove_file(&uri);
+            } else if let Ok(text) = std::fs::read_to_string(&path) {
+                self.workspace.lock().await.update_file(uri, text);
+            } else {
+                continue;
+            }
+            changed = true;
         }
 
-        let workspace = self.workspace_clone().await;
-        let source_text = workspace.source_text(file_info.source_file);
-        let idx = workspace.get_line_index(file_info.source_file);
-        let src = Source::new(&source_text, &idx);
-        let parsed = workspace.get_parsed_changelog(file_info.source_file);
-        let changelog = parsed.tree();
-
-        let edit = changelog::generate_timestamp_update_edit(&changelog, src);
-        Ok(edit.map(|e| vec![e]))
-    }
-
-    async fn did_close(&self, params: DidCloseTextDocumentParams) {
-        let mut files = self.files.lock().await;
-        files.remove(&params.text_document.uri);
-        drop(files);
-        // Clear diagnostics so stale squiggles don't linger after the file
-        // is closed.
-        self.client
-            .publish_diagnostics(params.text_document.uri, vec![], None)
-            .await;
+        if changed && self.pull_diagnostics.load(Ordering::Relaxed) {
+            if let Err(e) = self.client.workspace_diagnostic_refresh().await {
+                tracing::debug!("workspace/diagnostic/refresh failed: {e}");
+            }
+        }
     }
 
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
@@ -1309,13 +2165,14 @@ impl LanguageServer for Backend {
                 {
                     drop(workspace); // Release lock before async operations
                                      // Try async completions (relationship fields via package cache)
+                    let udd = self.settings.lock().await.udd;
                     if let Some(async_completions) = control::get_async_field_value_completions(
                         field_name,
                         value_prefix,
                         position,
                         &self.package_cache,
                         &self.architecture_list,
-                        &self.maintainer_cache,
+                        udd.then_some(&self.maintainer_cache),
                     )
                     .await
                     {
@@ -1406,9 +2263,15 @@ impl LanguageServer for Backend {
                 let src = Source::new(&source_text, &idx);
                 let parsed = workspace.get_parsed_changelog(source_file);
                 drop(workspace);
-                if let Some((items, is_incomplete)) =
-                    changelog::get_async_bug_completions(&parsed, src, position, &self.bug_cache)
-                        .await
+                let settings = self.settings.lock().await.clone();
+                if let Some((items, is_incomplete)) = changelog::get_async_bug_completions(
+                    &parsed,
+                    src,
+                    position,
+                    &self.bug_cache,
+                    &settings,
+                )
+                .await
                 {
                     if items.is_empty() {
                         return Ok(None);
@@ -1525,6 +2388,40 @@ impl LanguageServer for Backend {
                 let debian_dir = Self::find_debian_dir(&uri);
                 debhelper::manpages::get_completions(&source_text, position, debian_dir.as_deref())
             }
+            Some((FileType::Install, source_file)) => {
+                let workspace = self.workspace_clone().await;
+                let source_text = workspace.source_text(source_file);
+                let debian_dir = Self::find_debian_dir(&uri);
+                debhelper::install::get_completions(&source_text, position, debian_dir.as_deref())
+            }
+            Some((FileType::Links, source_file)) => {
+                let workspace = self.workspace_clone().await;
+                let source_text = workspace.source_text(source_file);
+                debhelper::links::get_completions(&source_text, position)
+            }
+            Some((FileType::Maintscript, source_file)) => {
+                let workspace = self.workspace_clone().await;
+                let source_text = workspace.source_text(source_file);
+                debhelper::maintscript::get_completions(&source_text, position)
+            }
+            Some((FileType::MaintainerScript, source_file)) => {
+                match maintainer_scripts::detection::script_kind(&uri) {
+                    Some(kind) => {
+                        let workspace = self.workspace_clone().await;
+                        let source_text = workspace.source_text(source_file);
+                        maintainer_scripts::get_completions(&source_text, kind, position)
+                    }
+                    None => Vec::new(),
+                }
+            }
+            Some((FileType::Templates, source_file)) => {
+                let workspace = self.workspace_clone().await;
+                let source_text = workspace.source_text(source_file);
+                let idx = workspace.get_line_index(source_file);
+                let src = Source::new(&source_text, &idx);
+                let parsed = workspace.get_parsed_deb822(source_file);
+                templates::get_completions(&parsed.tree(), src, position)
+            }
             None => Vec::new(),
         };
 
@@ -1539,6 +2436,72 @@ impl LanguageServer for Backend {
         Ok(item)
     }
 
+    async fn diagnostic(
+        &self,
+        params: DocumentDiagnosticParams,
+    ) -> Result<DocumentDiagnosticReportResult> {
+        let report = self
+            .pull_file_diagnostics(
+                &params.text_document.uri,
+                params.previous_result_id.as_deref(),
+            )
+            .await;
+        Ok(DocumentDiagnosticReportResult::Report(report))
+    }
+
+    async fn workspace_diagnostic(
+        &self,
+        params: WorkspaceDiagnosticParams,
+    ) -> Result<WorkspaceDiagnosticReportResult> {
+        let previous: HashMap<Uri, String> = params
+            .previous_result_ids
+            .into_iter()
+            .map(|p| (p.uri, p.value))
+            .collect();
+        let roots = self.workspace_roots.lock().await.clone();
+
+        let mut items = Vec::new();
+        for debian_dir in debian_files::debian_dirs(&roots) {
+            for path in debian_files::scan(&debian_dir) {
+                let Some(uri) = Uri::from_file_path(&path) else {
+                    continue;
+                };
+                if FileType::detect(&uri).is_none() {
+                    continue;
+                }
+                let report = self
+                    .pull_file_diagnostics(&uri, previous.get(&uri).map(String::as_str))
+                    .await;
+                items.push(match report {
+                    DocumentDiagnosticReport::Full(full) => {
+                        WorkspaceDocumentDiagnosticReport::Full(
+                            WorkspaceFullDocumentDiagnosticReport {
+                                uri,
+                                version: None,
+                                full_document_diagnostic_report: full
+                                    .full_document_diagnostic_report,
+                            },
+                        )
+                    }
+                    DocumentDiagnosticReport::Unchanged(unchanged) => {
+                        WorkspaceDocumentDiagnosticReport::Unchanged(
+                            WorkspaceUnchangedDocumentDiagnosticReport {
+                                uri,
+                                version: None,
+                                unchanged_document_diagnostic_report: unchanged
+                                    .unchanged_document_diagnostic_report,
+                            },
+                        )
+                    }
+                });
+            }
+        }
+
+        Ok(WorkspaceDiagnosticReportResult::Report(
+            WorkspaceDiagnosticReport { items },
+        ))
+    }
+
     async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
         let workspace = self.workspace_clone().await;
         let files = self.files.lock().await;
@@ -1560,7 +2523,11 @@ impl LanguageServer for Backend {
             | FileType::SourceOptions
             | FileType::LintianOverrides
             | FileType::PatchesSeries
-            | FileType::Conffiles => {}
+            | FileType::Conffiles
+            | FileType::Install
+            | FileType::Links
+            | FileType::Maintscript
+            | FileType::Templates => {}
             _ => return Ok(None),
         }
 
@@ -1705,6 +2672,29 @@ impl LanguageServer for Backend {
                 }
                 actions.extend(casing_actions);
 
+                actions.push(copyright::scan::scan_action(&params.text_document.uri));
+
+                actions.extend(copyright::common_licenses::license_text_actions(
+                    &params.text_document.uri,
+                    &parsed,
+                    src,
+                    text_range,
+                ));
+
+                actions.extend(copyright::licenses::license_actions(
+                    &params.text_document.uri,
+                    &parsed,
+                    src,
+                    &params.context.diagnostics,
+                ));
+
+                actions.extend(copyright::coverage::coverage_actions(
+                    &params.text_document.uri,
+                    &parsed,
+                    src,
+                    &params.context.diagnostics,
+                ));
+
                 #[cfg(feature = "spellcheck")]
                 actions.extend(copyright::spelling::copyright_actions(
                     &params.text_document.uri,
@@ -1747,16 +2737,33 @@ impl LanguageServer for Backend {
                     }
                 }
 
+                let parsed = workspace.get_parsed_changelog(file_info.source_file);
+                actions.extend(changelog::diagnostics::get_code_actions(
+                    &params.text_document.uri,
+                    &parsed,
+                    src,
+                    &params.context.diagnostics,
+                ));
+                actions.push(changelog::git::generate_action(&params.text_document.uri));
+                actions.extend(changelog::templates::template_actions(
+                    &params.text_document.uri,
+                ));
+                let package_files =
+                    changelog::consistency::PackageFiles::load(&workspace, &params.text_document.uri);
+                actions.extend(changelog::consistency::get_code_actions(
+                    &params.text_document.uri,
+                    &parsed,
+                    src,
+                    &package_files,
+                    &params.context.diagnostics,
+                ));
                 #[cfg(feature = "spellcheck")]
-                {
-                    let parsed = workspace.get_parsed_changelog(file_info.source_file);
-                    actions.extend(changelog::spelling::changelog_actions(
-                        &params.text_document.uri,
-                        &parsed,
-                        src,
-                        &params.context.diagnostics,
-                    ));
-                }
+                actions.extend(changelog::spelling::changelog_actions(
+                    &params.text_document.uri,
+                    &parsed,
+                    src,
+                    &params.context.diagnostics,
+                ));
             }
             FileType::Patch => {
                 #[cfg(feature = "spellcheck")]
@@ -1777,15 +2784,34 @@ impl LanguageServer for Backend {
                     &params.context.diagnostics,
                 ));
             }
-            FileType::Watch
-            | FileType::UpstreamMetadata
+            FileType::Install | FileType::Links | FileType::Maintscript => {
+                actions.extend(debhelper::actions::get_code_actions(
+                    src,
+                    &params.text_document.uri,
+                    &params.context.diagnostics,
+                ));
+            }
+            FileType::Watch => {
+                let parsed = workspace.get_parsed_watch(file_info.source_file);
+                actions.extend(watch::convert::get_code_actions(
+                    &params.text_document.uri,
+                    &parsed,
+                    src,
+                ));
+                actions.extend(watch::simulate::simulate_actions(
+                    &params.text_document.uri,
+                    &parsed.to_watch_file(),
+                ));
+            }
+            FileType::UpstreamMetadata
             | FileType::TestsControl
             // These formats contribute only comment-spelling actions, which are
             // collected before this match.
             | FileType::Rules
             | FileType::SourceOptions
             | FileType::LintianOverrides
-            | FileType::PatchesSeries => {}
+            | FileType::PatchesSeries
+            | FileType::Templates => {}
             _ => unreachable!(),
         }
         } // 'format_actions
@@ -2091,6 +3117,10 @@ impl LanguageServer for Backend {
                 let deb822_parse = workspace.get_parsed_deb822(file.source_file);
                 tests::generate_semantic_tokens(&deb822_parse, src)
             }
+            FileType::Templates => {
+                let deb822_parse = workspace.get_parsed_deb822(file.source_file);
+                templates::generate_semantic_tokens(&deb822_parse, src)
+            }
             FileType::UpstreamMetadata => {
                 let parsed = workspace.get_parsed_upstream_metadata(file.source_file);
                 let yaml_file = parsed.tree();
@@ -2104,7 +3134,8 @@ impl LanguageServer for Backend {
                 let makefile = parsed.tree();
                 rules::generate_semantic_tokens(&makefile, src)
             }
-            FileType::SourceFormat => vec![],
+            // Shell syntax is left to a shell language server.
+            FileType::SourceFormat | FileType::MaintainerScript => vec![],
             FileType::SourceOptions => source_options::generate_semantic_tokens(&source_text),
             FileType::LintianOverrides => {
                 let parsed = workspace.get_parsed_lintian_overrides(file.source_file);
@@ -2139,7 +3170,10 @@ impl LanguageServer for Backend {
             | FileType::Examples
             | FileType::Clean
             | FileType::Info
-            | FileType::Manpages => debhelper::semantic::generate_semantic_tokens(src),
+            | FileType::Manpages
+            | FileType::Install
+            | FileType::Links => debhelper::semantic::generate_semantic_tokens(src),
+            FileType::Maintscript => debhelper::maintscript::generate_semantic_tokens(src),
             FileType::Triggers => triggers::generate_semantic_tokens(src),
         };
 
@@ -2188,6 +3222,19 @@ impl LanguageServer for Backend {
                 let (parsed, _) = workspace.get_parsed_dep3_header(file.source_file);
                 dep3::generate_document_symbols(&parsed.tree(), src)
             }
+            FileType::MaintainerScript => maintainer_scripts::generate_document_symbols(src),
+            FileType::TestsControl => {
+                let parsed = workspace.get_parsed_deb822(file.source_file);
+                tests::generate_document_symbols(&parsed.tree(), src)
+            }
+            FileType::Rules => {
+                let parsed = workspace.get_parsed_rules(file.source_file);
+                rules::generate_document_symbols(&parsed.tree(), src)
+            }
+            FileType::PatchesSeries => {
+                let parsed = workspace.get_parsed_patches_series(file.source_file);
+                patches_series::generate_document_symbols(&parsed.tree(), src)
+            }
             _ => return Ok(None),
         };
 
@@ -2198,6 +3245,15 @@ impl LanguageServer for Backend {
         }
     }
 
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<WorkspaceSymbolResponse>> {
+        let workspace = self.workspace_clone().await;
+        let symbols = workspace_symbols::workspace_symbols(&workspace, &params.query);
+        Ok(Some(WorkspaceSymbolResponse::Nested(symbols)))
+    }
+
     async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
         let uri = &params.text_document.uri;
 
@@ -2230,7 +3286,7 @@ impl LanguageServer for Backend {
                 let parsed = workspace.get_parsed_watch(file.source_file);
                 watch::generate_folding_ranges(&parsed, src)
             }
-            FileType::TestsControl => {
+            FileType::TestsControl | FileType::Templates => {
                 let deb822_parse = workspace.get_parsed_deb822(file.source_file);
                 match deb822_parse.to_result() {
                     Ok(deb822) => deb822::folding::generate_folding_ranges(&deb822, src),
@@ -2290,7 +3346,7 @@ impl LanguageServer for Backend {
                 let parsed = workspace.get_parsed_watch(file.source_file);
                 watch::generate_selection_ranges(&parsed, src, &params.positions)
             }
-            FileType::TestsControl => {
+            FileType::TestsControl | FileType::Templates => {
                 let deb822_parse = workspace.get_parsed_deb822(file.source_file);
                 match deb822_parse.to_result() {
                     Ok(deb822) => deb822::selection_range::generate_selection_ranges(
@@ -2350,7 +3406,7 @@ impl LanguageServer for Backend {
                     &params.ch,
                 ))
             }
-            FileType::TestsControl => {
+            FileType::TestsControl | FileType::Templates => {
                 let deb822 = workspace.get_parsed_deb822(file.source_file).tree();
                 Ok(deb822::on_type_formatting::on_type_formatting(
                     &deb822,
@@ -2503,12 +3559,14 @@ impl LanguageServer for Backend {
                 drop(workspace);
                 let src = Source::new(&source_text, &idx);
 
+                let categories = self.settings.lock().await.enabled_code_lenses();
                 let ctx = control::code_lens::LensContext {
                     package_cache: &self.package_cache,
                     vcswatch_cache: &self.vcswatch_cache,
                     bug_cache: &self.bug_cache,
                     popcon_cache: &self.popcon_cache,
                     rdeps_cache: &self.rdeps_cache,
+                    categories: &categories,
                 };
                 let (lenses, uncached) = control::generate_code_lenses(&parsed, src, &ctx).await;
 
@@ -2519,31 +3577,29 @@ impl LanguageServer for Backend {
                     let bug_cache = self.bug_cache.clone();
                     let popcon_cache = self.popcon_cache.clone();
                     let rdeps_cache = self.rdeps_cache.clone();
+                    let data_generation = self.data_generation.clone();
                     tokio::spawn(async move {
                         if uncached.needs_policy_version {
                             let mut cache = package_cache.write().await;
                             cache.load_versions("debian-policy").await;
                         }
                         if let Some(url) = &uncached.vcs_git_url {
-                            let mut cache = vcswatch_cache.write().await;
-                            cache.get_version_for_url(url).await;
+                            vcswatch::get_version_for_url(&vcswatch_cache, url).await;
                         }
                         if let Some(source) = &uncached.source_package {
-                            let mut cache = bug_cache.write().await;
-                            cache.prefetch_bugs_for_package(source).await;
+                            bugs::BugCache::prefetch_bugs_for_package(&bug_cache, source).await;
+                            data_generation.bump();
                         }
                         for pkg in &uncached.binary_packages {
-                            {
-                                let mut cache = bug_cache.write().await;
-                                cache.prefetch_bugs_for_binary_package(pkg).await;
+                            if categories.bugs {
+                                bugs::BugCache::prefetch_bugs_for_binary_package(&bug_cache, pkg)
+                                    .await;
                             }
-                            {
-                                let mut cache = popcon_cache.write().await;
-                                cache.get_inst_count(pkg).await;
+                            if categories.popcon {
+                                popcon::get_inst_count(&popcon_cache, pkg).await;
                             }
-                            {
-                                let mut cache = rdeps_cache.write().await;
-                                cache.get_rdeps_count(pkg).await;
+                            if categories.reverse_dependencies {
+                                rdeps::get_rdeps_count(&rdeps_cache, pkg).await;
                             }
                         }
                         let _ = client.code_lens_refresh().await;
@@ -2571,6 +3627,9 @@ impl LanguageServer for Backend {
                         .and_then(|debian| debian.parent())
                         .map(|root| root.to_path_buf())
                 });
+                // Without a source root only the license lenses are shown.
+                let file_counts = self.settings.lock().await.code_lens.file_counts;
+                let source_root = source_root.filter(|_| file_counts);
 
                 let lenses = copyright::generate_code_lenses(
                     &parsed,
@@ -2601,6 +3660,9 @@ impl LanguageServer for Backend {
 
         match file.file_type {
             FileType::Changelog => {
+                if !self.settings.lock().await.inlay_hints.distributions {
+                    return Ok(None);
+                }
                 let workspace = self.workspace_clone().await;
                 let source_text = workspace.source_text(file.source_file);
                 let idx = workspace.get_line_index(file.source_file);
@@ -2633,9 +3695,11 @@ impl LanguageServer for Backend {
 
                 drop(workspace); // Release lock before async package cache access
                 let src = Source::new(&source_text, &idx);
+                let categories = self.settings.lock().await.inlay_hints.clone();
                 let ctx = control::inlay_hints::HintContext {
                     package_cache: &self.package_cache,
                     resolved_substvars: &resolved_substvars,
+                    categories: &categories,
                 };
                 let (hints, uncached_packages) =
                     control::generate_inlay_hints(&parsed, src, &params.range, &ctx).await;
@@ -2645,11 +3709,13 @@ impl LanguageServer for Backend {
                 if !uncached_packages.is_empty() {
                     let cache = self.package_cache.clone();
                     let client = self.client.clone();
+                    let data_generation = self.data_generation.clone();
                     tokio::spawn(async move {
                         let mut c = cache.write().await;
                         c.load_versions_batch(&uncached_packages).await;
                         c.load_providers_batch(&uncached_packages).await;
                         drop(c);
+                        data_generation.bump();
                         let _ = client.inlay_hint_refresh().await;
                     });
                 }
@@ -2689,10 +3755,17 @@ impl LanguageServer for Backend {
                 let parsed = workspace.get_parsed_deb822(file.source_file);
                 Ok(tests::get_hover(&parsed.tree(), src, position))
             }
+            FileType::Templates => {
+                let parsed = workspace.get_parsed_deb822(file.source_file);
+                Ok(templates::get_hover(&parsed.tree(), src, position))
+            }
             FileType::Copyright => {
                 let parsed = workspace.get_parsed_copyright(file.source_file);
                 let copyright = parsed.tree();
-                Ok(copyright::get_hover(copyright.as_deb822(), src, position))
+                Ok(
+                    copyright::common_licenses::get_hover(&parsed, src, position)
+                        .or_else(|| copyright::get_hover(copyright.as_deb822(), src, position)),
+                )
             }
             FileType::Watch => {
                 let parsed = workspace.get_parsed_watch(file.source_file);
@@ -2709,10 +3782,16 @@ impl LanguageServer for Backend {
             FileType::Changelog => {
                 let parsed = workspace.get_parsed_changelog(file.source_file);
                 drop(workspace);
-                Ok(
-                    changelog::get_hover(&parsed, src, position, &self.bug_cache, &self.cve_cache)
-                        .await,
+                let settings = self.settings.lock().await.clone();
+                Ok(changelog::get_hover(
+                    &parsed,
+                    src,
+                    position,
+                    &self.bug_cache,
+                    &self.cve_cache,
+                    &settings,
                 )
+                .await)
             }
             FileType::UpstreamMetadata => {
                 let parsed = workspace.get_parsed_upstream_metadata(file.source_file);
@@ -2726,6 +3805,38 @@ impl LanguageServer for Backend {
                 let (parsed, header_end) = workspace.get_parsed_dep3_header(file.source_file);
                 Ok(dep3::get_hover(&parsed.tree(), header_end, src, position))
             }
+            FileType::Rules => {
+                let parsed = workspace.get_parsed_rules(file.source_file);
+                let system = rules::variables::system_values().await;
+                // The changelog as the workspace has it, which may be an
+                // unsaved buffer.
+                let changelog_uri = Self::find_debian_dir(uri)
+                    .and_then(|debian_dir| Uri::from_file_path(debian_dir.join("changelog")));
+                let changelog = match changelog_uri {
+                    Some(changelog_uri) => self.load_file(&changelog_uri).await,
+                    None => None,
+                };
+                let changelog = match changelog {
+                    Some(info) => Some(
+                        self.workspace_clone()
+                            .await
+                            .get_parsed_changelog(info.source_file)
+                            .tree(),
+                    ),
+                    None => None,
+                };
+                let environment = uri
+                    .to_file_path()
+                    .map(|p| rules::variables::Environment::load(&p, changelog.as_ref(), system))
+                    .unwrap_or_default();
+                Ok(rules::get_hover(
+                    &parsed.tree(),
+                    src,
+                    uri,
+                    position,
+                    &environment,
+                ))
+            }
             FileType::DebcargoToml => Ok(debcargo::get_hover(&source_text, position)),
             FileType::SourceOptions => Ok(source_options::get_hover(&source_text, position)),
             FileType::Conffiles => {
@@ -2735,6 +3846,11 @@ impl LanguageServer for Backend {
                 let src = Source::new(&source_text, &idx);
                 Ok(conffiles::get_hover(src, position))
             }
+            FileType::Install => Ok(debhelper::install::get_hover(src, position)),
+            FileType::Links => Ok(debhelper::links::get_hover(src, position)),
+            FileType::Maintscript => Ok(debhelper::maintscript::get_hover(src, position)),
+            FileType::MaintainerScript => Ok(maintainer_scripts::detection::script_kind(uri)
+                .and_then(|kind| maintainer_scripts::get_hover(src, kind, position))),
             _ => Ok(None),
         }
     }
@@ -2893,6 +4009,56 @@ impl LanguageServer for Backend {
                 };
                 Ok(result.map(GotoDefinitionResponse::Scalar))
             }
+            FileType::MaintainerScript => {
+                let Some(kind) = maintainer_scripts::detection::script_kind(uri) else {
+                    return Ok(None);
+                };
+                let files = self.files.lock().await;
+                let mut workspace = self.workspace.lock().await;
+                let source_text = workspace.source_text(file.source_file);
+                let idx = workspace.get_line_index(file.source_file);
+                let src = Source::new(&source_text, &idx);
+                let Some((line, offset)) = debhelper::hover::line_at(src, position) else {
+                    return Ok(None);
+                };
+                let Some(question) = templates::definition::question_at(line, offset) else {
+                    return Ok(None);
+                };
+
+                let result = Self::get_templates_for_uri(uri, kind, &files, &mut workspace)
+                    .and_then(|(templates_sf, templates_uri)| {
+                        let templates_text = workspace.source_text(templates_sf);
+                        let templates_idx = workspace.get_line_index(templates_sf);
+                        let templates_src = Source::new(&templates_text, &templates_idx);
+                        let parsed = workspace.get_parsed_deb822(templates_sf);
+                        templates::goto_definition(
+                            &parsed.tree(),
+                            templates_src,
+                            &templates_uri,
+                            question,
+                        )
+                    });
+                Ok(result.map(GotoDefinitionResponse::Scalar))
+            }
+            FileType::Rules => {
+                let workspace = self.workspace_clone().await;
+                let source_text = workspace.source_text(file.source_file);
+                let idx = workspace.get_line_index(file.source_file);
+                let src = Source::new(&source_text, &idx);
+                let parsed = workspace.get_parsed_rules(file.source_file);
+                let root = uri.to_file_path().and_then(|p| {
+                    p.parent()
+                        .and_then(std::path::Path::parent)
+                        .map(|r| r.to_path_buf())
+                });
+                let mut locations =
+                    rules::goto_definition(&parsed.tree(), src, uri, position, root.as_deref());
+                Ok(match locations.len() {
+                    0 => None,
+                    1 => locations.pop().map(GotoDefinitionResponse::Scalar),
+                    _ => Some(GotoDefinitionResponse::Array(locations)),
+                })
+            }
             _ => Ok(None),
         }
     }
@@ -2916,8 +4082,56 @@ impl LanguageServer for Backend {
                 let idx = workspace.get_line_index(file.source_file);
                 let src = Source::new(&source_text, &idx);
                 let parsed = workspace.get_parsed_control(file.source_file);
-                let refs =
+                let mut refs =
                     control::find_references(&parsed, src, position, uri, include_declaration);
+
+                // Other packaging files in the same `debian/` directory,
+                // whether open or only indexed from disk.
+                let name = control::references::referenced_package_at(&parsed, src, position);
+                if let (Some(name), Some(debian_dir)) = (name, Self::find_debian_dir(uri)) {
+                    let mut siblings: Vec<(Uri, workspace::SourceFile)> = workspace
+                        .files()
+                        .filter(|(other, _)| *other != uri)
+                        .filter(|(other, _)| {
+                            Self::find_debian_dir(other).as_deref() == Some(&debian_dir)
+                        })
+                        .map(|(other, file)| (other.clone(), file))
+                        .collect();
+                    siblings.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+
+                    for (other, source_file) in siblings {
+                        refs.extend(control::references::find_package_file_reference(
+                            &other, &name,
+                        ));
+                        let text = workspace.source_text(source_file);
+                        let idx = workspace.get_line_index(source_file);
+                        let other_src = Source::new(&text, &idx);
+                        match FileType::detect(&other) {
+                            Some(FileType::LintianOverrides) => {
+                                let parsed = workspace.get_parsed_lintian_overrides(source_file);
+                                refs.extend(
+                                    control::references::find_lintian_overrides_references(
+                                        &parsed.tree(),
+                                        other_src,
+                                        &name,
+                                        &other,
+                                    ),
+                                );
+                            }
+                            Some(FileType::TestsControl) => {
+                                let parsed = workspace.get_parsed_deb822(source_file);
+                                refs.extend(control::references::find_tests_control_references(
+                                    &parsed.tree(),
+                                    other_src,
+                                    &name,
+                                    &other,
+                                ));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
                 if refs.is_empty() {
                     Ok(None)
                 } else {
@@ -2946,42 +4160,61 @@ impl LanguageServer for Backend {
                         .await;
                 }
             }
-        } else if params.command == changelog::ADD_CHANGELOG_ENTRY_COMMAND {
+        } else if params.command == changelog::ADD_CHANGELOG_ENTRY_COMMAND
+            || changelog::templates::COMMANDS.contains(&params.command.as_str())
+        {
             if let Some(uri_str) = params.arguments.first().and_then(|v| v.as_str()) {
                 if let Ok(uri) = uri_str.parse::<Uri>() {
                     let workspace = self.workspace_clone().await;
-                    let workspace_edit = {
+                    let template = changelog::templates::EntryTemplate::from_command(
+                        &params.command,
+                        &params.arguments[1..],
+                    );
+                    let new_entry = {
                         let files = self.files.lock().await;
-                        files.get(&uri).and_then(|file_info| {
+                        files.get(&uri).map(|file_info| {
                             let parsed = workspace.get_parsed_changelog(file_info.source_file);
                             let changelog = parsed.tree();
-                            changelog::generate_new_changelog_entry(&changelog)
-                                .ok()
-                                .map(|new_entry| WorkspaceEdit {
-                                    changes: Some(
-                                        vec![(
-                                            uri.clone(),
-                                            vec![TextEdit {
-                                                range: Range {
-                                                    start: Position {
-                                                        line: 0,
-                                                        character: 0,
-                                                    },
-                                                    end: Position {
-                                                        line: 0,
-                                                        character: 0,
-                                                    },
-                                                },
-                                                new_text: new_entry,
-                                            }],
-                                        )]
-                                        .into_iter()
-                                        .collect(),
-                                    ),
-                                    ..Default::default()
-                                })
+                            match template {
+                                None => changelog::generate_new_changelog_entry(&changelog),
+                                Some(template) => template.and_then(|template| {
+                                    changelog::templates::generate_entry(&changelog, &template)
+                                }),
+                            }
                         })
                     };
+                    let workspace_edit = match new_entry {
+                        Some(Err(message)) => {
+                            self.client
+                                .show_message(MessageType::WARNING, message)
+                                .await;
+                            None
+                        }
+                        Some(Ok(new_entry)) => Some(WorkspaceEdit {
+                            changes: Some(
+                                vec![(
+                                    uri.clone(),
+                                    vec![TextEdit {
+                                        range: Range {
+                                            start: Position {
+                                                line: 0,
+                                                character: 0,
+                                            },
+                                            end: Position {
+                                                line: 0,
+                                                character: 0,
+                                            },
+                                        },
+                                        new_text: new_entry,
+                                    }],
+                                )]
+                                .into_iter()
+                                .collect(),
+                            ),
+                            ..Default::default()
+                        }),
+                        None => None,
+                    };
                     if let Some(edit) = workspace_edit {
                         let _ = self.client.apply_edit(edit).await;
                     }
@@ -3006,6 +4239,24 @@ impl LanguageServer for Backend {
                     }
                 }
             }
+        } else if params.command == copyright::scan::SCAN_COPYRIGHT_COMMAND {
+            if let Some(uri_str) = params.arguments.first().and_then(|v| v.as_str()) {
+                if let Ok(uri) = uri_str.parse::<Uri>() {
+                    self.scan_copyright(&uri).await;
+                }
+            }
+        } else if params.command == changelog::git::GENERATE_CHANGELOG_ENTRY_COMMAND {
+            if let Some(uri_str) = params.arguments.first().and_then(|v| v.as_str()) {
+                if let Ok(uri) = uri_str.parse::<Uri>() {
+                    self.generate_changelog_entry(&uri).await;
+                }
+            }
+        } else if params.command == watch::simulate::SIMULATE_WATCH_COMMAND {
+            if let Some(uri_str) = params.arguments.first().and_then(|v| v.as_str()) {
+                if let Ok(uri) = uri_str.parse::<Uri>() {
+                    return Ok(self.simulate_watch(&uri, &params.arguments[1..]).await);
+                }
+            }
         }
         Ok(None)
     }
@@ -3062,7 +4313,7 @@ enum Command {
         #[arg(long)]
         project_root: Option<String>,
         /// Skip diagnostics that require network access (lintian-brush,
-        /// multiarch-hints). Builtin diagnostics still run.
+        /// multiarch-hints, closed bugs). Builtin diagnostics still run.
         #[arg(long)]
         offline: bool,
     },
@@ -3135,6 +4386,11 @@ async fn run_check(
     let multiarch_hints_store =
         enable_multiarch_hints.then(multiarch_hints::hints::HintsStore::default);
 
+    // Shared across files so `git ls-files` runs once per source tree.
+    let git_file_cache = copyright::code_lens::new_shared_git_file_cache();
+    // The bugs closed in changelogs are looked up in UDD.
+    let bug_cache = bugs::new_shared_bug_cache(udd::shared_pool());
+
     // Expand directories into individual files, tracking which were explicit.
     let explicit_paths: std::collections::HashSet<std::path::PathBuf> =
         paths.iter().filter(|p| !p.is_dir()).cloned().collect();
@@ -3203,7 +4459,14 @@ async fn run_check(
             // CLI lint: an overridden issue is intentionally suppressed,
             // so don't report it. There is no faded rendering in a
             // terminal anyway.
-            false,
+            &Settings {
+                show_overridden_issues: false,
+                ..Default::default()
+            },
+            None,
+            None,
+            Some(git_file_cache.clone()),
+            Some(bug_cache.clone()),
             #[cfg(feature = "multiarch-hints")]
             multiarch_hints_store.clone(),
         )
@@ -3399,6 +4662,8 @@ async fn attach_scip_diagnostics(
     // entirely in offline mode.
     #[cfg(feature = "multiarch-hints")]
     let multiarch_hints_store = (!offline).then(multiarch_hints::hints::HintsStore::default);
+    let git_file_cache = copyright::code_lens::new_shared_git_file_cache();
+    let bug_cache = bugs::new_shared_bug_cache(udd::shared_pool());
 
     // Collect the work to do first, so the immutable borrow of `index` ends
     // before we mutate it via `attach`.
@@ -3427,7 +4692,18 @@ async fn attach_scip_diagnostics(
         // Offline mode runs only the builtin (parser-based) diagnostics; the
         // lintian-brush and multiarch-hints producers both touch the network.
         let diagnostics = if offline {
-            match Backend::builtin_diagnostics(&uri, source_file, file_type, &workspace) {
+            let rules_context = if file_type == FileType::Rules {
+                Backend::rules_context(&uri, &HashMap::new(), &mut workspace).await
+            } else {
+                Default::default()
+            };
+            match Backend::builtin_diagnostics(
+                &uri,
+                source_file,
+                file_type,
+                &workspace,
+                &rules_context,
+            ) {
                 Some(d) => d,
                 None => continue,
             }
@@ -3440,7 +4716,14 @@ async fn attach_scip_diagnostics(
                 HashMap::new(),
                 RunPhase::Explicit,
                 None,
-                false,
+                &Settings {
+                    show_overridden_issues: false,
+                    ..Default::default()
+                },
+                None,
+                None,
+                Some(git_file_cache.clone()),
+                Some(bug_cache.clone()),
                 #[cfg(feature = "multiarch-hints")]
                 multiarch_hints_store.clone(),
             )
@@ -3551,8 +4834,12 @@ async fn main() {
             // Load package cache in background
             let package_cache = package_cache::new_shared_cache();
             let cache_for_loading = package_cache.clone();
+            let data_generation = Arc::new(DataGeneration::default());
+            let loaded = data_generation.clone();
             tokio::spawn(async move {
                 package_cache::stream_packages_into(&cache_for_loading).await;
+                // Unknown packages are only reported once the list is complete.
+                loaded.bump();
             });
 
             // Load architecture list in background
@@ -3568,28 +4855,44 @@ async fn main() {
             let vcswatch_cache = vcswatch::new_shared_vcswatch_cache(udd_pool.clone());
             let popcon_cache = popcon::new_shared_popcon_cache(udd_pool.clone());
             let maintainer_cache = maintainers::new_shared_maintainer_cache(udd_pool.clone());
-            let rdeps_cache = rdeps::new_shared_rdeps_cache(udd_pool);
-
-            let (service, socket) = LspService::new(|client| Backend {
-                client,
-                workspace: Arc::new(Mutex::new(Workspace::new())),
-                files: Arc::new(Mutex::new(HashMap::new())),
-                package_cache: package_cache.clone(),
-                architecture_list: architecture_list.clone(),
-                bug_cache: bug_cache.clone(),
-                cve_cache: cve_cache.clone(),
-                maintainer_cache: maintainer_cache.clone(),
-                vcswatch_cache: vcswatch_cache.clone(),
-                popcon_cache: popcon_cache.clone(),
-                rdeps_cache: rdeps_cache.clone(),
-                git_file_cache: copyright::code_lens::new_shared_git_file_cache(),
-                lintian_tag_cache: Arc::new(tokio::sync::RwLock::new(
-                    lintian_overrides::LintianTagCache::new(),
-                )),
-                upstream_cache: upstream_metadata::upstream_cache::new_shared(),
-                #[cfg(feature = "multiarch-hints")]
-                multiarch_hints_store: multiarch_hints::hints::HintsStore::default(),
-                settings: Arc::new(Mutex::new(Settings::default())),
+            let rdeps_cache = rdeps::new_shared_rdeps_cache(udd_pool.clone());
+
+            let (service, socket) = LspService::new(|client| {
+                let failure_client = client.clone();
+                udd_pool.set_failure_handler(move |message| {
+                    let client = failure_client.clone();
+                    tokio::spawn(async move {
+                        client.show_message(MessageType::WARNING, message).await;
+                    });
+                });
+                Backend {
+                    client,
+                    workspace: Arc::new(Mutex::new(Workspace::new())),
+                    files: Arc::new(Mutex::new(HashMap::new())),
+                    package_cache: package_cache.clone(),
+                    architecture_list: architecture_list.clone(),
+                    bug_cache: bug_cache.clone(),
+                    cve_cache: cve_cache.clone(),
+                    maintainer_cache: maintainer_cache.clone(),
+                    vcswatch_cache: vcswatch_cache.clone(),
+                    popcon_cache: popcon_cache.clone(),
+                    rdeps_cache: rdeps_cache.clone(),
+                    udd_pool: udd_pool.clone(),
+                    git_file_cache: copyright::code_lens::new_shared_git_file_cache(),
+                    lintian_tag_cache: Arc::new(tokio::sync::RwLock::new(
+                        lintian_overrides::LintianTagCache::new(),
+                    )),
+                    upstream_cache: upstream_metadata::upstream_cache::new_shared(),
+                    #[cfg(feature = "multiarch-hints")]
+                    multiarch_hints_store: multiarch_hints::hints::HintsStore::default(),
+                    settings: Arc::new(Mutex::new(Settings::default())),
+                    settings_generation: Default::default(),
+                    data_generation: data_generation.clone(),
+                    workspace_roots: Default::default(),
+                    pull_diagnostics: Default::default(),
+                    watch_files: Default::default(),
+                    pull_configuration: Default::default(),
+                }
             });
 
             Server::new(stdin, stdout, socket).serve(service).await;
@@ -0,0 +1,124 @@
+use tower_lsp_server::ls_types::{CompletionItem, CompletionItemKind, Documentation, Position};
+
+use super::parser::{lines_with_offsets, scan_until};
+use super::ScriptKind;
+
+/// Completions for a maintainer script at the given cursor position.
+///
+/// Action names are offered where a branch pattern of `case "$1" in` goes,
+/// and after a comparison such as `[ "$1" = "`.
+pub fn get_completions(text: &str, kind: ScriptKind, position: Position) -> Vec<CompletionItem> {
+    let Some((line_start, line)) = lines_with_offsets(text).nth(position.line as usize) else {
+        return Vec::new();
+    };
+    let offset = (position.character as usize).min(line.len());
+    let Some(before) = line.get(..offset) else {
+        return Vec::new();
+    };
+
+    let prefix = if let Some(prefix) = comparison_prefix(before) {
+        prefix
+    } else if scan_until(text, line_start).expects_action_pattern() {
+        match pattern_prefix(before) {
+            Some(prefix) => prefix,
+            None => return Vec::new(),
+        }
+    } else {
+        return Vec::new();
+    };
+
+    kind.actions()
+        .iter()
+        .filter(|a| a.name.starts_with(prefix))
+        .map(|a| CompletionItem {
+            label: a.name.to_string(),
+            kind: Some(CompletionItemKind::ENUM_MEMBER),
+            detail: Some(a.invocation.to_string()),
+            documentation: Some(Documentation::String(a.description.to_string())),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// The partial action name when the cursor is in a branch pattern list,
+/// e.g. `abort-upgrade|abort-r`. `None` once the list is closed with `)`.
+fn pattern_prefix(before: &str) -> Option<&str> {
+    let trimmed = before.trim_start();
+    if trimmed.starts_with('#') || trimmed.contains(')') {
+        return None;
+    }
+    let start = before
+        .rfind(['|', '(', '"', '\'', ' ', '\t'])
+        .map_or(0, |i| i + 1);
+    Some(&before[start..])
+}
+
+/// The partial action name when the cursor follows a comparison against
+/// `$1`, e.g. `if [ "$1" = "conf`.
+fn comparison_prefix(before: &str) -> Option<&str> {
+    let (_, rest) = before.rsplit_once("$1")?;
+    let rest = rest.strip_prefix('"').unwrap_or(rest).trim_start();
+    let rest = rest.strip_prefix('!').unwrap_or(rest);
+    let rest = rest.strip_prefix("==").or_else(|| rest.strip_prefix('='))?;
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix('"').unwrap_or(rest);
+    rest.chars()
+        .all(|c| c.is_ascii_lowercase() || c == '-')
+        .then_some(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(text: &str, kind: ScriptKind, line: u32, col: u32) -> Vec<String> {
+        get_completions(text, kind, Position::new(line, col))
+            .into_iter()
+            .map(|i| i.label)
+            .collect()
+    }
+
+    #[test]
+    fn offers_actions_for_a_case_pattern() {
+        let text = "case \"$1\" in\n    \n";
+        assert_eq!(
+            labels(text, ScriptKind::Preinst, 1, 4),
+            vec!["install", "upgrade", "abort-upgrade"]
+        );
+    }
+
+    #[test]
+    fn filters_by_the_current_alternative() {
+        let text = "case \"$1\" in\n    abort-upgrade|abort-r\n";
+        assert_eq!(
+            labels(text, ScriptKind::Postinst, 1, 25),
+            vec!["abort-remove"]
+        );
+    }
+
+    #[test]
+    fn nothing_inside_a_branch_body() {
+        let text = "case \"$1\" in\n    configure)\n        \n";
+        assert!(labels(text, ScriptKind::Postinst, 2, 8).is_empty());
+    }
+
+    #[test]
+    fn nothing_outside_a_case() {
+        assert!(labels("set -e\n\n", ScriptKind::Postinst, 1, 0).is_empty());
+    }
+
+    #[test]
+    fn offers_actions_after_a_comparison() {
+        let text = "if [ \"$1\" = \"pu";
+        assert_eq!(labels(text, ScriptKind::Postrm, 0, 15), vec!["purge"]);
+    }
+
+    #[test]
+    fn items_carry_the_invocation() {
+        let items = get_completions("case $1 in\n  \n", ScriptKind::Config, Position::new(1, 2));
+        assert_eq!(
+            items[0].detail.as_deref(),
+            Some("config configure installed-version")
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use tower_lsp_server::ls_types::Uri;
+
+use super::ScriptKind;
+
+/// The kind of maintainer script the URI names, if any.
+///
+/// Matches `debian/<script>` and `debian/<package>.<script>`. Unlike the
+/// debhelper config files the parent directory must be `debian/`, since a
+/// `.config` suffix on its own is far too common.
+pub fn script_kind(uri: &Uri) -> Option<ScriptKind> {
+    let path = uri.as_str();
+    let (dir, file_name) = path.rsplit_once('/')?;
+    if !dir.ends_with("/debian") {
+        return None;
+    }
+    ScriptKind::ALL.iter().copied().find(|kind| {
+        let stem = kind.name();
+        file_name == stem || file_name.ends_with(&format!(".{stem}"))
+    })
+}
+
+/// Whether the URI is a maintainer script.
+pub fn is_maintainer_script_file(uri: &Uri) -> bool {
+    script_kind(uri).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri(s: &str) -> Uri {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn detects_each_script() {
+        assert_eq!(
+            script_kind(&uri("file:///p/debian/postinst")),
+            Some(ScriptKind::Postinst)
+        );
+        assert_eq!(
+            script_kind(&uri("file:///p/debian/foo.preinst")),
+            Some(ScriptKind::Preinst)
+        );
+        assert_eq!(
+            script_kind(&uri("file:///p/debian/foo.prerm")),
+            Some(ScriptKind::Prerm)
+        );
+        assert_eq!(
+            script_kind(&uri("file:///p/debian/postrm")),
+            Some(ScriptKind::Postrm)
+        );
+        assert_eq!(
+            script_kind(&uri("file:///p/debian/foo.config")),
+            Some(ScriptKind::Config)
+        );
+    }
+
+    #[test]
+    fn rejects_files_outside_debian() {
+        assert!(!is_maintainer_script_file(&uri("file:///p/app.config")));
+        assert!(!is_maintainer_script_file(&uri(
+            "file:///p/debian/tests/postinst"
+        )));
+    }
+
+    #[test]
+    fn rejects_generated_and_backup_files() {
+        assert!(!is_maintainer_script_file(&uri(
+            "file:///p/debian/foo.postinst.debhelper"
+        )));
+        assert!(!is_maintainer_script_file(&uri(
+            "file:///p/debian/postinst.bak"
+        )));
+    }
+}
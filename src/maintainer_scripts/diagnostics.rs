@@ -0,0 +1,206 @@
+use text_size::{TextRange, TextSize};
+use tower_lsp_server::ls_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+use super::parser::{action_branches, debhelper_tokens, enables_errexit, is_shell_script};
+use super::{ScriptKind, DEBHELPER_TOKEN};
+use crate::position::Source;
+
+/// All types of diagnostic issues in a maintainer script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticIssue {
+    /// No `#DEBHELPER#` token, so debhelper's snippets are dropped.
+    MissingDebhelperToken { range: TextRange },
+    /// A second `#DEBHELPER#` token, which runs the snippets twice.
+    DuplicateDebhelperToken { range: TextRange },
+    /// A shell script that does not enable `set -e`.
+    MissingSetE { range: TextRange },
+    /// A `case "$1"` branch matching an action dpkg never passes.
+    UnknownAction { action: String, range: TextRange },
+}
+
+fn text_range(range: std::ops::Range<usize>) -> TextRange {
+    TextRange::new(
+        TextSize::from(range.start as u32),
+        TextSize::from(range.end as u32),
+    )
+}
+
+/// Find all diagnostic issues in a maintainer script.
+pub fn find_all_issues(text: &str, kind: ScriptKind) -> Vec<DiagnosticIssue> {
+    let mut issues = Vec::new();
+    // Problems with the script as a whole are reported on its first line.
+    let first_line = text_range(0..text.lines().next().map_or(0, str::len));
+
+    let tokens = debhelper_tokens(text);
+    if tokens.is_empty() {
+        issues.push(DiagnosticIssue::MissingDebhelperToken { range: first_line });
+    }
+    for token in tokens.into_iter().skip(1) {
+        issues.push(DiagnosticIssue::DuplicateDebhelperToken {
+            range: text_range(token),
+        });
+    }
+
+    if is_shell_script(text) && !enables_errexit(text) {
+        issues.push(DiagnosticIssue::MissingSetE { range: first_line });
+    }
+
+    for branch in action_branches(text) {
+        for pattern in branch.patterns {
+            if !pattern.is_glob() && kind.find_action(&pattern.text).is_none() {
+                issues.push(DiagnosticIssue::UnknownAction {
+                    action: pattern.text,
+                    range: text_range(pattern.range),
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+/// Convert a DiagnosticIssue to an LSP Diagnostic.
+pub fn issue_to_diagnostic(
+    issue: DiagnosticIssue,
+    kind: ScriptKind,
+    src: Source<'_>,
+) -> Diagnostic {
+    let (range, severity, code, message) = match issue {
+        DiagnosticIssue::MissingDebhelperToken { range } => (
+            range,
+            DiagnosticSeverity::WARNING,
+            "missing-debhelper-token",
+            format!(
+                "No {} token; snippets generated by debhelper will not be included",
+                DEBHELPER_TOKEN
+            ),
+        ),
+        DiagnosticIssue::DuplicateDebhelperToken { range } => (
+            range,
+            DiagnosticSeverity::ERROR,
+            "duplicate-debhelper-token",
+            format!(
+                "{} appears more than once; the debhelper snippets would run twice",
+                DEBHELPER_TOKEN
+            ),
+        ),
+        DiagnosticIssue::MissingSetE { range } => (
+            range,
+            DiagnosticSeverity::WARNING,
+            "missing-set-e",
+            "Maintainer script does not use 'set -e'".to_string(),
+        ),
+        DiagnosticIssue::UnknownAction { action, range } => (
+            range,
+            DiagnosticSeverity::WARNING,
+            "unknown-action",
+            format!("{} is never called with '{}'", kind.name(), action),
+        ),
+    };
+
+    Diagnostic {
+        range: src.text_range_to_lsp_range(range),
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some("debian-lsp".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Get all LSP diagnostics for a maintainer script.
+pub fn get_diagnostics(src: Source<'_>, kind: ScriptKind) -> Vec<Diagnostic> {
+    find_all_issues(src.text, kind)
+        .into_iter()
+        .map(|issue| issue_to_diagnostic(issue, kind, src))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_script_has_no_issues() {
+        let text = "\
+#!/bin/sh
+set -e
+
+case \"$1\" in
+    purge|remove)
+    ;;
+    *)
+    ;;
+esac
+
+#DEBHELPER#
+";
+        assert_eq!(find_all_issues(text, ScriptKind::Postrm), vec![]);
+    }
+
+    #[test]
+    fn flags_a_missing_token_on_the_first_line() {
+        let text = "#!/bin/sh\nset -e\n";
+        assert_eq!(
+            find_all_issues(text, ScriptKind::Postinst),
+            vec![DiagnosticIssue::MissingDebhelperToken {
+                range: TextRange::new(0.into(), 9.into())
+            }]
+        );
+    }
+
+    #[test]
+    fn flags_a_duplicate_token() {
+        let text = "#!/bin/sh\nset -e\n#DEBHELPER#\n#DEBHELPER#\n";
+        assert_eq!(
+            find_all_issues(text, ScriptKind::Postinst),
+            vec![DiagnosticIssue::DuplicateDebhelperToken {
+                range: TextRange::new(29.into(), 40.into())
+            }]
+        );
+    }
+
+    #[test]
+    fn flags_missing_set_e() {
+        let text = "#!/bin/sh\n#DEBHELPER#\n";
+        assert_eq!(
+            find_all_issues(text, ScriptKind::Prerm),
+            vec![DiagnosticIssue::MissingSetE {
+                range: TextRange::new(0.into(), 9.into())
+            }]
+        );
+    }
+
+    #[test]
+    fn perl_scripts_need_no_set_e() {
+        let text = "#!/usr/bin/perl\n#DEBHELPER#\n";
+        assert_eq!(find_all_issues(text, ScriptKind::Postinst), vec![]);
+    }
+
+    #[test]
+    fn flags_an_action_from_another_script() {
+        let text = "#!/bin/sh -e\ncase \"$1\" in\n  purge) ;;\nesac\n#DEBHELPER#\n";
+        assert_eq!(
+            find_all_issues(text, ScriptKind::Postinst),
+            vec![DiagnosticIssue::UnknownAction {
+                action: "purge".to_string(),
+                range: TextRange::new(28.into(), 33.into())
+            }]
+        );
+    }
+
+    #[test]
+    fn diagnostics_carry_codes() {
+        let text = "#!/bin/sh\n";
+        let idx = crate::position::LineIndex::new(text);
+        let diags = get_diagnostics(Source::new(text, &idx), ScriptKind::Preinst);
+        let codes: Vec<_> = diags
+            .iter()
+            .map(|d| match &d.code {
+                Some(NumberOrString::String(s)) => s.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(codes, vec!["missing-debhelper-token", "missing-set-e"]);
+    }
+}
@@ -0,0 +1,91 @@
+use tower_lsp_server::ls_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+
+use super::{ScriptKind, DEBHELPER_TOKEN};
+use crate::position::Source;
+
+/// Get hover information for a maintainer script at the given cursor position.
+pub fn get_hover(src: Source<'_>, kind: ScriptKind, position: Position) -> Option<Hover> {
+    let line = src.text.lines().nth(position.line as usize)?;
+    let line_start = src.try_position_to_offset(Position::new(position.line, 0))?;
+    let offset = usize::from(src.try_position_to_offset(position)? - line_start);
+
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '#';
+    let start = line[..offset].rfind(|c| !is_word(c)).map_or(0, |i| i + 1);
+    let end = line[offset..]
+        .find(|c| !is_word(c))
+        .map_or(line.len(), |i| offset + i);
+    let word = &line[start..end];
+
+    let value = if word == DEBHELPER_TOKEN {
+        format!(
+            "**{}**\n\nReplaced by dh_installdeb with the snippets other debhelper \
+             commands generate for this script.",
+            DEBHELPER_TOKEN
+        )
+    } else {
+        let action = kind.find_action(word)?;
+        format!(
+            "**{}** ({})\n\n{}\n\n`{}`",
+            action.name,
+            kind.name(),
+            action.description,
+            action.invocation
+        )
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+
+    fn hover(text: &str, kind: ScriptKind, line: u32, col: u32) -> Option<String> {
+        let idx = LineIndex::new(text);
+        let src = Source::new(text, &idx);
+        get_hover(src, kind, Position::new(line, col)).map(|h| match h.contents {
+            HoverContents::Markup(m) => m.value,
+            _ => panic!("expected markup"),
+        })
+    }
+
+    #[test]
+    fn hovers_an_action_in_a_pattern() {
+        let text = "case \"$1\" in\n    abort-upgrade|abort-remove)\n";
+        assert_eq!(
+            hover(text, ScriptKind::Postinst, 1, 20).unwrap(),
+            "**abort-remove** (postinst)\n\nRemoval of the package failed or was aborted \
+             in favour of a conflicting package; restore it to the configured state.\n\n\
+             `postinst abort-remove [in-favour package new-version]`"
+        );
+    }
+
+    #[test]
+    fn description_depends_on_the_script() {
+        let text = "    upgrade)\n";
+        assert!(hover(text, ScriptKind::Prerm, 0, 6)
+            .unwrap()
+            .contains("old-prerm upgrade new-version"));
+        assert!(hover(text, ScriptKind::Preinst, 0, 6)
+            .unwrap()
+            .contains("new-preinst upgrade old-version new-version"));
+    }
+
+    #[test]
+    fn hovers_the_debhelper_token() {
+        let h = hover("#DEBHELPER#\n", ScriptKind::Postrm, 0, 3).unwrap();
+        assert!(h.starts_with("**#DEBHELPER#**"));
+    }
+
+    #[test]
+    fn nothing_for_an_action_of_another_script() {
+        assert_eq!(hover("    purge)\n", ScriptKind::Postinst, 0, 6), None);
+    }
+}
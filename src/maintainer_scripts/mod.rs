@@ -0,0 +1,218 @@
+//! Support for maintainer scripts: debian/preinst, postinst, prerm, postrm
+//! and config, with or without a `<package>.` prefix.
+//!
+//! The scripts are shell (occasionally Perl) and are left to a real shell
+//! language server for general editing. This module only covers the parts
+//! that are Debian-specific: the action argument dpkg passes as `$1`, the
+//! `#DEBHELPER#` token and `set -e`.
+
+pub mod completion;
+pub mod detection;
+pub mod diagnostics;
+pub mod hover;
+pub mod parser;
+pub mod symbols;
+
+pub use completion::get_completions;
+pub use detection::is_maintainer_script_file;
+pub use diagnostics::get_diagnostics;
+pub use hover::get_hover;
+pub use symbols::generate_document_symbols;
+
+/// The token dh_installdeb replaces with generated snippets.
+pub const DEBHELPER_TOKEN: &str = "#DEBHELPER#";
+
+/// Which maintainer script a file is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptKind {
+    Preinst,
+    Postinst,
+    Prerm,
+    Postrm,
+    /// The debconf config script.
+    Config,
+}
+
+/// An action dpkg (or debconf) passes to a script as its first argument.
+pub struct Action {
+    pub name: &'static str,
+    /// How the script is invoked with this action, from Debian Policy 6.5.
+    pub invocation: &'static str,
+    pub description: &'static str,
+}
+
+const PREINST_ACTIONS: &[Action] = &[
+    Action {
+        name: "install",
+        invocation: "new-preinst install [old-version [new-version]]",
+        description: "Before the package is unpacked for the first time, or when it is \
+                      reinstalled after being removed with its configuration kept.",
+    },
+    Action {
+        name: "upgrade",
+        invocation: "new-preinst upgrade old-version new-version",
+        description: "Before a new version of the package is unpacked over an old one.",
+    },
+    Action {
+        name: "abort-upgrade",
+        invocation: "old-preinst abort-upgrade new-version",
+        description: "The new version's postrm failed during an upgrade and the old \
+                      version is being restored.",
+    },
+];
+
+const POSTINST_ACTIONS: &[Action] = &[
+    Action {
+        name: "configure",
+        invocation: "postinst configure most-recently-configured-version",
+        description: "After the package is unpacked, to configure it. The version is \
+                      empty on a first install.",
+    },
+    Action {
+        name: "abort-upgrade",
+        invocation: "old-postinst abort-upgrade new-version",
+        description: "An upgrade failed and the old version is being put back into \
+                      the configured state.",
+    },
+    Action {
+        name: "abort-remove",
+        invocation: "postinst abort-remove [in-favour package new-version]",
+        description: "Removal of the package failed or was aborted in favour of a \
+                      conflicting package; restore it to the configured state.",
+    },
+    Action {
+        name: "abort-deconfigure",
+        invocation: "deconfigured's-postinst abort-deconfigure in-favour \
+                     failed-install-package version [removing conflicting-package version]",
+        description: "Deconfiguring the package in favour of another failed; \
+                      restore it to the configured state.",
+    },
+    Action {
+        name: "triggered",
+        invocation: "postinst triggered \"trigger-name trigger-name ...\"",
+        description: "Triggers this package is interested in were activated.",
+    },
+    Action {
+        name: "reconfigure",
+        invocation: "postinst reconfigure version",
+        description: "The package is being reconfigured with dpkg-reconfigure (debconf).",
+    },
+];
+
+const PRERM_ACTIONS: &[Action] = &[
+    Action {
+        name: "remove",
+        invocation: "prerm remove",
+        description: "Before the package is removed.",
+    },
+    Action {
+        name: "upgrade",
+        invocation: "old-prerm upgrade new-version",
+        description: "Before the package is upgraded to a new version.",
+    },
+    Action {
+        name: "deconfigure",
+        invocation: "prerm deconfigure in-favour package-being-installed version \
+                     [removing conflicting-package version]",
+        description: "The package is being deconfigured so a conflicting package can \
+                      be installed.",
+    },
+    Action {
+        name: "failed-upgrade",
+        invocation: "new-prerm failed-upgrade old-version new-version",
+        description: "The old version's prerm failed during an upgrade; the new \
+                      version's prerm is run instead.",
+    },
+];
+
+const POSTRM_ACTIONS: &[Action] = &[
+    Action {
+        name: "remove",
+        invocation: "postrm remove",
+        description: "After the package's files have been removed.",
+    },
+    Action {
+        name: "purge",
+        invocation: "postrm purge",
+        description: "After the package has been removed, to also delete its \
+                      configuration files and any data it created.",
+    },
+    Action {
+        name: "upgrade",
+        invocation: "old-postrm upgrade new-version",
+        description: "After an old version's files have been replaced by a new version.",
+    },
+    Action {
+        name: "disappear",
+        invocation: "disappearer's-postrm disappear overwriter overwriter-version",
+        description: "Every file of the package was overwritten by another package.",
+    },
+    Action {
+        name: "failed-upgrade",
+        invocation: "new-postrm failed-upgrade old-version new-version",
+        description: "The old version's postrm failed during an upgrade; the new \
+                      version's postrm is run instead.",
+    },
+    Action {
+        name: "abort-install",
+        invocation: "new-postrm abort-install [old-version new-version]",
+        description: "Installing the package failed and it is being removed again.",
+    },
+    Action {
+        name: "abort-upgrade",
+        invocation: "new-postrm abort-upgrade old-version new-version",
+        description: "An upgrade failed and the new version's files are being removed \
+                      again.",
+    },
+];
+
+const CONFIG_ACTIONS: &[Action] = &[
+    Action {
+        name: "configure",
+        invocation: "config configure installed-version",
+        description: "Before postinst configure, to ask the debconf questions.",
+    },
+    Action {
+        name: "reconfigure",
+        invocation: "config reconfigure installed-version",
+        description: "The package is being reconfigured with dpkg-reconfigure.",
+    },
+];
+
+impl ScriptKind {
+    /// All script kinds, in the order dpkg runs them on an install.
+    pub const ALL: &'static [ScriptKind] = &[
+        ScriptKind::Config,
+        ScriptKind::Preinst,
+        ScriptKind::Postinst,
+        ScriptKind::Prerm,
+        ScriptKind::Postrm,
+    ];
+
+    /// The file name stem of the script.
+    pub fn name(self) -> &'static str {
+        match self {
+            ScriptKind::Preinst => "preinst",
+            ScriptKind::Postinst => "postinst",
+            ScriptKind::Prerm => "prerm",
+            ScriptKind::Postrm => "postrm",
+            ScriptKind::Config => "config",
+        }
+    }
+
+    /// The actions the script can be invoked with.
+    pub fn actions(self) -> &'static [Action] {
+        match self {
+            ScriptKind::Preinst => PREINST_ACTIONS,
+            ScriptKind::Postinst => POSTINST_ACTIONS,
+            ScriptKind::Prerm => PRERM_ACTIONS,
+            ScriptKind::Postrm => POSTRM_ACTIONS,
+            ScriptKind::Config => CONFIG_ACTIONS,
+        }
+    }
+
+    /// Look up an action by name.
+    pub fn find_action(self, name: &str) -> Option<&'static Action> {
+        self.actions().iter().find(|a| a.name == name)
+    }
+}
@@ -0,0 +1,372 @@
+//! A minimal line scanner for maintainer scripts.
+//!
+//! This is not a shell parser. It recognises the handful of constructs the
+//! Debian-specific features need: `case "$1" in ... esac` blocks dispatching
+//! on the action argument, the `#DEBHELPER#` token and `set -e`. Anything it
+//! does not understand is skipped, so a half-typed script still yields the
+//! branches around the cursor.
+//!
+//! All ranges are byte offsets into the whole script.
+
+use std::ops::Range;
+
+use super::DEBHELPER_TOKEN;
+
+/// One alternative in a `case` branch pattern, e.g. `abort-upgrade` in
+/// `abort-upgrade|abort-remove)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    /// The pattern with surrounding quotes removed.
+    pub text: String,
+    pub range: Range<usize>,
+}
+
+impl Pattern {
+    /// Whether the pattern is a glob rather than a literal action name.
+    pub fn is_glob(&self) -> bool {
+        self.text.contains(['*', '?', '['])
+    }
+}
+
+/// A branch of a `case` on the action argument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseBranch {
+    pub patterns: Vec<Pattern>,
+    /// From the start of the pattern list to the end of the terminating `;;`
+    /// line, or of the last line before `esac` when `;;` is missing.
+    pub range: Range<usize>,
+    /// The pattern list, without the closing `)`.
+    pub pattern_range: Range<usize>,
+}
+
+/// An open `case` block.
+struct Frame {
+    /// Whether the block dispatches on `$1`.
+    on_action: bool,
+    open: Option<CaseBranch>,
+}
+
+/// Whether a trimmed line opens a `case` on the action argument. Returns
+/// `None` when the line does not open a `case` at all.
+fn case_subject_is_action(trimmed: &str) -> Option<bool> {
+    let rest = trimmed.strip_prefix("case")?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let subject = rest.trim().strip_suffix("in")?.trim();
+    let subject = subject.trim_matches('"');
+    Some(subject == "$1" || subject == "${1}")
+}
+
+/// Split a pattern line into its alternatives. `offset` is the byte offset of
+/// `line` within the script. Returns the alternatives and the range of the
+/// pattern list.
+fn parse_patterns(line: &str, offset: usize) -> Option<(Vec<Pattern>, Range<usize>)> {
+    let start = line.find(|c: char| !c.is_whitespace())?;
+    if line[start..].starts_with('#') {
+        return None;
+    }
+    let close = start + line[start..].find(')')?;
+    let list_start = if line[start..].starts_with('(') {
+        start + 1
+    } else {
+        start
+    };
+
+    let mut patterns = Vec::new();
+    let mut cursor = list_start;
+    for part in line[list_start..close].split('|') {
+        let lead = part.len() - part.trim_start().len();
+        let trimmed = part.trim();
+        let unquoted = trimmed.trim_matches(|c| c == '"' || c == '\'');
+        if !unquoted.is_empty() {
+            let quote = (trimmed.len() - unquoted.len()) / 2;
+            let begin = cursor + lead + quote;
+            patterns.push(Pattern {
+                text: unquoted.to_string(),
+                range: offset + begin..offset + begin + unquoted.len(),
+            });
+        }
+        cursor += part.len() + 1;
+    }
+    Some((patterns, offset + list_start..offset + close))
+}
+
+/// The lines of `text` with the byte offset each starts at, without their
+/// line endings.
+pub fn lines_with_offsets(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_inclusive('\n').scan(0, |offset, raw| {
+        let start = *offset;
+        *offset += raw.len();
+        Some((start, raw.trim_end_matches(['\n', '\r'])))
+    })
+}
+
+/// State of the scan at a given point, used by completion to tell whether
+/// the cursor sits where a branch pattern goes.
+pub struct ScanState {
+    frames: Vec<Frame>,
+    branches: Vec<CaseBranch>,
+}
+
+impl ScanState {
+    fn new() -> Self {
+        ScanState {
+            frames: Vec::new(),
+            branches: Vec::new(),
+        }
+    }
+
+    /// Whether the next line would be read as a pattern of an action `case`.
+    pub fn expects_action_pattern(&self) -> bool {
+        matches!(self.frames.last(), Some(f) if f.on_action && f.open.is_none())
+    }
+
+    fn feed(&mut self, offset: usize, line: &str) {
+        let trimmed = line.trim();
+        let line_end = offset + line.len();
+
+        if let Some(on_action) = case_subject_is_action(trimmed) {
+            self.frames.push(Frame {
+                on_action,
+                open: None,
+            });
+            return;
+        }
+        if trimmed == "esac" || trimmed.starts_with("esac ") || trimmed.starts_with("esac;") {
+            if let Some(frame) = self.frames.pop() {
+                if let Some(branch) = frame.open {
+                    self.close(frame.on_action, branch);
+                }
+            }
+            return;
+        }
+
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        if frame.open.is_none() {
+            if let Some((patterns, pattern_range)) = parse_patterns(line, offset) {
+                frame.open = Some(CaseBranch {
+                    patterns,
+                    range: pattern_range.start..line_end,
+                    pattern_range,
+                });
+            } else {
+                return;
+            }
+        }
+
+        let on_action = frame.on_action;
+        if let Some(branch) = frame.open.as_mut() {
+            branch.range.end = line_end;
+        }
+        if trimmed.ends_with(";;") || trimmed.contains(";; ") {
+            let branch = frame.open.take().unwrap();
+            self.close(on_action, branch);
+        }
+    }
+
+    fn close(&mut self, on_action: bool, branch: CaseBranch) {
+        if on_action {
+            self.branches.push(branch);
+        }
+    }
+}
+
+/// Scan `text` up to (not including) the line starting at `stop`.
+pub fn scan_until(text: &str, stop: usize) -> ScanState {
+    let mut state = ScanState::new();
+    for (offset, line) in lines_with_offsets(text) {
+        if offset >= stop {
+            break;
+        }
+        state.feed(offset, line);
+    }
+    state
+}
+
+/// The branches of every `case` on the action argument, in source order of
+/// their end.
+pub fn action_branches(text: &str) -> Vec<CaseBranch> {
+    let mut state = scan_until(text, usize::MAX);
+    // Branches still open at the end of a truncated script.
+    while let Some(frame) = state.frames.pop() {
+        if let Some(branch) = frame.open {
+            state.close(frame.on_action, branch);
+        }
+    }
+    state.branches.sort_by_key(|b| b.range.start);
+    state.branches
+}
+
+/// Byte ranges of every `#DEBHELPER#` token.
+pub fn debhelper_tokens(text: &str) -> Vec<Range<usize>> {
+    text.match_indices(DEBHELPER_TOKEN)
+        .map(|(start, token)| start..start + token.len())
+        .collect()
+}
+
+/// Whether the script is a shell script. A script without a `#!` line is
+/// assumed to be one.
+pub fn is_shell_script(text: &str) -> bool {
+    match text.lines().next().and_then(|l| l.strip_prefix("#!")) {
+        Some(interpreter) => interpreter
+            .split_whitespace()
+            .next()
+            .and_then(|path| path.rsplit('/').next())
+            .is_some_and(|name| matches!(name, "sh" | "bash" | "dash")),
+        None => true,
+    }
+}
+
+/// Whether a `set` line or the `#!` line turns on `errexit`.
+pub fn enables_errexit(text: &str) -> bool {
+    let has_e_flag = |args: &str| {
+        args.split_whitespace()
+            .take_while(|a| !a.starts_with('#'))
+            .any(|a| {
+                a == "-o" && args.contains("errexit")
+                    || (a.starts_with('-') && !a.starts_with("--") && a.contains('e'))
+            })
+    };
+
+    let mut lines = text.lines();
+    if let Some(interpreter) = lines.next().and_then(|l| l.strip_prefix("#!")) {
+        let mut words = interpreter.split_whitespace();
+        words.next();
+        if has_e_flag(&words.collect::<Vec<_>>().join(" ")) {
+            return true;
+        }
+    }
+    text.lines()
+        .any(|line| line.trim().strip_prefix("set ").is_some_and(&has_e_flag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POSTINST: &str = "\
+#!/bin/sh
+set -e
+
+case \"$1\" in
+    configure)
+        update-alternatives --install /usr/bin/foo foo /usr/bin/foo.real 10
+    ;;
+
+    abort-upgrade|abort-remove|abort-deconfigure)
+    ;;
+
+    *)
+        echo \"postinst called with unknown argument '$1'\" >&2
+        exit 1
+    ;;
+esac
+
+#DEBHELPER#
+
+exit 0
+";
+
+    fn pattern_texts(branch: &CaseBranch) -> Vec<&str> {
+        branch.patterns.iter().map(|p| p.text.as_str()).collect()
+    }
+
+    #[test]
+    fn finds_the_action_branches() {
+        let branches = action_branches(POSTINST);
+        assert_eq!(branches.len(), 3);
+        assert_eq!(pattern_texts(&branches[0]), vec!["configure"]);
+        assert_eq!(
+            pattern_texts(&branches[1]),
+            vec!["abort-upgrade", "abort-remove", "abort-deconfigure"]
+        );
+        assert_eq!(pattern_texts(&branches[2]), vec!["*"]);
+        assert!(branches[2].patterns[0].is_glob());
+    }
+
+    #[test]
+    fn branch_ranges_cover_pattern_through_terminator() {
+        let branches = action_branches(POSTINST);
+        let configure = &branches[0];
+        assert_eq!(
+            &POSTINST[configure.range.clone()],
+            "configure)\n        update-alternatives --install /usr/bin/foo foo /usr/bin/foo.real 10\n    ;;"
+        );
+        assert_eq!(&POSTINST[configure.pattern_range.clone()], "configure");
+        let abort = &branches[1].patterns[1];
+        assert_eq!(&POSTINST[abort.range.clone()], "abort-remove");
+    }
+
+    #[test]
+    fn ignores_cases_on_other_subjects() {
+        let text = "case \"$2\" in\n  1.0) echo ;;\nesac\n";
+        assert_eq!(action_branches(text), vec![]);
+    }
+
+    #[test]
+    fn nested_case_does_not_leak_branches() {
+        let text = "\
+case $1 in
+  configure)
+    case \"$2\" in
+      1.*) echo old ;;
+    esac
+  ;;
+esac
+";
+        let branches = action_branches(text);
+        assert_eq!(branches.len(), 1);
+        assert_eq!(pattern_texts(&branches[0]), vec!["configure"]);
+    }
+
+    #[test]
+    fn quoted_patterns_and_same_line_terminator() {
+        let text = "case \"${1}\" in\n  \"purge\"|remove) rm -rf /var/lib/foo ;;\nesac\n";
+        let branches = action_branches(text);
+        assert_eq!(pattern_texts(&branches[0]), vec!["purge", "remove"]);
+        assert_eq!(&text[branches[0].patterns[0].range.clone()], "purge");
+    }
+
+    #[test]
+    fn unterminated_branch_is_still_reported() {
+        let text = "case \"$1\" in\n  configure)\n    echo hi\n";
+        let branches = action_branches(text);
+        assert_eq!(branches.len(), 1);
+        assert_eq!(&text[branches[0].range.clone()], "configure)\n    echo hi");
+    }
+
+    #[test]
+    fn expects_a_pattern_between_branches() {
+        let stop = POSTINST.find("    abort-upgrade").unwrap();
+        assert!(scan_until(POSTINST, stop).expects_action_pattern());
+        let stop = POSTINST.find("        update-alternatives").unwrap();
+        assert!(!scan_until(POSTINST, stop).expects_action_pattern());
+    }
+
+    #[test]
+    fn finds_debhelper_tokens() {
+        assert_eq!(debhelper_tokens(POSTINST), vec![285..296]);
+        assert_eq!(&POSTINST[285..296], "#DEBHELPER#");
+    }
+
+    #[test]
+    fn detects_shell_scripts() {
+        assert!(is_shell_script("#!/bin/sh\n"));
+        assert!(is_shell_script("#! /bin/bash -e\n"));
+        assert!(is_shell_script("set -e\n"));
+        assert!(!is_shell_script("#!/usr/bin/perl\n"));
+    }
+
+    #[test]
+    fn detects_errexit() {
+        assert!(enables_errexit("#!/bin/sh\nset -e\n"));
+        assert!(enables_errexit("#!/bin/sh\n  set -eu\n"));
+        assert!(enables_errexit("#!/bin/sh -e\n"));
+        assert!(enables_errexit("#!/bin/sh\nset -o errexit\n"));
+        assert!(!enables_errexit("#!/bin/sh\nset -u\n"));
+        assert!(!enables_errexit("#!/bin/sh\n# set -e\n"));
+    }
+}
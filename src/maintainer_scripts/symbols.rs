@@ -0,0 +1,86 @@
+//! Document symbols for maintainer scripts.
+//!
+//! Each branch of a `case "$1" in` block becomes a symbol named after the
+//! actions it handles, so the outline shows what the script does for each
+//! way dpkg can invoke it.
+
+use text_size::{TextRange, TextSize};
+use tower_lsp_server::ls_types::{DocumentSymbol, SymbolKind};
+
+use super::parser::action_branches;
+use crate::position::Source;
+
+fn text_range(range: std::ops::Range<usize>) -> TextRange {
+    TextRange::new(
+        TextSize::from(range.start as u32),
+        TextSize::from(range.end as u32),
+    )
+}
+
+/// Generate one document symbol per action branch of a maintainer script.
+#[allow(deprecated)] // DocumentSymbol::deprecated is required by the LSP type
+pub fn generate_document_symbols(src: Source<'_>) -> Vec<DocumentSymbol> {
+    action_branches(src.text)
+        .into_iter()
+        .filter(|branch| !branch.patterns.is_empty())
+        .map(|branch| {
+            let name = branch
+                .patterns
+                .iter()
+                .map(|p| p.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" | ");
+            DocumentSymbol {
+                name,
+                detail: None,
+                kind: SymbolKind::EVENT,
+                tags: None,
+                deprecated: None,
+                range: src.text_range_to_lsp_range(text_range(branch.range)),
+                selection_range: src.text_range_to_lsp_range(text_range(branch.pattern_range)),
+                children: None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+    use tower_lsp_server::ls_types::{Position, Range};
+
+    fn run(text: &str) -> Vec<DocumentSymbol> {
+        let idx = LineIndex::new(text);
+        generate_document_symbols(Source::new(text, &idx))
+    }
+
+    #[test]
+    fn one_symbol_per_branch() {
+        let text = "\
+case \"$1\" in
+    configure)
+        true
+    ;;
+    abort-upgrade|abort-remove)
+    ;;
+esac
+";
+        let symbols = run(text);
+        let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["configure", "abort-upgrade | abort-remove"]);
+        assert_eq!(
+            symbols[0].range,
+            Range::new(Position::new(1, 4), Position::new(3, 6))
+        );
+        assert_eq!(
+            symbols[0].selection_range,
+            Range::new(Position::new(1, 4), Position::new(1, 13))
+        );
+    }
+
+    #[test]
+    fn no_symbols_without_a_case() {
+        assert!(run("#!/bin/sh\nset -e\n#DEBHELPER#\n").is_empty());
+    }
+}
@@ -41,18 +41,22 @@ impl MaintainerCache {
     }
 
     async fn fetch_maintainers(&mut self) {
+        let Some(pool) = self.pool.get() else {
+            return;
+        };
         let rows: Vec<MaintainerRow> = match sqlx::query_as(
             "SELECT DISTINCT maintainer FROM sources \
              WHERE release = 'sid' \
              ORDER BY maintainer \
              LIMIT 5000",
         )
-        .fetch_all(&*self.pool)
+        .fetch_all(&pool)
         .await
         {
             Ok(rows) => rows,
             Err(e) => {
                 tracing::warn!(error = %e, "UDD maintainer query failed");
+                self.pool.report_error(&e);
                 self.maintainers = Some(Vec::new());
                 return;
             }
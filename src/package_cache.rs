@@ -37,8 +37,23 @@ pub trait PackageCache: Send + Sync {
     /// Get already-cached providers for a package, without triggering a lookup.
     fn get_cached_providers(&self, package: &str) -> Option<&[String]>;
 
+    /// Cache providers looked up elsewhere, e.g. with [`query_providers`].
+    fn insert_providers(&mut self, providers: HashMap<String, Vec<String>>);
+
     /// Insert a package name with its short description into the cache.
     fn insert_package(&mut self, name: String, description: String);
+
+    /// Whether a package with this exact name is in the cache.
+    fn has_package(&self, package: &str) -> bool;
+
+    /// Whether the full package list has been loaded.
+    ///
+    /// While the list is still streaming in, a missing package may simply
+    /// not have been read yet, so callers should not treat it as unknown.
+    fn is_complete(&self) -> bool;
+
+    /// Mark the package list as fully loaded.
+    fn mark_complete(&mut self);
 }
 
 /// Thread-safe shared package cache.
@@ -54,6 +69,8 @@ pub struct AptPackageCache {
     versions: HashMap<String, Vec<VersionInfo>>,
     /// Cached providers for virtual packages.
     providers: HashMap<String, Vec<String>>,
+    /// Whether `apt-cache search` has finished streaming the package list.
+    complete: bool,
 }
 
 impl AptPackageCache {
@@ -64,6 +81,7 @@ impl AptPackageCache {
             descriptions: HashMap::new(),
             versions: HashMap::new(),
             providers: HashMap::new(),
+            complete: false,
         }
     }
 }
@@ -239,62 +257,13 @@ impl PackageCache for AptPackageCache {
     }
 
     async fn load_providers_batch(&mut self, packages: &[String]) {
-        let uncached: Vec<&String> = packages
+        let uncached: Vec<String> = packages
             .iter()
             .filter(|p| !self.providers.contains_key(p.as_str()))
+            .cloned()
             .collect();
-        if uncached.is_empty() {
-            return;
-        }
-
-        let Ok(output) = Command::new("apt-cache")
-            .arg("showpkg")
-            .args(&uncached)
-            .output()
-            .await
-        else {
-            return;
-        };
-        if !output.status.success() {
-            return;
-        }
-
-        // Initialize empty entries so we don't re-query.
-        for pkg in &uncached {
-            self.providers.entry(pkg.to_string()).or_default();
-        }
-
-        let text = String::from_utf8_lossy(&output.stdout);
-        let mut current_package: Option<String> = None;
-        let mut in_reverse_provides = false;
-
-        for line in text.lines() {
-            if let Some(name) = line.strip_prefix("Package: ") {
-                current_package = Some(name.to_string());
-                in_reverse_provides = false;
-            } else if line.starts_with("Reverse Provides:") {
-                in_reverse_provides = true;
-            } else if line.starts_with("Versions:")
-                || line.starts_with("Reverse Depends:")
-                || line.starts_with("Dependencies:")
-                || line.starts_with("Provides:")
-            {
-                in_reverse_provides = false;
-            } else if in_reverse_provides {
-                if let (Some(pkg), Some(name)) = (&current_package, line.split_whitespace().next())
-                {
-                    let providers = self.providers.entry(pkg.clone()).or_default();
-                    let name = name.to_string();
-                    if !providers.contains(&name) {
-                        providers.push(name);
-                    }
-                }
-            }
-        }
-
-        // Sort providers for consistent display
-        for providers in self.providers.values_mut() {
-            providers.sort();
+        if let Some(providers) = query_providers(&uncached).await {
+            self.insert_providers(providers);
         }
     }
 
@@ -306,11 +275,114 @@ impl PackageCache for AptPackageCache {
         self.providers.get(package).map(|v| v.as_slice())
     }
 
+    fn insert_providers(&mut self, providers: HashMap<String, Vec<String>>) {
+        self.providers.extend(providers);
+    }
+
     fn insert_package(&mut self, name: String, description: String) {
         let pos = self.packages.binary_search(&name).unwrap_or_else(|p| p);
         self.packages.insert(pos, name.clone());
         self.descriptions.insert(name, description);
     }
+
+    fn has_package(&self, package: &str) -> bool {
+        self.packages
+            .binary_search_by(|p| p.as_str().cmp(package))
+            .is_ok()
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    fn mark_complete(&mut self) {
+        self.complete = true;
+    }
+}
+
+/// Look up the providers of `packages` with `apt-cache showpkg`. Every
+/// package gets an entry, empty if nothing provides it, so it isn't queried
+/// again. `None` if `apt-cache` could not be run.
+pub async fn query_providers(packages: &[String]) -> Option<HashMap<String, Vec<String>>> {
+    if packages.is_empty() {
+        return Some(HashMap::new());
+    }
+    let output = Command::new("apt-cache")
+        .arg("showpkg")
+        .args(packages)
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_reverse_provides(
+        packages,
+        &String::from_utf8_lossy(&output.stdout),
+    ))
+}
+
+/// Parse the `Reverse Provides:` sections of `apt-cache showpkg` output.
+fn parse_reverse_provides(packages: &[String], text: &str) -> HashMap<String, Vec<String>> {
+    let mut result: HashMap<String, Vec<String>> = packages
+        .iter()
+        .map(|pkg| (pkg.clone(), Vec::new()))
+        .collect();
+    let mut current_package: Option<String> = None;
+    let mut in_reverse_provides = false;
+
+    for line in text.lines() {
+        if let Some(name) = line.strip_prefix("Package: ") {
+            current_package = Some(name.to_string());
+            in_reverse_provides = false;
+        } else if line.starts_with("Reverse Provides:") {
+            in_reverse_provides = true;
+        } else if line.starts_with("Versions:")
+            || line.starts_with("Reverse Depends:")
+            || line.starts_with("Dependencies:")
+            || line.starts_with("Provides:")
+        {
+            in_reverse_provides = false;
+        } else if in_reverse_provides {
+            if let (Some(pkg), Some(name)) = (&current_package, line.split_whitespace().next()) {
+                let providers = result.entry(pkg.clone()).or_default();
+                let name = name.to_string();
+                if !providers.contains(&name) {
+                    providers.push(name);
+                }
+            }
+        }
+    }
+
+    // Sort providers for consistent display
+    for providers in result.values_mut() {
+        providers.sort();
+    }
+    result
+}
+
+/// Look up and cache the providers of those of `packages` that aren't cached
+/// yet. The cache is not locked while `apt-cache` runs. Returns whether any
+/// providers were added.
+pub async fn resolve_providers(cache: &SharedPackageCache, packages: &[String]) -> bool {
+    let uncached: Vec<String> = {
+        let cache = cache.read().await;
+        packages
+            .iter()
+            .filter(|p| cache.get_cached_providers(p).is_none())
+            .cloned()
+            .collect()
+    };
+    if uncached.is_empty() {
+        return false;
+    }
+    match query_providers(&uncached).await {
+        Some(providers) => {
+            cache.write().await.insert_providers(providers);
+            true
+        }
+        None => false,
+    }
 }
 
 /// Create a new shared cache backed by apt-cache.
@@ -345,6 +417,12 @@ pub async fn stream_packages_into(cache: &SharedPackageCache) {
                 .insert_package(name.to_string(), description.to_string());
         }
     }
+
+    // Only a successful run gives a complete list; a failed `apt-cache`
+    // leaves the cache partial so nothing is reported as missing.
+    if matches!(child.wait().await, Ok(status) if status.success()) {
+        cache.write().await.mark_complete();
+    }
 }
 
 #[cfg(test)]
@@ -401,9 +479,24 @@ impl PackageCache for TestPackageCache {
         self.providers.get(package).map(|v| v.as_slice())
     }
 
+    fn insert_providers(&mut self, providers: HashMap<String, Vec<String>>) {
+        self.providers.extend(providers);
+    }
+
     fn insert_package(&mut self, name: String, description: String) {
         self.packages.push((name, Some(description)));
     }
+
+    fn has_package(&self, package: &str) -> bool {
+        self.packages.iter().any(|(name, _)| name == package)
+    }
+
+    fn is_complete(&self) -> bool {
+        // Test cache is pre-populated.
+        true
+    }
+
+    fn mark_complete(&mut self) {}
 }
 
 #[cfg(test)]
@@ -441,6 +534,25 @@ mod tests {
         assert_eq!(cache.get_description("cmake"), Some("cross-platform make"));
     }
 
+    #[test]
+    fn test_has_package() {
+        let mut cache = AptPackageCache::new();
+        cache.insert_package("debhelper".to_string(), "helper tools".to_string());
+        cache.insert_package("cmake".to_string(), "cross-platform make".to_string());
+        assert!(cache.has_package("debhelper"));
+        assert!(cache.has_package("cmake"));
+        assert!(!cache.has_package("debhelper-compat"));
+        assert!(!cache.has_package("deb"));
+    }
+
+    #[test]
+    fn test_is_complete() {
+        let mut cache = AptPackageCache::new();
+        assert!(!cache.is_complete());
+        cache.mark_complete();
+        assert!(cache.is_complete());
+    }
+
     #[test]
     fn test_test_cache_prefix() {
         let cache_arc = TestPackageCache::new_shared(&[
@@ -530,4 +642,31 @@ debhelper:
         assert_eq!(versions[1].version, "13.11.6");
         assert_eq!(versions[1].suites, vec!["bookworm"]);
     }
+
+    #[test]
+    fn test_parse_reverse_provides() {
+        let output = "\
+Package: mail-transport-agent
+Versions: 
+
+Reverse Depends: 
+  mutt,mail-transport-agent
+Dependencies: 
+Provides: 
+Reverse Provides: 
+postfix 3.9.0-1 (= )
+exim4-daemon-light 4.97-4 (= )
+postfix 3.8.5-1 (= )
+";
+        let packages = vec![
+            "mail-transport-agent".to_string(),
+            "nonexistent".to_string(),
+        ];
+        let providers = parse_reverse_provides(&packages, output);
+        assert_eq!(
+            providers["mail-transport-agent"],
+            vec!["exim4-daemon-light", "postfix"]
+        );
+        assert_eq!(providers["nonexistent"], Vec::<String>::new());
+    }
 }
@@ -9,5 +9,7 @@ pub use completion::*;
 pub use detection::{is_patch_file, is_patches_series_file};
 
 pub mod semantic;
+pub mod symbols;
 pub use definition::goto_definition;
 pub use semantic::generate_semantic_tokens;
+pub use symbols::generate_document_symbols;
@@ -0,0 +1,47 @@
+//! Document symbol generation for debian/patches/series files.
+
+use crate::position::Source;
+use patchkit::edit::series::lossless::SeriesFile;
+use tower_lsp_server::ls_types::{DocumentSymbol, SymbolKind};
+
+/// Generate document symbols for a series file.
+///
+/// Each patch entry becomes a FILE symbol named after the patch.
+#[allow(deprecated)] // DocumentSymbol::deprecated field
+pub fn generate_document_symbols(series: &SeriesFile, src: Source<'_>) -> Vec<DocumentSymbol> {
+    series
+        .patch_entries()
+        .filter_map(|entry| {
+            let name = entry.name()?;
+            let token = entry.name_token()?;
+            let range = src.text_range_to_lsp_range(token.text_range());
+            Some(DocumentSymbol {
+                name,
+                detail: None,
+                kind: SymbolKind::FILE,
+                tags: None,
+                deprecated: None,
+                range,
+                selection_range: range,
+                children: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patch_entries() {
+        let text = "# applied in order\nfix-build.patch\nupstream/cve.patch -p1\n";
+        let parsed = patchkit::edit::series::parse(text);
+        let idx = crate::position::LineIndex::new(text);
+        let symbols = generate_document_symbols(&parsed.tree(), Source::new(text, &idx));
+
+        let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["fix-build.patch", "upstream/cve.patch"]);
+        assert_eq!(symbols[1].range.start.line, 2);
+    }
+}
@@ -2,12 +2,16 @@
 //!
 //! Queries the `popcon` table to find install counts for packages.
 
+use std::collections::HashSet;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Duration;
 
 use lru::LruCache;
 use tokio::sync::RwLock;
 
+use crate::disk_cache::DiskCache;
+
 /// Thread-safe shared cache for popcon lookups.
 pub type SharedPopconCache = Arc<RwLock<PopconCache>>;
 
@@ -16,11 +20,19 @@ pub type SharedPopconCache = Arc<RwLock<PopconCache>>;
 /// growing the cache.
 const POPCON_CACHE_CAPACITY: usize = 4096;
 
+/// How long install counts are kept on disk before being re-queried. Popcon
+/// moves slowly.
+const POPCON_DISK_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
 /// Cached popcon data from UDD.
 pub struct PopconCache {
     pool: crate::udd::SharedPool,
     /// Map from package name to install count. `None` means "looked up, not found".
     inst_by_package: LruCache<String, Option<u32>>,
+    disk: DiskCache,
+    /// Packages whose count is an expired on-disk entry, used because UDD
+    /// could not be reached.
+    stale: HashSet<String>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -36,19 +48,11 @@ impl PopconCache {
             inst_by_package: LruCache::new(
                 NonZeroUsize::new(POPCON_CACHE_CAPACITY).expect("non-zero capacity"),
             ),
+            disk: DiskCache::open("popcon", POPCON_DISK_TTL),
+            stale: HashSet::new(),
         }
     }
 
-    /// Look up the install count for a package, fetching if needed.
-    ///
-    /// Returns `None` if the package is not found in popcon or the query fails.
-    pub async fn get_inst_count(&mut self, package: &str) -> Option<u32> {
-        if !self.inst_by_package.contains(package) {
-            self.fetch_inst_count(package).await;
-        }
-        self.inst_by_package.get(package).and_then(|v| *v)
-    }
-
     /// Look up the install count from cache only, without fetching.
     /// Does not promote the entry in the LRU.
     ///
@@ -62,29 +66,17 @@ impl PopconCache {
         self.inst_by_package.contains(package)
     }
 
-    async fn fetch_inst_count(&mut self, package: &str) {
-        let row: Option<PopconRow> =
-            match sqlx::query_as("SELECT insts FROM popcon WHERE package = $1 LIMIT 1")
-                .bind(package)
-                .fetch_optional(&*self.pool)
-                .await
-            {
-                Ok(row) => row,
-                Err(e) => {
-                    tracing::warn!(package, error = %e, "UDD popcon query failed");
-                    return;
-                }
-            };
+    /// Returns `true` if the cached count is out of date because UDD could
+    /// not be reached.
+    pub fn is_stale(&self, package: &str) -> bool {
+        self.stale.contains(package)
+    }
 
-        match row {
-            Some(PopconRow { insts: Some(n) }) => {
-                self.inst_by_package
-                    .put(package.to_string(), u32::try_from(n).ok());
-            }
-            _ => {
-                self.inst_by_package.put(package.to_string(), None);
-            }
-        }
+    /// Use `disk` instead of the user's cache directory.
+    #[cfg(test)]
+    pub(crate) fn with_disk_cache(mut self, disk: DiskCache) -> Self {
+        self.disk = disk;
+        self
     }
 
     /// Insert a cached entry for testing purposes.
@@ -101,6 +93,49 @@ impl PopconCache {
     }
 }
 
+/// Look up the install count for a package, fetching if needed.
+///
+/// The cache lock is not held while the on-disk cache or UDD are read.
+/// Returns `None` if the package is not found in popcon or the query fails.
+pub async fn get_inst_count(cache: &SharedPopconCache, package: &str) -> Option<u32> {
+    let (pool, disk) = {
+        let mut guard = cache.write().await;
+        if let Some(count) = guard.inst_by_package.get(package) {
+            return *count;
+        }
+        (guard.pool.clone(), guard.disk.clone())
+    };
+    let query = async {
+        let udd = pool.get()?;
+        let row: Option<PopconRow> =
+            match sqlx::query_as("SELECT insts FROM popcon WHERE package = $1 LIMIT 1")
+                .bind(package)
+                .fetch_optional(&udd)
+                .await
+            {
+                Ok(row) => row,
+                Err(e) => {
+                    tracing::warn!(package, error = %e, "UDD popcon query failed");
+                    pool.report_error(&e);
+                    return None;
+                }
+            };
+        Some(match row {
+            Some(PopconRow { insts: Some(n) }) => u32::try_from(n).ok(),
+            _ => None,
+        })
+    };
+    let (count, stale) = disk.fetch(&pool.url(), package, query).await?;
+    let mut guard = cache.write().await;
+    if stale {
+        guard.stale.insert(package.to_string());
+    } else {
+        guard.stale.remove(package);
+    }
+    guard.inst_by_package.put(package.to_string(), count);
+    count
+}
+
 /// Create a new shared popcon cache.
 pub fn new_shared_popcon_cache(pool: crate::udd::SharedPool) -> SharedPopconCache {
     Arc::new(RwLock::new(PopconCache::new(pool)))
@@ -112,19 +147,45 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_inst_count_from_cache() {
-        let mut cache = PopconCache::new(crate::udd::shared_pool());
-        cache.insert_cached("hello", 42000);
+        let cache = new_shared_popcon_cache(crate::udd::shared_pool());
+        cache.write().await.insert_cached("hello", 42000);
 
-        let count = cache.get_inst_count("hello").await;
+        let count = get_inst_count(&cache, "hello").await;
         assert_eq!(count, Some(42000));
     }
 
     #[tokio::test]
     async fn test_get_inst_count_unknown_package() {
-        let mut cache = PopconCache::new(crate::udd::shared_pool());
-        cache.insert_cached_missing("nonexistent-package-xyz");
+        let cache = new_shared_popcon_cache(crate::udd::shared_pool());
+        cache
+            .write()
+            .await
+            .insert_cached_missing("nonexistent-package-xyz");
 
-        let count = cache.get_inst_count("nonexistent-package-xyz").await;
+        let count = get_inst_count(&cache, "nonexistent-package-xyz").await;
         assert_eq!(count, None);
     }
+
+    #[tokio::test]
+    async fn test_get_inst_count_from_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = crate::udd::shared_pool();
+        pool.configure(crate::udd::DEFAULT_UDD_URL, true);
+
+        DiskCache::in_dir(dir.path().to_path_buf(), POPCON_DISK_TTL)
+            .store(crate::udd::DEFAULT_UDD_URL, "hello", &Some(42u32))
+            .await;
+        let cache = Arc::new(RwLock::new(PopconCache::new(pool.clone()).with_disk_cache(
+            DiskCache::in_dir(dir.path().to_path_buf(), POPCON_DISK_TTL),
+        )));
+        assert_eq!(get_inst_count(&cache, "hello").await, Some(42));
+        assert!(!cache.read().await.is_stale("hello"));
+
+        // Expired entries are still used offline, but flagged as stale.
+        let cache = Arc::new(RwLock::new(PopconCache::new(pool).with_disk_cache(
+            DiskCache::in_dir(dir.path().to_path_buf(), Duration::ZERO),
+        )));
+        assert_eq!(get_inst_count(&cache, "hello").await, Some(42));
+        assert!(cache.read().await.is_stale("hello"));
+    }
 }
@@ -2,12 +2,16 @@
 //!
 //! Queries the `depends` table to count how many packages depend on a given package.
 
+use std::collections::HashSet;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Duration;
 
 use lru::LruCache;
 use tokio::sync::RwLock;
 
+use crate::disk_cache::DiskCache;
+
 /// Thread-safe shared cache for reverse dependency lookups.
 pub type SharedRdepsCache = Arc<RwLock<RdepsCache>>;
 
@@ -16,11 +20,19 @@ pub type SharedRdepsCache = Arc<RwLock<RdepsCache>>;
 /// the LRU exists to bound memory over very long sessions.
 const RDEPS_CACHE_CAPACITY: usize = 4096;
 
+/// How long reverse dependency counts are kept on disk before being
+/// re-queried.
+const RDEPS_DISK_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
 /// Cached reverse dependency counts from UDD.
 pub struct RdepsCache {
     pool: crate::udd::SharedPool,
     /// Map from package name to reverse dependency count. `None` means "looked up, not found".
     count_by_package: LruCache<String, Option<u32>>,
+    disk: DiskCache,
+    /// Packages whose count is an expired on-disk entry, used because UDD
+    /// could not be reached.
+    stale: HashSet<String>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -36,19 +48,11 @@ impl RdepsCache {
             count_by_package: LruCache::new(
                 NonZeroUsize::new(RDEPS_CACHE_CAPACITY).expect("non-zero capacity"),
             ),
+            disk: DiskCache::open("rdeps", RDEPS_DISK_TTL),
+            stale: HashSet::new(),
         }
     }
 
-    /// Look up the reverse dependency count for a package, fetching if needed.
-    ///
-    /// Returns `None` if the package is not found or the query fails.
-    pub async fn get_rdeps_count(&mut self, package: &str) -> Option<u32> {
-        if !self.count_by_package.contains(package) {
-            self.fetch_rdeps_count(package).await;
-        }
-        self.count_by_package.get(package).and_then(|v| *v)
-    }
-
     /// Look up the reverse dependency count from cache only, without fetching.
     /// Does not promote the entry in the LRU.
     ///
@@ -62,31 +66,10 @@ impl RdepsCache {
         self.count_by_package.contains(package)
     }
 
-    async fn fetch_rdeps_count(&mut self, package: &str) {
-        let row: Option<RdepsRow> = match sqlx::query_as(
-            "SELECT COUNT(DISTINCT source) AS count FROM all_packages \
-             WHERE depends LIKE '%' || $1 || '%' AND release = 'sid'",
-        )
-        .bind(package)
-        .fetch_optional(&*self.pool)
-        .await
-        {
-            Ok(row) => row,
-            Err(e) => {
-                tracing::warn!(package, error = %e, "UDD rdeps query failed");
-                return;
-            }
-        };
-
-        match row {
-            Some(RdepsRow { count: Some(n) }) => {
-                self.count_by_package
-                    .put(package.to_string(), u32::try_from(n).ok());
-            }
-            _ => {
-                self.count_by_package.put(package.to_string(), None);
-            }
-        }
+    /// Returns `true` if the cached count is out of date because UDD could
+    /// not be reached.
+    pub fn is_stale(&self, package: &str) -> bool {
+        self.stale.contains(package)
     }
 
     /// Insert a cached entry for testing purposes.
@@ -102,6 +85,51 @@ impl RdepsCache {
     }
 }
 
+/// Look up the reverse dependency count for a package, fetching if needed.
+///
+/// The cache lock is not held while the on-disk cache or UDD are read.
+/// Returns `None` if the package is not found or the query fails.
+pub async fn get_rdeps_count(cache: &SharedRdepsCache, package: &str) -> Option<u32> {
+    let (pool, disk) = {
+        let mut guard = cache.write().await;
+        if let Some(count) = guard.count_by_package.get(package) {
+            return *count;
+        }
+        (guard.pool.clone(), guard.disk.clone())
+    };
+    let query = async {
+        let udd = pool.get()?;
+        let row: Option<RdepsRow> = match sqlx::query_as(
+            "SELECT COUNT(DISTINCT source) AS count FROM all_packages \
+             WHERE depends LIKE '%' || $1 || '%' AND release = 'sid'",
+        )
+        .bind(package)
+        .fetch_optional(&udd)
+        .await
+        {
+            Ok(row) => row,
+            Err(e) => {
+                tracing::warn!(package, error = %e, "UDD rdeps query failed");
+                pool.report_error(&e);
+                return None;
+            }
+        };
+        Some(match row {
+            Some(RdepsRow { count: Some(n) }) => u32::try_from(n).ok(),
+            _ => None,
+        })
+    };
+    let (count, stale) = disk.fetch(&pool.url(), package, query).await?;
+    let mut guard = cache.write().await;
+    if stale {
+        guard.stale.insert(package.to_string());
+    } else {
+        guard.stale.remove(package);
+    }
+    guard.count_by_package.put(package.to_string(), count);
+    count
+}
+
 /// Create a new shared reverse dependencies cache.
 pub fn new_shared_rdeps_cache(pool: crate::udd::SharedPool) -> SharedRdepsCache {
     Arc::new(RwLock::new(RdepsCache::new(pool)))
@@ -113,19 +141,22 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_rdeps_count_from_cache() {
-        let mut cache = RdepsCache::new(crate::udd::shared_pool());
-        cache.insert_cached("libc6", 15000);
+        let cache = new_shared_rdeps_cache(crate::udd::shared_pool());
+        cache.write().await.insert_cached("libc6", 15000);
 
-        let count = cache.get_rdeps_count("libc6").await;
+        let count = get_rdeps_count(&cache, "libc6").await;
         assert_eq!(count, Some(15000));
     }
 
     #[tokio::test]
     async fn test_get_rdeps_count_unknown_package() {
-        let mut cache = RdepsCache::new(crate::udd::shared_pool());
-        cache.insert_cached_missing("nonexistent-package-xyz");
+        let cache = new_shared_rdeps_cache(crate::udd::shared_pool());
+        cache
+            .write()
+            .await
+            .insert_cached_missing("nonexistent-package-xyz");
 
-        let count = cache.get_rdeps_count("nonexistent-package-xyz").await;
+        let count = get_rdeps_count(&cache, "nonexistent-package-xyz").await;
         assert_eq!(count, None);
     }
 }
@@ -1,6 +1,11 @@
+use std::collections::BTreeSet;
+
 use makefile_lossless::Makefile;
 use tower_lsp_server::ls_types::{CompletionItem, CompletionItemKind, Position};
 
+use super::debhelper::{
+    helper_doc, installed_commands, iter_dh_commands, HelperDoc, COMMON_OPTIONS,
+};
 use super::fields::{RULES_TARGETS, RULES_VARIABLES};
 
 /// Get completions for a debian/rules file at the given position.
@@ -17,9 +22,13 @@ pub fn get_completions(
         return get_target_completions(makefile);
     }
 
-    // If the line starts with a tab, we're in a recipe — no completions for now
+    // If the line starts with a tab, we're in a recipe
     if line.starts_with('\t') {
-        return vec![];
+        let before = line
+            .char_indices()
+            .nth(position.character as usize)
+            .map_or(line, |(i, _)| &line[..i]);
+        return get_recipe_completions(before, installed_commands(), helper_doc);
     }
 
     // If the line looks like a variable assignment prefix, offer variable completions
@@ -57,6 +66,62 @@ fn get_target_completions(makefile: &Makefile) -> Vec<CompletionItem> {
         .collect()
 }
 
+/// Complete debhelper commands and their options in a recipe line, given the
+/// text of the line before the cursor.
+fn get_recipe_completions(
+    before: &str,
+    installed: Option<&BTreeSet<String>>,
+    doc: impl Fn(&str) -> Option<HelperDoc>,
+) -> Vec<CompletionItem> {
+    let word = before
+        .rsplit(|c: char| c.is_whitespace() || c == ';' || c == '(')
+        .next()
+        .unwrap_or("");
+
+    if word.starts_with("dh_") {
+        return installed
+            .into_iter()
+            .flatten()
+            .map(|command| CompletionItem {
+                label: command.clone(),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: doc(command).and_then(|d| d.summary),
+                ..Default::default()
+            })
+            .collect();
+    }
+
+    if !word.starts_with('-') {
+        return vec![];
+    }
+    let Some(command) = iter_dh_commands(before, 0).pop() else {
+        return vec![];
+    };
+    let options = doc(&command.name).map(|d| d.options).unwrap_or_default();
+    let mut seen = BTreeSet::new();
+    let specific = options.iter().flat_map(|option| {
+        option
+            .names
+            .iter()
+            .map(move |name| (name.clone(), option.description.clone()))
+    });
+    let common = COMMON_OPTIONS.iter().flat_map(|(names, description)| {
+        names
+            .iter()
+            .map(move |name| (name.to_string(), description.to_string()))
+    });
+    specific
+        .chain(common)
+        .filter(|(name, _)| seen.insert(name.clone()))
+        .map(|(name, description)| CompletionItem {
+            label: name,
+            kind: Some(CompletionItemKind::PROPERTY),
+            detail: Some(description),
+            ..Default::default()
+        })
+        .collect()
+}
+
 /// Generate variable name completions.
 fn get_variable_completions() -> Vec<CompletionItem> {
     RULES_VARIABLES
@@ -117,4 +182,43 @@ mod tests {
         assert_eq!(clean.insert_text.as_deref(), Some("clean:\n\t"));
         assert_eq!(clean.kind, Some(CompletionItemKind::FUNCTION));
     }
+
+    fn doc(command: &str) -> Option<HelperDoc> {
+        (command == "dh_install").then(|| HelperDoc {
+            summary: Some("install files into package build directories".to_string()),
+            synopsis: None,
+            options: vec![crate::rules::debhelper::HelperOption {
+                names: vec!["-X".to_string(), "--exclude=".to_string()],
+                description: "Exclude files".to_string(),
+            }],
+        })
+    }
+
+    #[test]
+    fn test_recipe_option_completions() {
+        let completions = get_recipe_completions("\tdh_install --", None, doc);
+        let labels = completions
+            .iter()
+            .map(|c| c.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(&labels[..3], ["-X", "--exclude=", "-v"]);
+        assert!(labels.contains(&"--remaining-packages"));
+        // Outside of a dh command there are no options to offer.
+        assert!(get_recipe_completions("\trm -", None, doc).is_empty());
+    }
+
+    #[test]
+    fn test_recipe_command_completions() {
+        let installed = ["dh_install", "dh_strip"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<BTreeSet<_>>();
+        let completions = get_recipe_completions("\tdh_", Some(&installed), doc);
+        assert_eq!(completions.len(), 2);
+        assert_eq!(
+            completions[0].detail.as_deref(),
+            Some("install files into package build directories")
+        );
+        assert!(get_recipe_completions("\tdh_", None, doc).is_empty());
+    }
 }
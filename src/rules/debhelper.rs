@@ -3,6 +3,19 @@
 //! Recognising `dh_*` commands and the `override_dh_*` / `execute_*_dh_*`
 //! target families is Debian packaging domain knowledge rather than generic
 //! Makefile parsing, so it lives here rather than in the Makefile parser.
+//!
+//! What the helpers do and which options they take is read from the POD
+//! documentation embedded in the installed `dh_*` scripts.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use debian_control::lossless::Control;
+use debian_control::relations::VersionConstraint;
+
+/// Where debhelper installs its commands.
+const DH_BIN_DIR: &str = "/usr/bin";
 
 /// A `dh_*` command found in recipe text, with its absolute source range.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -53,16 +66,314 @@ fn is_word_byte(b: u8) -> bool {
 /// Extract the debhelper command a rules target hooks into, if any.
 ///
 /// `override_dh_auto_test` -> `dh_auto_test`,
-/// `execute_before_dh_install` -> `dh_install`. Returns `None` for targets that
+/// `execute_before_dh_install` -> `dh_install`,
+/// `override_dh_strip-arch` -> `dh_strip`. Returns `None` for targets that
 /// are not dh hooks.
 pub fn command_for_target(target: &str) -> Option<&str> {
     let cmd = target
         .strip_prefix("override_")
         .or_else(|| target.strip_prefix("execute_before_"))
         .or_else(|| target.strip_prefix("execute_after_"))?;
+    let cmd = cmd
+        .strip_suffix("-arch")
+        .or_else(|| cmd.strip_suffix("-indep"))
+        .unwrap_or(cmd);
     cmd.starts_with("dh_").then_some(cmd)
 }
 
+/// A helper that `dh` stopped running.
+pub struct RetiredCommand {
+    pub name: &'static str,
+    /// The compat level from which `dh` no longer runs the helper; `None`
+    /// if debhelper dropped it altogether.
+    pub compat: Option<u32>,
+    /// What runs instead.
+    pub replacement: Option<&'static str>,
+}
+
+impl RetiredCommand {
+    const fn new(
+        name: &'static str,
+        compat: Option<u32>,
+        replacement: Option<&'static str>,
+    ) -> Self {
+        Self {
+            name,
+            compat,
+            replacement,
+        }
+    }
+}
+
+pub const RETIRED_COMMANDS: &[RetiredCommand] = &[
+    RetiredCommand::new("dh_systemd_enable", Some(11), Some("dh_installsystemd")),
+    RetiredCommand::new("dh_systemd_start", Some(11), Some("dh_installsystemd")),
+    RetiredCommand::new("dh_installmanpages", None, Some("dh_installman")),
+    RetiredCommand::new("dh_undocumented", None, None),
+    RetiredCommand::new("dh_suidregister", None, None),
+    RetiredCommand::new("dh_scrollkeeper", None, None),
+    RetiredCommand::new("dh_desktop", None, None),
+    RetiredCommand::new("dh_gconf", None, None),
+    RetiredCommand::new("dh_python", None, Some("dh_python3")),
+    RetiredCommand::new("dh_pysupport", None, Some("dh_python3")),
+    RetiredCommand::new("dh_pycentral", None, Some("dh_python3")),
+];
+
+/// Look up `command` in [`RETIRED_COMMANDS`].
+pub fn retired_command(command: &str) -> Option<&'static RetiredCommand> {
+    RETIRED_COMMANDS.iter().find(|c| c.name == command)
+}
+
+/// The `dh_*` commands in `dir`, or `None` if debhelper is not installed
+/// there.
+pub fn installed_commands_in(dir: &Path) -> Option<BTreeSet<String>> {
+    if !dir.join("dh").exists() {
+        return None;
+    }
+    let commands = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with("dh_"))
+        .collect();
+    Some(commands)
+}
+
+static INSTALLED_COMMANDS: OnceLock<Option<BTreeSet<String>>> = OnceLock::new();
+
+/// The installed debhelper commands, including those of dh addons, read once
+/// per process. `None` if debhelper is not installed.
+pub fn installed_commands() -> Option<&'static BTreeSet<String>> {
+    INSTALLED_COMMANDS
+        .get_or_init(|| installed_commands_in(Path::new(DH_BIN_DIR)))
+        .as_ref()
+}
+
+/// The debhelper compat level of a package, from the contents of its
+/// `debian/compat` or a `debhelper-compat (= N)` build dependency in
+/// `control`.
+pub fn compat_level(compat: Option<&str>, control: Option<&Control>) -> Option<u32> {
+    if let Some(compat) = compat {
+        return compat.trim().parse().ok();
+    }
+    compat_from_control(control?)
+}
+
+fn compat_from_control(control: &Control) -> Option<u32> {
+    let source = control.source()?;
+    [
+        source.build_depends(),
+        source.build_depends_arch(),
+        source.build_depends_indep(),
+    ]
+    .into_iter()
+    .flatten()
+    .flat_map(|relations| relations.entries().collect::<Vec<_>>())
+    .flat_map(|entry| entry.relations().collect::<Vec<_>>())
+    .find_map(|relation| {
+        if relation.try_name()? != "debhelper-compat" {
+            return None;
+        }
+        match relation.version()? {
+            (VersionConstraint::Equal, version) => version.upstream_version.parse().ok(),
+            _ => None,
+        }
+    })
+}
+
+/// An option of a debhelper command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelperOption {
+    /// The spellings of the option, e.g. `-X` and `--exclude=`.
+    pub names: Vec<String>,
+    pub description: String,
+}
+
+/// What the documentation of a debhelper command says.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HelperDoc {
+    /// The one-line summary from the NAME section.
+    pub summary: Option<String>,
+    pub synopsis: Option<String>,
+    pub options: Vec<HelperOption>,
+}
+
+/// Options every debhelper command accepts, from debhelper(7).
+pub const COMMON_OPTIONS: &[(&[&str], &str)] = &[
+    (
+        &["-v", "--verbose"],
+        "Show all commands that modify the package build directory",
+    ),
+    (&["--no-act"], "Do not really do anything"),
+    (&["-a", "--arch"], "Act on architecture dependent packages"),
+    (
+        &["-i", "--indep"],
+        "Act on all architecture independent packages",
+    ),
+    (&["-p", "--package="], "Act on the given package"),
+    (&["-N", "--no-package="], "Do not act on the given package"),
+    (
+        &["--remaining-packages"],
+        "Do not act on packages this command already acted on",
+    ),
+    (
+        &["-P", "--tmpdir="],
+        "Use the given directory as package build directory",
+    ),
+    (
+        &["--mainpackage="],
+        "Use the given package as the main package of the source",
+    ),
+];
+
+/// Remove POD formatting codes such as `B<dh_install>` or `I<< file >>`,
+/// keeping their text. `E<gt>`/`E<lt>` become `>`/`<`.
+fn strip_pod_codes(text: &str) -> String {
+    let mut out = String::new();
+    // The closing delimiter of each open formatting code.
+    let mut closers: Vec<String> = Vec::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some(closer) = closers.last() {
+            if rest.starts_with(closer.as_str()) {
+                rest = &rest[closer.len()..];
+                if closer.len() > 1 {
+                    // `B<< x >>` has whitespace inside the brackets.
+                    let trimmed = out.trim_end().len();
+                    out.truncate(trimmed);
+                }
+                closers.pop();
+                continue;
+            }
+        }
+        let bytes = rest.as_bytes();
+        if c.is_ascii_uppercase() && bytes.get(1) == Some(&b'<') {
+            if let Some(entity) = rest.strip_prefix("E<") {
+                if let Some((name, after)) = entity.split_once('>') {
+                    out.push_str(match name {
+                        "gt" => ">",
+                        "lt" => "<",
+                        "verbar" => "|",
+                        "sol" => "/",
+                        other => other,
+                    });
+                    rest = after;
+                    continue;
+                }
+            }
+            let brackets = rest[1..].chars().take_while(|&c| c == '<').count();
+            rest = &rest[1 + brackets..];
+            if brackets > 1 {
+                rest = rest.trim_start();
+            }
+            closers.push(">".repeat(brackets));
+            continue;
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// Join the lines of a POD paragraph.
+fn join_paragraph(lines: &[&str]) -> String {
+    strip_pod_codes(&lines.join(" ")).trim().to_string()
+}
+
+/// The option spellings in an `=item` line: each bold code starting with
+/// `-`, e.g. `B<-X>I<item>, B<--exclude=>I<item>` gives `-X` and
+/// `--exclude=`.
+fn item_options(item: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = item;
+    while let Some(start) = rest.find("B<") {
+        rest = &rest[start + 2..];
+        let end = rest.find('>').unwrap_or(rest.len());
+        let name = rest[..end].trim();
+        if name.starts_with('-') {
+            names.push(name.to_string());
+        }
+        rest = &rest[end..];
+    }
+    names
+}
+
+/// Parse the POD documentation in the text of a debhelper command.
+pub fn parse_pod(text: &str) -> HelperDoc {
+    let mut doc = HelperDoc::default();
+    let mut section = String::new();
+    let mut item: Option<Vec<String>> = None;
+    let mut paragraph: Vec<&str> = Vec::new();
+
+    for line in text.lines().chain(std::iter::once("")) {
+        if !line.trim().is_empty() {
+            paragraph.push(line);
+            continue;
+        }
+        if paragraph.is_empty() {
+            continue;
+        }
+        let first = paragraph[0];
+        if let Some(heading) = first.strip_prefix("=head1 ") {
+            section = heading.trim().to_string();
+        } else if let Some(spec) = first.strip_prefix("=item ") {
+            item = (section == "OPTIONS").then(|| item_options(spec));
+        } else if first.starts_with('=') {
+            if first.starts_with("=back") {
+                item = None;
+            }
+        } else {
+            match section.as_str() {
+                "NAME" if doc.summary.is_none() => {
+                    let name = join_paragraph(&paragraph);
+                    doc.summary = Some(
+                        name.split_once(" - ")
+                            .map_or(name.clone(), |(_, summary)| summary.to_string()),
+                    );
+                }
+                "SYNOPSIS" if doc.synopsis.is_none() => {
+                    doc.synopsis = Some(join_paragraph(&paragraph));
+                }
+                "OPTIONS" => {
+                    if let Some(names) = item.take().filter(|names| !names.is_empty()) {
+                        doc.options.push(HelperOption {
+                            names,
+                            description: join_paragraph(&paragraph),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        paragraph.clear();
+    }
+    doc
+}
+
+/// The documentation of `command` installed in `dir`.
+pub fn helper_doc_in(dir: &Path, command: &str) -> Option<HelperDoc> {
+    if !command.starts_with("dh_") || !command.bytes().all(is_word_byte) {
+        return None;
+    }
+    let text = std::fs::read_to_string(dir.join(command)).ok()?;
+    let doc = parse_pod(&text);
+    (doc.summary.is_some() || doc.synopsis.is_some()).then_some(doc)
+}
+
+static HELPER_DOCS: OnceLock<Mutex<HashMap<String, Option<HelperDoc>>>> = OnceLock::new();
+
+/// The documentation of the installed debhelper `command`, read once per
+/// command and process.
+pub fn helper_doc(command: &str) -> Option<HelperDoc> {
+    let mut docs = HELPER_DOCS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    docs.entry(command.to_string())
+        .or_insert_with(|| helper_doc_in(Path::new(DH_BIN_DIR), command))
+        .clone()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,6 +392,15 @@ mod tests {
             command_for_target("execute_after_dh_strip"),
             Some("dh_strip")
         );
+        // Hooks for architecture-dependent or -independent packages only.
+        assert_eq!(
+            command_for_target("override_dh_auto_test-arch"),
+            Some("dh_auto_test")
+        );
+        assert_eq!(
+            command_for_target("execute_after_dh_install-indep"),
+            Some("dh_install")
+        );
         // Not a dh hook.
         assert_eq!(command_for_target("build"), None);
         assert_eq!(command_for_target("override_something"), None);
@@ -101,4 +421,84 @@ mod tests {
         assert_eq!(cmds.len(), 1);
         assert_eq!(cmds[0].name, "dh_real");
     }
+
+    const POD: &str = "#!/usr/bin/perl
+
+=head1 NAME
+
+dh_foo - install foo files into package build directories
+
+=head1 SYNOPSIS
+
+B<dh_foo> [S<I<debhelper options>>] [B<-X>I<item>] [S<I<file> ...>]
+
+=head1 OPTIONS
+
+=over 4
+
+=item B<-X>I<item>, B<--exclude=>I<item>
+
+Exclude files that contain I<item>
+anywhere in their filename.
+
+=item B<--no-bar>
+
+Do not install C<< bar E<gt> baz >> files.
+
+=back
+
+=head1 SEE ALSO
+
+L<debhelper(7)>
+
+=cut
+
+print 1;
+";
+
+    #[test]
+    fn test_parse_pod() {
+        let doc = parse_pod(POD);
+        assert_eq!(
+            doc.summary.as_deref(),
+            Some("install foo files into package build directories")
+        );
+        assert_eq!(
+            doc.synopsis.as_deref(),
+            Some("dh_foo [debhelper options] [-Xitem] [file ...]")
+        );
+        assert_eq!(
+            doc.options,
+            vec![
+                HelperOption {
+                    names: vec!["-X".to_string(), "--exclude=".to_string()],
+                    description: "Exclude files that contain item anywhere in their filename."
+                        .to_string(),
+                },
+                HelperOption {
+                    names: vec!["--no-bar".to_string()],
+                    description: "Do not install bar > baz files.".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compat_from_control() {
+        let compat = |text: &str| compat_from_control(&Control::parse(text).tree());
+        assert_eq!(
+            compat(
+                "Source: foo\nBuild-Depends: debhelper-compat (= 13),\n pkg-config\n\nPackage: foo\n"
+            ),
+            Some(13)
+        );
+        assert_eq!(
+            compat("Source: foo\nBuild-Depends: pkg-config,\n# comment\n debhelper-compat (=12)\n"),
+            Some(12)
+        );
+        assert_eq!(
+            compat("Source: foo\nBuild-Depends: debhelper (>= 12)\n"),
+            None
+        );
+    }
 }
@@ -0,0 +1,66 @@
+//! Go-to-definition for debian/rules files.
+
+use std::path::Path;
+
+use makefile_lossless::Makefile;
+use tower_lsp_server::ls_types::{Location, Position, Uri};
+
+use super::variables::{definitions, variable_at};
+use crate::position::Source;
+
+/// The assignments of the variable at `position` in the rules file at `uri`,
+/// including those in makefiles it includes from the source tree at `root`.
+pub fn goto_definition(
+    makefile: &Makefile,
+    src: Source<'_>,
+    uri: &Uri,
+    position: Position,
+    root: Option<&Path>,
+) -> Vec<Location> {
+    let Some(name) = variable_at(makefile, src, position) else {
+        return Vec::new();
+    };
+    definitions(makefile, src, uri, root)
+        .into_iter()
+        .filter(|d| d.name == name)
+        .map(|d| d.location)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+    use tower_lsp_server::ls_types::Range;
+
+    #[test]
+    fn test_goto_definition() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("debian")).unwrap();
+        std::fs::write(dir.path().join("debian/flags.mk"), "\nFLAGS = -O2\n").unwrap();
+        let text = "include $(CURDIR)/debian/flags.mk\nFLAGS += -g\n\nbuild:\n\tmake $(FLAGS)\n";
+        let (makefile, _) = Makefile::from_str_relaxed(text);
+        let idx = LineIndex::new(text);
+        let uri = Uri::from_file_path(dir.path().join("debian/rules")).unwrap();
+        let locations = goto_definition(
+            &makefile,
+            Source::new(text, &idx),
+            &uri,
+            Position::new(4, 9),
+            Some(dir.path()),
+        );
+        assert_eq!(
+            locations,
+            vec![
+                Location {
+                    uri: Uri::from_file_path(dir.path().join("debian/flags.mk")).unwrap(),
+                    range: Range::new(Position::new(1, 0), Position::new(1, 5)),
+                },
+                Location {
+                    uri,
+                    range: Range::new(Position::new(1, 0), Position::new(1, 5)),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,594 @@
+//! Diagnostics for debian/rules files.
+//!
+//! Checks the `override_dh_*` and `execute_{before,after}_dh_*` targets against
+//! the installed debhelper commands and the package's compat level, and looks
+//! for the mistakes of hand-written rules files: missing required targets,
+//! recipes indented with spaces, targets missing from `.PHONY`, subshells
+//! that the dpkg makefile fragments replace and `findstring` checks of
+//! `DEB_BUILD_OPTIONS`.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use debian_control::lossless::Control;
+use makefile_lossless::{Makefile, SyntaxKind};
+use regex::Regex;
+use rowan::ast::AstNode;
+use rowan::{TextRange, TextSize};
+use tower_lsp_server::ls_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+use super::debhelper::{command_for_target, retired_command};
+use super::fields::RULES_TARGETS;
+use crate::position::Source;
+
+/// Commands run in subshells whose output a dpkg makefile fragment provides.
+const SUBSHELL_INCLUDES: &[(&str, &str)] = &[
+    ("dpkg-architecture", "/usr/share/dpkg/architecture.mk"),
+    ("dpkg-parsechangelog", "/usr/share/dpkg/pkg-info.mk"),
+];
+
+/// Makefile directives, which may be indented with spaces inside a rule.
+const DIRECTIVES: &[&str] = &[
+    "ifeq", "ifneq", "ifdef", "ifndef", "else", "endif", "include", "-include", "sinclude",
+    "define", "endef", "export", "unexport", "override", "vpath",
+];
+
+/// What the checks know about the debhelper the package is built with.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Debhelper<'a> {
+    /// The package's compat level.
+    pub compat: Option<u32>,
+    /// The installed `dh_*` commands; `None` if debhelper is not installed.
+    pub installed: Option<&'a BTreeSet<String>>,
+}
+
+/// What the checks read from outside the rules file. Reading it blocks, so
+/// it is gathered before the checks run.
+#[derive(Debug, Default, Clone)]
+pub struct Context {
+    /// The contents of `debian/compat`, if the package has one.
+    pub compat: Option<String>,
+    /// Whether the rules file has its executable bit set, if known.
+    pub executable: Option<bool>,
+    /// The installed `dh_*` commands; `None` if debhelper is not installed.
+    pub installed: Option<&'static BTreeSet<String>>,
+}
+
+impl Context {
+    /// Read the context of the rules file at `path` from disk, including
+    /// `debian/compat` if `compat` is set.
+    pub fn read(path: &Path, compat: bool) -> Self {
+        #[cfg(unix)]
+        let executable = std::fs::metadata(path).ok().map(|metadata| {
+            use std::os::unix::fs::PermissionsExt;
+            metadata.permissions().mode() & 0o111 != 0
+        });
+        #[cfg(not(unix))]
+        let executable = None;
+        Self {
+            compat: compat
+                .then(|| std::fs::read_to_string(path.with_file_name("compat")).ok())
+                .flatten(),
+            executable,
+            installed: super::debhelper::installed_commands(),
+        }
+    }
+
+    /// The debhelper the package is built with, given the source paragraph
+    /// in `control`.
+    pub fn debhelper(&self, control: Option<&Control>) -> Debhelper<'static> {
+        Debhelper {
+            compat: super::debhelper::compat_level(self.compat.as_deref(), control),
+            installed: self.installed,
+        }
+    }
+}
+
+fn diagnostic(
+    range: tower_lsp_server::ls_types::Range,
+    severity: DiagnosticSeverity,
+    code: &str,
+    message: String,
+) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some("debian-lsp".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Check the rules file `makefile`. `executable` is whether the file has its
+/// executable bit set, if known.
+pub fn get_diagnostics(
+    makefile: &Makefile,
+    src: Source<'_>,
+    debhelper: Debhelper<'_>,
+    executable: Option<bool>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = hook_diagnostics(makefile, src, debhelper);
+    if executable == Some(false) {
+        diagnostics.push(diagnostic(
+            first_line(src),
+            DiagnosticSeverity::WARNING,
+            "rules-not-executable",
+            "debian/rules is not executable".to_string(),
+        ));
+    }
+    diagnostics.extend(target_diagnostics(makefile, src));
+    diagnostics.extend(indentation_diagnostics(src));
+    diagnostics.extend(pattern_diagnostics(src));
+    diagnostics
+}
+
+/// The range of the first line, for findings about the whole file.
+fn first_line(src: Source<'_>) -> tower_lsp_server::ls_types::Range {
+    let end = src.text.find('\n').unwrap_or(src.text.len());
+    src.text_range_to_lsp_range(TextRange::up_to(TextSize::from(end as u32)))
+}
+
+/// Check for missing required targets and required targets missing from
+/// `.PHONY`.
+fn target_diagnostics(makefile: &Makefile, src: Source<'_>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let targets = makefile
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|e| e.into_token())
+        .filter(|t| t.kind() == SyntaxKind::IDENTIFIER)
+        .filter(|t| t.parent().is_some_and(|p| p.kind() == SyntaxKind::TARGETS))
+        .collect::<Vec<_>>();
+    let required = RULES_TARGETS.iter().filter(|t| t.required);
+
+    // A match-anything rule, such as dh's `%:`, or a makefile from outside
+    // of dpkg, such as CDBS, may provide the targets.
+    let has_pattern = targets.iter().any(|t| t.text() == "%");
+    let has_includes = makefile
+        .includes()
+        .filter_map(|i| i.path())
+        .any(|path| !path.starts_with("/usr/share/dpkg/"));
+    if !has_pattern && !has_includes {
+        for target in required.clone() {
+            if !targets.iter().any(|t| t.text() == target.name) {
+                diagnostics.push(diagnostic(
+                    first_line(src),
+                    DiagnosticSeverity::WARNING,
+                    "missing-required-target",
+                    format!(
+                        "debian/rules does not define the required target {}",
+                        target.name
+                    ),
+                ));
+            }
+        }
+    }
+
+    let phony = makefile
+        .rules()
+        .filter(|rule| rule.targets().any(|t| t == ".PHONY"))
+        .flat_map(|rule| rule.prerequisites().collect::<Vec<_>>())
+        .collect::<BTreeSet<_>>();
+    // Prerequisites computed by make can't be checked.
+    if phony.iter().any(|p| p.contains('$')) {
+        return diagnostics;
+    }
+    for token in &targets {
+        if required.clone().any(|t| t.name == token.text()) && !phony.contains(token.text()) {
+            diagnostics.push(diagnostic(
+                src.text_range_to_lsp_range(token.text_range()),
+                DiagnosticSeverity::WARNING,
+                "target-not-phony",
+                format!(
+                    "{} is not listed in .PHONY, so a file of that name stops it from running",
+                    token.text()
+                ),
+            ));
+        }
+    }
+    diagnostics
+}
+
+/// Whether the unindented `line` starts a rule: `targets: prerequisites`.
+fn is_rule_header(line: &str) -> bool {
+    match line.find(':') {
+        Some(i) => !line[..i].contains('=') && !line[i..].starts_with(":="),
+        None => false,
+    }
+}
+
+static ASSIGNMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*[A-Za-z0-9_.\-]+\s*(?:\?|:|::|\+|!)?=").expect("valid regex")
+});
+
+static SUBSHELL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$[({]shell\s+([\w-]+)[^)}]*[)}]").expect("valid regex"));
+
+static FINDSTRING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\$[({]findstring\s+([^,()]+),\s*\$[({]DEB_BUILD_OPTIONS[)}]\s*[)}]")
+        .expect("valid regex")
+});
+
+/// Whether `words` start a `define` directive, possibly prefixed with
+/// `export` or `override`.
+fn starts_define<'a>(mut words: impl Iterator<Item = &'a str>) -> bool {
+    loop {
+        match words.next() {
+            Some("export" | "override") => continue,
+            Some("define") => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Check for recipe lines indented with spaces, which make rejects with
+/// "missing separator".
+fn indentation_diagnostics(src: Source<'_>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut in_rule = false;
+    let mut in_define = false;
+    let mut continued = false;
+    let mut offset = 0;
+    for line in src.text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let content = line.trim_end_matches(['\n', '\r']);
+        let was_continued = continued;
+        continued = content.ends_with('\\');
+        if was_continued || content.trim().is_empty() || content.starts_with('\t') {
+            continue;
+        }
+        let trimmed = content.trim_start();
+        // The body of a multi-line variable is not a recipe, and the
+        // variable ends any rule before it.
+        if in_define {
+            in_define = trimmed.split_whitespace().next() != Some("endef");
+            continue;
+        }
+        if starts_define(trimmed.split_whitespace()) {
+            in_define = true;
+            in_rule = false;
+            continue;
+        }
+        let is_directive = trimmed
+            .split_whitespace()
+            .next()
+            .is_some_and(|word| DIRECTIVES.contains(&word));
+        // Comments and conditionals don't end a rule.
+        if trimmed.starts_with('#') || is_directive {
+            continue;
+        }
+        if !content.starts_with(' ') {
+            in_rule = is_rule_header(content);
+            continue;
+        }
+        if !in_rule || ASSIGNMENT.is_match(content) {
+            continue;
+        }
+        let indent = content.len() - trimmed.len();
+        diagnostics.push(diagnostic(
+            src.text_range_to_lsp_range(TextRange::at(
+                TextSize::from(start as u32),
+                TextSize::from(indent as u32),
+            )),
+            DiagnosticSeverity::ERROR,
+            "recipe-indented-with-spaces",
+            "Recipe lines must be indented with a tab, not spaces".to_string(),
+        ));
+    }
+    diagnostics
+}
+
+/// Check for legacy patterns: subshells that a dpkg makefile fragment
+/// replaces and `findstring` checks of `DEB_BUILD_OPTIONS`.
+fn pattern_diagnostics(src: Source<'_>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let range = |start: usize, end: usize| {
+        src.text_range_to_lsp_range(TextRange::new(
+            TextSize::from(start as u32),
+            TextSize::from(end as u32),
+        ))
+    };
+
+    for m in SUBSHELL.captures_iter(src.text) {
+        let (whole, command) = (m.get(0).unwrap(), &m[1]);
+        if let Some((_, include)) = SUBSHELL_INCLUDES.iter().find(|(c, _)| *c == command) {
+            diagnostics.push(diagnostic(
+                range(whole.start(), whole.end()),
+                DiagnosticSeverity::INFORMATION,
+                "subshell-instead-of-include",
+                format!(
+                    "Include {} instead of running {} in a subshell",
+                    include, command
+                ),
+            ));
+        }
+    }
+
+    for m in FINDSTRING.captures_iter(src.text) {
+        let (whole, option) = (m.get(0).unwrap(), m[1].trim());
+        let pattern = if option.ends_with('=') {
+            format!("{}%", option)
+        } else {
+            option.to_string()
+        };
+        diagnostics.push(diagnostic(
+            range(whole.start(), whole.end()),
+            DiagnosticSeverity::WARNING,
+            "findstring-build-options",
+            format!(
+                "findstring also matches {} inside other options; use $(filter {},$(DEB_BUILD_OPTIONS))",
+                option, pattern
+            ),
+        ));
+    }
+    diagnostics
+}
+
+/// Check the debhelper hook targets of `makefile`.
+fn hook_diagnostics(
+    makefile: &Makefile,
+    src: Source<'_>,
+    debhelper: Debhelper<'_>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for token in makefile
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|e| e.into_token())
+        .filter(|t| t.kind() == SyntaxKind::IDENTIFIER)
+        .filter(|t| t.parent().is_some_and(|p| p.kind() == SyntaxKind::TARGETS))
+    {
+        let target = token.text();
+        let Some(command) = command_for_target(target) else {
+            continue;
+        };
+        let range = src.text_range_to_lsp_range(token.text_range());
+        let instead = |replacement: Option<&str>| {
+            replacement.map_or(String::new(), |r| format!("; use {} instead", r))
+        };
+        match retired_command(command) {
+            Some(retired) if retired.compat.is_none() => {
+                diagnostics.push(diagnostic(
+                    range,
+                    DiagnosticSeverity::WARNING,
+                    "removed-debhelper-command",
+                    format!(
+                        "{} was removed from debhelper, so {} never runs{}",
+                        command,
+                        target,
+                        instead(retired.replacement)
+                    ),
+                ));
+                continue;
+            }
+            Some(retired)
+                if debhelper
+                    .compat
+                    .zip(retired.compat)
+                    .is_some_and(|(compat, retired)| compat >= retired) =>
+            {
+                diagnostics.push(diagnostic(
+                    range,
+                    DiagnosticSeverity::WARNING,
+                    "debhelper-command-not-run",
+                    format!(
+                        "dh does not run {} at compat level {}, so {} never runs{}",
+                        command,
+                        debhelper.compat.unwrap_or_default(),
+                        target,
+                        instead(retired.replacement)
+                    ),
+                ));
+                continue;
+            }
+            _ => {}
+        }
+        if debhelper
+            .installed
+            .is_some_and(|installed| !installed.contains(command))
+        {
+            diagnostics.push(diagnostic(
+                range,
+                DiagnosticSeverity::INFORMATION,
+                "unknown-debhelper-command",
+                format!(
+                    "{} is not an installed debhelper command; it may come from an addon that is not installed",
+                    command
+                ),
+            ));
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+
+    fn codes(diagnostics: Vec<Diagnostic>) -> Vec<(u32, String)> {
+        diagnostics
+            .into_iter()
+            .map(|d| {
+                let Some(NumberOrString::String(code)) = d.code else {
+                    panic!("diagnostic without code");
+                };
+                (d.range.start.line, code)
+            })
+            .collect()
+    }
+
+    fn check(text: &str, debhelper: Debhelper<'_>) -> Vec<(u32, String)> {
+        let makefile = Makefile::parse(text).tree();
+        let idx = LineIndex::new(text);
+        codes(hook_diagnostics(
+            &makefile,
+            Source::new(text, &idx),
+            debhelper,
+        ))
+    }
+
+    fn check_all(text: &str) -> Vec<(u32, String)> {
+        let makefile = Makefile::parse(text).tree();
+        let idx = LineIndex::new(text);
+        codes(get_diagnostics(
+            &makefile,
+            Source::new(text, &idx),
+            Debhelper::default(),
+            Some(true),
+        ))
+    }
+
+    #[test]
+    fn test_retired_commands() {
+        let text = "%:\n\tdh $@\n\noverride_dh_systemd_start:\n\tdh_systemd_start --no-start\n\noverride_dh_installmanpages:\n\ttrue\n";
+        assert_eq!(
+            check(
+                text,
+                Debhelper {
+                    compat: Some(13),
+                    installed: None
+                }
+            ),
+            vec![
+                (3, "debhelper-command-not-run".to_string()),
+                (6, "removed-debhelper-command".to_string()),
+            ]
+        );
+        // dh still runs dh_systemd_start at compat 10.
+        assert_eq!(
+            check(
+                text,
+                Debhelper {
+                    compat: Some(10),
+                    installed: None
+                }
+            ),
+            vec![(6, "removed-debhelper-command".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_unknown_commands() {
+        let installed = ["dh_auto_test", "dh_install"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<BTreeSet<_>>();
+        let text = "override_dh_auto_test-arch:\n\ttrue\n\nexecute_after_dh_instal:\n\ttrue\n\nbuild:\n\ttrue\n";
+        let debhelper = Debhelper {
+            compat: Some(13),
+            installed: Some(&installed),
+        };
+        assert_eq!(
+            check(text, debhelper),
+            vec![(3, "unknown-debhelper-command".to_string())]
+        );
+        // Without debhelper installed there is nothing to compare against.
+        assert!(check(text, Debhelper::default()).is_empty());
+    }
+
+    #[test]
+    fn test_dh_rules() {
+        let text = "#!/usr/bin/make -f\ninclude /usr/share/dpkg/pkg-info.mk\n\n%:\n\tdh $@\n\noverride_dh_auto_test:\nifeq (,$(filter nocheck,$(DEB_BUILD_OPTIONS)))\n\tdh_auto_test\nendif\n";
+        assert!(check_all(text).is_empty());
+    }
+
+    #[test]
+    fn test_missing_targets() {
+        let text =
+            "#!/usr/bin/make -f\n.PHONY: build clean\n\nbuild:\n\tmake\n\nclean:\n\tmake clean\n";
+        let missing = check_all(text)
+            .into_iter()
+            .filter(|(_, code)| code == "missing-required-target")
+            .count();
+        assert_eq!(missing, 5);
+        // Another makefile may provide the targets.
+        let text = "include /usr/share/cdbs/1/rules/debhelper.mk\n";
+        assert!(check_all(text).is_empty());
+    }
+
+    #[test]
+    fn test_target_not_phony() {
+        let text = "%:\n\tdh $@\n\n.PHONY: clean\nclean:\n\tdh clean\n\nbuild:\n\tdh build\n";
+        assert_eq!(check_all(text), vec![(7, "target-not-phony".to_string())]);
+    }
+
+    #[test]
+    fn test_recipe_indented_with_spaces() {
+        let text = "%:\n\tdh $@\n\noverride_dh_auto_build:\n    $(MAKE) all\n  ifdef FOO\n  X = 1\n  endif\n\tdh_auto_build \\\n    -- V=1\n";
+        let idx = LineIndex::new(text);
+        let diagnostics = indentation_diagnostics(Source::new(text, &idx));
+        assert_eq!(
+            codes(diagnostics.clone()),
+            vec![(4, "recipe-indented-with-spaces".to_string())]
+        );
+        assert_eq!(diagnostics[0].range.end.character, 4);
+    }
+
+    #[test]
+    fn test_define_body_is_not_a_recipe() {
+        let text = "build:\n\tdh build\ndefine HELP\n    Build the package.\n  endef here\n    Still help.\nendef\n\nclean:\n  dh clean\n";
+        let idx = LineIndex::new(text);
+        assert_eq!(
+            codes(indentation_diagnostics(Source::new(text, &idx))),
+            vec![(9, "recipe-indented-with-spaces".to_string())]
+        );
+        let text = "build:\n\tdh build\noverride define HELP\n    text\nendef\n    echo\n";
+        let idx = LineIndex::new(text);
+        assert!(indentation_diagnostics(Source::new(text, &idx)).is_empty());
+    }
+
+    #[test]
+    fn test_not_executable() {
+        let text = "%:\n\tdh $@\n";
+        let makefile = Makefile::parse(text).tree();
+        let idx = LineIndex::new(text);
+        let diagnostics = get_diagnostics(
+            &makefile,
+            Source::new(text, &idx),
+            Debhelper::default(),
+            Some(false),
+        );
+        assert_eq!(
+            codes(diagnostics),
+            vec![(0, "rules-not-executable".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_legacy_patterns() {
+        let text = "DEB_HOST_MULTIARCH ?= $(shell dpkg-architecture -qDEB_HOST_MULTIARCH)\nDATE := $(shell date)\n\n%:\n\tdh $@\n\noverride_dh_auto_test:\nifeq (,$(findstring nocheck,$(DEB_BUILD_OPTIONS)))\n\tdh_auto_test\nendif\n";
+        assert_eq!(
+            check_all(text),
+            vec![
+                (0, "subshell-instead-of-include".to_string()),
+                (7, "findstring-build-options".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_context_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let rules = dir.path().join("rules");
+        std::fs::write(&rules, "%:\n\tdh $@\n").unwrap();
+        std::fs::write(dir.path().join("compat"), "12\n").unwrap();
+        let control: Control = "Source: foo\nBuild-Depends: debhelper-compat (= 13)\n"
+            .parse()
+            .unwrap();
+
+        let context = Context::read(&rules, true);
+        assert_eq!(context.compat.as_deref(), Some("12\n"));
+        assert_eq!(context.debhelper(Some(&control)).compat, Some(12));
+        #[cfg(unix)]
+        assert_eq!(context.executable, Some(false));
+
+        // An open debian/compat is not read from disk.
+        let context = Context::read(&rules, false);
+        assert_eq!(context.compat, None);
+        assert_eq!(context.debhelper(Some(&control)).compat, Some(13));
+    }
+}
@@ -169,7 +169,6 @@ pub const RULES_VARIABLES: &[RulesVariable] = &[
 /// Falls back to synthesised descriptions for the `override_dh_*` and
 /// `execute_{before,after}_dh_*` families, which are open-ended and so cannot
 /// all be enumerated in [`RULES_TARGETS`].
-#[cfg(feature = "scip")]
 pub fn target_description(name: &str) -> Option<String> {
     if let Some(t) = RULES_TARGETS.iter().find(|t| t.name == name) {
         return Some(t.description.to_owned());
@@ -187,7 +186,6 @@ pub fn target_description(name: &str) -> Option<String> {
 }
 
 /// Look up a human-readable description for a known `debian/rules` variable.
-#[cfg(feature = "scip")]
 pub fn variable_description(name: &str) -> Option<&'static str> {
     RULES_VARIABLES
         .iter()
@@ -0,0 +1,232 @@
+//! Hover support for debian/rules files.
+//!
+//! Shows the value of the variable under the cursor, or the documentation of
+//! the debhelper command under it, either invoked in a recipe or hooked by an
+//! `override_dh_*` or `execute_{before,after}_dh_*` target.
+
+use makefile_lossless::{Makefile, SyntaxKind};
+use rowan::ast::AstNode;
+use tower_lsp_server::ls_types::{Hover, Position, Uri};
+
+use super::debhelper::{command_for_target, helper_doc, iter_dh_commands, HelperDoc};
+use super::fields::{target_description, variable_description};
+use super::variables::{
+    definitions, maint_options_effects, variable_at, Environment, Resolution, Resolver,
+};
+use crate::debhelper::hover::{line_at, markdown};
+use crate::position::Source;
+
+/// The markdown describing debhelper `command`.
+fn command_markdown(command: &str, doc: Option<&HelperDoc>) -> String {
+    let mut value = format!("**{}**", command);
+    if let Some(summary) = doc.and_then(|d| d.summary.as_deref()) {
+        value.push_str(&format!(" - {}", summary));
+    }
+    if let Some(synopsis) = doc.and_then(|d| d.synopsis.as_deref()) {
+        value.push_str(&format!("\n\n```\n{}\n```", synopsis));
+    }
+    value
+}
+
+/// The markdown describing variable `name`.
+fn variable_markdown(
+    makefile: &Makefile,
+    src: Source<'_>,
+    uri: &Uri,
+    environment: &Environment,
+    name: &str,
+) -> String {
+    let definitions = definitions(makefile, src, uri, environment.root.as_deref());
+    let resolver = Resolver::new(makefile, &definitions, environment);
+    let mut value = format!("**{}**", name);
+    if let Some(description) = variable_description(name) {
+        value.push_str(&format!("\n\n{}", description));
+    }
+    let assignments = resolver
+        .definitions_of(name)
+        .map(|d| format!("{} {} {}", d.name, d.operator, d.value))
+        .collect::<Vec<_>>();
+    if !assignments.is_empty() {
+        value.push_str(&format!("\n\n```make\n{}\n```", assignments.join("\n")));
+    }
+    let resolved = match resolver.resolve(name) {
+        Resolution::Assigned(resolved) => resolved,
+        Resolution::Dpkg { makefile, value: v } => {
+            value.push_str(&format!("\n\nSet by `/usr/share/dpkg/{}`.", makefile));
+            v
+        }
+        Resolution::NotIncluded {
+            makefile: "architecture.mk",
+        } => {
+            value.push_str(
+                "\n\nSet by dpkg-buildpackage; include `/usr/share/dpkg/architecture.mk` to also set it when `debian/rules` is run directly.",
+            );
+            environment.dpkg.get(name).cloned()
+        }
+        Resolution::NotIncluded { makefile } => {
+            value.push_str(&format!(
+                "\n\nNot set unless `/usr/share/dpkg/{}` is included.",
+                makefile
+            ));
+            None
+        }
+        Resolution::Unknown => None,
+    };
+    if let Some(resolved) = &resolved {
+        value.push_str(&format!("\n\nValue: `{}`", resolved));
+    }
+    if name == "DEB_BUILD_MAINT_OPTIONS" {
+        for effect in maint_options_effects(resolved.as_deref().unwrap_or_default()) {
+            value.push_str(&format!("\n- {}", effect));
+        }
+    }
+    value
+}
+
+/// Get hover information for the variable or debhelper command at
+/// `position` in the rules file at `uri`.
+pub fn get_hover(
+    makefile: &Makefile,
+    src: Source<'_>,
+    uri: &Uri,
+    position: Position,
+    environment: &Environment,
+) -> Option<Hover> {
+    if let Some(name) = variable_at(makefile, src, position) {
+        return Some(markdown(variable_markdown(
+            makefile,
+            src,
+            uri,
+            environment,
+            &name,
+        )));
+    }
+    hover_with(makefile, src, position, helper_doc)
+}
+
+fn hover_with(
+    makefile: &Makefile,
+    src: Source<'_>,
+    position: Position,
+    doc: impl Fn(&str) -> Option<HelperDoc>,
+) -> Option<Hover> {
+    let offset = src.try_position_to_offset(position)?;
+
+    if let Some(token) = makefile
+        .syntax()
+        .token_at_offset(offset)
+        .find(|t| t.kind() == SyntaxKind::IDENTIFIER)
+        .filter(|t| t.parent().is_some_and(|p| p.kind() == SyntaxKind::TARGETS))
+    {
+        let target = token.text();
+        let command = command_for_target(target)?;
+        let mut value = format!("**{}**", target);
+        if let Some(description) = target_description(target) {
+            value.push_str(&format!("\n\n{}", description));
+        }
+        value.push_str("\n\n");
+        value.push_str(&command_markdown(command, doc(command).as_ref()));
+        return Some(markdown(value));
+    }
+
+    let (line, column) = line_at(src, position)?;
+    if !line.starts_with('\t') {
+        return None;
+    }
+    let command = iter_dh_commands(line, 0)
+        .into_iter()
+        .find(|c| c.start as usize <= column && column <= c.end as usize)?;
+    let doc = doc(&command.name)?;
+    Some(markdown(command_markdown(&command.name, Some(&doc))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+    use tower_lsp_server::ls_types::HoverContents;
+
+    fn hover(text: &str, position: Position) -> Option<String> {
+        let makefile = Makefile::parse(text).tree();
+        let idx = LineIndex::new(text);
+        let doc = |command: &str| {
+            (command == "dh_install").then(|| HelperDoc {
+                summary: Some("install files into package build directories".to_string()),
+                synopsis: Some("dh_install [debhelper options] [file|dir ...]".to_string()),
+                options: Vec::new(),
+            })
+        };
+        let hover = hover_with(&makefile, Source::new(text, &idx), position, doc)?;
+        let HoverContents::Markup(content) = hover.contents else {
+            panic!("expected markup");
+        };
+        Some(content.value)
+    }
+
+    #[test]
+    fn test_hover_recipe_command() {
+        let text = "override_dh_install:\n\tdh_install --list-missing\n";
+        assert_eq!(
+            hover(text, Position::new(1, 4)).as_deref(),
+            Some(
+                "**dh_install** - install files into package build directories\n\n```\ndh_install [debhelper options] [file|dir ...]\n```"
+            )
+        );
+        // Options are not commands.
+        assert_eq!(hover(text, Position::new(1, 16)), None);
+        // Undocumented commands have nothing to show.
+        assert_eq!(hover("build:\n\tdh_foo\n", Position::new(1, 3)), None);
+    }
+
+    #[test]
+    fn test_hover_override_target() {
+        let text = "execute_after_dh_install-indep:\n\ttrue\n";
+        let value = hover(text, Position::new(0, 5)).unwrap();
+        assert!(value.starts_with(
+            "**execute_after_dh_install-indep**\n\nExecute after dh_install-indep step\n\n**dh_install** - "
+        ));
+        assert_eq!(hover("build:\n\ttrue\n", Position::new(0, 2)), None);
+    }
+
+    #[test]
+    fn test_hover_variable() {
+        let text = "include /usr/share/dpkg/pkg-info.mk\nexport DEB_BUILD_MAINT_OPTIONS = hardening=+all\nPKG = $(DEB_SOURCE)-bin\n\nbuild:\n\techo $(PKG) $(DEB_HOST_MULTIARCH)\n";
+        let makefile = Makefile::parse(text).tree();
+        let idx = LineIndex::new(text);
+        let uri: Uri = "file:///nonexistent/debian/rules".parse().unwrap();
+        let environment = Environment {
+            root: None,
+            dpkg: [
+                ("DEB_SOURCE", "foo"),
+                ("DEB_HOST_MULTIARCH", "x86_64-linux-gnu"),
+            ]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        };
+        let hover = |position| {
+            let hover = get_hover(
+                &makefile,
+                Source::new(text, &idx),
+                &uri,
+                position,
+                &environment,
+            )?;
+            let HoverContents::Markup(content) = hover.contents else {
+                panic!("expected markup");
+            };
+            Some(content.value)
+        };
+        assert_eq!(
+            hover(Position::new(5, 9)).as_deref(),
+            Some("**PKG**\n\n```make\nPKG = $(DEB_SOURCE)-bin\n```\n\nValue: `foo-bin`")
+        );
+        assert_eq!(
+            hover(Position::new(5, 20)).as_deref(),
+            Some("**DEB_HOST_MULTIARCH**\n\nMultiarch triplet for the host architecture\n\nSet by dpkg-buildpackage; include `/usr/share/dpkg/architecture.mk` to also set it when `debian/rules` is run directly.\n\nValue: `x86_64-linux-gnu`")
+        );
+        assert!(hover(Position::new(1, 10))
+            .unwrap()
+            .ends_with("Value: `hardening=+all`\n- Enables all hardening features"));
+    }
+}
@@ -3,12 +3,19 @@
 //! These files are Makefiles that define how to build a Debian package.
 
 pub mod completion;
-#[cfg(feature = "scip")]
 pub mod debhelper;
+pub mod definition;
 pub mod detection;
+pub mod diagnostics;
 pub mod fields;
+pub mod hover;
 pub mod semantic;
+pub mod symbols;
+pub mod variables;
 
 pub use completion::get_completions;
+pub use definition::goto_definition;
 pub use detection::is_rules_file;
+pub use hover::get_hover;
 pub use semantic::generate_semantic_tokens;
+pub use symbols::generate_document_symbols;
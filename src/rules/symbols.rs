@@ -0,0 +1,82 @@
+//! Document symbol generation for debian/rules files.
+
+use crate::position::Source;
+use makefile_lossless::Makefile;
+use rowan::ast::AstNode;
+use tower_lsp_server::ls_types::{DocumentSymbol, SymbolKind};
+
+/// Generate document symbols for a rules file.
+///
+/// Each rule target becomes a FUNCTION symbol spanning the whole rule, with
+/// the target name in the rule header as the selection range.
+#[allow(deprecated)] // DocumentSymbol::deprecated field
+pub fn generate_document_symbols(makefile: &Makefile, src: Source<'_>) -> Vec<DocumentSymbol> {
+    let mut symbols = Vec::new();
+
+    for rule in makefile.rules() {
+        let rule_range = rule.syntax().text_range();
+        let range = src.text_range_to_lsp_range(rule_range);
+        let rule_text = &src.text[rule_range];
+        let header = rule_text.split(':').next().unwrap_or(rule_text);
+
+        for target in rule.targets() {
+            if target.is_empty() {
+                continue;
+            }
+            let selection_range = header
+                .find(target.as_str())
+                .map(|pos| {
+                    let start = rule_range.start() + rowan::TextSize::from(pos as u32);
+                    let target_range = rowan::TextRange::at(start, rowan::TextSize::of(&target));
+                    src.text_range_to_lsp_range(target_range)
+                })
+                .unwrap_or(range);
+
+            symbols.push(DocumentSymbol {
+                name: target,
+                detail: None,
+                kind: SymbolKind::FUNCTION,
+                tags: None,
+                deprecated: None,
+                range,
+                selection_range,
+                children: None,
+            });
+        }
+    }
+
+    symbols
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp_server::ls_types::{Position, Range};
+
+    #[test]
+    fn test_targets() {
+        let text = "\
+#!/usr/bin/make -f
+
+%:
+\tdh $@
+
+override_dh_auto_test override_dh_auto_install:
+\ttrue
+";
+        let (makefile, _) = Makefile::from_str_relaxed(text);
+        let idx = crate::position::LineIndex::new(text);
+        let symbols = generate_document_symbols(&makefile, Source::new(text, &idx));
+
+        let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["%", "override_dh_auto_test", "override_dh_auto_install"]
+        );
+        assert_eq!(
+            symbols[2].selection_range,
+            Range::new(Position::new(5, 22), Position::new(5, 46))
+        );
+        assert_eq!(symbols[1].range.start, Position::new(5, 0));
+    }
+}
@@ -0,0 +1,892 @@
+//! Variable resolution for debian/rules files.
+//!
+//! A variable gets its value from the assignments in `debian/rules` and the
+//! makefiles it includes from the source tree, or from the dpkg makefile
+//! fragments in `/usr/share/dpkg`, which compute theirs from
+//! `debian/changelog` and `dpkg-architecture`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use debian_changelog::ChangeLog;
+use makefile_lossless::{Makefile, SyntaxKind};
+use rowan::ast::AstNode;
+use rowan::TextRange;
+use tokio::sync::OnceCell;
+use tower_lsp_server::ls_types::{Location, Position, Uri};
+
+use crate::position::{LineIndex, Source};
+
+/// Where dpkg installs its makefile fragments.
+const DPKG_DATADIR: &str = "/usr/share/dpkg";
+
+/// Makefiles included from the source tree are followed this deep.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// References are expanded this deep.
+const MAX_EXPANSION_DEPTH: usize = 16;
+
+/// The machine-specific part of the variables set by `architecture.mk`, as in
+/// `DEB_HOST_<part>`.
+const ARCHITECTURE_PARTS: &[&str] = &[
+    "ARCH",
+    "ARCH_ABI",
+    "ARCH_LIBC",
+    "ARCH_OS",
+    "ARCH_CPU",
+    "ARCH_BITS",
+    "ARCH_ENDIAN",
+    "GNU_CPU",
+    "GNU_SYSTEM",
+    "GNU_TYPE",
+    "MULTIARCH",
+];
+
+/// The dpkg makefile fragments and the variables they set, besides those of
+/// `architecture.mk`.
+const DPKG_MAKEFILES: &[(&str, &[&str])] = &[
+    (
+        "pkg-info.mk",
+        &[
+            "DEB_SOURCE",
+            "DEB_VERSION",
+            "DEB_VERSION_EPOCH_UPSTREAM",
+            "DEB_VERSION_UPSTREAM_REVISION",
+            "DEB_VERSION_UPSTREAM",
+            "DEB_DISTRIBUTION",
+            "SOURCE_DATE_EPOCH",
+        ],
+    ),
+    ("vendor.mk", &["DEB_VENDOR", "DEB_PARENT_VENDOR"]),
+    ("buildopts.mk", &["DEB_BUILD_OPTION_PARALLEL"]),
+    (
+        "buildflags.mk",
+        &[
+            "ASFLAGS",
+            "CFLAGS",
+            "CPPFLAGS",
+            "CXXFLAGS",
+            "OBJCFLAGS",
+            "OBJCXXFLAGS",
+            "DFLAGS",
+            "FFLAGS",
+            "FCFLAGS",
+            "LDFLAGS",
+        ],
+    ),
+    (
+        "buildtools.mk",
+        &[
+            "AS",
+            "CPP",
+            "CC",
+            "CXX",
+            "OBJC",
+            "OBJCXX",
+            "F77",
+            "FC",
+            "LD",
+            "LEX",
+            "PKG_CONFIG",
+            "YACC",
+            "AR",
+            "RANLIB",
+            "STRIP",
+            "OBJCOPY",
+            "OBJDUMP",
+            "NM",
+        ],
+    ),
+];
+
+/// The fragments `default.mk` includes.
+const DEFAULT_MAKEFILES: &[&str] = &[
+    "architecture.mk",
+    "buildflags.mk",
+    "buildopts.mk",
+    "pkg-info.mk",
+    "vendor.mk",
+];
+
+/// The dpkg makefile fragment that sets `name`.
+pub fn dpkg_makefile(name: &str) -> Option<&'static str> {
+    let is_architecture = ["DEB_BUILD_", "DEB_HOST_", "DEB_TARGET_"]
+        .iter()
+        .filter_map(|prefix| name.strip_prefix(prefix))
+        .any(|part| ARCHITECTURE_PARTS.contains(&part));
+    if is_architecture {
+        return Some("architecture.mk");
+    }
+    DPKG_MAKEFILES
+        .iter()
+        .find(|(_, variables)| variables.contains(&name))
+        .map(|(makefile, _)| *makefile)
+}
+
+/// What dpkg's makefile fragments set `DEB_BUILD_MAINT_OPTIONS` features to
+/// do, by area and feature, from dpkg-buildflags(1).
+const MAINT_OPTIONS: &[(&str, &str, &str)] = &[
+    (
+        "hardening",
+        "format",
+        "warn about insecure format string use",
+    ),
+    (
+        "hardening",
+        "fortify",
+        "check buffer overflows with _FORTIFY_SOURCE",
+    ),
+    (
+        "hardening",
+        "stackprotector",
+        "protect the stack against smashing",
+    ),
+    (
+        "hardening",
+        "stackprotectorstrong",
+        "protect more functions against stack smashing",
+    ),
+    (
+        "hardening",
+        "stackclash",
+        "protect against stack clash attacks",
+    ),
+    (
+        "hardening",
+        "branch",
+        "protect branches with CET or BTI/PAC",
+    ),
+    (
+        "hardening",
+        "relro",
+        "make sections read-only after relocation",
+    ),
+    (
+        "hardening",
+        "bindnow",
+        "resolve all symbols when loading (-z now)",
+    ),
+    ("hardening", "pie", "build position independent executables"),
+    ("qa", "bug", "make warnings about serious bugs fatal"),
+    (
+        "qa",
+        "bug-implicit-func",
+        "make implicit function declarations fatal",
+    ),
+    (
+        "qa",
+        "canary",
+        "add canary flags to catch flags that are not passed on",
+    ),
+    (
+        "reproducible",
+        "timeless",
+        "warn about __TIME__ and __DATE__ use",
+    ),
+    (
+        "reproducible",
+        "fixfilepath",
+        "map the build path out of __FILE__",
+    ),
+    (
+        "reproducible",
+        "fixdebugpath",
+        "map the build path out of debug info",
+    ),
+    ("optimize", "lto", "link time optimization"),
+    ("sanitize", "address", "AddressSanitizer"),
+    ("sanitize", "thread", "ThreadSanitizer"),
+    ("sanitize", "leak", "LeakSanitizer"),
+    ("sanitize", "undefined", "UndefinedBehaviorSanitizer"),
+    (
+        "future",
+        "lfs",
+        "large file support on 32-bit architectures",
+    ),
+    ("abi", "lfs", "large file support on 32-bit architectures"),
+    ("abi", "time64", "64-bit time_t on 32-bit architectures"),
+];
+
+/// Describe the features a `DEB_BUILD_MAINT_OPTIONS` value enables or
+/// disables, one line each.
+pub fn maint_options_effects(value: &str) -> Vec<String> {
+    let mut effects = Vec::new();
+    for option in value.split_whitespace() {
+        let Some((area, features)) = option.split_once('=') else {
+            continue;
+        };
+        for feature in features.split(',') {
+            let (enable, feature) = match feature.split_at_checked(1) {
+                Some(("+", rest)) => (true, rest),
+                Some(("-", rest)) => (false, rest),
+                _ => continue,
+            };
+            let verb = if enable { "Enables" } else { "Disables" };
+            if feature == "all" {
+                effects.push(format!("{} all {} features", verb, area));
+                continue;
+            }
+            match MAINT_OPTIONS
+                .iter()
+                .find(|(a, f, _)| *a == area && *f == feature)
+            {
+                Some((_, _, description)) => {
+                    effects.push(format!("{} {} `{}`: {}", verb, area, feature, description))
+                }
+                None => effects.push(format!("Unknown {} feature `{}`", area, feature)),
+            }
+        }
+    }
+    effects
+}
+
+/// An assignment of a variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+    pub name: String,
+    /// The assignment operator, e.g. `:=`.
+    pub operator: String,
+    pub value: String,
+    /// Where the name is assigned.
+    pub location: Location,
+}
+
+/// What variable resolution knows outside of `debian/rules` itself.
+#[derive(Debug, Default, Clone)]
+pub struct Environment {
+    /// The root of the source tree, which included paths are relative to.
+    pub root: Option<PathBuf>,
+    /// The values the dpkg makefile fragments would set.
+    pub dpkg: BTreeMap<String, String>,
+}
+
+impl Environment {
+    /// The environment of the rules file at `path`, with the values the
+    /// dpkg makefile fragments compute from `changelog` and `system`, the
+    /// result of [`system_values`].
+    pub fn load(
+        path: &Path,
+        changelog: Option<&ChangeLog>,
+        system: &BTreeMap<String, String>,
+    ) -> Self {
+        let root = path.parent().and_then(Path::parent).map(Path::to_path_buf);
+        let mut dpkg = system.clone();
+        if let Some(changelog) = changelog {
+            dpkg.extend(changelog_values(changelog));
+        }
+        Self { root, dpkg }
+    }
+}
+
+/// The values `pkg-info.mk` computes from `changelog`.
+fn changelog_values(changelog: &ChangeLog) -> BTreeMap<String, String> {
+    let mut values = BTreeMap::new();
+    let Some(entry) = changelog.iter().next() else {
+        return values;
+    };
+    if let Some(package) = entry.package() {
+        values.insert("DEB_SOURCE".to_string(), package);
+    }
+    if let Some(version) = entry.version() {
+        let version = version.to_string();
+        // The same edits pkg-info.mk makes with sed.
+        let epoch_upstream = version
+            .rsplit_once('-')
+            .map_or(version.as_str(), |(v, _)| v)
+            .to_string();
+        let strip_epoch = |v: &str| {
+            v.split_once(':')
+                .filter(|(epoch, _)| epoch.bytes().all(|b| b.is_ascii_digit()))
+                .map_or(v.to_string(), |(_, rest)| rest.to_string())
+        };
+        values.insert(
+            "DEB_VERSION_UPSTREAM_REVISION".to_string(),
+            strip_epoch(&version),
+        );
+        values.insert(
+            "DEB_VERSION_UPSTREAM".to_string(),
+            strip_epoch(&epoch_upstream),
+        );
+        values.insert("DEB_VERSION_EPOCH_UPSTREAM".to_string(), epoch_upstream);
+        values.insert("DEB_VERSION".to_string(), version);
+    }
+    if let Some(distributions) = entry.distributions() {
+        values.insert("DEB_DISTRIBUTION".to_string(), distributions.join(" "));
+    }
+    if let Some(datetime) = entry.datetime() {
+        values.insert(
+            "SOURCE_DATE_EPOCH".to_string(),
+            datetime.timestamp().to_string(),
+        );
+    }
+    values
+}
+
+/// Read `VAR=value` lines.
+fn parse_assignments(text: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    text.lines().filter_map(|line| {
+        let (name, value) = line.split_once('=')?;
+        Some((name.to_string(), value.to_string()))
+    })
+}
+
+/// The values of `architecture.mk` and `vendor.mk`, from running
+/// `dpkg-architecture` and `dpkg-vendor`.
+fn read_system_values() -> BTreeMap<String, String> {
+    let output = |program: &str, args: &[&str]| {
+        std::process::Command::new(program)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+    };
+    let mut values = BTreeMap::new();
+    if let Some(text) = output("dpkg-architecture", &[]) {
+        values.extend(parse_assignments(&text));
+    }
+    for (variable, field) in [("DEB_VENDOR", "Vendor"), ("DEB_PARENT_VENDOR", "Parent")] {
+        if let Some(text) = output("dpkg-vendor", &["--query", field]) {
+            values.insert(variable.to_string(), text.trim().to_string());
+        }
+    }
+    values
+}
+
+static SYSTEM_VALUES: OnceCell<BTreeMap<String, String>> = OnceCell::const_new();
+
+/// The values of `architecture.mk` and `vendor.mk`, which depend only on the
+/// system, read once per process. The commands are run on a blocking thread.
+pub async fn system_values() -> &'static BTreeMap<String, String> {
+    SYSTEM_VALUES
+        .get_or_init(|| async {
+            tokio::task::spawn_blocking(read_system_values)
+                .await
+                .unwrap_or_default()
+        })
+        .await
+}
+
+/// The dpkg makefile fragments `makefile` includes, by file name.
+pub fn included_dpkg_makefiles(makefile: &Makefile) -> BTreeSet<&'static str> {
+    let mut included = BTreeSet::new();
+    for path in makefile.includes().filter_map(|i| i.path()) {
+        let Some(name) = path
+            .strip_prefix(DPKG_DATADIR)
+            .and_then(|p| p.strip_prefix('/'))
+        else {
+            continue;
+        };
+        if name == "default.mk" {
+            included.extend(DEFAULT_MAKEFILES);
+        } else if let Some((name, _)) = DPKG_MAKEFILES.iter().find(|(n, _)| *n == name) {
+            included.insert(*name);
+        } else if name == "architecture.mk" {
+            included.insert("architecture.mk");
+        }
+    }
+    included
+}
+
+/// The range of the name in a variable definition.
+fn name_range(definition: &makefile_lossless::VariableDefinition) -> Option<TextRange> {
+    let name = definition.name()?;
+    definition
+        .syntax()
+        .children_with_tokens()
+        .filter_map(|e| e.into_token())
+        .find(|t| t.kind() == SyntaxKind::IDENTIFIER && t.text() == name)
+        .map(|t| t.text_range())
+}
+
+/// The file an `include` of `path` reads, if it is in the source tree at
+/// `root`. `..` components and symbolic links are resolved before the check,
+/// so they cannot lead out of the tree.
+fn included_path(path: &str, root: &Path) -> Option<PathBuf> {
+    let path = path.replace("$(CURDIR)", &root.to_string_lossy());
+    let path = path.replace("${CURDIR}", &root.to_string_lossy());
+    if path.contains('$') || path.split_whitespace().count() != 1 {
+        return None;
+    }
+    let root = std::fs::canonicalize(root).ok()?;
+    let path = std::fs::canonicalize(root.join(path)).ok()?;
+    path.starts_with(&root).then_some(path)
+}
+
+fn collect_definitions(
+    makefile: &Makefile,
+    src: Source<'_>,
+    uri: &Uri,
+    root: Option<&Path>,
+    depth: usize,
+    visited: &mut BTreeSet<PathBuf>,
+    out: &mut Vec<Definition>,
+) {
+    // Assignments and includes in the order make reads them.
+    let mut items: Vec<(TextRange, Option<Definition>, Option<PathBuf>)> = Vec::new();
+    for definition in makefile.variable_definitions() {
+        let (Some(name), Some(range)) = (definition.name(), name_range(&definition)) else {
+            continue;
+        };
+        items.push((
+            range,
+            Some(Definition {
+                name,
+                operator: definition.assignment_operator().unwrap_or_default(),
+                value: definition
+                    .raw_value()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+                location: Location {
+                    uri: uri.clone(),
+                    range: src.text_range_to_lsp_range(range),
+                },
+            }),
+            None,
+        ));
+    }
+    if let Some(root) = root.filter(|_| depth < MAX_INCLUDE_DEPTH) {
+        for include in makefile.includes() {
+            if let Some(path) = include.path().and_then(|p| included_path(&p, root)) {
+                items.push((include.syntax().text_range(), None, Some(path)));
+            }
+        }
+    }
+    items.sort_by_key(|(range, _, _)| range.start());
+
+    for (_, definition, path) in items {
+        if let Some(definition) = definition {
+            out.push(definition);
+        }
+        let Some(path) = path else {
+            continue;
+        };
+        if !visited.insert(path.clone()) {
+            continue;
+        }
+        let (Ok(text), Some(uri)) = (std::fs::read_to_string(&path), Uri::from_file_path(&path))
+        else {
+            continue;
+        };
+        let (included, _) = Makefile::from_str_relaxed(&text);
+        let idx = LineIndex::new(&text);
+        collect_definitions(
+            &included,
+            Source::new(&text, &idx),
+            &uri,
+            root,
+            depth + 1,
+            visited,
+            out,
+        );
+    }
+}
+
+/// The assignments of the rules file `makefile` at `uri` and the makefiles it
+/// includes from the source tree, in the order make reads them.
+pub fn definitions(
+    makefile: &Makefile,
+    src: Source<'_>,
+    uri: &Uri,
+    root: Option<&Path>,
+) -> Vec<Definition> {
+    let mut out = Vec::new();
+    let mut visited = BTreeSet::new();
+    if let Some(path) = uri.to_file_path() {
+        // Included paths are canonical.
+        visited.insert(std::fs::canonicalize(&path).unwrap_or_else(|_| path.into_owned()));
+    }
+    collect_definitions(makefile, src, uri, root, 0, &mut visited, &mut out);
+    out
+}
+
+/// Resolves variables of a rules file.
+pub struct Resolver<'a> {
+    definitions: &'a [Definition],
+    environment: &'a Environment,
+    included: BTreeSet<&'static str>,
+}
+
+/// The resolved value of a variable and where it comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    /// Assigned in the source tree.
+    Assigned(Option<String>),
+    /// Set by an included dpkg makefile fragment.
+    Dpkg {
+        makefile: &'static str,
+        value: Option<String>,
+    },
+    /// Set by a dpkg makefile fragment that is not included.
+    NotIncluded {
+        makefile: &'static str,
+    },
+    Unknown,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(
+        makefile: &Makefile,
+        definitions: &'a [Definition],
+        environment: &'a Environment,
+    ) -> Self {
+        Self {
+            definitions,
+            environment,
+            included: included_dpkg_makefiles(makefile),
+        }
+    }
+
+    /// The assignments of `name`.
+    pub fn definitions_of<'b>(
+        &'b self,
+        name: &'b str,
+    ) -> impl Iterator<Item = &'a Definition> + 'b {
+        self.definitions.iter().filter(move |d| d.name == name)
+    }
+
+    /// Where `name` gets its value and what it is, if that can be computed.
+    pub fn resolve(&self, name: &str) -> Resolution {
+        self.resolve_at(name, 0, self.definitions.len())
+    }
+
+    fn dpkg_value(&self, name: &str) -> Option<String> {
+        self.environment.dpkg.get(name).cloned()
+    }
+
+    /// Where `name` gets its value and what it is once make has read the
+    /// first `end` definitions.
+    fn resolve_at(&self, name: &str, depth: usize, end: usize) -> Resolution {
+        let dpkg = dpkg_makefile(name).filter(|m| self.included.contains(m));
+        let mut value: Option<Option<String>> = None;
+        // Whether the variable is simply expanded, as `:=` makes it.
+        let mut simple = false;
+        for (index, definition) in self.definitions[..end].iter().enumerate() {
+            if definition.name != name {
+                continue;
+            }
+            // An immediate assignment is expanded against the definitions
+            // read before it, a recursive one when the variable is used.
+            let expanded = |immediate: bool| {
+                self.expand_at(
+                    &definition.value,
+                    depth + 1,
+                    if immediate { index } else { end },
+                )
+            };
+            value = match definition.operator.as_str() {
+                "?=" if value.is_some() => value,
+                "?=" => {
+                    simple = false;
+                    Some(expanded(false))
+                }
+                "+=" => Some(match value {
+                    Some(Some(old)) => expanded(simple).map(|new| format!("{} {}", old, new)),
+                    Some(None) => None,
+                    None => expanded(false),
+                }),
+                // The output of a shell command.
+                "!=" => Some(None),
+                ":=" | "::=" | ":::=" => {
+                    simple = true;
+                    Some(expanded(true))
+                }
+                _ => {
+                    simple = false;
+                    Some(expanded(false))
+                }
+            };
+        }
+        let assigned_later = self.definitions[end..].iter().any(|d| d.name == name);
+        match (value, dpkg) {
+            (Some(value), _) => Resolution::Assigned(value),
+            // dpkg's fragments only set variables that are still unset.
+            (None, Some(makefile)) => Resolution::Dpkg {
+                makefile,
+                value: self.dpkg_value(name),
+            },
+            // Not assigned yet, so empty.
+            (None, None) if assigned_later => Resolution::Assigned(Some(String::new())),
+            (None, None) => match dpkg_makefile(name) {
+                Some(makefile) => Resolution::NotIncluded { makefile },
+                None => Resolution::Unknown,
+            },
+        }
+    }
+
+    /// The value of `name`, if it can be computed.
+    fn value_at(&self, name: &str, depth: usize, end: usize) -> Option<String> {
+        match self.resolve_at(name, depth, end) {
+            Resolution::Assigned(value) | Resolution::Dpkg { value, .. } => value,
+            // dpkg-buildpackage exports the architecture variables itself.
+            Resolution::NotIncluded {
+                makefile: "architecture.mk",
+            } => self.dpkg_value(name),
+            Resolution::NotIncluded { .. } | Resolution::Unknown => None,
+        }
+    }
+
+    /// Expand the variable references in `text` once make has read the
+    /// first `end` definitions, if they can all be computed.
+    fn expand_at(&self, text: &str, depth: usize, end: usize) -> Option<String> {
+        if depth > MAX_EXPANSION_DEPTH {
+            return None;
+        }
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(i) = rest.find('$') {
+            out.push_str(&rest[..i]);
+            rest = &rest[i..];
+            let close = match rest.as_bytes().get(1) {
+                Some(b'$') => {
+                    out.push('$');
+                    rest = &rest[2..];
+                    continue;
+                }
+                Some(b'(') => ')',
+                Some(b'{') => '}',
+                _ => return None,
+            };
+            let name_end = rest.find(close)?;
+            let name = &rest[2..name_end];
+            if !name
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+            {
+                // A function call.
+                return None;
+            }
+            out.push_str(&self.value_at(name, depth, end)?);
+            rest = &rest[name_end + 1..];
+        }
+        out.push_str(rest);
+        Some(out)
+    }
+}
+
+/// The variable under `position`: a reference in an expression or recipe, or
+/// the name of an assignment.
+pub fn variable_at(makefile: &Makefile, src: Source<'_>, position: Position) -> Option<String> {
+    let offset = src.try_position_to_offset(position)?;
+    let contains = |range: TextRange| range.start() <= offset && offset <= range.end();
+
+    let in_recipes = makefile
+        .rules()
+        .flat_map(|rule| rule.recipe_nodes().collect::<Vec<_>>())
+        .flat_map(|recipe| recipe.variable_references())
+        .find(|r| contains(r.text_range()))
+        .map(|r| r.name().to_string());
+    if in_recipes.is_some() {
+        return in_recipes;
+    }
+
+    let reference = makefile
+        .variable_references()
+        .filter_map(|r| {
+            let token = r
+                .syntax()
+                .children_with_tokens()
+                .filter_map(|e| e.into_token())
+                .find(|t| t.kind() == SyntaxKind::IDENTIFIER)?;
+            contains(token.text_range()).then(|| token.text().to_string())
+        })
+        .next();
+    if reference.is_some() {
+        return reference;
+    }
+
+    makefile
+        .variable_definitions()
+        .find(|d| name_range(d).is_some_and(contains))
+        .and_then(|d| d.name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri() -> Uri {
+        "file:///nonexistent/debian/rules".parse().unwrap()
+    }
+
+    fn resolve(text: &str, environment: &Environment, name: &str) -> Resolution {
+        let (makefile, _) = Makefile::from_str_relaxed(text);
+        let idx = LineIndex::new(text);
+        let definitions = definitions(&makefile, Source::new(text, &idx), &uri(), None);
+        Resolver::new(&makefile, &definitions, environment).resolve(name)
+    }
+
+    fn environment() -> Environment {
+        let changelog = ChangeLog::parse("foo (1:2.0-1) unstable; urgency=medium\n\n  * Initial.\n\n -- Jane Doe <jane@example.com>  Thu, 01 Jan 2026 00:00:00 +0000\n").tree();
+        let mut dpkg = changelog_values(&changelog);
+        dpkg.insert(
+            "DEB_HOST_MULTIARCH".to_string(),
+            "x86_64-linux-gnu".to_string(),
+        );
+        Environment { root: None, dpkg }
+    }
+
+    #[test]
+    fn test_changelog_values() {
+        let env = environment();
+        assert_eq!(env.dpkg["DEB_SOURCE"], "foo");
+        assert_eq!(env.dpkg["DEB_VERSION"], "1:2.0-1");
+        assert_eq!(env.dpkg["DEB_VERSION_EPOCH_UPSTREAM"], "1:2.0");
+        assert_eq!(env.dpkg["DEB_VERSION_UPSTREAM_REVISION"], "2.0-1");
+        assert_eq!(env.dpkg["DEB_VERSION_UPSTREAM"], "2.0");
+        assert_eq!(env.dpkg["DEB_DISTRIBUTION"], "unstable");
+        assert_eq!(env.dpkg["SOURCE_DATE_EPOCH"], "1767225600");
+    }
+
+    #[test]
+    fn test_resolve_assignments() {
+        let text = "include /usr/share/dpkg/pkg-info.mk\nNAME = $(DEB_SOURCE)-$(DEB_VERSION_UPSTREAM)\nNAME += extra\nLIB := /usr/lib/$(DEB_HOST_MULTIARCH)\nLIB ?= ignored\n";
+        let env = environment();
+        assert_eq!(
+            resolve(text, &env, "NAME"),
+            Resolution::Assigned(Some("foo-2.0 extra".to_string()))
+        );
+        assert_eq!(
+            resolve(text, &env, "LIB"),
+            Resolution::Assigned(Some("/usr/lib/x86_64-linux-gnu".to_string()))
+        );
+        assert_eq!(
+            resolve(text, &env, "DEB_VERSION"),
+            Resolution::Dpkg {
+                makefile: "pkg-info.mk",
+                value: Some("1:2.0-1".to_string())
+            }
+        );
+        assert_eq!(
+            resolve(text, &env, "DEB_HOST_MULTIARCH"),
+            Resolution::NotIncluded {
+                makefile: "architecture.mk"
+            }
+        );
+        assert_eq!(resolve(text, &env, "FOO"), Resolution::Unknown);
+    }
+
+    #[test]
+    fn test_resolve_evaluation_order() {
+        let text = "A := $(B)\nB = x\nC = $(D)\nE := $(C)\nD = y\nF := $(D)\nF += $(G)\nG = z\n";
+        let env = Environment::default();
+        // Immediate assignments see only the definitions before them.
+        assert_eq!(
+            resolve(text, &env, "A"),
+            Resolution::Assigned(Some(String::new()))
+        );
+        assert_eq!(
+            resolve(text, &env, "E"),
+            Resolution::Assigned(Some(String::new()))
+        );
+        assert_eq!(
+            resolve(text, &env, "F"),
+            Resolution::Assigned(Some("y ".to_string()))
+        );
+        // Recursive ones are expanded when used.
+        assert_eq!(
+            resolve(text, &env, "C"),
+            Resolution::Assigned(Some("y".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_resolve_default_mk() {
+        let text = "include /usr/share/dpkg/default.mk\n";
+        assert_eq!(
+            resolve(text, &environment(), "DEB_HOST_MULTIARCH"),
+            Resolution::Dpkg {
+                makefile: "architecture.mk",
+                value: Some("x86_64-linux-gnu".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn test_maint_options_effects() {
+        assert_eq!(
+            maint_options_effects("hardening=+all,-pie optimize=-lto qa=+frob"),
+            vec![
+                "Enables all hardening features",
+                "Disables hardening `pie`: build position independent executables",
+                "Disables optimize `lto`: link time optimization",
+                "Unknown qa feature `frob`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_included_definitions() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("debian")).unwrap();
+        std::fs::write(
+            dir.path().join("debian/common.mk"),
+            "# Shared settings\nFLAVOUR = gtk\n",
+        )
+        .unwrap();
+        let text = "include debian/common.mk\nFLAVOURS = $(FLAVOUR) qt\n";
+        let (makefile, _) = Makefile::from_str_relaxed(text);
+        let idx = LineIndex::new(text);
+        let uri = Uri::from_file_path(dir.path().join("debian/rules")).unwrap();
+        let definitions = definitions(&makefile, Source::new(text, &idx), &uri, Some(dir.path()));
+        let names = definitions
+            .iter()
+            .map(|d| (d.name.as_str(), d.location.range.start.line))
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![("FLAVOUR", 1), ("FLAVOURS", 1)]);
+        assert!(definitions[0]
+            .location
+            .uri
+            .as_str()
+            .ends_with("/debian/common.mk"));
+        let env = Environment::default();
+        let resolver = Resolver::new(&makefile, &definitions, &env);
+        assert_eq!(
+            resolver.resolve("FLAVOURS"),
+            Resolution::Assigned(Some("gtk qt".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_included_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("foo");
+        std::fs::create_dir_all(root.join("debian")).unwrap();
+        std::fs::write(root.join("debian/common.mk"), "").unwrap();
+        std::fs::write(dir.path().join("outside.mk"), "").unwrap();
+        let common = std::fs::canonicalize(root.join("debian/common.mk")).unwrap();
+        assert_eq!(
+            included_path("debian/../debian/common.mk", &root),
+            Some(common.clone())
+        );
+        assert_eq!(
+            included_path("$(CURDIR)/debian/common.mk", &root),
+            Some(common)
+        );
+        assert_eq!(included_path("debian/../../outside.mk", &root), None);
+        assert_eq!(included_path("debian/missing.mk", &root), None);
+        assert_eq!(included_path("$(FLAVOUR).mk", &root), None);
+    }
+
+    #[test]
+    fn test_variable_at() {
+        let text = "LIB := /usr/lib/$(DEB_HOST_MULTIARCH)\n\nbuild:\n\tinstall -d $(LIB)/foo\n";
+        let (makefile, _) = Makefile::from_str_relaxed(text);
+        let idx = LineIndex::new(text);
+        let src = Source::new(text, &idx);
+        assert_eq!(
+            variable_at(&makefile, src, Position::new(0, 22)).as_deref(),
+            Some("DEB_HOST_MULTIARCH")
+        );
+        assert_eq!(
+            variable_at(&makefile, src, Position::new(3, 14)).as_deref(),
+            Some("LIB")
+        );
+        assert_eq!(
+            variable_at(&makefile, src, Position::new(0, 1)).as_deref(),
+            Some("LIB")
+        );
+        assert_eq!(variable_at(&makefile, src, Position::new(3, 3)), None);
+    }
+}
@@ -0,0 +1,284 @@
+//! Server settings.
+//!
+//! Settings arrive in `initializationOptions`, in the `debian` section of
+//! `workspace/configuration` and in `workspace/didChangeConfiguration`
+//! notifications. Every field has a default, so clients only send what they
+//! change; the feature toggles default to on.
+
+use serde::{Deserialize, Serialize};
+
+/// The configuration section requested with `workspace/configuration`.
+pub const SECTION: &str = "debian";
+
+/// Server settings.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct Settings {
+    /// Allow the upstream-ontologist to make network requests when guessing
+    /// upstream metadata values. Defaults to `false`.
+    pub upstream_ontologist_net_access: bool,
+    /// Surface lintian-brush issues that are suppressed by a lintian
+    /// override as faded, informational diagnostics. Defaults to `true`.
+    pub show_overridden_issues: bool,
+    /// Report misspellings in comments and descriptions.
+    #[cfg_attr(not(feature = "spellcheck"), allow(dead_code))]
+    pub spellcheck: bool,
+    /// Query the Ultimate Debian Database for bugs, CVEs, maintainers,
+    /// popcon, reverse dependencies and vcswatch data.
+    pub udd: bool,
+    /// UDD connection URL, e.g. a local snapshot. Defaults to
+    /// `$DEBIAN_LSP_UDD_URL` or the public mirror.
+    pub udd_url: Option<String>,
+    /// Make no network requests to UDD or Launchpad. Also turned on by
+    /// `$DEBIAN_LSP_OFFLINE`.
+    pub offline: bool,
+    /// Query Launchpad for `LP: #NNN` bug details.
+    pub launchpad: bool,
+    /// Run the lintian-brush fixers as diagnostics.
+    #[cfg_attr(not(feature = "lintian-brush"), allow(dead_code))]
+    pub lintian_brush: bool,
+    /// Report multiarch hints as diagnostics.
+    #[cfg_attr(not(feature = "multiarch-hints"), allow(dead_code))]
+    pub multiarch_hints: bool,
+    /// Inlay hint categories.
+    pub inlay_hints: InlayHintSettings,
+    /// Code lens categories.
+    pub code_lens: CodeLensSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            upstream_ontologist_net_access: false,
+            show_overridden_issues: true,
+            spellcheck: true,
+            udd: true,
+            udd_url: None,
+            offline: false,
+            launchpad: true,
+            lintian_brush: true,
+            multiarch_hints: true,
+            inlay_hints: InlayHintSettings::default(),
+            code_lens: CodeLensSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Parse the settings from a `workspace/didChangeConfiguration` payload
+    /// or a `workspace/configuration` result. Both the bare section and an
+    /// object wrapping it under `debian` are accepted. `None` when the value
+    /// is null or does not describe settings.
+    pub fn from_configuration(value: serde_json::Value) -> Option<Self> {
+        Self::default().with_configuration(value)
+    }
+
+    /// Like [`Settings::from_configuration`], but what the payload leaves
+    /// unset keeps its current value rather than the default, so an empty
+    /// payload changes nothing.
+    pub fn with_configuration(&self, value: serde_json::Value) -> Option<Self> {
+        let section = match value {
+            serde_json::Value::Null => return None,
+            serde_json::Value::Object(mut map) if map.contains_key(SECTION) => {
+                map.remove(SECTION)?
+            }
+            other => other,
+        };
+        let mut merged = serde_json::to_value(self).ok()?;
+        merge_json(&mut merged, section);
+        match serde_json::from_value(merged) {
+            Ok(settings) => Some(settings),
+            Err(e) => {
+                tracing::warn!("Failed to parse settings: {e}");
+                None
+            }
+        }
+    }
+
+    /// The UDD connection URL and whether UDD is off limits, falling back
+    /// to the environment for what the settings leave unset.
+    pub fn udd_connection(&self) -> (String, bool) {
+        let url = self
+            .udd_url
+            .clone()
+            .unwrap_or_else(crate::udd::url_from_env);
+        (url, self.offline())
+    }
+
+    /// Whether no network requests may be made, by the settings or by
+    /// `$DEBIAN_LSP_OFFLINE`.
+    pub fn offline(&self) -> bool {
+        self.offline || crate::udd::offline_from_env()
+    }
+
+    /// Whether Launchpad may be queried.
+    pub fn launchpad_enabled(&self) -> bool {
+        self.launchpad && !self.offline()
+    }
+
+    /// The code lens categories to show, with those backed by UDD turned
+    /// off when UDD lookups are or the server is offline.
+    pub fn enabled_code_lenses(&self) -> CodeLensSettings {
+        let mut lenses = self.code_lens.clone();
+        if !self.udd || self.offline() {
+            lenses.vcs_git = false;
+            lenses.bugs = false;
+            lenses.popcon = false;
+            lenses.reverse_dependencies = false;
+        }
+        lenses
+    }
+}
+
+/// Merge the objects in `patch` into those in `base`, recursively; any
+/// other value in `patch` replaces the one in `base`.
+fn merge_json(base: &mut serde_json::Value, patch: serde_json::Value) {
+    match (base, patch) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge_json(base.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
+/// Which inlay hints to show.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct InlayHintSettings {
+    /// Archive versions after relations in debian/control.
+    pub archive_versions: bool,
+    /// Providers of virtual packages in debian/control.
+    pub virtual_packages: bool,
+    /// Values of substitution variables in debian/control.
+    pub substvars: bool,
+    /// Suites of changelog distributions.
+    pub distributions: bool,
+}
+
+impl Default for InlayHintSettings {
+    fn default() -> Self {
+        Self {
+            archive_versions: true,
+            virtual_packages: true,
+            substvars: true,
+            distributions: true,
+        }
+    }
+}
+
+/// Which code lenses to show.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct CodeLensSettings {
+    /// Latest policy version on `Standards-Version`.
+    pub standards_version: bool,
+    /// Supported compat levels on `debhelper-compat`.
+    pub debhelper_compat: bool,
+    /// Latest vcswatch version on `Vcs-Git`.
+    pub vcs_git: bool,
+    /// Open bug counts on source and binary packages.
+    pub bugs: bool,
+    /// Popcon install counts on binary packages.
+    pub popcon: bool,
+    /// Reverse dependency counts on binary packages.
+    pub reverse_dependencies: bool,
+    /// Matching file counts on debian/copyright `Files:` paragraphs.
+    pub file_counts: bool,
+}
+
+impl Default for CodeLensSettings {
+    fn default() -> Self {
+        Self {
+            standards_version: true,
+            debhelper_compat: true,
+            vcs_git: true,
+            bugs: true,
+            popcon: true,
+            reverse_dependencies: true,
+            file_counts: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_partial_settings_keep_defaults() {
+        let settings = Settings::from_configuration(json!({
+            "udd": false,
+            "inlayHints": { "substvars": false }
+        }))
+        .unwrap();
+        assert!(!settings.udd);
+        assert!(settings.launchpad);
+        assert!(!settings.inlay_hints.substvars);
+        assert!(settings.inlay_hints.archive_versions);
+        assert!(settings.show_overridden_issues);
+    }
+
+    #[test]
+    fn test_section_wrapper() {
+        let settings =
+            Settings::from_configuration(json!({ "debian": { "spellcheck": false } })).unwrap();
+        assert!(!settings.spellcheck);
+        assert_eq!(Settings::from_configuration(serde_json::Value::Null), None);
+    }
+
+    #[test]
+    fn test_with_configuration_keeps_current_values() {
+        let current = Settings::from_configuration(json!({
+            "udd": false,
+            "inlayHints": { "substvars": false }
+        }))
+        .unwrap();
+        assert_eq!(current.with_configuration(json!({})), Some(current.clone()));
+        assert_eq!(
+            current.with_configuration(json!({ "debian": {} })),
+            Some(current.clone())
+        );
+        assert_eq!(current.with_configuration(serde_json::Value::Null), None);
+
+        let updated = current
+            .with_configuration(json!({ "inlayHints": { "distributions": false } }))
+            .unwrap();
+        assert!(!updated.udd);
+        assert!(!updated.inlay_hints.substvars);
+        assert!(!updated.inlay_hints.distributions);
+        assert!(updated.inlay_hints.archive_versions);
+    }
+
+    #[test]
+    fn test_udd_off_disables_udd_lenses() {
+        let settings = Settings {
+            udd: false,
+            ..Default::default()
+        };
+        let lenses = settings.enabled_code_lenses();
+        assert!(!lenses.bugs && !lenses.popcon && !lenses.vcs_git);
+        assert!(lenses.standards_version && lenses.debhelper_compat);
+    }
+
+    #[test]
+    fn test_offline_from_env() {
+        let settings = Settings::default();
+        std::env::set_var(crate::udd::OFFLINE_ENV, "1");
+        let offline = settings.offline();
+        let launchpad = settings.launchpad_enabled();
+        let lenses = settings.enabled_code_lenses();
+        let (_, udd_offline) = settings.udd_connection();
+        std::env::remove_var(crate::udd::OFFLINE_ENV);
+
+        assert!(offline && udd_offline);
+        assert!(!launchpad);
+        assert!(!lenses.bugs && !lenses.popcon && !lenses.vcs_git);
+        assert!(lenses.standards_version);
+    }
+}
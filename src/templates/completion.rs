@@ -0,0 +1,148 @@
+use deb822_lossless::{Deb822, Paragraph};
+use tower_lsp_server::ls_types::{CompletionItem, CompletionItemKind, Documentation, Position};
+
+use super::fields::{
+    base_field_name, field_name, has_choices, split_choices, TEMPLATES_FIELDS, TYPE_VALUES,
+};
+use crate::deb822::completion::{get_cursor_context, get_field_completions, CursorContext};
+use crate::position::Source;
+
+/// Get completions for a debconf templates file at the given cursor position.
+///
+/// Field names are offered at the start of a line. `Type` completes to the
+/// debconf question types; `Default` completes to the paragraph's choices for
+/// select and multiselect questions, and to `true`/`false` for booleans.
+pub fn get_completions(
+    deb822: &Deb822,
+    src: Source<'_>,
+    position: Position,
+) -> Vec<CompletionItem> {
+    match get_cursor_context(deb822, src, position) {
+        Some(CursorContext::FieldValue { field_name, .. }) => {
+            let Some(offset) = src.try_position_to_offset(position) else {
+                return vec![];
+            };
+            let paragraph = deb822.paragraphs().find(|p| {
+                let r = p.text_range();
+                r.start() <= offset && offset <= r.end()
+            });
+            match base_field_name(&field_name) {
+                Some("Type") => get_type_value_completions(),
+                Some("Default") => paragraph
+                    .map(|p| get_default_value_completions(&p))
+                    .unwrap_or_default(),
+                _ => vec![],
+            }
+        }
+        Some(CursorContext::FieldKey | CursorContext::StartOfLine) => {
+            get_field_completions(TEMPLATES_FIELDS)
+        }
+        None => vec![],
+    }
+}
+
+/// Completion items for the `Type` field.
+pub fn get_type_value_completions() -> Vec<CompletionItem> {
+    TYPE_VALUES
+        .iter()
+        .map(|(value, description)| CompletionItem {
+            label: value.to_string(),
+            kind: Some(CompletionItemKind::ENUM_MEMBER),
+            detail: Some(description.to_string()),
+            documentation: Some(Documentation::String(description.to_string())),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Completion items for the `Default` field of a template paragraph.
+///
+/// The untranslated `Choices-C` values are preferred, since that is what
+/// `Default` is compared against when present.
+pub fn get_default_value_completions(paragraph: &Paragraph) -> Vec<CompletionItem> {
+    let template_type = field_value(paragraph, "Type").unwrap_or_default();
+    let values = if template_type == "boolean" {
+        vec!["true".to_string(), "false".to_string()]
+    } else if has_choices(&template_type) {
+        field_value(paragraph, "Choices-C")
+            .or_else(|| field_value(paragraph, "Choices"))
+            .map(|choices| split_choices(&choices))
+            .unwrap_or_default()
+    } else {
+        vec![]
+    };
+    values
+        .into_iter()
+        .map(|value| CompletionItem {
+            label: value,
+            kind: Some(CompletionItemKind::VALUE),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// The untranslated value of a field, allowing for po-debconf's `_` and `__`
+/// prefixes.
+pub(crate) fn field_value(paragraph: &Paragraph, name: &str) -> Option<String> {
+    paragraph
+        .entries()
+        .find(|e| e.key().and_then(|k| field_name(&k)) == Some(name))
+        .map(|e| e.value().trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+
+    fn complete(text: &str, position: Position) -> Vec<String> {
+        let deb822 = Deb822::parse(text).tree();
+        let idx = LineIndex::new(text);
+        get_completions(&deb822, Source::new(text, &idx), position)
+            .into_iter()
+            .map(|c| c.label)
+            .collect()
+    }
+
+    #[test]
+    fn completes_field_names_at_line_start() {
+        let labels = complete("Template: foo/bar\n", Position::new(1, 0));
+        assert!(labels.contains(&"Type".to_string()));
+        assert!(labels.contains(&"Choices".to_string()));
+    }
+
+    #[test]
+    fn completes_type_values() {
+        let labels = complete("Template: foo/bar\nType: s\n", Position::new(1, 7));
+        assert!(labels.contains(&"select".to_string()));
+        assert!(labels.contains(&"multiselect".to_string()));
+        assert!(labels.contains(&"boolean".to_string()));
+    }
+
+    #[test]
+    fn completes_default_from_choices() {
+        let text = "Template: foo/server\nType: select\n__Choices: apache2, nginx\nDefault: a\n";
+        assert_eq!(
+            complete(text, Position::new(3, 10)),
+            vec!["apache2", "nginx"]
+        );
+    }
+
+    #[test]
+    fn default_prefers_untranslated_choices() {
+        let text = "Template: foo/mode\nType: multiselect\nChoices-C: fast, slow\n_Choices: Fast, Slow\nDefault: f\n";
+        assert_eq!(complete(text, Position::new(4, 10)), vec!["fast", "slow"]);
+    }
+
+    #[test]
+    fn completes_boolean_default() {
+        let text = "Template: foo/enable\nType: boolean\nDefault: t\n";
+        assert_eq!(complete(text, Position::new(2, 10)), vec!["true", "false"]);
+    }
+
+    #[test]
+    fn default_of_string_has_no_completions() {
+        let text = "Template: foo/name\nType: string\nDefault: x\n";
+        assert!(complete(text, Position::new(2, 10)).is_empty());
+    }
+}
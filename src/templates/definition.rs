@@ -0,0 +1,114 @@
+//! Go-to-definition from debconf calls in maintainer scripts to the template
+//! paragraph defining the question.
+
+use deb822_lossless::Deb822;
+use text_size::{TextRange, TextSize};
+use tower_lsp_server::ls_types::{Location, Uri};
+
+use super::fields::field_name;
+use crate::maintainer_scripts::ScriptKind;
+use crate::position::Source;
+
+/// The debconf question named under `offset` in a line of a maintainer
+/// script, e.g. `foo/server` in `db_get "foo/server"`.
+///
+/// Only arguments following a `db_*` command on the same line count, and the
+/// name must contain a `/`, as every template name does.
+pub fn question_at(line: &str, offset: usize) -> Option<&str> {
+    let is_separator = |c: char| c.is_whitespace() || matches!(c, ';' | '&' | '|' | '(' | ')');
+    let start = line[..offset]
+        .rfind(is_separator)
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let end = line[offset..]
+        .find(is_separator)
+        .map(|i| i + offset)
+        .unwrap_or(line.len());
+
+    let has_command = line[..start]
+        .split(is_separator)
+        .any(|word| word.starts_with("db_"));
+    let name = line[start..end].trim_matches(|c| c == '"' || c == '\'');
+    (has_command && name.contains('/') && !name.starts_with('$')).then_some(name)
+}
+
+/// The templates files a maintainer script's questions live in, most specific
+/// first: `debian/<package>.templates`, then `debian/templates`.
+pub fn templates_file_names(script_file_name: &str, kind: ScriptKind) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Some(package) = script_file_name.strip_suffix(&format!(".{}", kind.name())) {
+        names.push(format!("{package}.templates"));
+    }
+    names.push("templates".to_string());
+    names
+}
+
+/// The location of the `Template` field defining `name` in a templates file.
+pub fn goto_definition(
+    deb822: &Deb822,
+    src: Source<'_>,
+    uri: &Uri,
+    name: &str,
+) -> Option<Location> {
+    let entry = deb822.paragraphs().find_map(|p| {
+        p.entries().find(|e| {
+            e.key().and_then(|k| field_name(&k)) == Some("Template") && e.value().trim() == name
+        })
+    })?;
+    // The entry's range includes its line ending.
+    let range = entry.text_range();
+    let text = &src.text[usize::from(range.start())..usize::from(range.end())];
+    let range = TextRange::at(range.start(), TextSize::of(text.trim_end()));
+    Some(Location {
+        uri: uri.clone(),
+        range: src.text_range_to_lsp_range(range),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+    use tower_lsp_server::ls_types::{Position, Range};
+
+    #[test]
+    fn finds_the_question_in_a_db_call() {
+        let line = "    db_get foo/server || true";
+        assert_eq!(question_at(line, 14), Some("foo/server"));
+        let line = "db_input high \"foo/server\" || true";
+        assert_eq!(question_at(line, 17), Some("foo/server"));
+    }
+
+    #[test]
+    fn ignores_other_words() {
+        assert_eq!(question_at("db_input high foo/server", 10), None);
+        assert_eq!(question_at("cp /etc/foo/bar /tmp", 6), None);
+        assert_eq!(question_at("db_get $QUESTION/name", 10), None);
+    }
+
+    #[test]
+    fn candidate_templates_files() {
+        assert_eq!(
+            templates_file_names("mypkg.config", ScriptKind::Config),
+            vec!["mypkg.templates", "templates"]
+        );
+        assert_eq!(
+            templates_file_names("postinst", ScriptKind::Postinst),
+            vec!["templates"]
+        );
+    }
+
+    #[test]
+    fn locates_the_template_paragraph() {
+        let text = "Template: foo/a\nType: note\n\nTemplate: foo/b\nType: string\n";
+        let deb822 = Deb822::parse(text).tree();
+        let idx = LineIndex::new(text);
+        let uri: Uri = "file:///p/debian/templates".parse().unwrap();
+        let location = goto_definition(&deb822, Source::new(text, &idx), &uri, "foo/b").unwrap();
+        assert_eq!(
+            location.range,
+            Range::new(Position::new(3, 0), Position::new(3, 15))
+        );
+        assert!(goto_definition(&deb822, Source::new(text, &idx), &uri, "foo/c").is_none());
+    }
+}
@@ -0,0 +1,31 @@
+use tower_lsp_server::ls_types::Uri;
+
+use crate::debhelper::detection::is_debhelper_file;
+
+/// Whether the URI is a debian/templates or debian/<package>.templates file.
+pub fn is_templates_file(uri: &Uri) -> bool {
+    is_debhelper_file(uri, "templates")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri(s: &str) -> Uri {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn detects_qualified_and_unqualified() {
+        assert!(is_templates_file(&uri("file:///p/debian/templates")));
+        assert!(is_templates_file(&uri("file:///p/debian/mypkg.templates")));
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(!is_templates_file(&uri(
+            "file:///p/debian/po/templates.pot"
+        )));
+        assert!(!is_templates_file(&uri("file:///p/debian/control")));
+    }
+}
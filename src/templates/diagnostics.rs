@@ -0,0 +1,390 @@
+use std::collections::HashSet;
+
+use deb822_lossless::{Deb822, Entry, Paragraph};
+use text_size::{TextRange, TextSize};
+use tower_lsp_server::ls_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Range};
+
+use super::fields::{field_name, has_choices, split_choices, TYPE_VALUES};
+use crate::position::Source;
+
+/// All types of diagnostic issues in a debconf templates file.
+#[derive(Debug, Clone)]
+pub enum DiagnosticIssue {
+    /// Paragraph without a Template field
+    MissingTemplate { range: Range },
+    /// Template without a Type field
+    MissingType { name: String, range: Range },
+    /// Type is not one of the debconf question types
+    InvalidType { value: String, range: Range },
+    /// select or multiselect template without Choices
+    MissingChoices { name: String, range: Range },
+    /// Choices on a template whose type does not use them
+    UnusedChoices { template_type: String, range: Range },
+    /// Default names a value that is not among the Choices
+    DefaultNotInChoices { value: String, range: Range },
+    /// boolean Default other than true or false
+    InvalidBooleanDefault { value: String, range: Range },
+    /// Template without a Description field
+    MissingDescription { name: String, range: Range },
+    /// Template name already used by an earlier paragraph
+    DuplicateTemplate { name: String, range: Range },
+}
+
+/// The untranslated entry for a field, allowing for po-debconf's `_` and
+/// `__` prefixes.
+fn find_entry(paragraph: &Paragraph, name: &str) -> Option<Entry> {
+    paragraph
+        .entries()
+        .find(|e| e.key().and_then(|k| field_name(&k)) == Some(name))
+}
+
+/// The range of an entry's value without trailing whitespace, or of its key
+/// when the value is empty.
+fn value_range(src: Source<'_>, entry: &Entry) -> Range {
+    let range = match entry.value_range() {
+        Some(r) => {
+            let text = &src.text[usize::from(r.start())..usize::from(r.end())];
+            let len = TextSize::of(text.trim_end());
+            if len == 0.into() {
+                entry.key_range().unwrap_or(r)
+            } else {
+                TextRange::at(r.start(), len)
+            }
+        }
+        None => entry.key_range().unwrap_or(entry.text_range()),
+    };
+    src.text_range_to_lsp_range(range)
+}
+
+fn key_range(src: Source<'_>, entry: &Entry) -> Range {
+    src.text_range_to_lsp_range(entry.key_range().unwrap_or(entry.text_range()))
+}
+
+/// Find all diagnostic issues in a debconf templates file.
+pub fn find_all_issues(deb822: &Deb822, src: Source<'_>) -> Vec<DiagnosticIssue> {
+    let mut issues = Vec::new();
+    let mut seen = HashSet::new();
+
+    for paragraph in deb822.paragraphs() {
+        let Some(first) = paragraph.entries().next() else {
+            continue;
+        };
+        let Some(template) = find_entry(&paragraph, "Template") else {
+            issues.push(DiagnosticIssue::MissingTemplate {
+                range: key_range(src, &first),
+            });
+            continue;
+        };
+        let name = template.value().trim().to_string();
+        let template_range = key_range(src, &template);
+
+        if !seen.insert(name.clone()) {
+            issues.push(DiagnosticIssue::DuplicateTemplate {
+                name: name.clone(),
+                range: value_range(src, &template),
+            });
+        }
+        if find_entry(&paragraph, "Description").is_none() {
+            issues.push(DiagnosticIssue::MissingDescription {
+                name: name.clone(),
+                range: template_range,
+            });
+        }
+
+        let Some(type_entry) = find_entry(&paragraph, "Type") else {
+            issues.push(DiagnosticIssue::MissingType {
+                name,
+                range: template_range,
+            });
+            continue;
+        };
+        let template_type = type_entry.value().trim().to_string();
+        if !TYPE_VALUES.iter().any(|(v, _)| *v == template_type) {
+            issues.push(DiagnosticIssue::InvalidType {
+                value: template_type,
+                range: value_range(src, &type_entry),
+            });
+            continue;
+        }
+
+        let choices =
+            find_entry(&paragraph, "Choices-C").or_else(|| find_entry(&paragraph, "Choices"));
+        let default = find_entry(&paragraph, "Default");
+
+        if has_choices(&template_type) {
+            let Some(choices) = choices else {
+                issues.push(DiagnosticIssue::MissingChoices {
+                    name,
+                    range: value_range(src, &type_entry),
+                });
+                continue;
+            };
+            let Some(default) = default else {
+                continue;
+            };
+            let choices = choices.value();
+            let default_value = default.value();
+            // Substituted at runtime with db_subst; nothing to check.
+            if choices.contains("${") || default_value.contains("${") {
+                continue;
+            }
+            let allowed = split_choices(&choices);
+            let defaults = if template_type == "multiselect" {
+                split_choices(&default_value)
+            } else {
+                vec![default_value.trim().to_string()]
+            };
+            for value in defaults {
+                if !value.is_empty() && !allowed.contains(&value) {
+                    issues.push(DiagnosticIssue::DefaultNotInChoices {
+                        value,
+                        range: value_range(src, &default),
+                    });
+                }
+            }
+        } else {
+            if let Some(choices) = choices {
+                issues.push(DiagnosticIssue::UnusedChoices {
+                    template_type: template_type.clone(),
+                    range: key_range(src, &choices),
+                });
+            }
+            if template_type == "boolean" {
+                if let Some(default) = default {
+                    let value = default.value().trim().to_string();
+                    if value != "true" && value != "false" {
+                        issues.push(DiagnosticIssue::InvalidBooleanDefault {
+                            value,
+                            range: value_range(src, &default),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+fn make_diagnostic(
+    range: Range,
+    severity: DiagnosticSeverity,
+    code: &str,
+    message: String,
+) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some("debian-lsp".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Convert a DiagnosticIssue to an LSP Diagnostic.
+pub fn issue_to_diagnostic(issue: DiagnosticIssue) -> Diagnostic {
+    use DiagnosticSeverity as S;
+    match issue {
+        DiagnosticIssue::MissingTemplate { range } => make_diagnostic(
+            range,
+            S::ERROR,
+            "missing-template",
+            "Paragraph has no Template field".to_string(),
+        ),
+        DiagnosticIssue::MissingType { name, range } => make_diagnostic(
+            range,
+            S::ERROR,
+            "missing-type",
+            format!("Template '{}' has no Type field", name),
+        ),
+        DiagnosticIssue::InvalidType { value, range } => make_diagnostic(
+            range,
+            S::ERROR,
+            "invalid-template-type",
+            format!(
+                "Unknown template type '{}', expected one of: {}",
+                value,
+                TYPE_VALUES
+                    .iter()
+                    .map(|(v, _)| *v)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ),
+        DiagnosticIssue::MissingChoices { name, range } => make_diagnostic(
+            range,
+            S::ERROR,
+            "missing-choices",
+            format!("Template '{}' needs a Choices field", name),
+        ),
+        DiagnosticIssue::UnusedChoices {
+            template_type,
+            range,
+        } => make_diagnostic(
+            range,
+            S::WARNING,
+            "unused-choices",
+            format!("Choices is ignored for {} templates", template_type),
+        ),
+        DiagnosticIssue::DefaultNotInChoices { value, range } => make_diagnostic(
+            range,
+            S::ERROR,
+            "default-not-in-choices",
+            format!("Default '{}' is not one of the Choices", value),
+        ),
+        DiagnosticIssue::InvalidBooleanDefault { value, range } => make_diagnostic(
+            range,
+            S::ERROR,
+            "invalid-boolean-default",
+            format!("Default '{}' of a boolean must be true or false", value),
+        ),
+        DiagnosticIssue::MissingDescription { name, range } => make_diagnostic(
+            range,
+            S::WARNING,
+            "missing-description",
+            format!("Template '{}' has no Description field", name),
+        ),
+        DiagnosticIssue::DuplicateTemplate { name, range } => make_diagnostic(
+            range,
+            S::ERROR,
+            "duplicate-template",
+            format!("Template '{}' is already defined", name),
+        ),
+    }
+}
+
+/// Get diagnostics for a debconf templates file.
+pub fn get_diagnostics(deb822: &Deb822, src: Source<'_>) -> Vec<Diagnostic> {
+    find_all_issues(deb822, src)
+        .into_iter()
+        .map(issue_to_diagnostic)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+    use tower_lsp_server::ls_types::Position;
+
+    fn diagnostics(text: &str) -> Vec<Diagnostic> {
+        let deb822 = Deb822::parse(text).tree();
+        let idx = LineIndex::new(text);
+        get_diagnostics(&deb822, Source::new(text, &idx))
+    }
+
+    fn codes(text: &str) -> Vec<String> {
+        diagnostics(text)
+            .iter()
+            .map(|d| match &d.code {
+                Some(NumberOrString::String(s)) => s.clone(),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn valid_templates_have_no_diagnostics() {
+        let text = "\
+Template: foo/server
+Type: select
+__Choices: apache2, lighttpd\\, fast, none
+Default: lighttpd, fast
+_Description: Web server:
+ Pick the web server to configure.
+
+Template: foo/enable
+Type: boolean
+Default: true
+_Description: Enable foo?
+
+Template: foo/modules
+Type: multiselect
+Choices-C: a, b, c
+_Choices: A, B, C
+Default: a, c
+_Description: Modules:
+";
+        assert_eq!(codes(text), Vec::<String>::new());
+    }
+
+    #[test]
+    fn flags_an_invalid_type() {
+        let text = "Template: foo/bar\nType: dropdown\nDescription: x\n";
+        let diags = diagnostics(text);
+        assert_eq!(codes(text), vec!["invalid-template-type"]);
+        assert_eq!(
+            diags[0].range,
+            Range::new(Position::new(1, 6), Position::new(1, 14))
+        );
+    }
+
+    #[test]
+    fn flags_missing_fields() {
+        assert_eq!(
+            codes("Type: string\nDescription: x\n"),
+            vec!["missing-template"]
+        );
+        assert_eq!(
+            codes("Template: foo/bar\n"),
+            vec!["missing-description", "missing-type"]
+        );
+        assert_eq!(
+            codes("Template: foo/bar\nType: select\nDescription: x\n"),
+            vec!["missing-choices"]
+        );
+    }
+
+    #[test]
+    fn flags_default_outside_choices() {
+        let text = "Template: foo/bar\nType: select\nChoices: a, b\nDefault: c\nDescription: x\n";
+        let diags = diagnostics(text);
+        assert_eq!(codes(text), vec!["default-not-in-choices"]);
+        assert_eq!(
+            diags[0].range,
+            Range::new(Position::new(3, 9), Position::new(3, 10))
+        );
+    }
+
+    #[test]
+    fn checks_each_multiselect_default() {
+        let text = "Template: foo/bar\nType: multiselect\nChoices: a, b\nDefault: a, z, y\nDescription: x\n";
+        let diags = diagnostics(text);
+        let messages: Vec<&str> = diags.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Default 'z' is not one of the Choices",
+                "Default 'y' is not one of the Choices"
+            ]
+        );
+    }
+
+    #[test]
+    fn substituted_choices_are_not_checked() {
+        let text =
+            "Template: foo/bar\nType: select\nChoices: ${choices}\nDefault: c\nDescription: x\n";
+        assert_eq!(codes(text), Vec::<String>::new());
+    }
+
+    #[test]
+    fn flags_choices_on_a_string() {
+        let text = "Template: foo/bar\nType: string\nChoices: a, b\nDescription: x\n";
+        assert_eq!(codes(text), vec!["unused-choices"]);
+    }
+
+    #[test]
+    fn flags_a_non_boolean_default() {
+        let text = "Template: foo/bar\nType: boolean\nDefault: yes\nDescription: x\n";
+        assert_eq!(codes(text), vec!["invalid-boolean-default"]);
+    }
+
+    #[test]
+    fn flags_duplicate_templates() {
+        let text = "Template: foo/bar\nType: note\nDescription: x\n\nTemplate: foo/bar\nType: note\nDescription: y\n";
+        let diags = diagnostics(text);
+        assert_eq!(codes(text), vec!["duplicate-template"]);
+        assert_eq!(diags[0].range.start, Position::new(4, 10));
+    }
+}
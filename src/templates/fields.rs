@@ -0,0 +1,120 @@
+use crate::deb822::completion::FieldInfo;
+
+/// All fields of a debconf template, from debconf-devel(7).
+pub const TEMPLATES_FIELDS: &[FieldInfo] = &[
+    FieldInfo::new(
+        "Template",
+        "Name of the question, conventionally <package>/<name>",
+    ),
+    FieldInfo::new("Type", "Kind of question, which decides how it is asked"),
+    FieldInfo::new("Default", "Answer used when the question is not asked"),
+    FieldInfo::new(
+        "Choices",
+        "Comma-separated answers offered by select and multiselect questions",
+    ),
+    FieldInfo::new(
+        "Choices-C",
+        "Untranslated values for Choices, used by the scripts and Default",
+    ),
+    FieldInfo::new(
+        "Description",
+        "Short description on the first line, extended description below",
+    )
+    .prose(),
+];
+
+/// Values of the `Type` field. Each entry is (value, description).
+pub const TYPE_VALUES: &[(&str, &str)] = &[
+    ("string", "Free-form text answer"),
+    (
+        "password",
+        "Secret answer, not echoed and not stored by default",
+    ),
+    ("boolean", "Yes/no answer, stored as true or false"),
+    ("select", "One of the Choices"),
+    ("multiselect", "Any number of the Choices"),
+    ("note", "Information shown to the user, with no answer"),
+    ("text", "Deprecated: information shown to the user"),
+    ("error", "Error message shown to the user"),
+    ("title", "Title for the following questions"),
+];
+
+/// The field a key names, ignoring po-debconf's translation markers:
+/// `_Description` and `__Choices` name their plain field.
+pub fn field_name(key: &str) -> Option<&'static str> {
+    let key = key.trim_start_matches('_');
+    TEMPLATES_FIELDS
+        .iter()
+        .find(|f| f.name.eq_ignore_ascii_case(key))
+        .map(|f| f.name)
+}
+
+/// Like [`field_name`], but translated variants such as
+/// `Description-de.UTF-8` also name their plain field. `Choices-C` is a field
+/// of its own.
+pub fn base_field_name(key: &str) -> Option<&'static str> {
+    field_name(key).or_else(|| {
+        let (base, _language) = key.split_once('-')?;
+        field_name(base)
+    })
+}
+
+/// Whether the type offers a list of choices.
+pub fn has_choices(template_type: &str) -> bool {
+    matches!(template_type, "select" | "multiselect")
+}
+
+/// Split a `Choices` or multiselect `Default` value into its items.
+///
+/// Items are separated by `, `; a comma inside an item is escaped as `\,`.
+pub fn split_choices(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&',') => {
+                current.push(',');
+                chars.next();
+            }
+            ',' => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    items.push(current);
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_name_ignores_translations() {
+        assert_eq!(field_name("_Description"), Some("Description"));
+        assert_eq!(field_name("Description-de.UTF-8"), None);
+    }
+
+    #[test]
+    fn base_field_name_strips_translation_markers() {
+        assert_eq!(base_field_name("_Description"), Some("Description"));
+        assert_eq!(base_field_name("__Choices"), Some("Choices"));
+        assert_eq!(base_field_name("Description-de.UTF-8"), Some("Description"));
+        assert_eq!(base_field_name("Choices-C"), Some("Choices-C"));
+        assert_eq!(base_field_name("type"), Some("Type"));
+        assert_eq!(base_field_name("Priority"), None);
+    }
+
+    #[test]
+    fn split_choices_handles_escaped_commas() {
+        assert_eq!(
+            split_choices("apache2, lighttpd\\, fast, none"),
+            vec!["apache2", "lighttpd, fast", "none"]
+        );
+        assert_eq!(split_choices(""), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,86 @@
+use deb822_lossless::Deb822;
+use tower_lsp_server::ls_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+
+use super::fields::{base_field_name, TEMPLATES_FIELDS, TYPE_VALUES};
+use crate::deb822::completion::field_description;
+use crate::position::Source;
+
+/// Get hover information for a debconf templates file at the given cursor
+/// position.
+///
+/// Translated and translatable fields (`_Description`, `Choices-de.UTF-8`)
+/// show the description of their plain field. Hovering a `Type` value
+/// describes that question type.
+pub fn get_hover(deb822: &Deb822, src: Source<'_>, position: Position) -> Option<Hover> {
+    let offset = src.try_position_to_offset(position)?;
+    let entry = deb822
+        .paragraphs()
+        .flat_map(|p| p.entries().collect::<Vec<_>>())
+        .find(|entry| {
+            let r = entry.text_range();
+            r.start() <= offset && offset <= r.end()
+        })?;
+    let name = base_field_name(&entry.key()?)?;
+
+    if name == "Type" && entry.value_range().is_some_and(|r| r.start() <= offset) {
+        let value = entry.value();
+        let value = value.trim();
+        if let Some((value, description)) = TYPE_VALUES.iter().find(|(v, _)| *v == value) {
+            return Some(make_hover(value, description));
+        }
+    }
+
+    field_description(TEMPLATES_FIELDS, name)
+        .map(|(canonical, description)| make_hover(canonical, description))
+}
+
+fn make_hover(title: &str, description: &str) -> Hover {
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!("**{}**\n\n{}", title, description),
+        }),
+        range: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+
+    fn hover_text(text: &str, position: Position) -> Option<String> {
+        let deb822 = Deb822::parse(text).tree();
+        let idx = LineIndex::new(text);
+        get_hover(&deb822, Source::new(text, &idx), position).map(|h| match h.contents {
+            HoverContents::Markup(m) => m.value,
+            _ => panic!("Expected markup content"),
+        })
+    }
+
+    #[test]
+    fn hovers_a_translatable_field() {
+        let text = "Template: foo/bar\n_Description: Pick one\n";
+        let value = hover_text(text, Position::new(1, 3)).unwrap();
+        assert!(value.starts_with("**Description**"));
+    }
+
+    #[test]
+    fn hovers_a_type_value() {
+        let text = "Template: foo/bar\nType: multiselect\n";
+        let value = hover_text(text, Position::new(1, 9)).unwrap();
+        assert!(value.starts_with("**multiselect**"));
+    }
+
+    #[test]
+    fn hovers_a_type_key() {
+        let text = "Template: foo/bar\nType: multiselect\n";
+        let value = hover_text(text, Position::new(1, 1)).unwrap();
+        assert!(value.starts_with("**Type**"));
+    }
+
+    #[test]
+    fn nothing_on_an_unknown_field() {
+        assert!(hover_text("Priority: high\n", Position::new(0, 2)).is_none());
+    }
+}
@@ -0,0 +1,22 @@
+//! Module for handling debconf templates files (debian/templates and
+//! debian/<package>.templates)
+//!
+//! Templates are deb822 paragraphs, one per debconf question. Fields may
+//! carry leading underscores (`_Description`, `__Choices`) to mark them for
+//! translation with po-debconf; they are treated as the plain field
+//! throughout.
+
+pub mod completion;
+pub mod definition;
+pub mod detection;
+pub mod diagnostics;
+pub mod fields;
+pub mod hover;
+pub mod semantic;
+
+pub use completion::get_completions;
+pub use definition::goto_definition;
+pub use detection::is_templates_file;
+pub use diagnostics::get_diagnostics;
+pub use hover::get_hover;
+pub use semantic::generate_semantic_tokens;
@@ -0,0 +1,51 @@
+//! Semantic token generation for debconf templates files.
+
+use tower_lsp_server::ls_types::SemanticToken;
+
+use super::fields::base_field_name;
+use crate::deb822::semantic::{generate_tokens, FieldValidator};
+use crate::position::Source;
+
+/// Field validator for debconf templates files
+struct TemplatesFieldValidator;
+
+impl FieldValidator for TemplatesFieldValidator {
+    fn get_standard_field_name(&self, name: &str) -> Option<&'static str> {
+        base_field_name(name)
+    }
+}
+
+/// Generate semantic tokens for a debconf templates file
+pub fn generate_semantic_tokens(
+    deb822_parse: &deb822_lossless::Parse<deb822_lossless::Deb822>,
+    src: Source<'_>,
+) -> Vec<SemanticToken> {
+    let deb822 = deb822_parse.tree();
+    generate_tokens(&deb822, src, &TemplatesFieldValidator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deb822::semantic::TokenType;
+
+    #[test]
+    fn translated_fields_are_known() {
+        let text =
+            "Template: foo/bar\n_Description: hi\nDescription-de.UTF-8: hallo\nPriority: x\n";
+        let parsed = deb822_lossless::Deb822::parse(text);
+        let idx = crate::position::LineIndex::new(text);
+        let tokens = generate_semantic_tokens(&parsed, Source::new(text, &idx));
+
+        let keys: Vec<u32> = tokens.iter().step_by(2).map(|t| t.token_type).collect();
+        assert_eq!(
+            keys,
+            vec![
+                TokenType::Field as u32,
+                TokenType::Field as u32,
+                TokenType::Field as u32,
+                TokenType::UnknownField as u32,
+            ]
+        );
+    }
+}
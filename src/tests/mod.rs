@@ -12,9 +12,11 @@ pub mod fields;
 pub mod hover;
 pub mod resolve;
 pub mod semantic;
+pub mod symbols;
 
 pub use completion::*;
 pub use definition::goto_definition;
 pub use detection::is_tests_control_file;
 pub use hover::get_hover;
 pub use semantic::generate_semantic_tokens;
+pub use symbols::generate_document_symbols;
@@ -0,0 +1,95 @@
+//! Document symbol generation for debian/tests/control files.
+
+use crate::position::Source;
+use rowan::ast::AstNode;
+use tower_lsp_server::ls_types::{DocumentSymbol, SymbolKind};
+
+/// Generate document symbols for a tests/control file.
+///
+/// Each name in a `Tests:` field becomes a METHOD symbol spanning its
+/// paragraph, with the name itself as the selection range. Paragraphs
+/// with a `Test-Command:` instead are named after the command.
+#[allow(deprecated)] // DocumentSymbol::deprecated field
+pub fn generate_document_symbols(
+    deb822: &deb822_lossless::Deb822,
+    src: Source<'_>,
+) -> Vec<DocumentSymbol> {
+    let mut symbols = Vec::new();
+
+    for paragraph in deb822.paragraphs() {
+        let range = src.text_range_to_lsp_range(paragraph.syntax().text_range());
+
+        if let Some(entry) = paragraph
+            .entries()
+            .find(|e| e.key().as_deref() == Some("Tests"))
+        {
+            let Some(value_range) = entry.value_range() else {
+                continue;
+            };
+            let value = &src.text[value_range];
+            let mut offset = 0;
+            for name in value.split(|c: char| c.is_whitespace() || c == ',') {
+                let start = offset;
+                offset += name.len() + 1;
+                if name.is_empty() {
+                    continue;
+                }
+                let name_range = rowan::TextRange::at(
+                    value_range.start() + rowan::TextSize::from(start as u32),
+                    rowan::TextSize::of(name),
+                );
+                symbols.push(DocumentSymbol {
+                    name: name.to_string(),
+                    detail: None,
+                    kind: SymbolKind::METHOD,
+                    tags: None,
+                    deprecated: None,
+                    range,
+                    selection_range: src.text_range_to_lsp_range(name_range),
+                    children: None,
+                });
+            }
+        } else if let Some(command) = paragraph.get("Test-Command") {
+            symbols.push(DocumentSymbol {
+                name: command.trim().to_string(),
+                detail: Some("Test-Command".to_string()),
+                kind: SymbolKind::METHOD,
+                tags: None,
+                deprecated: None,
+                range,
+                selection_range: range,
+                children: None,
+            });
+        }
+    }
+
+    symbols
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp_server::ls_types::{Position, Range};
+
+    #[test]
+    fn test_names_and_commands() {
+        let text = "\
+Tests: smoke, integration
+Depends: @
+
+Test-Command: foo --version
+Restrictions: superficial
+";
+        let parsed = deb822_lossless::Deb822::parse(text);
+        let idx = crate::position::LineIndex::new(text);
+        let symbols = generate_document_symbols(&parsed.tree(), Source::new(text, &idx));
+
+        let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["smoke", "integration", "foo --version"]);
+        assert_eq!(
+            symbols[1].selection_range,
+            Range::new(Position::new(0, 14), Position::new(0, 25))
+        );
+        assert_eq!(symbols[2].detail.as_deref(), Some("Test-Command"));
+    }
+}
@@ -3,12 +3,16 @@
 //! Queries the `vcswatch` table to find the latest packaged version
 //! for a given VCS repository URL.
 
+use std::collections::HashSet;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Duration;
 
 use lru::LruCache;
 use tokio::sync::RwLock;
 
+use crate::disk_cache::DiskCache;
+
 /// Thread-safe shared cache for VCS watch lookups.
 pub type SharedVcsWatchCache = Arc<RwLock<VcsWatchCache>>;
 
@@ -17,11 +21,18 @@ pub type SharedVcsWatchCache = Arc<RwLock<VcsWatchCache>>;
 /// the oldest entries fall out once the cap is reached.
 const VCSWATCH_CACHE_CAPACITY: usize = 1024;
 
+/// How long packaged versions are kept on disk before being re-queried.
+const VCSWATCH_DISK_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
 /// Cached VCS watch data from UDD.
 pub struct VcsWatchCache {
     pool: crate::udd::SharedPool,
     /// Map from VCS URL to packaged version. `None` means "looked up, not found".
     version_by_url: LruCache<String, Option<String>>,
+    disk: DiskCache,
+    /// URLs whose version is an expired on-disk entry, used because UDD
+    /// could not be reached.
+    stale: HashSet<String>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -38,19 +49,11 @@ impl VcsWatchCache {
             version_by_url: LruCache::new(
                 NonZeroUsize::new(VCSWATCH_CACHE_CAPACITY).expect("non-zero capacity"),
             ),
+            disk: DiskCache::open("vcswatch", VCSWATCH_DISK_TTL),
+            stale: HashSet::new(),
         }
     }
 
-    /// Look up the packaged version for a VCS URL, fetching if needed.
-    ///
-    /// Returns `None` if the URL is not found in vcswatch or the query fails.
-    pub async fn get_version_for_url(&mut self, url: &str) -> Option<&str> {
-        if !self.version_by_url.contains(url) {
-            self.fetch_version_for_url(url).await;
-        }
-        self.version_by_url.get(url).and_then(|v| v.as_deref())
-    }
-
     /// Look up the packaged version from cache only, without fetching.
     /// Does not promote the entry in the LRU.
     pub fn get_cached_version_for_url(&self, url: &str) -> Option<&str> {
@@ -62,40 +65,62 @@ impl VcsWatchCache {
         self.version_by_url.contains(url)
     }
 
-    async fn fetch_version_for_url(&mut self, url: &str) {
+    /// Returns `true` if the cached version is out of date because UDD could
+    /// not be reached.
+    pub fn is_stale(&self, url: &str) -> bool {
+        self.stale.contains(url)
+    }
+
+    /// Insert a cached entry for testing purposes.
+    #[cfg(test)]
+    pub(crate) fn insert_cached(&mut self, url: &str, version: &str) {
+        self.version_by_url
+            .put(url.to_string(), Some(version.to_string()));
+    }
+}
+
+/// Look up the packaged version for a VCS URL, fetching if needed.
+///
+/// The cache lock is not held while the on-disk cache or UDD are read.
+/// Returns `None` if the URL is not found in vcswatch or the query fails.
+pub async fn get_version_for_url(cache: &SharedVcsWatchCache, url: &str) -> Option<String> {
+    let (pool, disk) = {
+        let mut guard = cache.write().await;
+        if let Some(version) = guard.version_by_url.get(url) {
+            return version.clone();
+        }
+        (guard.pool.clone(), guard.disk.clone())
+    };
+    let query = async {
+        let udd = pool.get()?;
         let row: Option<VcsWatchRow> =
             match sqlx::query_as("SELECT url, version::text FROM vcswatch WHERE url = $1 LIMIT 1")
                 .bind(url)
-                .fetch_optional(&*self.pool)
+                .fetch_optional(&udd)
                 .await
             {
                 Ok(row) => row,
                 Err(e) => {
                     tracing::warn!(url, error = %e, "UDD vcswatch query failed");
-                    return;
+                    pool.report_error(&e);
+                    return None;
                 }
             };
-
-        match row {
-            Some(VcsWatchRow {
-                url: Some(row_url),
-                version,
-            }) => {
-                self.version_by_url.put(row_url, version);
-            }
-            _ => {
-                // Not found — cache as None to avoid re-querying
-                self.version_by_url.put(url.to_string(), None);
-            }
-        }
-    }
-
-    /// Insert a cached entry for testing purposes.
-    #[cfg(test)]
-    pub(crate) fn insert_cached(&mut self, url: &str, version: &str) {
-        self.version_by_url
-            .put(url.to_string(), Some(version.to_string()));
+        // Not found — cached as None to avoid re-querying.
+        Some(
+            row.filter(|row| row.url.is_some())
+                .and_then(|row| row.version),
+        )
+    };
+    let (version, stale) = disk.fetch(&pool.url(), url, query).await?;
+    let mut guard = cache.write().await;
+    if stale {
+        guard.stale.insert(url.to_string());
+    } else {
+        guard.stale.remove(url);
     }
+    guard.version_by_url.put(url.to_string(), version.clone());
+    version
 }
 
 /// Create a new shared VCS watch cache.
@@ -109,42 +134,44 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_version_from_cache() {
-        let mut cache = VcsWatchCache::new(crate::udd::shared_pool());
-        cache.insert_cached(
+        let cache = new_shared_vcswatch_cache(crate::udd::shared_pool());
+        cache.write().await.insert_cached(
             "https://salsa.debian.org/python-team/packages/dulwich.git",
             "1.1.0-1",
         );
 
-        let version = cache
-            .get_version_for_url("https://salsa.debian.org/python-team/packages/dulwich.git")
-            .await;
-        assert_eq!(version, Some("1.1.0-1"));
+        let version = get_version_for_url(
+            &cache,
+            "https://salsa.debian.org/python-team/packages/dulwich.git",
+        )
+        .await;
+        assert_eq!(version.as_deref(), Some("1.1.0-1"));
     }
 
     #[tokio::test]
     async fn test_get_version_unknown_url() {
-        let mut cache = VcsWatchCache::new(crate::udd::shared_pool());
-        cache.insert_cached(
+        let cache = new_shared_vcswatch_cache(crate::udd::shared_pool());
+        cache.write().await.insert_cached(
             "https://salsa.debian.org/python-team/packages/dulwich.git",
             "1.1.0-1",
         );
 
         // This URL is not in the cache so it will try UDD and fail (no network),
-        // but fetch_version_for_url returns early on error without caching.
+        // but get_version_for_url returns early on error without caching.
         // On second call it would retry, which is acceptable for network errors.
-        let version = cache
-            .get_version_for_url("https://example.com/nonexistent.git")
-            .await;
+        let version = get_version_for_url(&cache, "https://example.com/nonexistent.git").await;
         assert_eq!(version, None);
     }
 
     #[tokio::test]
     #[ignore] // requires network access to UDD
     async fn test_fetch_from_udd() {
-        let mut cache = VcsWatchCache::new(crate::udd::shared_pool());
-        let version = cache
-            .get_version_for_url("https://salsa.debian.org/python-team/packages/dulwich.git")
-            .await;
+        let cache = new_shared_vcswatch_cache(crate::udd::shared_pool());
+        let version = get_version_for_url(
+            &cache,
+            "https://salsa.debian.org/python-team/packages/dulwich.git",
+        )
+        .await;
         assert!(version.is_some(), "dulwich should be tracked in vcswatch");
     }
 }
@@ -0,0 +1,419 @@
+//! Converting line-based watch files to the deb822 format of version 5.
+//!
+//! Each entry becomes a paragraph: the URL and matching pattern become
+//! `Source` and `Matching-Pattern`, the version policy and script become
+//! `Version-Policy` and `Script`, and each `opts=` option becomes the field of
+//! the same meaning, with flags set to `yes`. Comments are kept before the
+//! paragraph of the entry that follows them.
+//!
+//! The generated file is parsed again and only offered if every entry means
+//! the same to uscan as the one it was converted from.
+
+use debian_watch::linebased::{Entry, WatchFile};
+use debian_watch::parse::{ParsedEntry, ParsedWatchFile};
+use debian_watch::{SearchMode, SyntaxKind, VersionPolicy};
+use text_size::{TextRange, TextSize};
+use tower_lsp_server::ls_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, TextEdit, Uri, WorkspaceEdit,
+};
+
+use super::diagnostics::{entry_option, linebased_entry};
+use super::fields::WATCH_FIELDS;
+use crate::position::Source;
+
+/// The only line-based version that is converted; older versions differ in
+/// more than syntax.
+const CONVERTED_VERSION: u32 = 4;
+
+/// Options whose field in version 5 has a different name, besides those in
+/// [`WATCH_FIELDS`].
+const RENAMED_OPTIONS: &[(&str, &str)] = &[
+    ("pretty", "Git-Pretty"),
+    ("passive", "Passive"),
+    ("nopasv", "Active"),
+];
+
+/// The version 5 field for the line-based option `option`.
+fn field_name(option: &str) -> Option<&'static str> {
+    RENAMED_OPTIONS
+        .iter()
+        .find(|(name, _)| *name == option)
+        .map(|(_, field)| *field)
+        .or_else(|| {
+            WATCH_FIELDS
+                .iter()
+                .find(|f| f.linebased_name == Some(option))
+                .map(|f| f.deb822_name)
+        })
+}
+
+fn child_text(entry: &Entry, kind: SyntaxKind) -> Option<String> {
+    entry
+        .syntax()
+        .children()
+        .find(|n| n.kind() == kind)
+        .map(|n| n.text().to_string())
+}
+
+/// The fields of the paragraph for `entry`, in order.
+fn entry_fields(entry: &Entry) -> Result<Vec<(String, String)>, String> {
+    let parts = linebased_entry(entry);
+    let url = parts.url.map(|v| v.text).unwrap_or_default();
+    let mut pattern = parts.pattern.map(|v| v.text);
+    let mut version_policy = child_text(entry, SyntaxKind::VERSION_POLICY);
+    let mut script = child_text(entry, SyntaxKind::SCRIPT);
+
+    // When the last path component of the URL is the pattern, what follows
+    // the URL is the version policy and script.
+    let mut source = url.clone();
+    if let Some((dir, last)) = url.rsplit_once('/') {
+        if last.contains('(') {
+            if script.is_some() {
+                return Err(format!(
+                    "The entry on line {} has too many parts",
+                    entry.line() + 1
+                ));
+            }
+            source = format!("{}/", dir);
+            script = version_policy.take();
+            version_policy = pattern.replace(last.to_string());
+        }
+    }
+
+    let mut fields = vec![("Source".to_string(), source)];
+    if let Some(pattern) = pattern {
+        fields.push(("Matching-Pattern".to_string(), pattern));
+    }
+    if let Some(version_policy) = version_policy {
+        fields.push(("Version-Policy".to_string(), version_policy));
+    }
+    if let Some(script) = script {
+        fields.push(("Script".to_string(), script));
+    }
+    for option in parts.options {
+        let field = field_name(&option.key)
+            .ok_or_else(|| format!("Unknown option {} on line {}", option.key, entry.line() + 1))?;
+        let value = option.value.map_or_else(|| "yes".to_string(), |v| v.text);
+        fields.push((field.to_string(), value));
+    }
+    Ok(fields)
+}
+
+/// The mangle options, which keep their names in version 5.
+const MANGLES: &[&str] = &[
+    "pagemangle",
+    "uversionmangle",
+    "dversionmangle",
+    "dirversionmangle",
+    "oversionmangle",
+    "downloadurlmangle",
+    "pgpsigurlmangle",
+    "filenamemangle",
+];
+
+/// What uscan makes of an entry, read through the accessors both formats
+/// share.
+#[derive(Debug, PartialEq)]
+struct Semantics {
+    url: String,
+    pattern: Option<String>,
+    version_policy: Option<Result<VersionPolicy, String>>,
+    script: Option<String>,
+    component: Option<String>,
+    mangles: Vec<Option<String>>,
+    searchmode: SearchMode,
+}
+
+impl Semantics {
+    fn of(entry: &ParsedEntry) -> Self {
+        let option = |name: &str| entry_option(entry, name);
+        let (url, pattern, version_policy, script) = match entry {
+            ParsedEntry::LineBased(e) => {
+                let url = e.url();
+                let pattern = e.matching_pattern();
+                let version_policy = e.version_node().map(|n| n.policy());
+                // uscan reads a last path component with a group as the
+                // pattern, shifting the parts that follow the URL.
+                match url.rsplit_once('/') {
+                    Some((dir, last)) if last.contains('(') => (
+                        format!("{}/", dir),
+                        Some(last.to_string()),
+                        pattern,
+                        version_policy,
+                    ),
+                    _ => (url, pattern, version_policy, e.script()),
+                }
+            }
+            ParsedEntry::Deb822(e) => (
+                e.url(),
+                entry.matching_pattern(),
+                e.get_option("Version-Policy"),
+                entry.script(),
+            ),
+        };
+        Self {
+            url,
+            pattern,
+            version_policy: version_policy.map(|policy| {
+                policy
+                    .parse()
+                    .map_err(|e: debian_watch::ParseError| e.to_string())
+            }),
+            script,
+            component: option("component"),
+            mangles: MANGLES.iter().map(|name| option(name)).collect(),
+            searchmode: option("searchmode")
+                .and_then(|mode| mode.parse().ok())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Check that `text` reads back as a version 5 watch file whose entries
+/// mean the same as the `original` ones.
+fn verify(text: &str, original: &[Entry]) -> Result<(), String> {
+    let wf = text
+        .parse::<debian_watch::deb822::WatchFile>()
+        .map_err(|e| format!("The converted watch file does not parse: {}", e))?;
+    let entries = wf.entries().collect::<Vec<_>>();
+    if entries.len() != original.len() {
+        return Err(format!(
+            "The converted watch file has {} entries instead of {}",
+            entries.len(),
+            original.len()
+        ));
+    }
+    for (converted, original) in entries.into_iter().zip(original) {
+        let before = Semantics::of(&ParsedEntry::LineBased(original.clone()));
+        let after = Semantics::of(&ParsedEntry::Deb822(converted));
+        if before != after {
+            return Err(format!(
+                "The entry on line {} would change in the conversion",
+                original.line() + 1
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Convert the line-based watch file `wf` to the deb822 format, or explain
+/// why it can't be converted faithfully.
+pub fn convert_to_v5(wf: &WatchFile) -> Result<String, String> {
+    convert_with(wf, entry_fields)
+}
+
+/// Convert `wf` with the paragraph fields `fields` gives for each entry.
+fn convert_with(
+    wf: &WatchFile,
+    fields: impl Fn(&Entry) -> Result<Vec<(String, String)>, String>,
+) -> Result<String, String> {
+    let entries = wf.entries().collect::<Vec<_>>();
+    let mut paragraphs = entries
+        .iter()
+        .map(|entry| Ok((Vec::new(), fields(entry)?)))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    // Comments go before the paragraph of the next entry, or at the end.
+    let mut trailing = Vec::new();
+    for token in wf
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|e| e.into_token())
+        .filter(|t| t.kind() == SyntaxKind::COMMENT)
+    {
+        let next = entries
+            .iter()
+            .position(|e| e.syntax().text_range().end() > token.text_range().start());
+        match next {
+            Some(i) => paragraphs[i].0.push(token.text().trim_end().to_string()),
+            None => trailing.push(token.text().trim_end().to_string()),
+        }
+    }
+
+    let mut text = "Version: 5\n".to_string();
+    for (comments, fields) in &paragraphs {
+        text.push('\n');
+        for comment in comments {
+            text.push_str(comment);
+            text.push('\n');
+        }
+        for (field, value) in fields {
+            text.push_str(&format!("{}: {}\n", field, value));
+        }
+    }
+    if !trailing.is_empty() {
+        text.push('\n');
+        for comment in &trailing {
+            text.push_str(comment);
+            text.push('\n');
+        }
+    }
+
+    verify(&text, &entries)?;
+    Ok(text)
+}
+
+/// Offer to convert a version 4 watch file to version 5.
+pub fn get_code_actions(
+    uri: &Uri,
+    parse: &debian_watch::parse::Parse,
+    src: Source<'_>,
+) -> Vec<CodeActionOrCommand> {
+    let ParsedWatchFile::LineBased(wf) = parse.to_watch_file() else {
+        return Vec::new();
+    };
+    if wf.version() != CONVERTED_VERSION || wf.entries().next().is_none() {
+        return Vec::new();
+    }
+    let new_text = match convert_to_v5(&wf) {
+        Ok(text) => text,
+        Err(e) => {
+            tracing::debug!("not offering watch file conversion: {e}");
+            return Vec::new();
+        }
+    };
+    let range = src.text_range_to_lsp_range(TextRange::up_to(TextSize::of(src.text)));
+    vec![CodeActionOrCommand::CodeAction(CodeAction {
+        title: "Convert to watch file format version 5".to_string(),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        edit: Some(WorkspaceEdit {
+            changes: Some(
+                vec![(uri.clone(), vec![TextEdit { range, new_text }])]
+                    .into_iter()
+                    .collect(),
+            ),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+
+    fn convert(text: &str) -> Result<String, String> {
+        let ParsedWatchFile::LineBased(wf) =
+            debian_watch::parse::Parse::parse(text).to_watch_file()
+        else {
+            panic!("not a line-based watch file");
+        };
+        convert_to_v5(&wf)
+    }
+
+    #[test]
+    fn test_convert() {
+        let text = r#"# Watch file for foo
+version=4
+# Releases are signed.
+opts="pgpmode=auto,uversionmangle=s/(\d)[_\.\-\+]?((RC|rc|pre|dev|beta|alpha)\d*)$/$1~$2/,repack,compression=xz" \
+  https://example.com/releases/ foo-@ANY_VERSION@@ARCHIVE_EXT@ debian uupdate
+
+opts=component=bar,pretty=describe https://example.com/bar/bar-(\d[\d.]*)\.tar\.gz same
+# The end.
+"#;
+        assert_eq!(
+            convert(text).unwrap(),
+            r#"Version: 5
+
+# Watch file for foo
+# Releases are signed.
+Source: https://example.com/releases/
+Matching-Pattern: foo-@ANY_VERSION@@ARCHIVE_EXT@
+Version-Policy: debian
+Script: uupdate
+Pgpmode: auto
+Uversionmangle: s/(\d)[_\.\-\+]?((RC|rc|pre|dev|beta|alpha)\d*)$/$1~$2/
+Repack: yes
+Compression: xz
+
+Source: https://example.com/bar/
+Matching-Pattern: bar-(\d[\d.]*)\.tar\.gz
+Version-Policy: same
+Component: bar
+Git-Pretty: describe
+
+# The end.
+"#
+        );
+    }
+
+    #[test]
+    fn test_convert_option_with_equals() {
+        let text =
+            "version=4\nopts=uversionmangle=s/a=b/c/ https://example.com/ foo-(\\d+)\\.tar\\.gz\n";
+        assert!(convert(text)
+            .unwrap()
+            .contains("\nUversionmangle: s/a=b/c/\n"));
+    }
+
+    #[test]
+    fn test_convert_unknown_option() {
+        let text = "version=4\nopts=frobnicate=yes https://example.com/ foo-(\\d+)\\.tar\\.gz\n";
+        assert_eq!(
+            convert(text),
+            Err("Unknown option frobnicate on line 2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_convert_wrong_mapping_is_rejected() {
+        let text = "version=4\nopts=uversionmangle=s/-/~/ https://example.com/foo-(\\d+)\\.tar\\.gz debian\n";
+        let ParsedWatchFile::LineBased(wf) =
+            debian_watch::parse::Parse::parse(text).to_watch_file()
+        else {
+            panic!("not a line-based watch file");
+        };
+        assert!(convert_with(&wf, entry_fields).is_ok());
+
+        // A mangle mapped to the wrong field.
+        let wrong_field = |entry: &Entry| {
+            let mut fields = entry_fields(entry)?;
+            for (name, _) in &mut fields {
+                if name == "Uversionmangle" {
+                    *name = "Dversionmangle".to_string();
+                }
+            }
+            Ok(fields)
+        };
+        assert_eq!(
+            convert_with(&wf, wrong_field),
+            Err("The entry on line 2 would change in the conversion".to_string())
+        );
+
+        // The pattern in the URL split off as the version policy.
+        let wrong_split = |entry: &Entry| {
+            Ok(entry_fields(entry)?
+                .into_iter()
+                .map(|(name, value)| match name.as_str() {
+                    "Matching-Pattern" => ("Version-Policy".to_string(), value),
+                    "Version-Policy" => ("Matching-Pattern".to_string(), value),
+                    _ => (name, value),
+                })
+                .collect())
+        };
+        assert!(convert_with(&wf, wrong_split).is_err());
+    }
+
+    fn actions(text: &str) -> Vec<CodeActionOrCommand> {
+        let parse = debian_watch::parse::Parse::parse(text);
+        let idx = LineIndex::new(text);
+        let uri: Uri = "file:///src/foo/debian/watch".parse().unwrap();
+        get_code_actions(&uri, &parse, Source::new(text, &idx))
+    }
+
+    #[test]
+    fn test_code_actions() {
+        assert_eq!(
+            actions("version=4\nhttps://example.com/ foo-(\\d+)\\.tar\\.gz\n").len(),
+            1
+        );
+        // Older versions and version 5 are left alone.
+        assert!(actions("version=3\nhttps://example.com/ foo-(\\d+)\\.tar\\.gz\n").is_empty());
+        assert!(actions(
+            "Version: 5\n\nSource: https://example.com/\nMatching-Pattern: foo-(\\d+)\\.tar\\.gz\n"
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,754 @@
+//! Diagnostics for debian/watch files.
+//!
+//! Both the line-based (v1-4) and the deb822 (v5) formats are checked for:
+//! - deprecated or unknown format versions;
+//! - unknown options and fields;
+//! - matching patterns and mangle rules that are not valid regexes, and
+//!   matching patterns without a capture group for the version;
+//! - mangle rules that are not `s///` or `tr///` expressions;
+//! - unknown `@VARIABLE@` substitutions, substitutions in files older than
+//!   version 4 and `@COMPONENT@` in an entry without a component.
+
+use debian_watch::parse::{ParsedEntry, ParsedWatchFile};
+use debian_watch::SyntaxKind;
+use regex::Regex;
+use text_size::{TextRange, TextSize};
+use tower_lsp_server::ls_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+use super::fields::WATCH_FIELDS;
+use crate::position::Source;
+
+type WatchSyntaxNode = rowan::SyntaxNode<debian_watch::linebased::Lang>;
+
+const DEPRECATED_VERSION_CODE: &str = "deprecated-version";
+const UNKNOWN_VERSION_CODE: &str = "unknown-version";
+const UNKNOWN_OPTION_CODE: &str = "unknown-option";
+const INVALID_REGEX_CODE: &str = "invalid-regex";
+const MISSING_CAPTURE_GROUP_CODE: &str = "missing-capture-group";
+const INVALID_MANGLE_CODE: &str = "invalid-mangle-rule";
+const UNKNOWN_SUBSTITUTION_CODE: &str = "unknown-substitution";
+const SUBSTITUTION_VERSION_CODE: &str = "substitution-before-version-4";
+const COMPONENT_SUBSTITUTION_CODE: &str = "component-substitution-without-component";
+
+/// Oldest format version that is not deprecated.
+const OLDEST_SUPPORTED_VERSION: u32 = 4;
+
+/// Newest format version uscan understands.
+const NEWEST_VERSION: u32 = 5;
+
+/// Options whose values are `;`-separated `s///` or `tr///` rules, by
+/// normalized name.
+const MANGLE_OPTIONS: &[&str] = &[
+    "uversionmangle",
+    "dversionmangle",
+    "oversionmangle",
+    "dirversionmangle",
+    "versionmangle",
+    "pagemangle",
+    "downloadurlmangle",
+    "filenamemangle",
+    "pgpsigurlmangle",
+];
+
+/// Line-based spellings of options, besides those in [`WATCH_FIELDS`].
+const LINEBASED_ALIASES: &[&str] = &["passive", "nopasv"];
+
+/// Substitutions uscan knows, besides the `@<TYPE>_VERSION@` ones.
+const SUBSTITUTIONS: &[&str] = &[
+    "PACKAGE",
+    "COMPONENT",
+    "ANY_VERSION",
+    "ARCHIVE_EXT",
+    "SIGNATURE_EXT",
+    "DEB_EXT",
+];
+
+/// A value checked by the diagnostics, with the range it occupies.
+pub(super) struct Value {
+    pub(super) text: String,
+    pub(super) range: TextRange,
+}
+
+/// An option or field of an entry.
+pub(super) struct WatchOption {
+    /// The name, lowercased and without `-` or `_`.
+    pub(super) name: String,
+    pub(super) key: String,
+    pub(super) key_range: TextRange,
+    pub(super) value: Option<Value>,
+}
+
+/// The parts of a line-based entry or deb822 paragraph that are checked.
+#[derive(Default)]
+pub(super) struct EntryParts {
+    pub(super) options: Vec<WatchOption>,
+    pub(super) url: Option<Value>,
+    pub(super) pattern: Option<Value>,
+}
+
+impl EntryParts {
+    fn option(&self, name: &str) -> Option<&WatchOption> {
+        self.options.iter().find(|o| o.name == name)
+    }
+}
+
+pub(super) fn normalize_name(name: &str) -> String {
+    name.to_lowercase().replace(['-', '_'], "")
+}
+
+fn diagnostic(
+    src: Source<'_>,
+    range: TextRange,
+    severity: DiagnosticSeverity,
+    code: &str,
+    message: String,
+) -> Diagnostic {
+    Diagnostic {
+        range: src.text_range_to_lsp_range(range),
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some("debian-lsp".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// The value of a line-based `key=value` option: everything after the first
+/// `=`, which may itself contain `=`.
+fn linebased_option(node: &WatchSyntaxNode) -> Option<WatchOption> {
+    let key_token = node
+        .children_with_tokens()
+        .filter_map(|e| e.into_token())
+        .find(|t| t.kind() == SyntaxKind::KEY)?;
+    let value_start = node
+        .children_with_tokens()
+        .filter_map(|e| e.into_token())
+        .find(|t| t.kind() == SyntaxKind::EQUALS)
+        .map(|t| t.text_range().end());
+    let value = value_start.map(|start| {
+        let range = TextRange::new(start, node.text_range().end());
+        let offset = range.start() - node.text_range().start();
+        let text = node.text().to_string()[usize::from(offset)..].to_string();
+        Value { text, range }
+    });
+    Some(WatchOption {
+        name: normalize_name(key_token.text()),
+        key: key_token.text().to_string(),
+        key_range: key_token.text_range(),
+        value,
+    })
+}
+
+fn node_value(node: &WatchSyntaxNode) -> Value {
+    Value {
+        text: node.text().to_string(),
+        range: node.text_range(),
+    }
+}
+
+pub(super) fn linebased_entry(entry: &debian_watch::linebased::Entry) -> EntryParts {
+    let mut parts = EntryParts::default();
+    for node in entry.syntax().descendants() {
+        match node.kind() {
+            SyntaxKind::OPTION => parts.options.extend(linebased_option(&node)),
+            SyntaxKind::URL => parts.url = Some(node_value(&node)),
+            SyntaxKind::MATCHING_PATTERN => parts.pattern = Some(node_value(&node)),
+            _ => {}
+        }
+    }
+    parts
+}
+
+/// The value of the option `name` of `entry`. Line-based options are read
+/// from the syntax tree, since the library's accessor stops at an `=` inside
+/// the value.
+pub(super) fn entry_option(entry: &ParsedEntry, name: &str) -> Option<String> {
+    match entry {
+        ParsedEntry::LineBased(e) => linebased_entry(e)
+            .options
+            .into_iter()
+            .find(|option| option.key == name)
+            .map(|option| option.value.map_or_else(String::new, |v| v.text)),
+        ParsedEntry::Deb822(_) => entry.get_option(name),
+    }
+}
+
+/// The value of a deb822 entry without trailing whitespace.
+fn deb822_value(src: Source<'_>, entry: &deb822_lossless::Entry) -> Option<Value> {
+    let range = entry.value_range()?;
+    let text = src.text[usize::from(range.start())..usize::from(range.end())].trim_end();
+    Some(Value {
+        text: text.to_string(),
+        range: TextRange::at(range.start(), TextSize::of(text)),
+    })
+}
+
+fn deb822_paragraph(src: Source<'_>, paragraph: &deb822_lossless::Paragraph) -> EntryParts {
+    let mut parts = EntryParts::default();
+    for entry in paragraph.entries() {
+        let (Some(key), Some(key_range)) = (entry.key(), entry.key_range()) else {
+            continue;
+        };
+        let name = normalize_name(&key);
+        let value = deb822_value(src, &entry);
+        match name.as_str() {
+            "source" | "url" => parts.url = deb822_value(src, &entry),
+            "matchingpattern" => parts.pattern = deb822_value(src, &entry),
+            _ => {}
+        }
+        parts.options.push(WatchOption {
+            name,
+            key,
+            key_range,
+            value,
+        });
+    }
+    parts
+}
+
+fn check_linebased_version(
+    wf: &debian_watch::linebased::WatchFile,
+    src: Source<'_>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(node) = wf.version_node() else {
+        if let Some(url) = wf.entries().next().and_then(|e| e.url_node()) {
+            diagnostics.push(diagnostic(
+                src,
+                url.text_range(),
+                DiagnosticSeverity::WARNING,
+                DEPRECATED_VERSION_CODE,
+                "Watch file without a version line uses the deprecated version 1".to_string(),
+            ));
+        }
+        return;
+    };
+    let range = node.text_range();
+    let text = node.to_string();
+    let range = TextRange::at(range.start(), TextSize::of(text.trim_end()));
+    let value = text.trim().split_once('=').map_or("", |(_, v)| v.trim());
+    match value.parse::<u32>() {
+        Ok(version) if (1..OLDEST_SUPPORTED_VERSION).contains(&version) => {
+            diagnostics.push(diagnostic(
+                src,
+                range,
+                DiagnosticSeverity::WARNING,
+                DEPRECATED_VERSION_CODE,
+                format!(
+                    "Watch file version {} is deprecated, use version {} or {}",
+                    version, OLDEST_SUPPORTED_VERSION, NEWEST_VERSION
+                ),
+            ));
+        }
+        Ok(version) if (1..=NEWEST_VERSION).contains(&version) => {}
+        _ => diagnostics.push(diagnostic(
+            src,
+            range,
+            DiagnosticSeverity::ERROR,
+            UNKNOWN_VERSION_CODE,
+            format!("Unknown watch file version {}", value),
+        )),
+    }
+}
+
+/// Whether `pattern` uses Perl regex features the `regex` crate lacks, so
+/// it can't be checked here even though uscan accepts it.
+pub(super) fn uses_perl_only_features(pattern: &str) -> bool {
+    if ["(?=", "(?!", "(?<=", "(?<!", "(?>"]
+        .iter()
+        .any(|s| pattern.contains(s))
+    {
+        return true;
+    }
+    // Backreferences.
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.next().is_some_and(|n| n.is_ascii_digit() && n != '0') {
+            return true;
+        }
+    }
+    false
+}
+
+/// Expand the substitutions in `text` the way uscan does for `package` and
+/// `component`.
+pub(super) fn expand(text: &str, package: &str, component: &str) -> String {
+    let mut expanded =
+        debian_watch::subst::subst(text, || package.to_string(), || component.to_string());
+    // The other @<TYPE>_VERSION@ substitutions of Version-Type capture the
+    // version like @ANY_VERSION@.
+    for (name, range) in substitutions(&expanded).into_iter().rev() {
+        if name.ends_with("_VERSION") {
+            expanded.replace_range(range, r"[-_]?(\d[\-+\.:\~\da-zA-Z]*)");
+        }
+    }
+    expanded
+}
+
+/// Compile `pattern` after expanding its substitutions, or return why it's
+/// invalid. `None` when it can't be checked.
+fn compile(pattern: &str) -> Option<Result<Regex, String>> {
+    let expanded = expand(pattern, "package", "");
+    if uses_perl_only_features(&expanded) {
+        return None;
+    }
+    Some(Regex::new(&expanded).map_err(|e| {
+        match e {
+            regex::Error::Syntax(message) => message
+                .lines()
+                .last()
+                .unwrap_or_default()
+                .trim_start_matches("error: ")
+                .to_string(),
+            e => e.to_string(),
+        }
+    }))
+}
+
+fn check_pattern(pattern: &Value, src: Source<'_>, diagnostics: &mut Vec<Diagnostic>) {
+    let text = pattern.text.trim();
+    // A git mode entry tracking a branch has no version in its pattern.
+    if text.is_empty() || text == "HEAD" || text.starts_with("heads/") {
+        return;
+    }
+    match compile(text) {
+        Some(Err(error)) => diagnostics.push(diagnostic(
+            src,
+            pattern.range,
+            DiagnosticSeverity::ERROR,
+            INVALID_REGEX_CODE,
+            format!("Invalid matching pattern: {}", error),
+        )),
+        Some(Ok(re)) if re.captures_len() < 2 => diagnostics.push(diagnostic(
+            src,
+            pattern.range,
+            DiagnosticSeverity::ERROR,
+            MISSING_CAPTURE_GROUP_CODE,
+            "Matching pattern has no capture group for the version".to_string(),
+        )),
+        _ => {}
+    }
+}
+
+/// Split `s` on the delimiter characters that are not escaped.
+fn split_unescaped(s: &str, delimiter: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == delimiter {
+            parts.push(&s[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Check a single `s///` or `tr///` mangle rule, returning the problem.
+fn mangle_rule_error(rule: &str) -> Option<(&'static str, String)> {
+    let (body, flags, is_subst) = if let Some(rest) = rule.strip_prefix("tr") {
+        (rest, "", false)
+    } else if let Some(rest) = rule.strip_prefix('y') {
+        (rest, "", false)
+    } else if let Some(rest) = rule.strip_prefix('s') {
+        (rest, "gix", true)
+    } else {
+        return Some((
+            INVALID_MANGLE_CODE,
+            format!("Mangle rule `{}` is not an s/// or tr/// expression", rule),
+        ));
+    };
+    let Some(delimiter) = body.chars().next().filter(|c| !c.is_alphanumeric()) else {
+        return Some((
+            INVALID_MANGLE_CODE,
+            format!("Mangle rule `{}` has no delimiter", rule),
+        ));
+    };
+    let parts = split_unescaped(&body[delimiter.len_utf8()..], delimiter);
+    let [pattern, _replacement, rule_flags] = parts.as_slice() else {
+        return Some((
+            INVALID_MANGLE_CODE,
+            format!(
+                "Mangle rule `{}` needs a pattern and a replacement, each ending in `{}`",
+                rule, delimiter
+            ),
+        ));
+    };
+    let allowed = if is_subst { flags } else { "cdrs" };
+    if let Some(flag) = rule_flags.chars().find(|c| !allowed.contains(*c)) {
+        return Some((
+            INVALID_MANGLE_CODE,
+            format!("Unknown flag `{}` in mangle rule", flag),
+        ));
+    }
+    if is_subst {
+        if let Some(Err(error)) = compile(pattern) {
+            return Some((INVALID_REGEX_CODE, format!("Invalid regex: {}", error)));
+        }
+    }
+    None
+}
+
+fn check_mangle(value: &Value, src: Source<'_>, diagnostics: &mut Vec<Diagnostic>) {
+    let mut offset = 0;
+    for rule in value.text.split(';') {
+        let start = offset + (rule.len() - rule.trim_start().len());
+        offset += rule.len() + 1;
+        let rule = rule.trim();
+        // `auto` picks uscan's built-in rule for dversionmangle.
+        if rule.is_empty() || rule == "auto" {
+            continue;
+        }
+        if let Some((code, message)) = mangle_rule_error(rule) {
+            let range = TextRange::at(
+                value.range.start() + TextSize::from(start as u32),
+                TextSize::of(rule),
+            );
+            diagnostics.push(diagnostic(
+                src,
+                range,
+                DiagnosticSeverity::ERROR,
+                code,
+                message,
+            ));
+        }
+    }
+}
+
+/// The `@NAME@` substitutions in `text`, with their byte ranges.
+fn substitutions(text: &str) -> Vec<(String, std::ops::Range<usize>)> {
+    let mut found = Vec::new();
+    let mut search = 0;
+    while let Some(start) = text[search..].find('@').map(|i| search + i) {
+        let rest = &text[start + 1..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_uppercase() || c == '_'))
+            .unwrap_or(rest.len());
+        if len > 0 && rest[len..].starts_with('@') {
+            found.push((rest[..len].to_string(), start..start + len + 2));
+            search = start + len + 2;
+        } else {
+            search = start + 1;
+        }
+    }
+    found
+}
+
+fn check_substitutions(
+    value: &Value,
+    version: u32,
+    has_component: bool,
+    src: Source<'_>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for (name, range) in substitutions(&value.text) {
+        let range = TextRange::new(
+            value.range.start() + TextSize::from(range.start as u32),
+            value.range.start() + TextSize::from(range.end as u32),
+        );
+        let known = SUBSTITUTIONS.contains(&name.as_str())
+            || (version >= NEWEST_VERSION && name.ends_with("_VERSION"));
+        if !known {
+            diagnostics.push(diagnostic(
+                src,
+                range,
+                DiagnosticSeverity::WARNING,
+                UNKNOWN_SUBSTITUTION_CODE,
+                format!("Unknown substitution @{}@", name),
+            ));
+        } else if version < OLDEST_SUPPORTED_VERSION {
+            diagnostics.push(diagnostic(
+                src,
+                range,
+                DiagnosticSeverity::WARNING,
+                SUBSTITUTION_VERSION_CODE,
+                format!(
+                    "@{}@ is only substituted in watch files of version {} and later",
+                    name, OLDEST_SUPPORTED_VERSION
+                ),
+            ));
+        } else if name == "COMPONENT" && !has_component {
+            diagnostics.push(diagnostic(
+                src,
+                range,
+                DiagnosticSeverity::WARNING,
+                COMPONENT_SUBSTITUTION_CODE,
+                "@COMPONENT@ is empty in an entry without a component".to_string(),
+            ));
+        }
+    }
+}
+
+/// Check an entry; `known_option` tells whether an option name is valid.
+fn check_entry(
+    parts: &EntryParts,
+    version: u32,
+    known_option: impl Fn(&WatchOption) -> bool,
+    src: Source<'_>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let has_component = parts.option("component").is_some();
+    for option in &parts.options {
+        if !known_option(option) {
+            diagnostics.push(diagnostic(
+                src,
+                option.key_range,
+                DiagnosticSeverity::WARNING,
+                UNKNOWN_OPTION_CODE,
+                format!("Unknown option {}", option.key),
+            ));
+            continue;
+        }
+        let Some(value) = &option.value else {
+            continue;
+        };
+        if MANGLE_OPTIONS.contains(&option.name.as_str()) {
+            check_mangle(value, src, diagnostics);
+        }
+        if option.name != "matchingpattern" && option.name != "source" {
+            check_substitutions(value, version, has_component, src, diagnostics);
+        }
+    }
+    if let Some(url) = &parts.url {
+        check_substitutions(url, version, has_component, src, diagnostics);
+    }
+    if let Some(pattern) = &parts.pattern {
+        check_substitutions(pattern, version, has_component, src, diagnostics);
+        check_pattern(pattern, src, diagnostics);
+    }
+}
+
+fn linebased_diagnostics(
+    wf: &debian_watch::linebased::WatchFile,
+    src: Source<'_>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    check_linebased_version(wf, src, &mut diagnostics);
+    let version = wf.version();
+    let known_option = |option: &WatchOption| {
+        LINEBASED_ALIASES.contains(&option.key.as_str())
+            || WATCH_FIELDS
+                .iter()
+                .any(|f| f.linebased_name == Some(option.key.as_str()))
+    };
+    for entry in wf.entries() {
+        let mut parts = linebased_entry(&entry);
+        // Without a separate matching pattern, the last path component of
+        // the URL is the pattern.
+        if parts.pattern.is_none() {
+            if let Some(url) = &parts.url {
+                if let Some(slash) = url.text.rfind('/') {
+                    let last = &url.text[slash + 1..];
+                    if last.contains('(') {
+                        parts.pattern = Some(Value {
+                            text: last.to_string(),
+                            range: TextRange::new(
+                                url.range.start() + TextSize::from(slash as u32 + 1),
+                                url.range.end(),
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+        check_entry(&parts, version, known_option, src, &mut diagnostics);
+    }
+    diagnostics
+}
+
+fn deb822_diagnostics(wf: &debian_watch::deb822::WatchFile, src: Source<'_>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let known_option = |option: &WatchOption| {
+        WATCH_FIELDS
+            .iter()
+            .any(|f| normalize_name(f.deb822_name) == option.name)
+    };
+    for (i, paragraph) in wf.as_deb822().paragraphs().enumerate() {
+        let parts = deb822_paragraph(src, &paragraph);
+        if i == 0 {
+            if let Some(version) = parts.option("version") {
+                let value = version.value.as_ref();
+                if value.is_none_or(|v| v.text.trim() != NEWEST_VERSION.to_string()) {
+                    diagnostics.push(diagnostic(
+                        src,
+                        value.map_or(version.key_range, |v| v.range),
+                        DiagnosticSeverity::ERROR,
+                        UNKNOWN_VERSION_CODE,
+                        format!(
+                            "Unknown watch file version {}",
+                            value.map_or("", |v| v.text.trim())
+                        ),
+                    ));
+                }
+            }
+        }
+        // Entries generated from a template have no pattern of their own.
+        let parts = if parts.option("template").is_some() {
+            EntryParts {
+                pattern: None,
+                ..parts
+            }
+        } else {
+            parts
+        };
+        check_entry(&parts, NEWEST_VERSION, known_option, src, &mut diagnostics);
+    }
+    diagnostics
+}
+
+/// Get diagnostics for a watch file.
+pub fn get_diagnostics(parse: &debian_watch::parse::Parse, src: Source<'_>) -> Vec<Diagnostic> {
+    match parse.to_watch_file() {
+        ParsedWatchFile::LineBased(wf) => linebased_diagnostics(&wf, src),
+        ParsedWatchFile::Deb822(wf) => deb822_diagnostics(&wf, src),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::LineIndex;
+
+    /// The code and the text covered by each diagnostic.
+    fn diagnose(text: &str) -> Vec<(String, String)> {
+        let parse = debian_watch::parse::Parse::parse(text);
+        let idx = LineIndex::new(text);
+        let src = Source::new(text, &idx);
+        get_diagnostics(&parse, src)
+            .into_iter()
+            .map(|d| {
+                let Some(NumberOrString::String(code)) = d.code else {
+                    panic!("missing code");
+                };
+                let range = src.try_lsp_range_to_text_range(&d.range).unwrap();
+                (code, text[range].to_string())
+            })
+            .collect()
+    }
+
+    fn d(code: &str, text: &str) -> (String, String) {
+        (code.to_string(), text.to_string())
+    }
+
+    #[test]
+    fn test_valid_linebased() {
+        let text = "version=4\nopts=\"uversionmangle=s/-?rc/~rc/;s/(?=x)y//,filenamemangle=s/.+\\/v?(\\d\\S+)\\.tar\\.gz/foo-$1\\.tar\\.gz/\" \\\n  https://example.com/@PACKAGE@/ .*/@PACKAGE@@ANY_VERSION@@ARCHIVE_EXT@ debian uupdate\n";
+        assert_eq!(diagnose(text), vec![]);
+    }
+
+    #[test]
+    fn test_valid_deb822() {
+        let text = "Version: 5\n\nSource: https://example.com/files\nMatching-Pattern: .*/foo-@ANY_VERSION@@ARCHIVE_EXT@\nUversionmangle: s/rc/~rc/\n\nTemplate: GitHub\nOwner: foo\nProject: bar\n";
+        assert_eq!(diagnose(text), vec![]);
+    }
+
+    #[test]
+    fn test_versions() {
+        assert_eq!(
+            diagnose("version=3\nhttps://example.com/ foo-(\\d+)\\.tar\\.gz\n"),
+            vec![d(DEPRECATED_VERSION_CODE, "version=3")]
+        );
+        assert_eq!(
+            diagnose("https://example.com/foo-(\\d+)\\.tar\\.gz\n"),
+            vec![d(
+                DEPRECATED_VERSION_CODE,
+                "https://example.com/foo-(\\d+)\\.tar\\.gz"
+            )]
+        );
+        assert_eq!(
+            diagnose("version=9\nhttps://example.com/ foo-(\\d+)\\.tar\\.gz\n"),
+            vec![d(UNKNOWN_VERSION_CODE, "version=9")]
+        );
+    }
+
+    #[test]
+    fn test_unknown_options() {
+        assert_eq!(
+            diagnose(
+                "version=4\nopts=pasv,frobnicate=1 https://example.com/ foo-(\\d+)\\.tar\\.gz\n"
+            ),
+            vec![d(UNKNOWN_OPTION_CODE, "frobnicate")]
+        );
+        assert_eq!(
+            diagnose("Version: 5\n\nSource: https://example.com/\nMatching-Pattern: foo-(\\d+)\nX-Frobnicate: 1\n"),
+            vec![d(UNKNOWN_OPTION_CODE, "X-Frobnicate")]
+        );
+    }
+
+    #[test]
+    fn test_matching_pattern() {
+        assert_eq!(
+            diagnose("version=4\nhttps://example.com/ foo-(\\d+\\.tar\\.gz\n"),
+            vec![d(INVALID_REGEX_CODE, "foo-(\\d+\\.tar\\.gz")]
+        );
+        assert_eq!(
+            diagnose("version=4\nhttps://example.com/ foo-\\d+\\.tar\\.gz\n"),
+            vec![d(MISSING_CAPTURE_GROUP_CODE, "foo-\\d+\\.tar\\.gz")]
+        );
+        assert_eq!(
+            diagnose("Version: 5\n\nSource: https://example.com/\nMatching-Pattern: foo-[0-9.+\\.tar\\.gz\n"),
+            vec![d(INVALID_REGEX_CODE, "foo-[0-9.+\\.tar\\.gz")]
+        );
+        // Lookarounds are valid in uscan's Perl regexes.
+        assert_eq!(
+            diagnose("version=4\nhttps://example.com/ foo-(?!rc)(\\d+)\\.tar\\.gz\n"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_mangle_rules() {
+        assert_eq!(
+            diagnose("version=4\nopts=uversionmangle=s/a/b/;x/a/b/;s/a/b;s/(/x/;s/a/b/q https://example.com/ foo-(\\d+)\n"),
+            vec![
+                d(INVALID_MANGLE_CODE, "x/a/b/"),
+                d(INVALID_MANGLE_CODE, "s/a/b"),
+                d(INVALID_REGEX_CODE, "s/(/x/"),
+                d(INVALID_MANGLE_CODE, "s/a/b/q"),
+            ]
+        );
+        assert_eq!(
+            diagnose("version=4\nopts=dversionmangle=auto,uversionmangle=tr/a-z/A-Z/ https://example.com/ foo-(\\d+)\n"),
+            vec![]
+        );
+        assert_eq!(
+            diagnose("Version: 5\n\nSource: https://example.com/\nMatching-Pattern: foo-(\\d+)\nFilenamemangle: s/foo/bar/ ; s/[/x/\n"),
+            vec![d(INVALID_REGEX_CODE, "s/[/x/")]
+        );
+    }
+
+    #[test]
+    fn test_substitutions() {
+        assert_eq!(
+            diagnose(
+                "version=4\nhttps://example.com/@PACKGE@/ @PACKAGE@@ANY_VERSION@@COMPONENT@\n"
+            ),
+            vec![
+                d(UNKNOWN_SUBSTITUTION_CODE, "@PACKGE@"),
+                d(COMPONENT_SUBSTITUTION_CODE, "@COMPONENT@"),
+            ]
+        );
+        assert_eq!(
+            diagnose(
+                "version=4\nopts=component=bar https://example.com/ @COMPONENT@@ANY_VERSION@\n"
+            ),
+            vec![]
+        );
+        assert_eq!(
+            diagnose("version=3\nhttps://example.com/ foo@ANY_VERSION@\n"),
+            vec![
+                d(DEPRECATED_VERSION_CODE, "version=3"),
+                d(SUBSTITUTION_VERSION_CODE, "@ANY_VERSION@"),
+            ]
+        );
+        assert_eq!(
+            diagnose("Version: 5\n\nSource: https://example.com/\nMatching-Pattern: foo@SEMANTIC_VERSION@\n"),
+            vec![]
+        );
+    }
+}
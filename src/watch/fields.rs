@@ -80,6 +80,17 @@ fn ctype_completions(prefix: &str) -> Vec<CompletionItem> {
     enum_completions(&["perl", "nodejs"], prefix)
 }
 
+fn version_policy_completions(prefix: &str) -> Vec<CompletionItem> {
+    enum_completions(
+        &["debian", "same", "previous", "ignore", "group", "checksum"],
+        prefix,
+    )
+}
+
+fn hrefdecode_completions(prefix: &str) -> Vec<CompletionItem> {
+    enum_completions(&["percent-encoding"], prefix)
+}
+
 // TODO: derive template names from debian_watch::templates::Template enum
 fn template_completions(prefix: &str) -> Vec<CompletionItem> {
     enum_completions(
@@ -172,6 +183,27 @@ pub const WATCH_FIELDS: &[WatchField] = &[
         OptionValueType::String,
         no_completions,
     ),
+    WatchField::new(
+        "Version-Policy",
+        None,
+        "How the upstream version relates to the Debian version (debian, same, previous, ignore, group, checksum)",
+        OptionValueType::Enum(&["debian", "same", "previous", "ignore", "group", "checksum"]),
+        version_policy_completions,
+    ),
+    WatchField::new(
+        "Script",
+        None,
+        "Command run after a new upstream version was downloaded (e.g. uupdate)",
+        OptionValueType::String,
+        no_completions,
+    ),
+    WatchField::new(
+        "Git-Pretty",
+        None,
+        "Format of the version of a git snapshot",
+        OptionValueType::String,
+        no_completions,
+    ),
     // Fields available in both v1-4 (as options) and v5 (as deb822 fields)
     WatchField::new(
         "Component",
@@ -336,6 +368,41 @@ pub const WATCH_FIELDS: &[WatchField] = &[
         OptionValueType::Boolean,
         boolean_completions,
     ),
+    WatchField::new(
+        "Git-Date",
+        Some("date"),
+        "Date format used in the version of a git snapshot",
+        OptionValueType::String,
+        no_completions,
+    ),
+    WatchField::new(
+        "Passive",
+        Some("pasv"),
+        "Use passive mode for FTP connections",
+        OptionValueType::Boolean,
+        boolean_completions,
+    ),
+    WatchField::new(
+        "Active",
+        Some("active"),
+        "Use active mode for FTP connections",
+        OptionValueType::Boolean,
+        boolean_completions,
+    ),
+    WatchField::new(
+        "Unzipopt",
+        Some("unzipopt"),
+        "Extra options passed to unzip",
+        OptionValueType::String,
+        no_completions,
+    ),
+    WatchField::new(
+        "Hrefdecode",
+        Some("hrefdecode"),
+        "Decode the links found on the page before matching",
+        OptionValueType::Enum(&["percent-encoding"]),
+        hrefdecode_completions,
+    ),
     WatchField::new(
         "Repack",
         Some("repack"),
@@ -433,6 +500,9 @@ mod tests {
                 "Dist",
                 "Release-Only",
                 "Version-Type",
+                "Version-Policy",
+                "Script",
+                "Git-Pretty",
             ]
             .contains(&field.deb822_name)
             {
@@ -1,10 +1,13 @@
 pub mod completion;
+pub mod convert;
 pub mod detection;
+pub mod diagnostics;
 pub mod fields;
 pub mod folding;
 pub mod hover;
 pub mod selection_range;
 pub mod semantic;
+pub mod simulate;
 
 pub use completion::*;
 pub use detection::is_watch_file;
@@ -0,0 +1,695 @@
+//! Simulating uscan's version check for a watch entry.
+//!
+//! [`SIMULATE_WATCH_COMMAND`] applies an entry's `pagemangle`, matching
+//! pattern, `uversionmangle`, `downloadurlmangle` and `filenamemangle` to a
+//! saved copy of the upstream download page, a plain text listing or a local
+//! directory, instead of fetching the page the way `uscan` would. This makes
+//! it possible to debug a watch rule without network access. Searching
+//! versioned directories, and with it `dirversionmangle`, is not simulated.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use debian_watch::parse::{ParsedEntry, ParsedWatchFile};
+use debian_watch::SearchMode;
+use debversion::Version;
+use regex::Regex;
+use serde::Serialize;
+use tower_lsp_server::ls_types::{CodeAction, CodeActionKind, CodeActionOrCommand, Command, Uri};
+
+use super::diagnostics::{entry_option, expand, uses_perl_only_features};
+
+pub const SIMULATE_WATCH_COMMAND: &str = "debian-lsp.simulateWatch";
+
+/// What a watch entry looks for, with its substitutions expanded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub url: String,
+    pub pattern: String,
+    pub pagemangle: Vec<String>,
+    pub uversionmangle: Vec<String>,
+    pub downloadurlmangle: Vec<String>,
+    pub filenamemangle: Vec<String>,
+    /// Whether the entry has a `dirversionmangle`, which isn't simulated.
+    pub dirversionmangle: bool,
+    pub searchmode: SearchMode,
+    /// Whether links are percent-decoded before matching (`hrefdecode`).
+    pub hrefdecode: bool,
+}
+
+impl Rule {
+    /// The rule of `entry` in a watch file for `package`.
+    pub fn from_entry(entry: &ParsedEntry, package: &str) -> Result<Self, String> {
+        let component = entry.component().unwrap_or_default();
+        let url = entry.url();
+        let pattern = match entry.matching_pattern() {
+            Some(pattern) => pattern,
+            // The line-based format allows the pattern to be the last path
+            // segment of the URL.
+            None => match url.rsplit_once('/') {
+                Some((_, last)) if last.contains('(') => last.to_string(),
+                _ => return Err("The watch entry has no matching pattern".to_string()),
+            },
+        };
+        let mangle = |rules: Option<String>| -> Vec<String> {
+            rules
+                .map(|rules| {
+                    rules
+                        .split(';')
+                        .map(str::trim)
+                        .filter(|rule| !rule.is_empty())
+                        .map(|rule| {
+                            debian_watch::subst::subst(
+                                rule,
+                                || package.to_string(),
+                                || component.clone(),
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Ok(Self {
+            url: expand(&url, package, &component),
+            pattern: expand(&pattern, package, &component),
+            pagemangle: mangle(entry_option(entry, "pagemangle")),
+            uversionmangle: mangle(entry_option(entry, "uversionmangle")),
+            downloadurlmangle: mangle(entry_option(entry, "downloadurlmangle")),
+            filenamemangle: mangle(entry_option(entry, "filenamemangle")),
+            dirversionmangle: entry.has_option("dirversionmangle"),
+            searchmode: entry.searchmode(),
+            hrefdecode: entry.has_option("hrefdecode"),
+        })
+    }
+
+    /// The regex links are matched against. As uscan does, an HTML link only
+    /// has to match from the start of a path segment, since its site and
+    /// directory are optional.
+    fn regex(&self) -> Result<Regex, String> {
+        if uses_perl_only_features(&self.pattern) {
+            return Err(format!(
+                "The matching pattern {} uses Perl regex features that can't be simulated",
+                self.pattern
+            ));
+        }
+        let anchored = match self.searchmode {
+            SearchMode::Html => format!("^(?:.*/)?(?:{})$", self.pattern),
+            SearchMode::Plain => self.pattern.clone(),
+        };
+        Regex::new(&anchored).map_err(|e| format!("Invalid matching pattern: {}", e))
+    }
+
+    /// The download URL of `link` on the page, with `downloadurlmangle`
+    /// applied, and the name the download is saved under.
+    fn download(&self, link: &str) -> Result<(String, String), String> {
+        let url = apply_mangle(
+            "downloadurlmangle",
+            &self.downloadurlmangle,
+            &resolve(&self.url, link),
+        )?;
+        let filename = if self.filenamemangle.is_empty() {
+            let path = url.split(['?', '#']).next().unwrap_or_default();
+            path.rsplit('/').next().unwrap_or_default().to_string()
+        } else {
+            apply_mangle("filenamemangle", &self.filenamemangle, link)?
+        };
+        Ok((url, filename))
+    }
+}
+
+/// Apply the rules of the mangle option `name` to `text`, in order.
+fn apply_mangle(name: &str, rules: &[String], text: &str) -> Result<String, String> {
+    rules.iter().try_fold(text.to_string(), |text, rule| {
+        debian_watch::mangle::apply_mangle(rule, &text)
+            .map_err(|e| format!("{} failed: {}: {}", name, rule, e))
+    })
+}
+
+/// The absolute URL of `link` on the page at `base`.
+fn resolve(base: &str, link: &str) -> String {
+    if link.contains("://") {
+        return link.to_string();
+    }
+    let (scheme, rest) = base.split_once("://").unwrap_or(("", base));
+    if let Some(link) = link.strip_prefix("//") {
+        return format!("{}://{}", scheme, link);
+    }
+    if link.starts_with('/') {
+        let host = rest.split('/').next().unwrap_or_default();
+        return format!("{}://{}{}", scheme, host, link);
+    }
+    match base.rfind('/') {
+        Some(end) if end > scheme.len() + 2 => format!("{}{}", &base[..=end], link),
+        _ => format!("{}/{}", base, link),
+    }
+}
+
+/// A saved upstream page or a local directory standing in for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fixture {
+    /// The contents of an HTML page or plain text listing.
+    Page(String),
+    /// The names of the files in a directory.
+    Directory(Vec<String>),
+}
+
+impl Fixture {
+    pub fn read(path: &Path) -> std::io::Result<Self> {
+        if path.is_dir() {
+            let mut names = std::fs::read_dir(path)?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect::<Vec<_>>();
+            names.sort();
+            Ok(Self::Directory(names))
+        } else {
+            let bytes = std::fs::read(path)?;
+            Ok(Self::Page(String::from_utf8_lossy(&bytes).into_owned()))
+        }
+    }
+}
+
+/// The local path of `fixture`, a path or `file://` URL. Relative paths are
+/// taken to be relative to the source tree at `root`.
+pub fn fixture_path(fixture: &str, root: &Path) -> Option<PathBuf> {
+    if fixture.starts_with("file:") {
+        return fixture
+            .parse::<Uri>()
+            .ok()?
+            .to_file_path()
+            .map(|p| p.into_owned());
+    }
+    if fixture.contains("://") {
+        return None;
+    }
+    Some(root.join(fixture))
+}
+
+/// The source package name, from the latest entry of `changelog`.
+pub fn package_name(changelog: &debian_changelog::ChangeLog) -> Option<String> {
+    changelog.iter().next()?.package()
+}
+
+/// A link that matched the pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Match {
+    pub link: String,
+    /// The version captured by the pattern.
+    pub version: String,
+    /// The version after `uversionmangle`.
+    pub mangled_version: Option<String>,
+    /// The URL uscan would download, after `downloadurlmangle`.
+    pub download_url: Option<String>,
+    /// The name the download would be saved under, after `filenamemangle`.
+    pub filename: Option<String>,
+    /// Why a mangle failed.
+    pub error: Option<String>,
+}
+
+/// The outcome of a simulated version check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Simulation {
+    pub pattern: String,
+    /// How many links were searched; for plain text pages, `None`.
+    pub links: Option<usize>,
+    pub matches: Vec<Match>,
+    /// The newest mangled version.
+    pub newest: Option<String>,
+    /// Whether the entry has a `dirversionmangle`, which was not simulated.
+    pub dirversionmangle: bool,
+}
+
+impl Simulation {
+    fn newest_match(&self) -> Option<&Match> {
+        let newest = self.newest.as_deref()?;
+        self.matches
+            .iter()
+            .find(|m| m.mangled_version.as_deref() == Some(newest))
+    }
+
+    /// A one-line summary of the outcome.
+    pub fn summary(&self) -> String {
+        let matched = match self.links {
+            Some(links) => format!("{} of {} links matched", self.matches.len(), links),
+            None => format!("{} matches", self.matches.len()),
+        };
+        let summary = match self.newest_match() {
+            Some(newest) => format!(
+                "Newest upstream version: {} from {} ({})",
+                self.newest.as_deref().unwrap_or_default(),
+                newest.link,
+                matched
+            ),
+            None => format!("No upstream version found ({})", matched),
+        };
+        if self.dirversionmangle {
+            format!("{}; dirversionmangle was not simulated", summary)
+        } else {
+            summary
+        }
+    }
+
+    /// A report listing every match.
+    pub fn report(&self) -> String {
+        let mut report = format!("Matching pattern: {}\n", self.pattern);
+        for m in &self.matches {
+            report.push_str(&format!("  {}: {}", m.link, m.version));
+            if let Some(mangled) = m.mangled_version.as_ref().filter(|v| **v != m.version) {
+                report.push_str(&format!(" -> {}", mangled));
+            }
+            if let Some(error) = &m.error {
+                report.push_str(&format!(" ({})", error));
+            }
+            report.push('\n');
+            if let (Some(url), Some(filename)) = (&m.download_url, &m.filename) {
+                report.push_str(&format!("    downloads {} as {}\n", url, filename));
+            }
+        }
+        report.push_str(&self.summary());
+        report
+    }
+}
+
+/// Decode the HTML character references uscan decodes in links.
+fn decode_entities(href: &str) -> String {
+    href.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn percent_decode(href: &str) -> String {
+    let bytes = href.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+static HTML_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)<\s*a\s[^>]*?\bhref\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#)
+        .expect("valid regex")
+});
+
+/// The targets of the `<a href>` links in `html`.
+pub fn html_links(html: &str) -> Vec<String> {
+    HTML_LINK
+        .captures_iter(html)
+        .filter_map(|c| c.get(1).or_else(|| c.get(2)).or_else(|| c.get(3)))
+        .map(|href| {
+            let mut href = decode_entities(href.as_str());
+            href.retain(|c| !c.is_whitespace());
+            href
+        })
+        .collect()
+}
+
+/// The version captured by `captures`: uscan joins several capture groups
+/// with dots.
+fn captured_version(captures: &regex::Captures<'_>) -> Option<String> {
+    let groups = captures
+        .iter()
+        .skip(1)
+        .flatten()
+        .map(|m| m.as_str())
+        .collect::<Vec<_>>();
+    (!groups.is_empty()).then(|| groups.join("."))
+}
+
+/// Check `fixture` for new versions the way uscan would with `rule`.
+pub fn simulate(rule: &Rule, fixture: &Fixture) -> Result<Simulation, String> {
+    let re = rule.regex()?;
+    let fixture = match fixture {
+        Fixture::Page(page) => &Fixture::Page(apply_mangle("pagemangle", &rule.pagemangle, page)?),
+        fixture => fixture,
+    };
+    let mut found = Vec::new();
+    let links = match (rule.searchmode, fixture) {
+        (SearchMode::Plain, Fixture::Page(text)) => {
+            for captures in re.captures_iter(text) {
+                let link = captures[0].to_string();
+                if let Some(version) = captured_version(&captures) {
+                    found.push((link, version));
+                }
+            }
+            None
+        }
+        (_, fixture) => {
+            let links = match fixture {
+                Fixture::Page(html) => html_links(html),
+                Fixture::Directory(names) => names.clone(),
+            };
+            for link in &links {
+                let decoded = if rule.hrefdecode {
+                    percent_decode(link)
+                } else {
+                    link.clone()
+                };
+                if let Some(version) = re.captures(&decoded).and_then(|c| captured_version(&c)) {
+                    found.push((link.clone(), version));
+                }
+            }
+            Some(links.len())
+        }
+    };
+
+    let matches = found
+        .into_iter()
+        .map(|(link, version)| {
+            let mangled_version = apply_mangle("uversionmangle", &rule.uversionmangle, &version);
+            let download = rule.download(&link);
+            let error = mangled_version
+                .as_ref()
+                .err()
+                .or(download.as_ref().err())
+                .cloned();
+            let (download_url, filename) = download.ok().unzip();
+            Match {
+                link,
+                version,
+                mangled_version: mangled_version.ok(),
+                download_url,
+                filename,
+                error,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let newest = matches
+        .iter()
+        .filter_map(|m| {
+            let mangled = m.mangled_version.as_ref()?;
+            Some((mangled.parse::<Version>().ok()?, mangled))
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, mangled)| mangled.clone());
+
+    Ok(Simulation {
+        pattern: rule.pattern.clone(),
+        links,
+        matches,
+        newest,
+        dirversionmangle: rule.dirversionmangle,
+    })
+}
+
+/// Actions running [`SIMULATE_WATCH_COMMAND`] on each entry of the watch
+/// file at `uri`. An entry pointing at a local `file://` URL is checked
+/// against it. For any other entry the command is left without its third
+/// argument, the path of a saved copy of the page, for the client to pick.
+pub fn simulate_actions(uri: &Uri, watch: &ParsedWatchFile) -> Vec<CodeActionOrCommand> {
+    watch
+        .entries()
+        .enumerate()
+        .map(|(index, entry)| {
+            let url = entry.url();
+            let mut arguments = vec![uri.to_string().into(), index.into()];
+            let title = if url.starts_with("file://") {
+                let title = format!("Simulate version check against {}", url);
+                arguments.push(url.into());
+                title
+            } else {
+                format!("Simulate version check of {} against a saved page", url)
+            };
+            CodeActionOrCommand::CodeAction(CodeAction {
+                title: title.clone(),
+                kind: Some(CodeActionKind::SOURCE),
+                command: Some(Command {
+                    title,
+                    command: SIMULATE_WATCH_COMMAND.to_string(),
+                    arguments: Some(arguments),
+                }),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(text: &str) -> Rule {
+        let parsed = debian_watch::parse::Parse::parse(text).to_watch_file();
+        let entry = parsed.entries().next().unwrap();
+        Rule::from_entry(&entry, "foo").unwrap()
+    }
+
+    const PAGE: &str = r#"<html><body>
+<a href="foo-1.0.tar.gz">1.0</a>
+<A HREF='https://example.com/files/foo-1.10.tar.gz'>1.10</A>
+<a class="x" href="foo-1.2rc1.tar.gz">1.2rc1</a>
+<a href="foo-1.0.tar.gz.asc">signature</a>
+<a href="/other/bar-3.0.tar.gz">bar</a>
+</body></html>"#;
+
+    #[test]
+    fn test_html_links() {
+        assert_eq!(
+            html_links(PAGE),
+            vec![
+                "foo-1.0.tar.gz",
+                "https://example.com/files/foo-1.10.tar.gz",
+                "foo-1.2rc1.tar.gz",
+                "foo-1.0.tar.gz.asc",
+                "/other/bar-3.0.tar.gz",
+            ]
+        );
+        assert_eq!(html_links(r#"<a href="a?x=1&amp;y=2">"#), vec!["a?x=1&y=2"]);
+    }
+
+    #[test]
+    fn test_simulate_html() {
+        let rule = rule(
+            "version=4\nopts=uversionmangle=s/rc/~rc/ https://example.com/files/ @PACKAGE@-([\\d.]+\\w*)\\.tar\\.gz\n",
+        );
+        assert_eq!(rule.pattern, r"foo-([\d.]+\w*)\.tar\.gz");
+        let simulation = simulate(&rule, &Fixture::Page(PAGE.to_string())).unwrap();
+        assert_eq!(simulation.links, Some(5));
+        let found = simulation
+            .matches
+            .iter()
+            .map(|m| (m.version.as_str(), m.mangled_version.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("1.0", Some("1.0")),
+                ("1.10", Some("1.10")),
+                ("1.2rc1", Some("1.2~rc1")),
+            ]
+        );
+        assert_eq!(simulation.newest.as_deref(), Some("1.10"));
+        assert_eq!(
+            simulation.summary(),
+            "Newest upstream version: 1.10 from https://example.com/files/foo-1.10.tar.gz (3 of 5 links matched)"
+        );
+    }
+
+    #[test]
+    fn test_simulate_directory() {
+        let dir = std::env::temp_dir().join(format!("debian-lsp-simulate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "foo_1.0.orig.tar.gz",
+            "foo-2.0.tar.xz",
+            "foo-2.1.tar.xz",
+            "README",
+        ] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+        let fixture = Fixture::read(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let rule = rule("version=4\nfile:///srv/foo/ foo-@ANY_VERSION@@ARCHIVE_EXT@\n");
+        let simulation = simulate(&rule, &fixture).unwrap();
+        assert_eq!(simulation.links, Some(4));
+        assert_eq!(simulation.matches.len(), 2);
+        assert_eq!(simulation.newest.as_deref(), Some("2.1"));
+    }
+
+    #[test]
+    fn test_simulate_plain() {
+        let rule = rule(
+            "version=4\nopts=searchmode=plain https://example.com/releases.json \"version\":\\s*\"(\\d[\\d.]*)\"\n",
+        );
+        let json = r#"[{"version": "0.9"}, {"version":"1.1"}, {"name": "1.5"}]"#;
+        let simulation = simulate(&rule, &Fixture::Page(json.to_string())).unwrap();
+        assert_eq!(simulation.links, None);
+        assert_eq!(simulation.matches.len(), 2);
+        assert_eq!(simulation.newest.as_deref(), Some("1.1"));
+    }
+
+    #[test]
+    fn test_simulate_joins_capture_groups() {
+        let rule = rule("version=4\nhttps://example.com/ foo-(\\d+)_(\\d+)\\.tar\\.gz\n");
+        let page = r#"<a href="foo-1_2.tar.gz">"#;
+        let simulation = simulate(&rule, &Fixture::Page(page.to_string())).unwrap();
+        assert_eq!(simulation.matches[0].version, "1.2");
+    }
+
+    #[test]
+    fn test_simulate_invalid_mangle() {
+        let rule = rule(
+            "version=4\nopts=uversionmangle=s/(/x/ https://example.com/ foo-([\\d.]+)\\.tar\\.gz\n",
+        );
+        let page = r#"<a href="foo-1.0.tar.gz">"#;
+        let simulation = simulate(&rule, &Fixture::Page(page.to_string())).unwrap();
+        assert_eq!(simulation.matches[0].mangled_version, None);
+        assert!(simulation.matches[0].error.is_some());
+        assert_eq!(simulation.newest, None);
+    }
+
+    #[test]
+    fn test_simulate_pagemangle() {
+        // Attributes renamed so that they are read as links.
+        let rule = rule(
+            "version=4\nopts=pagemangle=s/data-tarball=/href=/g https://api.example.com/foo/releases/ .*/v?(\\d[\\d.]*)\\.tar\\.gz\n",
+        );
+        let page = r#"<a data-tarball="https://example.com/foo/v1.2.tar.gz">"#;
+        let simulation = simulate(&rule, &Fixture::Page(page.to_string())).unwrap();
+        assert_eq!(simulation.links, Some(1));
+        assert_eq!(simulation.newest.as_deref(), Some("1.2"));
+    }
+
+    #[test]
+    fn test_simulate_download() {
+        let mangled = rule(
+            "version=4\nopts=downloadurlmangle=s/\\.tar/.tar.gz.download/,filenamemangle=s/.*v([\\d.]+)\\.tar/foo-$1.tar/ https://example.com/files/ v([\\d.]+)\\.tar\n",
+        );
+        let page = r#"<a href="v1.0.tar">"#;
+        let simulation = simulate(&mangled, &Fixture::Page(page.to_string())).unwrap();
+        let found = &simulation.matches[0];
+        assert_eq!(
+            found.download_url.as_deref(),
+            Some("https://example.com/files/v1.0.tar.gz.download")
+        );
+        assert_eq!(found.filename.as_deref(), Some("foo-1.0.tar"));
+        assert!(simulation
+            .report()
+            .contains("downloads https://example.com/files/v1.0.tar.gz.download as foo-1.0.tar"));
+
+        let plain = rule("version=4\nhttps://example.com/files/ v([\\d.]+)\\.tar\\?raw=1\n");
+        let page = r#"<a href="/dl/v1.0.tar?raw=1">"#;
+        let simulation = simulate(&plain, &Fixture::Page(page.to_string())).unwrap();
+        let found = &simulation.matches[0];
+        assert_eq!(
+            found.download_url.as_deref(),
+            Some("https://example.com/dl/v1.0.tar?raw=1")
+        );
+        assert_eq!(found.filename.as_deref(), Some("v1.0.tar"));
+    }
+
+    #[test]
+    fn test_simulate_dirversionmangle_not_simulated() {
+        let rule = rule(
+            "version=4\nopts=dirversionmangle=s/-/./ https://example.com/ foo-([\\d.]+)\\.tar\\.gz\n",
+        );
+        let page = r#"<a href="foo-1.0.tar.gz">"#;
+        let simulation = simulate(&rule, &Fixture::Page(page.to_string())).unwrap();
+        assert!(simulation
+            .summary()
+            .ends_with("; dirversionmangle was not simulated"));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let base = "https://example.com/files/";
+        assert_eq!(
+            resolve(base, "foo.tar.gz"),
+            "https://example.com/files/foo.tar.gz"
+        );
+        assert_eq!(
+            resolve(base, "/dl/foo.tar.gz"),
+            "https://example.com/dl/foo.tar.gz"
+        );
+        assert_eq!(
+            resolve(base, "//cdn.example.com/foo.tar.gz"),
+            "https://cdn.example.com/foo.tar.gz"
+        );
+        assert_eq!(resolve(base, "http://other/foo"), "http://other/foo");
+        assert_eq!(
+            resolve("https://example.com", "foo"),
+            "https://example.com/foo"
+        );
+    }
+
+    #[test]
+    fn test_pattern_in_url() {
+        let rule = rule("version=4\nhttps://example.com/files/foo-(\\d[\\d.]*)\\.tar\\.gz\n");
+        assert_eq!(rule.pattern, r"foo-(\d[\d.]*)\.tar\.gz");
+    }
+
+    #[test]
+    fn test_fixture_path() {
+        let root = Path::new("/src/foo");
+        assert_eq!(
+            fixture_path("debian/tests/page.html", root),
+            Some(PathBuf::from("/src/foo/debian/tests/page.html"))
+        );
+        assert_eq!(
+            fixture_path("file:///tmp/page.html", root),
+            Some(PathBuf::from("/tmp/page.html"))
+        );
+        assert_eq!(fixture_path("https://example.com/", root), None);
+    }
+
+    #[test]
+    fn test_simulate_actions() {
+        let text = "version=4\nfile:///srv/foo/ foo-(\\d[\\d.]*)\\.tar\\.gz\nhttps://example.com/ foo-(\\d[\\d.]*)\\.tar\\.gz\n";
+        let parsed = debian_watch::parse::Parse::parse(text).to_watch_file();
+        let uri: Uri = "file:///src/foo/debian/watch".parse().unwrap();
+        let commands = simulate_actions(&uri, &parsed)
+            .into_iter()
+            .map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => action.command.unwrap(),
+                CodeActionOrCommand::Command(command) => command,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            commands[0].title,
+            "Simulate version check against file:///srv/foo/"
+        );
+        assert_eq!(
+            commands[0].arguments,
+            Some(vec![
+                uri.to_string().into(),
+                0.into(),
+                "file:///srv/foo/".into()
+            ])
+        );
+        assert_eq!(
+            commands[1].title,
+            "Simulate version check of https://example.com/ against a saved page"
+        );
+        assert_eq!(
+            commands[1].arguments,
+            Some(vec![uri.to_string().into(), 1.into()])
+        );
+    }
+
+    #[test]
+    fn test_package_name() {
+        let changelog = debian_changelog::ChangeLog::parse(
+            "foo (1.0-1) unstable; urgency=medium\n\n  * Initial release.\n\n -- Foo <foo@example.com>  Mon, 01 Jan 2024 00:00:00 +0000\n",
+        )
+        .tree();
+        assert_eq!(package_name(&changelog).as_deref(), Some("foo"));
+    }
+}
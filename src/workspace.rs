@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use rowan::ast::AstNode;
@@ -141,6 +142,9 @@ pub fn line_index(db: &dyn salsa::Database, file: SourceFile) -> Arc<crate::posi
 pub struct Workspace {
     storage: salsa::Storage<Self>,
     files: HashMap<Uri, SourceFile>,
+    /// Bumped on every input change, like salsa's own revision. Shared
+    /// between clones so revisions stay unique however the file was loaded.
+    next_revision: Arc<AtomicU64>,
 }
 
 impl salsa::Database for Workspace {}
@@ -150,8 +154,17 @@ impl Workspace {
         Self::default()
     }
 
+    /// Set the text of the file at `url`, adding it if it is new. Setting the
+    /// text a file already has is a no-op, so cached queries and the
+    /// revision are kept.
     pub fn update_file(&mut self, url: Uri, text: String) -> SourceFile {
+        if let Some(&existing) = self.files.get(&url) {
+            if *existing.text(self) == *text {
+                return existing;
+            }
+        }
         let arc: Arc<str> = Arc::from(text);
+        self.next_revision.fetch_add(1, Ordering::Relaxed);
         if let Some(&existing) = self.files.get(&url) {
             existing.set_text(self).to(arc);
             existing
@@ -162,6 +175,18 @@ impl Workspace {
         }
     }
 
+    /// Forget the file at `url`, e.g. after it was deleted on disk.
+    pub fn remove_file(&mut self, url: &Uri) {
+        if self.files.remove(url).is_some() {
+            self.next_revision.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Every file in the workspace, open in the editor or not.
+    pub fn files(&self) -> impl Iterator<Item = (&Uri, SourceFile)> {
+        self.files.iter().map(|(url, file)| (url, *file))
+    }
+
     pub fn get_parsed_control(
         &self,
         file: SourceFile,
@@ -369,6 +394,13 @@ impl Workspace {
         parse_lintian_overrides(self, file)
     }
 
+    /// The revision of the most recent change to any file. Results that
+    /// depend on other files than the one they are about, like diagnostics,
+    /// are current as long as this is unchanged.
+    pub fn revision(&self) -> u64 {
+        self.next_revision.load(Ordering::Relaxed)
+    }
+
     /// Salsa-cached line index for `file`. Use the methods on the
     /// returned [`crate::position::LineIndex`] to convert byte
     /// offsets to LSP positions and back.
@@ -425,6 +457,27 @@ impl Workspace {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_revision_tracks_text_changes() {
+        let mut workspace = Workspace::new();
+        let url: Uri = str::parse("file:///debian/control").unwrap();
+        workspace.update_file(url.clone(), "Source: foo\n".to_string());
+        let first = workspace.revision();
+
+        workspace.update_file(url.clone(), "Source: foo\n".to_string());
+        assert_eq!(workspace.revision(), first);
+
+        workspace.update_file(url, "Source: bar\n".to_string());
+        let second = workspace.revision();
+        assert!(second > first);
+
+        workspace.update_file(
+            str::parse("file:///debian/changelog").unwrap(),
+            String::new(),
+        );
+        assert!(workspace.revision() > second);
+    }
+
     #[test]
     fn test_parse_copyright_with_correct_casing() {
         let mut workspace = Workspace::new();
@@ -0,0 +1,350 @@
+//! Workspace-wide symbol search (`workspace/symbol`).
+//!
+//! The symbols come from the per-file `generate_document_symbols` functions,
+//! run over every file in the [`Workspace`] — open or indexed from disk.
+//! Only the entities worth jumping to from anywhere are kept: the source and
+//! binary stanzas of `debian/control`, standalone License paragraphs of
+//! `debian/copyright`, changelog versions, autopkgtest test names, rules
+//! targets and patches listed in `debian/patches/series`.
+//!
+//! Each symbol's container is the descriptor path `scip::symbols` uses for
+//! the same entity (`<source>/changelog`, `<source>/license`, ...), so the two
+//! name things the same way. With the `scip` feature the full SCIP symbol is
+//! attached as `data.scip`.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use tower_lsp_server::ls_types::{
+    DocumentSymbol, Location, OneOf, SymbolKind, Uri, WorkspaceSymbol,
+};
+
+use crate::position::Source;
+use crate::workspace::{SourceFile, Workspace};
+use crate::FileType;
+
+/// The kind of entity a workspace symbol names, following the descriptor
+/// layout of `scip::symbols`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    Source,
+    Binary,
+    License,
+    Changelog,
+    Tests,
+    Rules,
+    Patches,
+}
+
+impl Scope {
+    /// The namespace below the source package, as in `scip::symbols`.
+    fn namespace(self) -> Option<&'static str> {
+        match self {
+            Scope::Source | Scope::Binary => None,
+            Scope::License => Some("license"),
+            Scope::Changelog => Some("changelog"),
+            Scope::Tests => Some("tests"),
+            Scope::Rules => Some("rules"),
+            Scope::Patches => Some("patches"),
+        }
+    }
+
+    /// The bare entity name of a document symbol, without the label prefix
+    /// the outline shows.
+    #[cfg(any(feature = "scip", test))]
+    fn entity_name(self, label: &str) -> &str {
+        match self {
+            Scope::Source => label.strip_prefix("Source: ").unwrap_or(label),
+            Scope::Binary => label.strip_prefix("Package: ").unwrap_or(label),
+            Scope::License => label.strip_prefix("License: ").unwrap_or(label),
+            Scope::Changelog => label
+                .rsplit_once(" (")
+                .and_then(|(_, v)| v.strip_suffix(')'))
+                .unwrap_or(label),
+            Scope::Tests | Scope::Rules | Scope::Patches => label,
+        }
+    }
+}
+
+/// The source package a `debian/` directory builds.
+#[derive(Debug, Default)]
+struct SourcePackage {
+    name: Option<String>,
+    /// Only the SCIP symbols carry the version.
+    #[cfg_attr(not(feature = "scip"), allow(dead_code))]
+    version: Option<String>,
+}
+
+/// The document symbols of a file that are worth a workspace symbol, with
+/// their scope. Empty for file types without any.
+fn scoped_symbols(
+    workspace: &Workspace,
+    file: SourceFile,
+    file_type: FileType,
+) -> Vec<(Scope, DocumentSymbol)> {
+    let text = workspace.source_text(file);
+    let idx = workspace.get_line_index(file);
+    let src = Source::new(&text, &idx);
+
+    let symbols = match file_type {
+        FileType::Control => {
+            let parsed = workspace.get_parsed_control(file);
+            return crate::control::generate_document_symbols(&parsed, src)
+                .into_iter()
+                .map(|s| {
+                    let scope = if s.kind == SymbolKind::NAMESPACE {
+                        Scope::Source
+                    } else {
+                        Scope::Binary
+                    };
+                    (scope, s)
+                })
+                .collect();
+        }
+        FileType::Copyright => {
+            let parsed = workspace.get_parsed_copyright(file);
+            return crate::copyright::generate_document_symbols(&parsed, src)
+                .into_iter()
+                .filter(|s| s.kind == SymbolKind::KEY)
+                .map(|s| (Scope::License, s))
+                .collect();
+        }
+        FileType::Changelog => {
+            let parsed = workspace.get_parsed_changelog(file);
+            (
+                Scope::Changelog,
+                crate::changelog::generate_document_symbols(&parsed, src),
+            )
+        }
+        FileType::TestsControl => {
+            let parsed = workspace.get_parsed_deb822(file);
+            (
+                Scope::Tests,
+                crate::tests::generate_document_symbols(&parsed.tree(), src),
+            )
+        }
+        FileType::Rules => {
+            let parsed = workspace.get_parsed_rules(file);
+            (
+                Scope::Rules,
+                crate::rules::generate_document_symbols(&parsed.tree(), src),
+            )
+        }
+        FileType::PatchesSeries => {
+            let parsed = workspace.get_parsed_patches_series(file);
+            (
+                Scope::Patches,
+                crate::patches_series::generate_document_symbols(&parsed.tree(), src),
+            )
+        }
+        _ => return Vec::new(),
+    };
+    let (scope, symbols) = symbols;
+    symbols.into_iter().map(|s| (scope, s)).collect()
+}
+
+/// The source package named by `debian/control`, falling back to the
+/// latest changelog entry, and the version of that entry.
+fn source_package(
+    workspace: &Workspace,
+    control: Option<SourceFile>,
+    changelog: Option<SourceFile>,
+) -> SourcePackage {
+    let latest = changelog.and_then(|file| {
+        let parsed = workspace.get_parsed_changelog(file);
+        let entry = parsed.tree().iter().next()?;
+        Some((entry.package(), entry.version().map(|v| v.to_string())))
+    });
+    let control_name = control.and_then(|file| {
+        let parsed = workspace.get_parsed_control(file);
+        parsed.tree().source()?.name()
+    });
+    let (changelog_name, version) = latest.unwrap_or_default();
+    SourcePackage {
+        name: control_name.or(changelog_name),
+        version,
+    }
+}
+
+/// The SCIP descriptor path of the container of a symbol.
+fn container_name(scope: Scope, source: &SourcePackage) -> Option<String> {
+    match (scope.namespace(), &source.name) {
+        (None, _) if scope == Scope::Source => None,
+        (None, name) => name.clone(),
+        (Some(ns), Some(name)) => Some(format!("{name}/{ns}")),
+        (Some(ns), None) => Some(ns.to_string()),
+    }
+}
+
+/// The SCIP symbol `scip-debian` emits for the same entity.
+#[cfg(feature = "scip")]
+fn scip_symbol(scope: Scope, source: &SourcePackage, name: &str) -> Option<String> {
+    use crate::scip::symbols;
+
+    let version = source.version.as_deref();
+    let src = source.name.as_deref();
+    Some(match scope {
+        Scope::Binary => symbols::binary_package(name),
+        Scope::Source => symbols::source_package(src?, version),
+        Scope::License => symbols::license(src?, version, name),
+        Scope::Changelog => symbols::changelog_version(src?, name),
+        Scope::Tests => symbols::autopkgtest_test(src?, version, name),
+        Scope::Rules => symbols::rules_target(src?, version, name),
+        Scope::Patches => symbols::patch(src?, version, name),
+    })
+}
+
+/// Whether `name` matches a workspace symbol query: the query's characters
+/// appear in order, ignoring case. The empty query matches everything.
+fn matches_query(name: &str, query: &str) -> bool {
+    let mut chars = name.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|q| chars.any(|c| c == q))
+}
+
+/// The workspace symbols of every file in `workspace` matching `query`,
+/// grouped by `debian/` directory and sorted by file.
+pub fn workspace_symbols(workspace: &Workspace, query: &str) -> Vec<WorkspaceSymbol> {
+    let mut packages: BTreeMap<PathBuf, Vec<(Uri, SourceFile, FileType)>> = BTreeMap::new();
+    for (uri, file) in workspace.files() {
+        let (Some(file_type), Some(debian_dir)) =
+            (FileType::detect(uri), crate::Backend::find_debian_dir(uri))
+        else {
+            continue;
+        };
+        packages
+            .entry(debian_dir)
+            .or_default()
+            .push((uri.clone(), file, file_type));
+    }
+
+    let mut result = Vec::new();
+    for mut files in packages.into_values() {
+        files.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        let find = |wanted: FileType| {
+            files
+                .iter()
+                .find(|(_, _, t)| *t == wanted)
+                .map(|(_, f, _)| *f)
+        };
+        let source = source_package(
+            workspace,
+            find(FileType::Control),
+            find(FileType::Changelog),
+        );
+
+        for (uri, file, file_type) in &files {
+            for (scope, symbol) in scoped_symbols(workspace, *file, *file_type) {
+                if !matches_query(&symbol.name, query) {
+                    continue;
+                }
+                #[cfg(feature = "scip")]
+                let data = scip_symbol(scope, &source, scope.entity_name(&symbol.name))
+                    .map(|s| serde_json::json!({ "scip": s }));
+                #[cfg(not(feature = "scip"))]
+                let data = None;
+
+                result.push(WorkspaceSymbol {
+                    container_name: container_name(scope, &source),
+                    name: symbol.name,
+                    kind: symbol.kind,
+                    tags: None,
+                    location: OneOf::Left(Location {
+                        uri: uri.clone(),
+                        range: symbol.selection_range,
+                    }),
+                    data,
+                });
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace_with(files: &[(&str, &str)]) -> Workspace {
+        let mut workspace = Workspace::new();
+        for (path, text) in files {
+            let uri = Uri::from_file_path(format!("/tmp/foo/debian/{path}")).unwrap();
+            workspace.update_file(uri, text.to_string());
+        }
+        workspace
+    }
+
+    const CONTROL: &str = "\
+Source: foo
+Maintainer: Test <test@example.com>
+
+Package: foo-utils
+Architecture: any
+Description: Utilities
+";
+
+    const CHANGELOG: &str = "\
+foo (1.2-1) unstable; urgency=medium
+
+  * New upstream release.
+
+ -- Test <test@example.com>  Mon, 01 Jan 2024 12:00:00 +0000
+";
+
+    #[test]
+    fn test_symbols_across_files() {
+        let workspace = workspace_with(&[
+            ("control", CONTROL),
+            ("changelog", CHANGELOG),
+            (
+                "copyright",
+                "Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/\n\n\
+                 Files: *\nCopyright: 2024 Test\nLicense: GPL-2+\n\n\
+                 License: GPL-2+\n Some text.\n",
+            ),
+            ("rules", "#!/usr/bin/make -f\n%:\n\tdh $@\n"),
+            ("tests/control", "Tests: smoke\nDepends: @\n"),
+            ("patches/series", "fix-build.patch\n"),
+            ("foo-utils.install", "usr/bin\n"),
+        ]);
+
+        let symbols = workspace_symbols(&workspace, "");
+        let found: Vec<(&str, Option<&str>)> = symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.container_name.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("foo (1.2-1)", Some("foo/changelog")),
+                ("Source: foo", None),
+                ("Package: foo-utils", Some("foo")),
+                ("License: GPL-2+", Some("foo/license")),
+                ("fix-build.patch", Some("foo/patches")),
+                ("%", Some("foo/rules")),
+                ("smoke", Some("foo/tests")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_query_filters_by_subsequence() {
+        let workspace = workspace_with(&[("control", CONTROL), ("changelog", CHANGELOG)]);
+
+        let names: Vec<String> = workspace_symbols(&workspace, "futl")
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, vec!["Package: foo-utils"]);
+        assert!(workspace_symbols(&workspace, "xyz").is_empty());
+    }
+
+    #[test]
+    fn test_entity_names() {
+        assert_eq!(Scope::Binary.entity_name("Package: foo-utils"), "foo-utils");
+        assert_eq!(Scope::Changelog.entity_name("foo (1:1.2-1)"), "1:1.2-1");
+        assert_eq!(Scope::License.entity_name("License: MIT"), "MIT");
+    }
+}
@@ -59,6 +59,26 @@ export function activate(context: ExtensionContext) {
       { scheme: 'file', language: 'debrules' },
       { scheme: 'file', language: 'debpatches' },
     ],
+    middleware: {
+      // Simulating a watch entry against a remote page needs a saved copy of
+      // it, which the server leaves for the user to pick.
+      executeCommand: async (command, args, next) => {
+        if (command === 'debian-lsp.simulateWatch' && args.length === 2) {
+          const picked = await window.showOpenDialog({
+            canSelectFiles: true,
+            canSelectFolders: true,
+            canSelectMany: false,
+            openLabel: 'Simulate',
+            title: 'Saved copy of the upstream page',
+          });
+          if (!picked) {
+            return undefined;
+          }
+          args = [...args, picked[0].fsPath];
+        }
+        return next(command, args);
+      },
+    },
     initializationOptions: {
       upstreamOntologistNetAccess: config.get<boolean>('upstreamOntologistNetAccess', false),
       showOverriddenIssues: config.get<boolean>('showOverriddenIssues', true),
@@ -116,6 +136,50 @@ export function activate(context: ExtensionContext) {
         });
       }
     }),
+    commands.registerCommand('debian-lsp.palette.addNewUpstreamEntry', async () => {
+      const uri = window.activeTextEditor?.document.uri.toString();
+      if (!uri) {
+        return;
+      }
+      const version = await window.showInputBox({
+        prompt: 'New upstream version',
+        placeHolder: '1.2.3',
+        validateInput: (value) => value.trim() ? undefined : 'A version is required',
+      });
+      if (version) {
+        client.sendRequest('workspace/executeCommand', {
+          command: 'debian-lsp.addNewUpstreamEntry',
+          arguments: [uri, version.trim()],
+        });
+      }
+    }),
+    commands.registerCommand('debian-lsp.palette.addNmuEntry', () => {
+      const uri = window.activeTextEditor?.document.uri.toString();
+      if (uri) {
+        client.sendRequest('workspace/executeCommand', {
+          command: 'debian-lsp.addNmuEntry',
+          arguments: [uri],
+        });
+      }
+    }),
+    commands.registerCommand('debian-lsp.palette.addTeamUploadEntry', () => {
+      const uri = window.activeTextEditor?.document.uri.toString();
+      if (uri) {
+        client.sendRequest('workspace/executeCommand', {
+          command: 'debian-lsp.addTeamUploadEntry',
+          arguments: [uri],
+        });
+      }
+    }),
+    commands.registerCommand('debian-lsp.palette.addBackportEntry', () => {
+      const uri = window.activeTextEditor?.document.uri.toString();
+      if (uri) {
+        client.sendRequest('workspace/executeCommand', {
+          command: 'debian-lsp.addBackportEntry',
+          arguments: [uri],
+        });
+      }
+    }),
   );
 
   // Start the client (this will also launch the server)